@@ -0,0 +1,109 @@
+//! Benchmarks of the image pipeline, to measure changes to its performance
+//!
+//! Run with `cargo bench --features bench`
+#![allow(
+    missing_docs,
+    clippy::unwrap_used,
+    clippy::missing_docs_in_private_items,
+    reason = "relaxed lints in benchmarks"
+)]
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ferrishot::{
+    App, Config,
+    bench::{OnConflict, OnDuplicate, RgbaHandle},
+};
+use iced::{
+    Rectangle,
+    keyboard::{Key, Modifiers},
+};
+
+/// Resolutions of the screenshots
+const RESOLUTIONS: [(&str, u32, u32); 3] = [
+    ("720p", 1280, 720),
+    ("1080p", 1920, 1080),
+    ("4k", 3840, 2160),
+];
+
+/// A screenshot of `width` x `height` with a gradient, so that it does not compress
+/// unrealistically well
+fn screenshot(width: u32, height: u32) -> RgbaHandle {
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).flat_map(move |x| [x as u8, y as u8, (x ^ y) as u8, 255]))
+        .collect::<Vec<_>>();
+
+    RgbaHandle::new(width, height, pixels)
+}
+
+/// Crop a region out of the whole screenshot, as happens when it is copied or saved
+fn capture_to_crop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("capture-to-crop");
+
+    for (name, width, height) in RESOLUTIONS {
+        let image = screenshot(width, height);
+        let region = Rectangle::new(
+            iced::Point::new(width as f32 / 4.0, height as f32 / 4.0),
+            iced::Size::new(width as f32 / 2.0, height as f32 / 2.0),
+        );
+
+        group.bench_with_input(BenchmarkId::from_parameter(name), &image, |b, image| {
+            b.iter(|| App::process_image(black_box(region), image));
+        });
+    }
+
+    group.finish();
+}
+
+/// Encode the whole screenshot as PNG and write it to a file, on a single thread and on every core
+fn png_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("png-encode");
+    group.sample_size(10);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("screenshot.png");
+
+    for (name, width, height) in RESOLUTIONS {
+        let image = screenshot(width, height);
+        let image = App::process_image(image.bounds(), &image);
+
+        for (encoding, parallel) in [("sequential", false), ("parallel", true)] {
+            group.bench_with_input(BenchmarkId::new(encoding, name), &image, |b, image| {
+                b.iter(|| {
+                    ferrishot::save_image(
+                        image,
+                        &path,
+                        OnDuplicate::Save,
+                        OnConflict::Overwrite,
+                        parallel,
+                    )
+                    .unwrap()
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Find the command bound to `gj`, which happens on every key press
+fn keymap_lookup(c: &mut Criterion) {
+    // there is no config file at this path, so the default keybindings are used
+    let config = Config::parse("").unwrap();
+    let keys = &config.keys;
+
+    c.bench_function("keymap-lookup", |b| {
+        b.iter(|| {
+            keys.get(
+                black_box(Key::Character("g".into())),
+                black_box(Some(Key::Character("j".into()))),
+                Modifiers::empty(),
+                None,
+            )
+        });
+    });
+}
+
+criterion_group!(benches, capture_to_crop, png_encode, keymap_lookup);
+criterion_main!(benches);
@@ -0,0 +1,200 @@
+//! Keep a copy of every screenshot, no matter which action was taken with it
+//!
+//! With `always-autosave` enabled, each screenshot is also written to a spool directory.
+//! This way, a screenshot that was copied to the clipboard is not lost once
+//! something else is copied.
+//!
+//! Old screenshots are removed from the spool directory, according to the retention limits.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use image::DynamicImage;
+
+/// Name of the directory in which the screenshots are kept
+pub const AUTOSAVE_DIRNAME: &str = "ferrishot-autosave";
+
+/// Could not autosave the screenshot
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Can't find the directory of the file
+    #[error(transparent)]
+    Paths(#[from] crate::paths::Error),
+    /// Failed to write the screenshot, or to remove old ones
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Failed to encode the screenshot
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+}
+
+/// Path to the spool directory
+pub fn directory() -> Result<PathBuf, Error> {
+    Ok(crate::paths::data_dir()?.join(AUTOSAVE_DIRNAME))
+}
+
+/// How autosaved screenshots are written, and how long they are kept around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Autosave {
+    /// Remove screenshots older than this
+    pub max_age: Option<Duration>,
+    /// Remove the oldest screenshots once all of them take up more bytes than this
+    pub max_size: Option<u64>,
+    /// Compress large screenshots on multiple threads
+    pub parallel_encoding: bool,
+}
+
+impl Autosave {
+    /// Write the screenshot to the spool directory, then remove screenshots which exceed the retention limits.
+    ///
+    /// This should never prevent the actual action from happening, so errors are only logged
+    pub fn run(self, image: &DynamicImage) {
+        let result = directory().and_then(|directory| {
+            let path = save(image, &directory, self.parallel_encoding)?;
+            log::info!("Autosaved screenshot to {}", path.display());
+            self.cleanup(&directory, SystemTime::now())
+        });
+
+        if let Err(err) = result {
+            log::error!("Failed to autosave the screenshot: {err}");
+        }
+    }
+
+    /// Remove screenshots in `directory` which are older than `max_age`, then the oldest
+    /// screenshots until they all fit into `max_size`
+    fn cleanup(self, directory: &Path, now: SystemTime) -> Result<(), Error> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(directory)? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_file() {
+                files.push((entry.path(), meta.modified()?, meta.len()));
+            }
+        }
+
+        // newest first, so we keep as many recent screenshots as possible
+        files.sort_by(|(_, a, _), (_, b, _)| b.cmp(a));
+
+        let mut total_size = 0;
+        for (path, modified, len) in files {
+            total_size += len;
+
+            let is_expired = self
+                .max_age
+                .is_some_and(|max_age| now.duration_since(modified).is_ok_and(|age| age > max_age));
+            let is_over_size = self.max_size.is_some_and(|max_size| total_size > max_size);
+
+            if is_expired || is_over_size {
+                log::info!("Removing old autosaved screenshot {}", path.display());
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Save the image into `directory`, named after the current time
+fn save(image: &DynamicImage, directory: &Path, parallel_encoding: bool) -> Result<PathBuf, Error> {
+    fs::create_dir_all(directory)?;
+
+    let path = directory.join(format!(
+        "{}.png",
+        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S%.3f")
+    ));
+
+    Ok(crate::save_image(
+        image,
+        &path,
+        crate::image::duplicate::OnDuplicate::Save,
+        // an autosaved screenshot never replaces another one
+        crate::image::conflict::OnConflict::Rename,
+        parallel_encoding,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Create a file of `len` bytes, which was last modified `age` ago
+    fn file(directory: &Path, name: &str, len: usize, age: Duration, now: SystemTime) {
+        let path = directory.join(name);
+        fs::write(&path, vec![0; len]).unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(now - age)
+            .unwrap();
+    }
+
+    /// Names of the files in the directory, sorted
+    fn remaining(directory: &Path) -> Vec<String> {
+        let mut names = fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    const DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+    #[test]
+    fn removes_expired() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        file(dir.path(), "new.png", 10, DAY, now);
+        file(dir.path(), "old.png", 10, DAY * 10, now);
+
+        Autosave {
+            max_age: Some(DAY * 7),
+            max_size: None,
+            parallel_encoding: false,
+        }
+        .cleanup(dir.path(), now)
+        .unwrap();
+
+        assert_eq!(remaining(dir.path()), vec!["new.png"]);
+    }
+
+    #[test]
+    fn removes_oldest_over_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        file(dir.path(), "a.png", 10, DAY * 3, now);
+        file(dir.path(), "b.png", 10, DAY * 2, now);
+        file(dir.path(), "c.png", 10, DAY, now);
+
+        Autosave {
+            max_age: None,
+            max_size: Some(25),
+            parallel_encoding: false,
+        }
+        .cleanup(dir.path(), now)
+        .unwrap();
+
+        assert_eq!(remaining(dir.path()), vec!["b.png", "c.png"]);
+    }
+
+    #[test]
+    fn keeps_everything_without_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        file(dir.path(), "a.png", 1000, DAY * 1000, now);
+
+        Autosave {
+            max_age: None,
+            max_size: None,
+            parallel_encoding: false,
+        }
+        .cleanup(dir.path(), now)
+        .unwrap();
+
+        assert_eq!(remaining(dir.path()), vec!["a.png"]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Crop the same region out of many images, without launching the app
+//!
+//! Each image goes through the same cropping and encoding as a screenshot taken in the app.
+//! Images are processed in parallel, as each of them is independent.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use crate::{
+    App,
+    image::{conflict::OnConflict, duplicate::OnDuplicate, screenshot::CaptureOptions},
+    lazy_rect::LazyRectangle,
+};
+
+/// Failed to crop an image
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Could not open the image
+    #[error(transparent)]
+    GetImage(#[from] crate::image::GetImageError),
+    /// Could not save the cropped image
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+    /// The input is not a file
+    #[error("not a file: {}", .0.display())]
+    NotAFile(PathBuf),
+}
+
+/// How to crop the images
+#[derive(Debug, Clone, Copy)]
+pub struct Batch<'a> {
+    /// Region to crop out of each image, relative to the size of that image
+    pub region: LazyRectangle,
+    /// Directory in which to save the cropped images, under the same file name
+    pub out_dir: &'a Path,
+    /// What to do if a cropped image is identical to one already in `out_dir`
+    pub on_duplicate: OnDuplicate,
+    /// What to do if a cropped image has the same name as a file already in `out_dir`
+    pub on_conflict: OnConflict,
+    /// Compress large PNGs on multiple threads
+    pub parallel_encoding: bool,
+}
+
+impl Batch<'_> {
+    /// Crop a single image, returning where it was saved
+    fn crop(self, file: &Path) -> Result<PathBuf, Error> {
+        let file_name = file
+            .file_name()
+            .ok_or_else(|| Error::NotAFile(file.to_path_buf()))?;
+
+        let image = crate::get_image(Some(&file.to_path_buf()), true, CaptureOptions::default())?;
+        let region = self.region.init(image.bounds());
+
+        Ok(crate::save_image(
+            &App::process_image(region, &image),
+            &self.out_dir.join(file_name),
+            self.on_duplicate,
+            self.on_conflict,
+            self.parallel_encoding,
+        )?)
+    }
+
+    /// Crop all of the `files` in parallel.
+    ///
+    /// Returns, in the same order as the `files`, where each cropped image was saved.
+    /// One image failing does not stop the others from being cropped.
+    pub fn run(self, files: &[PathBuf]) -> std::io::Result<Vec<Result<PathBuf, Error>>> {
+        fs::create_dir_all(self.out_dir)?;
+
+        let workers = thread::available_parallelism()
+            .map_or(1, usize::from)
+            .min(files.len());
+
+        // index of the next file to crop
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new((0..files.len()).map(|_| None).collect::<Vec<_>>());
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file) = files.get(index) else {
+                            break;
+                        };
+                        let result = self.crop(file);
+                        results
+                            .lock()
+                            .expect("no worker panics while holding the lock")[index] =
+                            Some(result);
+                    }
+                });
+            }
+        });
+
+        Ok(results
+            .into_inner()
+            .expect("no worker panics while holding the lock")
+            .into_iter()
+            .map(|result| result.expect("every file is cropped"))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn crops_every_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("cropped");
+
+        let files = ["a.png", "b.png"].map(|name| {
+            let path = dir.path().join(name);
+            image::RgbaImage::from_pixel(10, 8, image::Rgba([255, 0, 0, 255]))
+                .save(&path)
+                .unwrap();
+            path
+        });
+        let missing = dir.path().join("missing.png");
+
+        let results = Batch {
+            region: "4x0.5+1+1".parse().unwrap(),
+            out_dir: &out_dir,
+            on_duplicate: OnDuplicate::Save,
+            on_conflict: OnConflict::Overwrite,
+            parallel_encoding: false,
+        }
+        .run(&[files[0].clone(), missing, files[1].clone()])
+        .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[1].is_err(), "the file does not exist");
+
+        for (result, name) in [(&results[0], "a.png"), (&results[2], "b.png")] {
+            let path = result.as_ref().unwrap();
+            assert_eq!(*path, out_dir.join(name));
+            assert_eq!(image::image_dimensions(path).unwrap(), (4, 4));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Take screenshots from other Rust programs, without launching the app
+//!
+//! The screenshot goes through the same cropping and encoding as one taken in the app.
+//!
+//! ```no_run
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let image = ferrishot::capture()
+//!     .region("800x600+0+0".parse()?)
+//!     .trim(true)
+//!     .save_to("screenshot.png")
+//!     .call()?;
+//!
+//! println!("captured {}x{}", image.width(), image.height());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::PathBuf;
+
+use image::DynamicImage;
+
+use crate::{
+    App,
+    geometry::RectangleExt as _,
+    image::{GetImageError, conflict::OnConflict, duplicate::OnDuplicate, screenshot},
+    lazy_rect::LazyRectangle,
+};
+
+/// Could not capture the screenshot
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Could not take the screenshot, or open the image
+    #[error(transparent)]
+    GetImage(#[from] GetImageError),
+    /// Could not save the screenshot
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+}
+
+/// Which monitor to take the screenshot of
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Monitor {
+    /// The monitor the mouse is on
+    #[default]
+    Cursor,
+    /// The monitor containing this point of the desktop
+    At {
+        /// Horizontal position of the point
+        x: i32,
+        /// Vertical position of the point
+        y: i32,
+    },
+}
+
+/// Take a screenshot, returning the captured image
+///
+/// Nothing is shown on the screen. Finish the builder with `.call()`
+#[bon::builder]
+pub fn capture(
+    /// Monitor to take the screenshot of
+    #[builder(default)]
+    monitor: Monitor,
+    /// Instead of taking a screenshot, capture from this image
+    #[builder(into)]
+    file: Option<PathBuf>,
+    /// Region to capture, same syntax as `--region`. Defaults to everything
+    region: Option<LazyRectangle>,
+    /// Shrink the region to its content, removing margins of the same color
+    #[builder(default)]
+    trim: bool,
+    /// Keep the alpha channel of the screenshot, instead of making every pixel fully opaque.
+    /// Images opened from a `file` always keep it
+    #[builder(default)]
+    preserve_transparency: bool,
+    /// Capture the next frame which the desktop presents, instead of capturing right away
+    #[builder(default)]
+    on_next_frame: bool,
+    /// How many frames to capture and average, to reduce noise
+    #[builder(default = 1)]
+    frames: u32,
+    /// Also save the image to this path. The format is inferred from the extension
+    #[builder(into)]
+    save_to: Option<PathBuf>,
+    /// Compress large PNGs saved with `save_to` on multiple threads
+    #[builder(default = true)]
+    parallel_encoding: bool,
+) -> Result<DynamicImage, Error> {
+    let capture_options = screenshot::CaptureOptions {
+        on_next_frame,
+        frames,
+    };
+    let handle = match (file, monitor) {
+        (Some(file), _) => crate::get_image(Some(&file), preserve_transparency, capture_options)?,
+        (None, Monitor::Cursor) => crate::get_image(None, preserve_transparency, capture_options)?,
+        (None, Monitor::At { x, y }) => {
+            let handle = screenshot::take_at(x, y, capture_options).map_err(GetImageError::from)?;
+
+            if preserve_transparency {
+                handle
+            } else {
+                handle.flattened()
+            }
+        }
+    };
+
+    let region = region
+        .unwrap_or(LazyRectangle::FULL)
+        .init(handle.bounds())
+        .norm();
+    let region = if trim {
+        crate::image::trim::content_bounds(&handle, region).unwrap_or(region)
+    } else {
+        region
+    };
+
+    let image = App::process_image(region, &handle);
+
+    if let Some(path) = save_to {
+        crate::save_image(
+            &image,
+            &path,
+            OnDuplicate::Save,
+            OnConflict::Overwrite,
+            parallel_encoding,
+        )?;
+    }
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// 20x10 white image with a black 4x2 rectangle at (6, 3)
+    fn image_file(dir: &std::path::Path) -> PathBuf {
+        let path = dir.join("image.png");
+        image::RgbaImage::from_fn(20, 10, |x, y| {
+            if (6..10).contains(&x) && (3..5).contains(&y) {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            }
+        })
+        .save(&path)
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn captures_region() {
+        let dir = tempfile::tempdir().unwrap();
+        let save_to = dir.path().join("captured.png");
+
+        let image = capture()
+            .file(image_file(dir.path()))
+            .region("0.5x4+0+0".parse().unwrap())
+            .save_to(&save_to)
+            .call()
+            .unwrap();
+
+        assert_eq!((image.width(), image.height()), (10, 4));
+        assert_eq!(image::image_dimensions(save_to).unwrap(), (10, 4));
+    }
+
+    #[test]
+    fn trims_to_content() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let image = capture()
+            .file(image_file(dir.path()))
+            .trim(true)
+            .call()
+            .unwrap();
+
+        assert_eq!((image.width(), image.height()), (4, 2));
+    }
+}
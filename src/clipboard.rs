@@ -3,6 +3,8 @@
 //! - PNG image
 //! - Text
 //!
+//! Or read text from it.
+//!
 //! This module includes a small daemon for Linux that runs in the background,
 //! providing clipboard access.
 
@@ -24,6 +26,29 @@ pub enum ClipboardError {
     Io(#[from] std::io::Error),
 }
 
+/// Get the text content of the clipboard.
+///
+/// With `primary`, reads the primary selection instead on Linux: the text which was
+/// last selected, that middle-click pastes
+#[cfg_attr(
+    not(target_os = "linux"),
+    expect(unused_variables, reason = "only Linux has a primary selection")
+)]
+pub fn get_text(primary: bool) -> Result<String, ClipboardError> {
+    let mut clipboard = arboard::Clipboard::new()?;
+
+    #[cfg(target_os = "linux")]
+    if primary {
+        use arboard::GetExtLinux as _;
+        return Ok(clipboard
+            .get()
+            .clipboard(arboard::LinuxClipboardKind::Primary)
+            .text()?);
+    }
+
+    Ok(clipboard.get_text()?)
+}
+
 /// Set the text content of the clipboard
 pub fn set_text(text: &str) -> Result<(), ClipboardError> {
     #[cfg(target_os = "linux")]
@@ -0,0 +1,67 @@
+//! How long ferrishot stays open without any input, before it closes by itself
+
+use std::{str::FromStr, time::Duration};
+
+use ferrishot_knus::{DecodeScalar, ast::Literal, errors::DecodeError, traits::ErrorSpan};
+
+/// How long ferrishot stays open when nothing is pressed or moved.
+/// `None` if it never closes by itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AutoExitAfter(pub Option<Duration>);
+
+impl FromStr for AutoExitAfter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "never" => Ok(Self(None)),
+            duration => crate::daemon::parse_interval(duration).map(|after| Self(Some(after))),
+        }
+    }
+}
+
+impl<S: ErrorSpan> DecodeScalar<S> for AutoExitAfter {
+    fn type_check(
+        _type_name: &Option<ferrishot_knus::span::Spanned<ferrishot_knus::ast::TypeName, S>>,
+        _ctx: &mut ferrishot_knus::decode::Context<S>,
+    ) {
+    }
+
+    fn raw_decode(
+        value: &ferrishot_knus::span::Spanned<Literal, S>,
+        ctx: &mut ferrishot_knus::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        match &**value {
+            Literal::String(s) => match s.parse() {
+                Ok(after) => Ok(after),
+                Err(err) => {
+                    ctx.emit_error(DecodeError::conversion(value, err));
+                    Ok(Self::default())
+                }
+            },
+            _ => {
+                ctx.emit_error(DecodeError::scalar_kind(
+                    ferrishot_knus::decode::Kind::String,
+                    value,
+                ));
+                Ok(Self::default())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse() {
+        assert_eq!("never".parse(), Ok(AutoExitAfter(None)));
+        assert_eq!(
+            "5m".parse(),
+            Ok(AutoExitAfter(Some(Duration::from_secs(5 * 60))))
+        );
+        assert!("soon".parse::<AutoExitAfter>().is_err(), "not a duration");
+    }
+}
@@ -3,7 +3,6 @@ use std::time::Duration;
 use std::{path::PathBuf, sync::LazyLock};
 
 use clap::{Parser, ValueHint};
-use etcetera::BaseStrategy as _;
 
 use crate::lazy_rect::LazyRectangle;
 
@@ -24,15 +23,15 @@ const STYLES: clap::builder::Styles = clap::builder::Styles::styled()
 #[command(version, styles = STYLES, long_about = None)]
 #[expect(clippy::struct_excessive_bools, reason = "normal for CLIs")]
 pub struct Cli {
+    /// Do something else than taking a screenshot
+    #[command(subcommand)]
+    pub action: Option<Action>,
+
     /// Instead of taking a screenshot of the desktop, open this image instead
-    //
-    // NOTE: Currently disabled because if the screenshot is not the same size as the desktop,
-    // it will cause bugs as we consider 0,0 in the Canvas to be the origin but it is not necessarily,
-    // when the desktop and the image are not the same size
-    //
-    // TODO: Fix this argument
-    //
-    #[arg(hide = true, value_hint = ValueHint::FilePath)]
+    ///
+    /// The image is scaled to fit into the window. Use `--windowed` to open it in a window
+    /// of its own, instead of covering the whole screen.
+    #[arg(value_hint = ValueHint::FilePath)]
     pub file: Option<PathBuf>,
 
     //
@@ -57,14 +56,27 @@ pub struct Cli {
     /// With the above syntax, you can create all the regions you want.
     /// - `100x1.0+0.5+0-50%`: Create a 100px wide, full height, horizontally centered region
     /// - `1.0x1.0+0+0`: Create a region that spans the full screen. You can use alias `full` for this
+    ///
+    /// Also available as `-g` and `--geometry`, for compatibility with `maim` and `slop`
     #[arg(
         short,
         long,
+        short_alias = 'g',
+        visible_alias = "geometry",
         value_name = "WxH+X+Y",
         value_hint = ValueHint::Other
     )]
     pub region: Option<LazyRectangle>,
 
+    /// Take `--region` out of the whole desktop, instead of the monitor the mouse is on
+    ///
+    /// The region can span multiple monitors, which are stitched together. Its coordinates start
+    /// at the top-left corner of the area covered by all monitors, and relative values are relative
+    /// to the size of that area. For example, with 2 monitors of 1920x1080 side by side,
+    /// `3840x1080+0+0` captures both and `1.0x1.0+0+0` is the same.
+    #[arg(long, requires = "region", conflicts_with = "file")]
+    pub desktop: bool,
+
     /// Use last region
     #[arg(short, long, conflicts_with = "region")]
     pub last_region: bool,
@@ -82,7 +94,12 @@ pub struct Cli {
     ///
     /// Using this option with `--region` or `--last-region` will run ferrishot in 'headless mode',
     /// without making a new window.
-    #[arg(short, long, value_name = "ACTION")]
+    #[arg(
+        short,
+        long,
+        value_name = "ACTION",
+        default_value_if("raw", "true", "save-screenshot")
+    )]
     pub accept_on_select: Option<crate::image::action::Command>,
 
     /// Wait this long before launch
@@ -95,16 +112,165 @@ pub struct Cli {
     )]
     pub delay: Option<Duration>,
 
+    /// Wait until a window with a title matching this regex is shown, before taking the screenshot
+    ///
+    /// Useful in scripts which launch an app and take a screenshot of it. Once the window is shown,
+    /// it is given a moment to draw itself. Fails if the window does not show up within 30 seconds.
+    ///
+    /// For example: `--wait-for-window '^Firefox'`
+    #[arg(
+        long,
+        value_name = "REGEX",
+        value_parser = |s: &str| regex::Regex::new(s),
+        conflicts_with = "file",
+        value_hint = ValueHint::Other
+    )]
+    pub wait_for_window: Option<regex::Regex>,
+
     /// Save image to path
     #[arg(
         short,
         long,
         value_name = "PATH",
-        long_help = "Instead of opening a file picker to save the screenshot, save it to this path instead",
+        long_help = "Instead of picking where to save the screenshot inside of the app, save it to this path",
         value_hint = ValueHint::FilePath
     )]
     pub save_path: Option<PathBuf>,
 
+    /// Run in a normal window of this size, instead of covering the whole screen
+    ///
+    /// The screenshot is scaled to fit into the window, and the window can be resized.
+    /// Useful for cropping a `FILE`, or for showing ferrishot off.
+    #[arg(
+        long,
+        value_name = "WxH",
+        value_parser = parse_window_size,
+        value_hint = ValueHint::Other
+    )]
+    pub windowed: Option<iced::Size<u32>>,
+
+    /// Hide all UI except for the crosshair and the selection frame
+    #[arg(
+        short,
+        long,
+        long_help = "Hide the welcome message, size indicator and selection icons. Only the crosshair and the selection frame are shown. Overrides the `minimal` config option"
+    )]
+    pub minimal: bool,
+
+    /// Listen for commands from other programs on a socket at this path
+    ///
+    /// Each line sent to the socket is a command:
+    /// - `set-region <WxH+X+Y>`: Select a region, same syntax as `--region`
+    /// - `move|extend|shrink <left|right|up|down> [pixels]`: Move or resize the selection
+    /// - `accept <copy|save|upload>`: Perform an action on the selection
+    /// - `cancel`: Exit
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub control_socket: Option<PathBuf>,
+
+    /// Run these commands once ferrishot launches. Pass `-` to read them from standard input
+    ///
+    /// Commands are separated by `;` or new lines, and are the same as for `--control-socket`.
+    /// Additionally, `wait <MILLISECONDS>` pauses before the next command.
+    ///
+    /// For example: `select 100x100+10+10; move right 50; wait 500; accept copy`
+    #[arg(long, value_name = "SCRIPT", value_hint = ValueHint::Other)]
+    pub script: Option<String>,
+
+    /// Record how the selection changes and which actions are taken into this file,
+    /// to replay it later with `--replay-session`
+    ///
+    /// Attaching a session to a bug report shows exactly what happened.
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub record_session: Option<PathBuf>,
+
+    /// Replay a session recorded with `--record-session`
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with = "script",
+        value_hint = ValueHint::FilePath
+    )]
+    pub replay_session: Option<PathBuf>,
+
+    /// Instead of launching, stay in the background and take a screenshot
+    /// whenever this file is created or touched
+    ///
+    /// The screenshot is of `--region` (full screen by default), and is saved to `--save-path`.
+    /// The save path can contain `strftime` placeholders like `%Y-%m-%d_%H-%M-%S`, which will
+    /// be replaced with the time of the screenshot.
+    ///
+    /// ferrishot detaches from the terminal and prints the ID of the background process.
+    /// Stop it with `kill <pid>`, or `taskkill /PID <pid>` on Windows.
+    #[arg(
+        long,
+        value_name = "PATH",
+        requires = "save_path",
+        conflicts_with = "accept_on_select",
+        value_hint = ValueHint::FilePath
+    )]
+    pub trigger_file: Option<PathBuf>,
+
+    /// Keep `--trigger-file` running in the terminal instead of in the background,
+    /// so it can be stopped with Ctrl+C
+    #[arg(long, requires = "trigger_file")]
+    pub foreground: bool,
+
+    /// Instead of launching, take a screenshot periodically, such as `30s`, `10m` or `2h`
+    ///
+    /// The screenshot is of `--region` (full screen by default), and is saved to `--save-path`,
+    /// which can contain `strftime` placeholders like `%Y-%m-%d_%H-%M-%S`.
+    #[arg(
+        long,
+        value_name = "INTERVAL",
+        requires = "save_path",
+        conflicts_with_all = ["accept_on_select", "trigger_file"],
+        value_parser = crate::daemon::parse_interval,
+        value_hint = ValueHint::Other
+    )]
+    pub every: Option<Duration>,
+
+    /// Stop taking periodic screenshots at this time of day, such as `18:00`
+    #[arg(
+        long,
+        value_name = "HH:MM",
+        requires = "every",
+        value_parser = |s: &str| chrono::NaiveTime::parse_from_str(s, "%H:%M"),
+        value_hint = ValueHint::Other
+    )]
+    pub until: Option<chrono::NaiveTime>,
+
+    /// Stop taking periodic screenshots once the directory they are saved in
+    /// takes up this many megabytes
+    #[arg(long, value_name = "MEGABYTES", requires = "every", value_hint = ValueHint::Other)]
+    pub max_disk_usage: Option<u64>,
+
+    /// Instead of launching, serve automation requests over HTTP at this address
+    ///
+    /// Clients call the JSON-RPC method `capture` with an optional `region` (same syntax
+    /// as `--region`), and receive the screenshot as base64-encoded PNG.
+    ///
+    /// Requests must include the `Authorization: Bearer <token>` header, where the token is the value
+    /// of the `FERRISHOT_AUTOMATION_TOKEN` environment variable. Only loopback addresses are
+    /// allowed, unless `--serve-remote` is passed.
+    #[arg(long, value_name = "ADDRESS", value_hint = ValueHint::Other)]
+    pub serve: Option<std::net::SocketAddr>,
+
+    /// Allow `--serve` to listen on an address which is not a loopback address
+    ///
+    /// The token is sent in plain text, so anyone on the network between the client
+    /// and ferrishot can read it.
+    #[arg(long, requires = "serve")]
+    pub serve_remote: bool,
+
+    /// Add ferrishot to the context menu of the Explorer, or remove it
+    ///
+    /// Right-clicking the desktop shows "Capture region with ferrishot",
+    /// and right-clicking an image shows "Crop with ferrishot"
+    #[cfg(windows)]
+    #[arg(long, value_name = "ACTION")]
+    pub integrate: Option<crate::integrate::Integration>,
+
     //
     // --- Config ---
     //
@@ -124,11 +290,32 @@ pub struct Cli {
         short = 'C',
         long,
         value_name = "FILE.KDL",
+        global = true,
         default_value_t = DEFAULT_CONFIG_FILE_PATH.to_string_lossy().to_string(),
         value_hint = ValueHint::FilePath
     )]
     pub config_file: String,
 
+    /// Keep the config, logs and everything else ferrishot saves next to the executable
+    ///
+    /// Files are kept in a `ferrishot-data` directory next to the executable, instead of the
+    /// usual directories of the platform. Useful to carry ferrishot around on a USB stick.
+    ///
+    /// Can also be enabled by setting the `FERRISHOT_PORTABLE` environment variable.
+    #[arg(help_heading = "Config", long)]
+    pub portable: bool,
+
+    /// Save the keybindings cheatsheet as an image, to print or share it
+    ///
+    /// The format is chosen from the extension, like `.png`
+    #[arg(
+        help_heading = "Config",
+        long,
+        value_name = "PATH",
+        value_hint = ValueHint::FilePath
+    )]
+    pub export_cheatsheet: Option<PathBuf>,
+
     //
     // --- Output
     //
@@ -145,6 +332,19 @@ pub struct Cli {
     #[arg(help_heading = "Output", short, long, conflicts_with = "silent")]
     pub json: bool,
 
+    /// Write the screenshot to standard output as PNG, instead of saving it to a file
+    ///
+    /// Saves the screenshot as soon as a region is selected, unless `--accept-on-select` says otherwise.
+    /// Nothing else is printed. Together with `--region`, this works like `maim -g <WxH+X+Y>`:
+    ///
+    /// `ferrishot --raw -g 300x200+0+0 > screenshot.png`
+    #[arg(
+        help_heading = "Output",
+        long,
+        conflicts_with_all = ["save_path", "json"]
+    )]
+    pub raw: bool,
+
     //
     // --- Debug ---
     //
@@ -191,6 +391,14 @@ pub struct Cli {
     )]
     pub log_filter: Option<String>,
 
+    /// Write a file with logs and system information to the data directory, to attach to bug reports
+    #[arg(help_heading = "Debug", long)]
+    pub bug_report: bool,
+
+    /// Explain an error shown in the app by its code, like `FER-0001`, and how to fix it
+    #[arg(help_heading = "Debug", long, value_name = "CODE", value_hint = ValueHint::Other)]
+    pub explain: Option<crate::error_code::ErrorCode>,
+
     /// Launch in debug mode (F12)
     #[arg(
         help_heading = "Debug",
@@ -200,24 +408,116 @@ pub struct Cli {
     pub debug: bool,
 }
 
+impl Cli {
+    /// Whether the screenshot is saved to a file picked inside of the app,
+    /// because neither `--save-path` nor `--raw` say where it goes
+    pub const fn picks_save_path(&self) -> bool {
+        self.save_path.is_none() && !self.raw
+    }
+
+    /// Action to perform without launching a window, once the region is known
+    ///
+    /// Saving needs a window to pick where to save to, unless it is already known
+    pub fn headless_action(&self) -> Option<crate::image::action::Command> {
+        self.accept_on_select.filter(|action| {
+            *action != crate::image::action::Command::SaveScreenshot || !self.picks_save_path()
+        })
+    }
+}
+
+/// Parse the size of a window, like `1280x720`
+fn parse_window_size(s: &str) -> Result<iced::Size<u32>, String> {
+    let (width, height) = s
+        .split_once('x')
+        .ok_or_else(|| format!("invalid size `{s}`, expected `<width>x<height>`"))?;
+
+    let parse = |dimension: &str| {
+        dimension
+            .parse::<u32>()
+            .ok()
+            .filter(|dimension| *dimension > 0)
+            .ok_or_else(|| format!("invalid size `{s}`, `{dimension}` is not a positive number"))
+    };
+
+    Ok(iced::Size::new(parse(width)?, parse(height)?))
+}
+
+/// Something to do instead of taking a screenshot
+#[derive(clap::Subcommand, Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Manage the config file
+    #[command(subcommand)]
+    Config(ConfigAction),
+    /// Check the graphics, clipboard and screenshot backends for common problems
+    Doctor,
+    /// Show statistics of how ferrishot is used, collected when the `statistics` option is enabled
+    Stats,
+    /// Crop `--region` out of each of the images and save them to `--out-dir`
+    ///
+    /// The region is relative to the size of each image, so `0.5x1.0+0+0` is the left half of every image.
+    /// The cropped images have the same file name as the original.
+    Batch {
+        /// Images to crop
+        #[arg(required = true, value_hint = ValueHint::FilePath)]
+        files: Vec<PathBuf>,
+        /// Region to crop out of each image, in the same format as `ferrishot --region`
+        #[arg(short, long, value_name = "WxH+X+Y", value_hint = ValueHint::Other)]
+        region: LazyRectangle,
+        /// Directory in which to save the cropped images
+        #[arg(short, long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+        out_dir: PathBuf,
+    },
+}
+
+/// Something to do with the config file
+#[derive(clap::Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Open the config file in `$VISUAL` or `$EDITOR`, and check it for mistakes once the editor is closed
+    ///
+    /// If the config file does not exist yet, it is created with the default config first.
+    /// While the config has mistakes, the editor can be opened again to fix them.
+    Edit,
+    /// Check the config file for mistakes
+    Check {
+        /// Also check that the colors of the theme have enough contrast to be easy to see
+        ///
+        /// Text needs a contrast of at least 4.5:1 against its background, and the selection frame and
+        /// icons at least 3:1, as recommended by WCAG. Translucent colors are checked on top of both a white
+        /// and a black screen. A safer color is suggested for each pair which does not have enough contrast.
+        #[arg(long)]
+        contrast: bool,
+    },
+}
+
 /// Represents the default location of the config file
 static DEFAULT_CONFIG_FILE_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
-    etcetera::choose_base_strategy().map_or_else(
-        |err| {
-            log::warn!("Could not determine the config directory: {err}");
-            PathBuf::from("ferrishot.kdl")
-        },
-        |strategy| strategy.config_dir().join("ferrishot.kdl"),
-    )
+    crate::paths::config_file().unwrap_or_else(|err| {
+        log::warn!("Could not determine the config directory: {err}");
+        PathBuf::from(crate::paths::CONFIG_FILENAME)
+    })
 });
 
 /// Represents the default location of the config file
 pub static DEFAULT_LOG_FILE_PATH: LazyLock<PathBuf> = LazyLock::new(|| {
-    etcetera::choose_base_strategy().map_or_else(
-        |err| {
-            log::warn!("Could not determine the config directory: {err}");
-            PathBuf::from("ferrishot.log")
-        },
-        |strategy| strategy.cache_dir().join("ferrishot.log"),
-    )
+    crate::paths::log_file().unwrap_or_else(|err| {
+        log::warn!("Could not determine the cache directory: {err}");
+        PathBuf::from(crate::paths::LOG_FILENAME)
+    })
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn window_size() {
+        assert_eq!(
+            parse_window_size("1280x720"),
+            Ok(iced::Size::new(1280, 720))
+        );
+        assert!(parse_window_size("1280").is_err(), "missing height");
+        assert!(parse_window_size("0x720").is_err(), "window can't be empty");
+        assert!(parse_window_size("-5x720").is_err(), "negative width");
+    }
+}
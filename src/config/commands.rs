@@ -7,7 +7,7 @@
 //! - Add the module to the `declare_global_commands!` macro invocation below
 //! - Implement the [`crate::command::Handler`] trait for the `Command` generated by `declare_commands!`.
 
-use super::key_map::{KeyMods, KeySequence};
+use super::key_map::Binding;
 use crate::ui;
 use ui::popup::keybindings_cheatsheet;
 
@@ -54,6 +54,14 @@ pub trait CommandHandler {
 /// ```no_compile
 /// Key::Keybind(10, false, 0.8, "hello", KeySequence("g", None), KeyMods::CTRL)
 /// ```
+///
+/// Every keybinding can also have a description, which is shown in the keybindings cheatsheet:
+///
+/// ```kdl
+/// keys {
+///   keybind 10 #false 0.8 hello key=g mods=ctrl desc="Say hello"
+/// }
+/// ```
 #[macro_export]
 macro_rules! declare_commands {
     (
@@ -82,6 +90,10 @@ macro_rules! declare_commands {
                 keys: $crate::config::key_map::KeySequence,
                 #[ferrishot_knus(default, property(name = "mod"), str)]
                 mods: $crate::config::key_map::KeyMods,
+                #[ferrishot_knus(default, property(name = "layer"))]
+                layer: Option<String>,
+                #[ferrishot_knus(default, property(name = "desc"))]
+                desc: Option<String>,
             }
         )*
 
@@ -89,7 +101,7 @@ macro_rules! declare_commands {
         ///
         /// See [`Command`](crate::config::commands::Command) for more info.
         #[allow(clippy::derive_partial_eq_without_eq, reason = "f32 cannot derive `Eq`")]
-        #[derive(Debug, Clone, PartialEq, Copy)]
+        #[derive(Debug, Clone, PartialEq)]
         $(#[$Command_Attr])*
         pub enum $Command {
             $(
@@ -104,6 +116,17 @@ macro_rules! declare_commands {
             )*
         }
 
+        impl $Command {
+            /// Name of the command, such as `CopyToClipboard`
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$Keymappable_Command { .. } => stringify!($Keymappable_Command),
+                    )*
+                }
+            }
+        }
+
         /// Parses the corresponding commands in the KDL file.
         ///
         /// See [`KeymappableCommand`](crate::config::commands::KeymappableCommand) for more info.
@@ -117,9 +140,11 @@ macro_rules! declare_commands {
         impl KeymappableCommand {
             /// # Returns
             ///
-            /// The keys necessary to trigger the `Command`, as well as the `Command` itself.
-            /// This is a key-value pair which will be stored in the `KeyMap`.
-            pub fn action(self) -> (($crate::config::key_map::KeySequence, $crate::config::key_map::KeyMods), Command) {
+            /// The keys (and the layer they are in) necessary to trigger the `Command`, as well as
+            /// the `Command` itself. This is a key-value pair which will be stored in the `KeyMap`.
+            ///
+            /// Also the description of the keybinding, if the user wrote one
+            pub fn action(self) -> ($crate::config::key_map::Binding, Command, Option<String>) {
                 match self {
                     $(
                         Self::$Keymappable_Command($Keymappable_Command {
@@ -127,13 +152,16 @@ macro_rules! declare_commands {
                                 $($Command_Argument,)*
                             )?
                             keys,
-                            mods
+                            mods,
+                            layer,
+                            desc
                         }) => {
                             (
-                                (keys, mods),
+                                (keys, mods, layer),
                                 Command::$Keymappable_Command$({
                                     $($Command_Argument),*
-                                })?
+                                })?,
+                                desc
                             )
                         },
                     )*
@@ -167,6 +195,17 @@ macro_rules! declare_global_commands {
             )*
         }
 
+        impl $CommandIdent {
+            /// Name of the command, such as `CopyToClipboard`
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $(
+                        Self::$EnumVariant(cmd) => cmd.name(),
+                    )*
+                }
+            }
+        }
+
         impl $crate::command::Handler for $CommandIdent {
             fn handle(self, app: &mut $crate::App, count: u32) -> iced::Task<$crate::Message> {
                 match self {
@@ -187,13 +226,13 @@ macro_rules! declare_global_commands {
         }
 
         impl $EnumIdent {
-            /// Key sequence required for this command
-            pub fn action(self) -> ((KeySequence, KeyMods), Command) {
+            /// Key sequence required for this command, and its description
+            pub fn action(self) -> (Binding, Command, Option<String>) {
                 match self {
                     $(
                         Self::$EnumVariant(cmd) => {
-                            let (keys, cmd) = cmd.action();
-                            (keys, $CommandIdent::$EnumVariant(cmd))
+                            let (keys, cmd, desc) = cmd.action();
+                            (keys, $CommandIdent::$EnumVariant(cmd), desc)
                         },
                     )*
                 }
@@ -211,7 +250,7 @@ declare_global_commands! {
     /// payload representing the `Command` that we invoked, as well as the curretn `count`
     /// which lets the user input a number before running a command, which will execute it
     /// that many times. For instance, `200j` executes whatever is bound to `j` 200 times.
-    #[derive(Debug, Clone)]
+    #[derive(Debug, Clone, PartialEq)]
     enum Command,
 
     /// This is the "raw" command, we get a `Vec` of it when we read the KDL config file.
@@ -228,14 +267,28 @@ declare_global_commands! {
     enum KeymappableCommand {
         /// Image Upload
         ImageUpload(crate::image::action),
+        /// Adjustments of brightness, contrast and saturation
+        Adjust(crate::image::adjust),
+        /// Blurring and pixelating parts of the image
+        Redact(crate::image::redact),
+        /// Drawing with a pen or highlighter
+        Annotate(crate::image::annotate),
         /// App
         App(ui::app),
+        /// Undo and redo
+        History(ui::history),
         /// Debug overlay
         DebugOverlay(ui::debug_overlay),
         /// Keybindings Cheatsheet
         KeybindingsCheatsheet(keybindings_cheatsheet),
+        /// Key macros
+        KeyMacros(ui::key_macros),
         /// Letters
         Letters(ui::popup::letters),
+        /// Pixel inspector
+        PixelInspector(ui::popup::pixel_inspector),
+        /// Settings
+        Settings(ui::popup::settings),
         /// Selection
         Selection(ui::selection),
     }
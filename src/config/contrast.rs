@@ -0,0 +1,395 @@
+//! Check that the colors of the theme are easy to tell apart
+//!
+//! Contrast is measured as defined by WCAG 2, from `1:1` for identical colors to `21:1` for
+//! black on white. Text needs at least `4.5:1`, and other elements like the selection
+//! frame need at least `3:1`.
+//!
+//! The screenshot behind the UI can be anything, so translucent colors are checked on top
+//! of both a white and a black screen, and the lower contrast of the two is reported.
+
+use std::fmt::{self, Write as _};
+
+use iced::Color;
+
+use super::Theme;
+
+/// Minimum contrast of text against its background
+const TEXT_CONTRAST: f32 = 4.5;
+
+/// Minimum contrast of elements which are not text, like the selection frame
+const GRAPHICS_CONTRAST: f32 = 3.0;
+
+/// Screens on top of which translucent colors are checked
+const SCREENS: [Color; 2] = [Color::WHITE, Color::BLACK];
+
+/// A color of the theme, with the key it is set by
+type Key = (&'static str, Color);
+
+/// Two colors of the theme which are shown on top of each other
+struct Pair {
+    /// Color in the front
+    fg: Key,
+    /// Color behind `fg`, or `None` if `fg` is shown directly on top of the screenshot
+    bg: Option<Key>,
+    /// Minimum contrast between them
+    required: f32,
+}
+
+impl Pair {
+    /// Text with color `fg` shown on top of `bg`
+    const fn text(fg: Key, bg: Key) -> Self {
+        Self {
+            fg,
+            bg: Some(bg),
+            required: TEXT_CONTRAST,
+        }
+    }
+
+    /// Whether the `fg` color on top of the `bg` color has enough contrast
+    fn passes(&self, fg: Color, bg: Color) -> bool {
+        worst_contrast(fg, bg) >= self.required
+    }
+
+    /// Change to the colors of the pair which gives it enough contrast, as it would be
+    /// written in the config
+    fn suggestion(&self) -> Option<String> {
+        let (fg_key, fg) = self.fg;
+
+        let Some((bg_key, bg)) = self.bg else {
+            // shown on top of any screen, so only a color in the middle can work
+            return (1..=10)
+                .map(|step| mix(fg, Color::from_rgb8(0x77, 0x77, 0x77), step as f32 / 10.0))
+                .find(|fg| self.passes(*fg, Color::TRANSPARENT))
+                .map(|fg| format!("{fg_key} {}", kdl_color(fg)));
+        };
+
+        // a more opaque background hides the screen behind it
+        let more_opaque = (1..=20)
+            .map(|step| step as f32 / 20.0)
+            .filter(|opacity| *opacity > bg.a)
+            .map(|opacity| Color { a: opacity, ..bg })
+            .find(|bg| self.passes(fg, *bg))
+            .map(|bg| format!("{bg_key} {}", kdl_color(bg)));
+
+        // black or white text
+        let black_or_white = || {
+            [Color::BLACK, Color::WHITE]
+                .into_iter()
+                .filter(|candidate| *candidate != Color { a: 1.0, ..fg })
+                .find(|candidate| self.passes(*candidate, bg))
+                .map(|fg| format!("{fg_key} {}", kdl_color(fg)))
+        };
+
+        // an opaque background, darker for light text and lighter for dark text
+        let further_from_fg = || {
+            let target = if contrast(fg, Color::BLACK) > contrast(fg, Color::WHITE) {
+                Color::BLACK
+            } else {
+                Color::WHITE
+            };
+
+            (1..=10)
+                .map(|step| mix(Color { a: 1.0, ..bg }, target, step as f32 / 10.0))
+                .find(|bg| self.passes(fg, *bg))
+                .map(|bg| format!("{bg_key} {}", kdl_color(bg)))
+        };
+
+        more_opaque.or_else(black_or_white).or_else(further_from_fg)
+    }
+}
+
+/// Pairs of colors of the `theme` which must have enough contrast
+fn pairs(theme: &Theme) -> [Pair; 15] {
+    [
+        Pair {
+            fg: ("selection-frame", theme.selection_frame),
+            bg: None,
+            required: GRAPHICS_CONTRAST,
+        },
+        Pair {
+            fg: ("icon-fg", theme.icon_fg),
+            bg: Some(("icon-bg", theme.icon_bg)),
+            required: GRAPHICS_CONTRAST,
+        },
+        Pair::text(
+            ("size-indicator-fg", theme.size_indicator_fg),
+            ("size-indicator-bg", theme.size_indicator_bg),
+        ),
+        Pair::text(
+            ("dimensions-label-fg", theme.dimensions_label_fg),
+            ("dimensions-label-bg", theme.dimensions_label_bg),
+        ),
+        Pair::text(
+            ("tooltip-fg", theme.tooltip_fg),
+            ("tooltip-bg", theme.tooltip_bg),
+        ),
+        Pair::text(("error-fg", theme.error_fg), ("error-bg", theme.error_bg)),
+        Pair::text(
+            ("warning-fg", theme.warning_fg),
+            ("warning-bg", theme.warning_bg),
+        ),
+        Pair::text(
+            ("info-box-fg", theme.info_box_fg),
+            ("info-box-bg", theme.info_box_bg),
+        ),
+        Pair::text(
+            ("letters-fg", theme.letters_fg),
+            ("letters-bg", theme.letters_bg),
+        ),
+        Pair::text(
+            ("image-uploaded-fg", theme.image_uploaded_fg),
+            ("image-uploaded-bg", theme.image_uploaded_bg),
+        ),
+        Pair::text(
+            ("pixel-inspector-fg", theme.pixel_inspector_fg),
+            ("pixel-inspector-bg", theme.pixel_inspector_bg),
+        ),
+        Pair::text(
+            ("file-picker-fg", theme.file_picker_fg),
+            ("file-picker-bg", theme.file_picker_bg),
+        ),
+        Pair::text(
+            ("permission-fg", theme.permission_fg),
+            ("permission-bg", theme.permission_bg),
+        ),
+        Pair::text(
+            ("settings-fg", theme.settings_fg),
+            ("settings-bg", theme.settings_bg),
+        ),
+        Pair::text(
+            ("cheatsheet-fg", theme.cheatsheet_fg),
+            ("cheatsheet-bg", theme.cheatsheet_bg),
+        ),
+    ]
+}
+
+/// Result of checking a single pair of colors
+struct Outcome {
+    /// Name of the pair
+    name: String,
+    /// Lowest contrast of the pair, on top of any screen
+    contrast: f32,
+    /// Minimum contrast the pair needs
+    required: f32,
+    /// How to fix the pair, if it does not have enough contrast
+    suggestion: Option<String>,
+}
+
+/// Contrast of every pair of colors of the theme
+pub struct Report {
+    /// Outcome of each pair
+    outcomes: Vec<Outcome>,
+}
+
+impl Report {
+    /// How many pairs of colors do not have enough contrast
+    pub fn failures(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.contrast < outcome.required)
+            .count()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for outcome in &self.outcomes {
+            let label = if outcome.contrast < outcome.required {
+                "fail"
+            } else {
+                "ok"
+            };
+
+            let mut message = format!("{:.1}:1", outcome.contrast);
+            if outcome.contrast < outcome.required {
+                let _ = write!(message, ", needs {}:1", outcome.required);
+
+                if let Some(suggestion) = &outcome.suggestion {
+                    let _ = write!(message, ". Try `{suggestion}`");
+                }
+            }
+
+            writeln!(f, "[{label:>4}] {:<40} {message}", outcome.name)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check the contrast of the colors of the `theme`
+pub fn check(theme: &Theme) -> Report {
+    let outcomes = pairs(theme)
+        .into_iter()
+        .map(|pair| {
+            let (fg_key, fg) = pair.fg;
+            let contrast = worst_contrast(fg, pair.bg.map_or(Color::TRANSPARENT, |(_, bg)| bg));
+
+            Outcome {
+                name: pair.bg.map_or_else(
+                    || format!("{fg_key} on the screenshot"),
+                    |(bg_key, _)| format!("{fg_key} on {bg_key}"),
+                ),
+                contrast,
+                required: pair.required,
+                suggestion: (contrast < pair.required)
+                    .then(|| pair.suggestion())
+                    .flatten(),
+            }
+        })
+        .collect();
+
+    Report { outcomes }
+}
+
+/// Relative luminance of the `color`, ignoring its opacity.
+/// `0.0` is the darkest black and `1.0` is the lightest white
+pub fn relative_luminance(color: Color) -> f32 {
+    let [r, g, b] = [color.r, color.g, color.b].map(|channel| {
+        if channel <= 0.040_45 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    });
+
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// Contrast ratio between two opaque colors, from `1.0` to `21.0`
+pub fn contrast(a: Color, b: Color) -> f32 {
+    let [darker, lighter] = {
+        let mut luminances = [relative_luminance(a), relative_luminance(b)];
+        luminances.sort_by(f32::total_cmp);
+        luminances
+    };
+
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+/// Lowest contrast of `fg` on top of `bg`, when both are on top of any of the `SCREENS`
+fn worst_contrast(fg: Color, bg: Color) -> f32 {
+    SCREENS
+        .into_iter()
+        .map(|screen| {
+            let bg = over(bg, screen);
+            contrast(over(fg, bg), bg)
+        })
+        .min_by(f32::total_cmp)
+        .unwrap_or(1.0)
+}
+
+/// The opaque color seen when `color` is drawn on top of the opaque `backdrop`
+fn over(color: Color, backdrop: Color) -> Color {
+    Color::from_rgb(
+        color.r * color.a + backdrop.r * (1.0 - color.a),
+        color.g * color.a + backdrop.g * (1.0 - color.a),
+        color.b * color.a + backdrop.b * (1.0 - color.a),
+    )
+}
+
+/// Move the `color` towards the `target` by `amount`, keeping its opacity
+fn mix(color: Color, target: Color, amount: f32) -> Color {
+    Color {
+        a: color.a,
+        ..over(
+            Color {
+                a: amount,
+                ..target
+            },
+            color,
+        )
+    }
+}
+
+/// The `color` as it is written in the config
+fn kdl_color(color: Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    let hex = format!("0x{r:02x}_{g:02x}_{b:02x}");
+
+    if color.a < 1.0 {
+        format!("{hex} opacity={}", (color.a * 100.0).round() / 100.0)
+    } else {
+        hex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn measures_contrast() {
+        let is_about = |contrast: f32, expected: f32| (contrast - expected).abs() < 0.01;
+
+        assert!(
+            is_about(contrast(Color::BLACK, Color::WHITE), 21.0),
+            "black on white has the highest contrast"
+        );
+        assert!(
+            is_about(contrast(Color::WHITE, Color::BLACK), 21.0),
+            "order of the colors does not matter"
+        );
+        assert!(
+            is_about(contrast(Color::WHITE, Color::WHITE), 1.0),
+            "identical colors have no contrast"
+        );
+        assert!(
+            is_about(
+                contrast(Color::from_rgb8(0x77, 0x77, 0x77), Color::WHITE),
+                4.48
+            ),
+            "gray on white"
+        );
+    }
+
+    #[test]
+    fn translucent_colors_are_checked_on_any_screen() {
+        let half_black = Color {
+            a: 0.5,
+            ..Color::BLACK
+        };
+
+        assert!(
+            worst_contrast(Color::WHITE, half_black) < TEXT_CONTRAST,
+            "white text on a translucent black background is unreadable on a white screen"
+        );
+        assert!(
+            worst_contrast(Color::WHITE, Color::BLACK) > 20.0,
+            "an opaque background hides the screen"
+        );
+    }
+
+    #[test]
+    fn suggestions_pass() {
+        let pair = Pair::text(
+            ("error-fg", Color::WHITE),
+            (
+                "error-bg",
+                Color {
+                    a: 0.6,
+                    ..Color::from_rgb8(0xff, 0x00, 0x00)
+                },
+            ),
+        );
+
+        assert!(
+            !pair.passes(Color::WHITE, pair.bg.unwrap().1),
+            "translucent red is too light on a white screen"
+        );
+        let suggestion = pair.suggestion().expect("a darker red is readable");
+        assert!(
+            suggestion.starts_with("error-bg 0x") && !suggestion.contains("opacity"),
+            "an opaque, darker background: {suggestion}"
+        );
+    }
+
+    #[test]
+    fn writes_colors_like_the_config() {
+        assert_eq!(kdl_color(Color::from_rgb8(0xab, 0x61, 0x37)), "0xab_61_37");
+        assert_eq!(
+            kdl_color(Color::from_rgba8(0, 0, 0, 0.5)),
+            "0x00_00_00 opacity=0.5"
+        );
+    }
+}
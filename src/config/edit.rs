@@ -0,0 +1,124 @@
+//! Edit the config file in the editor of the user, with `ferrishot config edit`
+
+use std::{
+    env, fs, io,
+    path::Path,
+    process::{Command, ExitStatus},
+};
+
+/// Could not edit the config file
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Failed to write the default config
+    #[error("Failed to create {path}: {err}")]
+    Create {
+        /// Path to the config file
+        path: String,
+        /// Why it could not be created
+        err: io::Error,
+    },
+    /// The command of the editor is empty
+    #[error("The editor is empty. Set `$VISUAL` or `$EDITOR` to the command of your editor")]
+    NoEditor,
+    /// Failed to launch the editor
+    #[error("Failed to launch the editor `{editor}`: {err}")]
+    Launch {
+        /// Command of the editor
+        editor: String,
+        /// Why it could not be launched
+        err: io::Error,
+    },
+    /// The editor exited with an error
+    #[error("The editor `{editor}` exited with {status}")]
+    Failed {
+        /// Command of the editor
+        editor: String,
+        /// How the editor exited
+        status: ExitStatus,
+    },
+}
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const FALLBACK_EDITOR: &str = if cfg!(windows) { "notepad" } else { "vi" };
+
+/// Write the default config to `path`, creating its directory if needed
+pub fn write_default(path: &Path) -> Result<(), Error> {
+    let create = || -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(path, super::DEFAULT_KDL_CONFIG_STR)
+    };
+
+    create().map_err(|err| Error::Create {
+        path: path.display().to_string(),
+        err,
+    })
+}
+
+/// Command which opens the editor of the user, as set by `$VISUAL` or `$EDITOR`
+pub fn editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .find_map(|var| {
+            env::var(var)
+                .ok()
+                .filter(|editor| !editor.trim().is_empty())
+        })
+        .unwrap_or_else(|| FALLBACK_EDITOR.to_string())
+}
+
+/// Open the config file at `path` in the `editor`, and wait until it is closed.
+///
+/// The `editor` can contain arguments, like `code --wait`
+pub fn open(editor: &str, path: &Path) -> Result<(), Error> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or(Error::NoEditor)?;
+
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|err| Error::Launch {
+            editor: editor.to_string(),
+            err,
+        })?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Failed {
+            editor: editor.to_string(),
+            status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn writes_default_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("ferrishot.kdl");
+
+        write_default(&path).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            super::super::DEFAULT_KDL_CONFIG_STR,
+            "the directory is created"
+        );
+    }
+
+    #[test]
+    fn empty_editor() {
+        assert!(
+            matches!(open("  ", Path::new("ferrishot.kdl")), Err(Error::NoEditor)),
+            "nothing to launch"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! The selection that ferrishot starts with, when neither `--region` nor `--last-region` are passed
+
+use std::str::FromStr;
+
+use ferrishot_knus::{DecodeScalar, ast::Literal, errors::DecodeError, traits::ErrorSpan};
+use iced::Rectangle;
+
+use crate::lazy_rect::{LazyRectangle, ParseRectError};
+
+/// The selection that ferrishot starts with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InitialSelection {
+    /// Start without a selection
+    None,
+    /// Select the entire screen
+    FullScreen,
+    /// Select the region used the last time ferrishot was run
+    LastRegion,
+    /// Select this region, in the same format as `--region`
+    Region(LazyRectangle),
+}
+
+impl FromStr for InitialSelection {
+    type Err = ParseRectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "none" => Self::None,
+            "full-screen" => Self::FullScreen,
+            "last-region" => Self::LastRegion,
+            region => Self::Region(region.parse()?),
+        })
+    }
+}
+
+impl<S: ErrorSpan> DecodeScalar<S> for InitialSelection {
+    fn type_check(
+        _type_name: &Option<ferrishot_knus::span::Spanned<ferrishot_knus::ast::TypeName, S>>,
+        _ctx: &mut ferrishot_knus::decode::Context<S>,
+    ) {
+    }
+
+    fn raw_decode(
+        value: &ferrishot_knus::span::Spanned<Literal, S>,
+        ctx: &mut ferrishot_knus::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        match &**value {
+            Literal::String(s) => match s.parse() {
+                Ok(selection) => Ok(selection),
+                Err(err) => {
+                    ctx.emit_error(DecodeError::conversion(value, err));
+                    Ok(Self::None)
+                }
+            },
+            _ => {
+                ctx.emit_error(DecodeError::scalar_kind(
+                    ferrishot_knus::decode::Kind::String,
+                    value,
+                ));
+                Ok(Self::None)
+            }
+        }
+    }
+}
+
+impl InitialSelection {
+    /// The selected region inside of the `bounds` of the image
+    pub fn init(self, bounds: Rectangle) -> Option<Rectangle> {
+        match self {
+            Self::None => None,
+            Self::FullScreen => Some(bounds),
+            Self::LastRegion => crate::last_region::read(bounds)
+                .inspect_err(|err| log::warn!("Failed to read the last region: {err}"))
+                .ok()
+                .flatten(),
+            Self::Region(region) => Some(region.init(bounds)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse() {
+        assert_eq!("none".parse(), Ok(InitialSelection::None));
+        assert_eq!("full-screen".parse(), Ok(InitialSelection::FullScreen));
+        assert_eq!("last-region".parse(), Ok(InitialSelection::LastRegion));
+        assert_eq!(
+            "full".parse(),
+            Ok(InitialSelection::Region(LazyRectangle::FULL))
+        );
+        assert!(
+            "everything".parse::<InitialSelection>().is_err(),
+            "not a region"
+        );
+    }
+}
@@ -1,7 +1,8 @@
 //! Parse user keybindings
 
 use crate::config::named_key::Named;
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, fmt, str::FromStr};
+use strum::IntoEnumIterator as _;
 
 use iced::{
     advanced::debug::core::SmolStr,
@@ -10,11 +11,19 @@ use iced::{
 
 use super::commands::{Command, KeymappableCommand};
 
+/// Keys and modifiers that trigger a `Command`, and the layer it is in.
+///
+/// Keybindings without a layer are always active. Keybindings in a layer are only
+/// active while that layer is, and take priority over the ones without a layer.
+pub type Binding = (KeySequence, KeyMods, Option<String>);
+
 /// Represents the keybindings for ferrishot
 #[derive(Debug, Default)]
 pub struct KeyMap {
     /// Map of Key Pressed => Action when pressing that key
-    pub keys: HashMap<(KeySequence, KeyMods), Command>,
+    pub keys: HashMap<Binding, Command>,
+    /// Descriptions of the keybindings, which the user wrote with `desc="..."`
+    pub descriptions: HashMap<Binding, String>,
 }
 
 impl KeyMap {
@@ -32,15 +41,184 @@ impl KeyMap {
     /// adds too much unnecessary complexity whilst probably being slower. (We have to go
     /// through the V-Table lookup, as it requires using dynamic dispatch)
     ///
+    /// Keybindings in the active `layer` take priority
     pub fn get(
         &self,
         key: IcedKey,
         previous_key: Option<IcedKey>,
         mods: Modifiers,
+        layer: Option<&str>,
     ) -> Option<&Command> {
+        let sequence = KeySequence((key, previous_key));
+        let mods = KeyMods(mods);
+
+        layer
+            .and_then(|layer| {
+                self.keys
+                    .get(&(sequence.clone(), mods.clone(), Some(layer.to_owned())))
+            })
+            .or_else(|| self.keys.get(&(sequence, mods, None)))
+    }
+
+    /// Whether the `key` is the first of a sequence of 2 keys, like the `g` of `gg`
+    pub fn starts_sequence(&self, key: &IcedKey) -> bool {
         self.keys
-            .get(&(KeySequence((key, previous_key)), KeyMods(mods)))
+            .keys()
+            .any(|(KeySequence((first, second)), _, _)| first == key && second.is_some())
+    }
+
+    /// Keybindings which have a description, as the keys to press, the `Command` and the
+    /// description. Sorted by the keys
+    pub fn described(&self) -> Vec<(String, &Command, &str)> {
+        let mut described = self
+            .descriptions
+            .iter()
+            .filter_map(|(binding, description)| {
+                let command = self.keys.get(binding)?;
+                Some((keys_to_press(binding), command, description.as_str()))
+            })
+            .collect::<Vec<_>>();
+
+        described.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+        described
+    }
+
+    /// Keybindings of 2 keys which can never be triggered, because the first key
+    /// already triggers another keybinding. Sorted by the keys
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = self
+            .keys
+            .iter()
+            .filter_map(|(binding, command)| {
+                let (KeySequence((first_key, Some(_))), mods, layer) = binding else {
+                    return None;
+                };
+
+                let prefix = (
+                    KeySequence((first_key.clone(), None)),
+                    mods.clone(),
+                    layer.clone(),
+                );
+                let winner = self.keys.get(&prefix)?;
+
+                Some(Conflict::Shadowed {
+                    prefix: keys_to_press(&prefix),
+                    winner: kdl_name(winner),
+                    keys: keys_to_press(binding),
+                    loser: kdl_name(command),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        conflicts.sort_by_key(ToString::to_string);
+
+        conflicts
+    }
+}
+
+/// Two keybindings which can't both be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conflict {
+    /// The same keys are bound twice, so only the `winner` which comes later is used
+    Duplicate {
+        /// Keys of both keybindings
+        keys: String,
+        /// Name of the command that is no longer bound
+        loser: String,
+        /// Name of the command that the keys trigger
+        winner: String,
+    },
+    /// Pressing the first of the `keys` already triggers the `winner`,
+    /// so the `loser` is never triggered
+    Shadowed {
+        /// The first of the `keys`, which triggers the `winner`
+        prefix: String,
+        /// Name of the command that the `prefix` triggers
+        winner: String,
+        /// Keys of the keybinding that is never triggered
+        keys: String,
+        /// Name of the command that is never triggered
+        loser: String,
+    },
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Duplicate {
+                keys,
+                loser,
+                winner,
+            } => write!(
+                f,
+                "`{keys}` is bound to both {loser} and {winner}. Only {winner} is used"
+            ),
+            Self::Shadowed {
+                prefix,
+                winner,
+                keys,
+                loser,
+            } => write!(
+                f,
+                "`{keys}` ({loser}) is never triggered, because `{prefix}` ({winner}) is triggered first"
+            ),
+        }
+    }
+}
+
+/// Keys which are bound more than once in the same list of keybindings.
+/// The keybinding which comes last is used
+pub fn duplicates(keys: &[KeymappableCommand]) -> Vec<Conflict> {
+    let mut bound = HashMap::<Binding, Command>::new();
+    let mut conflicts = vec![];
+
+    for command in keys.iter().cloned() {
+        let (binding, command, _) = command.action();
+
+        if let Some(previous) = bound.get(&binding)
+            && *previous != command
+        {
+            conflicts.push(Conflict::Duplicate {
+                keys: keys_to_press(&binding),
+                loser: kdl_name(previous),
+                winner: kdl_name(&command),
+            });
+        }
+
+        bound.insert(binding, command);
     }
+
+    conflicts
+}
+
+/// Name of the `command` as it is written in the config, such as `copy-to-clipboard`
+fn kdl_name(command: &Command) -> String {
+    command
+        .name()
+        .chars()
+        .enumerate()
+        .fold(String::new(), |mut name, (i, ch)| {
+            if i > 0 && ch.is_ascii_uppercase() {
+                name.push('-');
+            }
+            name.push(ch.to_ascii_lowercase());
+            name
+        })
+}
+
+/// The keys which trigger the `binding`, as they are shown to the user
+fn keys_to_press((keys, mods, layer): &Binding) -> String {
+    let mut keys = if mods.0.is_empty() {
+        keys.to_string()
+    } else {
+        format!("{mods}+{keys}")
+    };
+    if let Some(layer) = layer {
+        keys = format!("{keys} ({layer})");
+    }
+
+    keys
 }
 
 /// Keybindings for ferrishot
@@ -53,9 +231,22 @@ pub struct Keys {
 
 impl FromIterator<KeymappableCommand> for KeyMap {
     fn from_iter<T: IntoIterator<Item = KeymappableCommand>>(iter: T) -> Self {
-        Self {
-            keys: iter.into_iter().map(KeymappableCommand::action).collect(),
+        let mut key_map = Self::default();
+
+        for command in iter {
+            let (binding, command, description) = command.action();
+
+            // a keybinding which overrides another one does not keep its description
+            if let Some(description) = description {
+                key_map.descriptions.insert(binding.clone(), description);
+            } else {
+                key_map.descriptions.remove(&binding);
+            }
+
+            key_map.keys.insert(binding, command);
         }
+
+        key_map
     }
 }
 
@@ -69,10 +260,46 @@ impl FromIterator<KeymappableCommand> for KeyMap {
 #[derive(Debug, Hash, PartialEq, PartialOrd, Ord, Eq, Clone)]
 pub struct KeySequence(pub (IcedKey, Option<IcedKey>));
 
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (first_key, second_key) = &self.0;
+
+        for key in std::iter::once(first_key).chain(second_key) {
+            match key {
+                IcedKey::Named(named) => match Named::iter().find(|key| key.to_iced() == *named) {
+                    Some(key) => write!(f, "<{}>", <&str>::from(key))?,
+                    None => write!(f, "<{named:?}>")?,
+                },
+                IcedKey::Character(ch) => write!(f, "{ch}")?,
+                IcedKey::Unidentified => write!(f, "<unidentified>")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Modifier keys
 #[derive(Debug, Default, Clone, Hash, Eq, PartialEq)]
 pub struct KeyMods(pub Modifiers);
 
+impl fmt::Display for KeyMods {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mods = [
+            (Modifiers::CTRL, "ctrl"),
+            (Modifiers::ALT, "alt"),
+            (Modifiers::LOGO, "super"),
+            (Modifiers::SHIFT, "shift"),
+        ]
+        .into_iter()
+        .filter(|(modifier, _)| self.0.contains(*modifier))
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+
+        write!(f, "{}", mods.join("+"))
+    }
+}
+
 impl FromStr for KeyMods {
     type Err = String;
 
@@ -241,4 +468,71 @@ mod test {
             Err("Invalid key: <@>. Matching variant not found".to_string()),
         );
     }
+
+    #[test]
+    fn display_key_sequence() {
+        for input in [
+            "gh",
+            "x",
+            "<space>x",
+            "<<",
+            "<>",
+            "<esc>",
+            "g<down>",
+            "<f32><f31>",
+        ] {
+            assert_eq!(
+                input.parse::<KeySequence>().unwrap().to_string(),
+                input,
+                "{input:?} is displayed as it is written"
+            );
+        }
+    }
+
+    #[test]
+    fn find_conflicts() {
+        use crate::config::commands::Command;
+        use crate::ui::app;
+
+        let key_map = KeyMap {
+            keys: HashMap::from([
+                (
+                    ("g".parse().unwrap(), KeyMods::default(), None),
+                    Command::App(app::Command::Exit),
+                ),
+                (
+                    ("gg".parse().unwrap(), KeyMods::default(), None),
+                    Command::App(app::Command::ToggleShade),
+                ),
+                (
+                    (
+                        "gg".parse().unwrap(),
+                        KeyMods::default(),
+                        Some("adjust".to_owned()),
+                    ),
+                    Command::App(app::Command::ToggleShade),
+                ),
+            ]),
+            descriptions: HashMap::new(),
+        };
+
+        assert_eq!(
+            key_map.conflicts(),
+            vec![Conflict::Shadowed {
+                prefix: "g".to_owned(),
+                winner: "exit".to_owned(),
+                keys: "gg".to_owned(),
+                loser: "toggle-shade".to_owned(),
+            }]
+        );
+    }
+
+    #[test]
+    fn display_key_mods() {
+        assert_eq!(
+            "alt+ctrl".parse::<KeyMods>().unwrap().to_string(),
+            "ctrl+alt"
+        );
+        assert_eq!("".parse::<KeyMods>().unwrap().to_string(), "");
+    }
 }
@@ -0,0 +1,126 @@
+//! Migrate configs written for older versions of ferrishot
+//!
+//! Each config has a `version`. When an option is renamed, [`CONFIG_VERSION`] is
+//! incremented and a [`Migration`] is added to [`MIGRATIONS`]. Configs with an older
+//! `version` (or without one) are migrated before being parsed, so they keep working after upgrades.
+
+/// Version of the config format that this version of ferrishot uses
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Changes to the config made in a single version
+#[derive(Debug)]
+pub struct Migration {
+    /// Configs older than this version need the migration
+    pub version: u32,
+    /// Nodes which were renamed, from the old to the new name
+    pub renames: &'static [(&'static str, &'static str)],
+}
+
+/// Every migration, in the order they were made
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 2,
+    // `upload` read like an action, rather than whether uploading is allowed
+    renames: &[("upload", "allow-upload")],
+}];
+
+/// Version of the config, `0` if it does not specify a version
+pub fn version(config: &str) -> u32 {
+    config
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("version "))
+        .and_then(|version| version.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Apply every migration that the `config` needs.
+///
+/// Returns the migrated config and a summary of what was changed
+pub fn migrate(config: &str, migrations: &[Migration]) -> (String, Vec<String>) {
+    let version = version(config);
+    let mut summary = vec![];
+
+    let migrated = config
+        .lines()
+        .map(|line| {
+            let indent_len = line.len() - line.trim_start().len();
+            let (indent, rest) = line.split_at(indent_len);
+
+            let renamed = migrations
+                .iter()
+                .filter(|migration| version < migration.version)
+                .flat_map(|migration| migration.renames)
+                .find_map(|(old, new)| {
+                    rest.strip_prefix(old)
+                        .filter(|after| {
+                            after.is_empty() || after.starts_with([' ', '\t', '{', ';'])
+                        })
+                        .map(|after| (old, new, after))
+                });
+
+            if let Some((old, new, after)) = renamed {
+                summary.push(format!("renamed `{old}` to `{new}`"));
+                format!("{indent}{new}{after}")
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    (migrated, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Migrations used for testing
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            renames: &[("size-indicator-visible", "size-indicator")],
+        },
+        Migration {
+            version: 2,
+            renames: &[("goto-place", "goto")],
+        },
+    ];
+
+    #[test]
+    fn detect_version() {
+        assert_eq!(version("size-indicator #true"), 0);
+        assert_eq!(version("version 3\nsize-indicator #true"), 3);
+    }
+
+    #[test]
+    fn migrate_unversioned() {
+        let (migrated, summary) = migrate(
+            "size-indicator-visible #true\nkeys {\n  goto-place top-left key=gg\n}",
+            TEST_MIGRATIONS,
+        );
+
+        assert_eq!(
+            migrated,
+            "size-indicator #true\nkeys {\n  goto top-left key=gg\n}"
+        );
+        assert_eq!(summary.len(), 2);
+    }
+
+    #[test]
+    fn skip_applied_migrations() {
+        let config = "version 1\nsize-indicator-visible #true\ngoto-placement";
+        let (migrated, summary) = migrate(config, TEST_MIGRATIONS);
+
+        assert_eq!(migrated, config);
+        assert_eq!(summary, Vec::<String>::new());
+    }
+
+    #[test]
+    fn migrate_allow_upload() {
+        let (migrated, summary) = migrate("version 1\nupload #false\nuploads", MIGRATIONS);
+
+        assert_eq!(migrated, "version 1\nallow-upload #false\nuploads");
+        assert_eq!(summary, vec!["renamed `upload` to `allow-upload`"]);
+    }
+}
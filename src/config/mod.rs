@@ -17,17 +17,24 @@
 #[cfg(test)]
 mod tests;
 
+pub mod auto_exit;
 pub mod cli;
 pub mod commands;
+pub mod contrast;
+pub mod edit;
+pub mod initial_selection;
 pub mod key_map;
+mod migrations;
+pub mod mouse;
 mod named_key;
 mod options;
 mod theme;
+pub mod write;
 
 use crate::config::key_map::KeyMap;
-pub use crate::config::theme::{Color, Theme};
+pub use crate::config::theme::{Color, Preset, Theme};
 
-pub use cli::Cli;
+pub use cli::{Action, Cli, ConfigAction};
 use miette::miette;
 
 use std::fs;
@@ -35,33 +42,111 @@ use std::path::PathBuf;
 
 use options::{DefaultKdlConfig, UserKdlConfig};
 
+pub use auto_exit::AutoExitAfter;
 pub use cli::DEFAULT_LOG_FILE_PATH;
+pub use initial_selection::InitialSelection;
 pub use options::Config;
 
+/// Style of the cursor, when it would be a crosshair
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Crosshair {
+    /// The crosshair cursor of the operating system
+    Native,
+    /// Lines spanning the entire screen, intersecting at the cursor
+    Guides,
+    /// No cursor at all
+    Hidden,
+}
+
 /// The default configuration for ferrishot, to be merged with the user's config
 ///
 /// When modifying any of the config options, this will also need to be updated
 pub const DEFAULT_KDL_CONFIG_STR: &str = include_str!("../../default.kdl");
 
 impl Config {
+    /// Parse the user's config, merged into the default config.
+    ///
+    /// Returns the config along with warnings about it, such as options which were migrated.
+    /// They are for the user to fix, so it is up to the caller to show them.
+    ///
     /// # Errors
     ///
     /// Default config, or the user's config is invalid
-    pub fn parse(user_config: &str) -> Result<Self, miette::Error> {
+    pub fn parse(user_config: &str) -> Result<(Self, Vec<miette::Report>), miette::Error> {
         let config_file_path = PathBuf::from(user_config);
+        let mut warnings = vec![];
 
         let default_config =
             ferrishot_knus::parse::<DefaultKdlConfig>("<default-config>", DEFAULT_KDL_CONFIG_STR)?;
 
-        let user_config = ferrishot_knus::parse::<UserKdlConfig>(
-            &user_config,
-            // if there is no config file, act as if it's simply empty
-            &fs::read_to_string(&config_file_path).unwrap_or_default(),
-        )?;
+        // if there is no config file, act as if it's simply empty
+        let user_config_str = fs::read_to_string(&config_file_path).unwrap_or_default();
+
+        // a config written for a newer version of ferrishot might use options we don't know about
+        let version = migrations::version(&user_config_str);
+        if version > migrations::CONFIG_VERSION {
+            log::warn!("{user_config} is for a newer version of ferrishot: version {version}");
+            warnings.push(miette!(
+                severity = miette::Severity::Warning,
+                help = "Update ferrishot to use the options it does not recognize",
+                "Your config at {user_config} is for a newer version of ferrishot \
+                (`version {version}`, but this version of ferrishot supports up to `version {}`)",
+                migrations::CONFIG_VERSION
+            ));
+        }
 
-        default_config
+        // configs written for older versions of ferrishot might use options
+        // that have since been renamed
+        let (user_config_str, migrated) =
+            migrations::migrate(&user_config_str, migrations::MIGRATIONS);
+        if !migrated.is_empty() {
+            log::warn!("Migrated {user_config}: {}", migrated.join(", "));
+            warnings.push(miette!(
+                severity = miette::Severity::Warning,
+                help = format!(
+                    "Make these changes to your config, and set `version {}`",
+                    migrations::CONFIG_VERSION
+                ),
+                "Your config at {user_config} is for an older version of ferrishot. \
+                It was migrated:\n{}",
+                migrated
+                    .iter()
+                    .map(|change| format!("- {change}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ));
+        }
+
+        let user_config = ferrishot_knus::parse::<UserKdlConfig>(&user_config, &user_config_str)?;
+
+        // overriding a default keybinding is fine, but binding the same keys twice is a mistake
+        let duplicates = user_config
+            .keys
+            .as_ref()
+            .map(|keys| key_map::duplicates(&keys.keys))
+            .unwrap_or_default();
+
+        let config: Self = default_config
             .merge_user_config(user_config)
             .try_into()
-            .map_err(|err| miette!("{err}"))
+            .map_err(|err| miette!("{err}"))?;
+
+        let conflicts = duplicates
+            .into_iter()
+            .chain(config.keys.conflicts())
+            .map(|conflict| format!("- {conflict}"))
+            .collect::<Vec<_>>();
+        if !conflicts.is_empty() {
+            log::warn!("Conflicting keybindings: {}", conflicts.join(" "));
+            warnings.push(miette!(
+                severity = miette::Severity::Warning,
+                help = "Bind one of each pair of keybindings to different keys",
+                "Some keybindings in {} conflict:\n{}",
+                config_file_path.display(),
+                conflicts.join("\n")
+            ));
+        }
+
+        Ok((config, warnings))
     }
 }
@@ -0,0 +1,113 @@
+//! Configuration of what happens on mouse actions
+//!
+//! ```kdl
+//! mouse {
+//!   double-click-inside copy-to-clipboard
+//!   double-click-outside none
+//!   double-click-interval 400
+//!   click-outside none
+//!   drag-threshold 4.0
+//! }
+//! ```
+
+use std::time::Duration;
+
+/// Action to take when double-clicking
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseAction {
+    /// Do nothing
+    None,
+    /// Copy the selection to the clipboard
+    CopyToClipboard,
+    /// Save the selection to a file
+    SaveScreenshot,
+    /// Upload the selection to the internet
+    UploadScreenshot,
+    /// Select the entire screen
+    SelectFullScreen,
+    /// Remove the selection
+    ClearSelection,
+}
+
+impl MouseAction {
+    /// The `Command` to execute for this action, if any
+    pub fn command(self) -> Option<crate::Command> {
+        use crate::image::action::Command as Image;
+        use crate::ui::selection::Command as Selection;
+
+        Some(match self {
+            Self::None => return None,
+            Self::CopyToClipboard => crate::Command::ImageUpload(Image::CopyToClipboard),
+            Self::SaveScreenshot => crate::Command::ImageUpload(Image::SaveScreenshot),
+            Self::UploadScreenshot => crate::Command::ImageUpload(Image::UploadScreenshot),
+            Self::SelectFullScreen => crate::Command::Selection(Selection::SelectRegion {
+                selection: crate::lazy_rect::LazyRectangle::FULL,
+            }),
+            Self::ClearSelection => crate::Command::Selection(Selection::ClearSelection),
+        })
+    }
+}
+
+/// What happens on mouse actions
+#[derive(ferrishot_knus::Decode, Debug, Clone, Copy)]
+pub struct Mouse {
+    /// Action when double-clicking inside of the selection
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub double_click_inside: MouseAction,
+    /// Action when double-clicking outside of the selection
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub double_click_outside: MouseAction,
+    /// Maximum time between 2 clicks for them to count as a double-click, in milliseconds
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub double_click_interval: u64,
+    /// Action when clicking outside of the selection without dragging
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub click_outside: MouseAction,
+    /// How many pixels to drag outside of the selection before a new selection replaces it
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub drag_threshold: f32,
+}
+
+/// User's overrides of the mouse config. All values are optional
+#[derive(ferrishot_knus::Decode, Debug)]
+pub struct UserMouse {
+    /// Action when double-clicking inside of the selection
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub double_click_inside: Option<MouseAction>,
+    /// Action when double-clicking outside of the selection
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub double_click_outside: Option<MouseAction>,
+    /// Maximum time between 2 clicks for them to count as a double-click, in milliseconds
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub double_click_interval: Option<u64>,
+    /// Action when clicking outside of the selection without dragging
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub click_outside: Option<MouseAction>,
+    /// How many pixels to drag outside of the selection before a new selection replaces it
+    #[ferrishot_knus(child, unwrap(argument))]
+    pub drag_threshold: Option<f32>,
+}
+
+impl Mouse {
+    /// Values in the user's mouse config take priority over the default ones
+    pub fn merge_user_mouse(self, user_mouse: UserMouse) -> Self {
+        Self {
+            double_click_inside: user_mouse
+                .double_click_inside
+                .unwrap_or(self.double_click_inside),
+            double_click_outside: user_mouse
+                .double_click_outside
+                .unwrap_or(self.double_click_outside),
+            double_click_interval: user_mouse
+                .double_click_interval
+                .unwrap_or(self.double_click_interval),
+            click_outside: user_mouse.click_outside.unwrap_or(self.click_outside),
+            drag_threshold: user_mouse.drag_threshold.unwrap_or(self.drag_threshold),
+        }
+    }
+
+    /// Maximum time between 2 clicks for them to count as a double-click
+    pub const fn double_click_interval(&self) -> Duration {
+        Duration::from_millis(self.double_click_interval)
+    }
+}
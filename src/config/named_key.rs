@@ -31,7 +31,7 @@ macro_rules! named_keys {
 }
 
 named_keys! {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, strum::EnumString, strum::EnumIter)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, strum::EnumString, strum::EnumIter, strum::IntoStaticStr)]
     #[strum(serialize_all = "kebab-case")]
     #[expect(
         clippy::upper_case_acronyms,
@@ -19,6 +19,8 @@ macro_rules! declare_config_options {
             $keys:ident: $Keys:ty,
             $(#[$theme_doc:meta])*
             $theme:ident: $Theme:ty,
+            $(#[$mouse_doc:meta])*
+            $mouse:ident: $Mouse:ty,
             $(
                 $(#[$doc:meta])*
                 $key:ident: $typ:ty
@@ -31,6 +33,8 @@ macro_rules! declare_config_options {
             pub $theme: $Theme,
             $(#[$keys_doc])*
             pub $keys: $Keys,
+            $(#[$mouse_doc])*
+            pub $mouse: $Mouse,
             $(
                 $(#[$doc])*
                 pub $key: $typ,
@@ -47,6 +51,9 @@ macro_rules! declare_config_options {
             /// The default theme of ferrishot
             #[ferrishot_knus(child)]
             pub $theme: super::theme::DefaultKdlTheme,
+            /// The default mouse actions of ferrishot
+            #[ferrishot_knus(child)]
+            pub $mouse: super::mouse::Mouse,
             $(
                 $(#[$doc])*
                 #[ferrishot_knus(child, unwrap(argument))]
@@ -93,10 +100,17 @@ macro_rules! declare_config_options {
                     .keys
                     .extend(user_config.keys.unwrap_or_default().keys);
 
+                // the user's palette takes priority over the preset
+                self.theme.apply_preset(self.theme_preset);
+
                 if let Some(user_theme) = user_config.theme {
                     self.theme = self.theme.merge_user_theme(user_theme);
                 };
 
+                if let Some(user_mouse) = user_config.$mouse {
+                    self.$mouse = self.$mouse.merge_user_mouse(user_mouse);
+                };
+
                 self
             }
         }
@@ -110,6 +124,7 @@ macro_rules! declare_config_options {
                         $key: value.$key,
                     )*
                     theme: value.theme.try_into()?,
+                    $mouse: value.$mouse,
                     keys: value.keys.keys.into_iter().collect::<$crate::config::KeyMap>(),
                 })
             }
@@ -125,6 +140,9 @@ macro_rules! declare_config_options {
             /// User-defined colors
             #[ferrishot_knus(child)]
             pub theme: Option<super::theme::UserKdlTheme>,
+            /// User-defined mouse actions
+            #[ferrishot_knus(child)]
+            pub $mouse: Option<super::mouse::UserMouse>,
             $(
                 $(#[$doc])*
                 #[ferrishot_knus(child, unwrap(argument))]
@@ -142,12 +160,245 @@ crate::declare_config_options! {
         keys: super::key_map::KeyMap,
         /// Ferrishot's theme and colors
         theme: super::Theme,
+        /// What happens on mouse actions, such as double-clicking
+        mouse: super::mouse::Mouse,
+        /// Version of the config format. Older configs are migrated to the current version
+        version: u32,
+        /// Selection to start with, unless `--region` or `--last-region` is passed.
+        ///
+        /// One of `none`, `full-screen`, `last-region` or a region like `WxH+X+Y`.
+        initial_selection: super::InitialSelection,
+        /// Close ferrishot when nothing is pressed or moved for this long, like `60s` or `5m`,
+        /// so a forgotten window does not cover the screen forever. `never` to keep it open
+        auto_exit_after: super::AutoExitAfter,
+        /// Keep the screen from locking or turning off while ferrishot is open,
+        /// so the selection is not lost when the screen locks in the middle of making it
+        inhibit_idle: bool,
         /// Renders a size indicator in the bottom left corner.
         /// It shows the current height and width of the selection.
         ///
         /// You can manually enter a value to change the selection by hand.
         size_indicator: bool,
+        /// Renders a read-only `W × H` label just outside of the selection,
+        /// which follows it around as it moves
+        dimensions_label: bool,
+        /// While resizing the selection, renders how far the cursor is from the nearest
+        /// corner of the selection, like `+12, -3`
+        cursor_offset: bool,
+        /// How the selection stands out from the rest of the screen.
+        ///
+        /// - `dim`: Darken everything outside of the selection
+        /// - `highlight`: Tint the selection, leaving the rest of the screen untouched
+        shade_style: crate::ui::selection::ShadeStyle,
+        /// Colors of the `palette` which the theme is built from. Colors in the `palette`
+        /// of the user's `theme` take priority.
+        ///
+        /// - `dark`: Light text on dark backgrounds, with an orange accent
+        /// - `light`: Dark text on light backgrounds, with a blue accent
+        /// - `high-contrast`: White text on black backgrounds, with a blue accent
+        theme_preset: super::theme::Preset,
+        /// Style of the cursor, when it would be a crosshair
+        ///
+        /// - `native`: The crosshair cursor of the operating system
+        /// - `guides`: Lines spanning the entire screen, intersecting at the cursor
+        /// - `hidden`: No cursor at all
+        crosshair: super::Crosshair,
+        /// Thickness of the lines when `crosshair` is `guides`
+        crosshair_thickness: f32,
+        /// Width of the strokes drawn with the pen, in pixels
+        pen_width: f32,
+        /// Width of the strokes drawn with the highlighter, in pixels
+        highlighter_width: f32,
+        /// How long to hold down a key before its command starts repeating, in milliseconds.
+        ///
+        /// Only commands that move or resize the selection are repeated
+        key_repeat_delay: u64,
+        /// How many times per second the command of a held down key is repeated
+        key_repeat_rate: u32,
         /// Render icons around the selection
         selection_icons: bool,
+        /// Render the icons in a toolbar which can be dragged anywhere on the screen,
+        /// instead of around the selection
+        toolbar: bool,
+        /// Width of the area around each side of the selection's frame, in which
+        /// the side can be grabbed to resize it
+        grab_area: f32,
+        /// Distance from each corner of the selection's frame, within which the
+        /// corner can be grabbed to resize it
+        corner_grab_radius: f32,
+        /// Smallest width and height of a selection that can be copied, saved or uploaded,
+        /// in pixels. Smaller selections, like the one made by an accidental click, are rejected
+        min_selection_size: u32,
+        /// Width and height of each icon button around the selection
+        icon_button_size: f32,
+        /// Show a tooltip when hovering over an icon
+        tooltips: bool,
+        /// How long to hover over an icon before its tooltip shows, in milliseconds
+        tooltip_delay: u64,
+        /// Keep the alpha channel of the screenshot in the output image.
+        ///
+        /// Useful when the compositor captures translucent windows, such as terminals
+        /// with a transparent background. Otherwise, the image is flattened to be fully opaque.
+        preserve_transparency: bool,
+        /// Wait for the desktop to present its next frame, and capture that frame.
+        ///
+        /// Capturing right away can catch a playing video in the middle of a frame, so it
+        /// comes out torn. Not every desktop supports this, in which case the screen is
+        /// captured right away as usual.
+        capture_on_next_frame: bool,
+        /// Capture this many frames and average them into the screenshot, which reduces noise
+        /// of camera feeds and remote desktops. `1` to capture a single frame, and at most 64
+        capture_frames: u32,
+        /// Hide the welcome message, size indicator and icons around the selection,
+        /// leaving just the crosshair and the frame of the selection.
+        ///
+        /// Useful for screen recordings, or if you prefer no UI at all.
+        minimal: bool,
+        /// Glide the selection to its place when moving it with `goto`, and let it slide
+        /// a bit further when it is released while being dragged quickly
+        animate_selection: bool,
+        /// How long animations take, in milliseconds
+        animation_duration: u64,
+        /// Show how to fix errors in the app, in addition to the error and its code.
+        /// Every code can also be explained with `ferrishot --explain <CODE>`
+        detailed_errors: bool,
+        /// Show popups and move the selection instantly, without any animations.
+        /// Takes priority over `animate-selection`
+        reduced_motion: bool,
+        /// Count how many screenshots are taken, copied, saved and uploaded, and which keybindings
+        /// are used. The statistics are stored locally, and are viewable with `ferrishot stats`
+        statistics: bool,
+        /// What to do when a saved screenshot is identical to a file which already
+        /// exists in the same directory
+        on_duplicate: crate::image::duplicate::OnDuplicate,
+        /// What to do when a screenshot is saved to a file which already exists
+        on_conflict: crate::image::conflict::OnConflict,
+        /// Compress large PNG screenshots on multiple threads, which saves and uploads
+        /// them much faster
+        parallel_encoding: bool,
+        /// Format of uploaded screenshots, and of the file name suggested when saving.
+        /// One of `png`, `jpeg` or `webp`
+        image_format: crate::image::encode::Format,
+        /// Also save every screenshot to a spool directory, even when it is only
+        /// copied or uploaded
+        always_autosave: bool,
+        /// Remove autosaved screenshots older than this many days. `0` keeps them forever
+        autosave_retention_days: u64,
+        /// Remove the oldest autosaved screenshots once they take up more than this
+        /// many megabytes. `0` means there is no limit
+        autosave_max_size: u64,
+        /// Allow uploading screenshots to the internet. When disabled, the upload icon is hidden
+        allow_upload: bool,
+        /// Service which screenshots are uploaded to. One of `litterbox`, `catbox`,
+        /// `the-null-pointer` or `uguu`, or `fastest` to upload to all of them at once
+        /// and keep the link of the first one to succeed
+        upload_provider: crate::image::upload::UploadProvider,
+        /// Show a desktop notification when a screenshot is copied to the clipboard
+        notify_on_copy: bool,
+        /// Show a desktop notification when a screenshot is saved to a file.
+        /// On Linux, clicking it shows the file in the file manager
+        notify_on_save: bool,
+        /// Show a desktop notification when a screenshot is uploaded to the internet.
+        /// On Linux, clicking it opens the link
+        notify_on_upload: bool,
+        /// Show the screenshot in the desktop notifications
+        notification_thumbnail: bool,
+    }
+}
+
+impl Config {
+    /// How close the cursor has to be to the frame of the selection to grab it
+    pub const fn grab_area(&self) -> crate::geometry::GrabArea {
+        crate::geometry::GrabArea {
+            sides: self.grab_area,
+            corner_radius: self.corner_grab_radius,
+        }
+    }
+
+    /// How long to hold down a key before its command starts repeating
+    pub fn key_repeat_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.key_repeat_delay)
+    }
+
+    /// Time between each repeat of the command of a held down key
+    pub fn key_repeat_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(1) / self.key_repeat_rate.max(1)
+    }
+
+    /// Whether screenshots can be uploaded to the internet, which needs both the `allow-upload`
+    /// option and ferrishot to be built with the `upload` feature
+    pub const fn can_upload(&self) -> bool {
+        cfg!(feature = "upload") && self.allow_upload
+    }
+
+    /// Where and for how long to keep every screenshot, if `always-autosave` is enabled
+    pub fn autosave(&self) -> Option<crate::autosave::Autosave> {
+        self.always_autosave.then(|| crate::autosave::Autosave {
+            max_age: (self.autosave_retention_days != 0).then(|| {
+                std::time::Duration::from_secs(self.autosave_retention_days * 60 * 60 * 24)
+            }),
+            max_size: (self.autosave_max_size != 0).then(|| self.autosave_max_size * 1024 * 1024),
+            parallel_encoding: self.parallel_encoding,
+        })
+    }
+
+    /// Where and in which format to upload screenshots
+    pub const fn upload(&self) -> crate::image::upload::Upload {
+        crate::image::upload::Upload {
+            provider: self.upload_provider,
+            format: self.image_format,
+            parallel_encoding: self.parallel_encoding,
+        }
+    }
+
+    /// Which desktop notifications to show
+    pub const fn notifications(&self) -> crate::notify::Notifications {
+        crate::notify::Notifications {
+            on_copy: self.notify_on_copy,
+            on_save: self.notify_on_save,
+            on_upload: self.notify_on_upload,
+            thumbnail: self.notification_thumbnail,
+        }
+    }
+
+    /// How long animations take, which is instant with `reduced-motion`
+    pub fn animation_duration(&self) -> std::time::Duration {
+        if self.reduced_motion {
+            std::time::Duration::ZERO
+        } else {
+            std::time::Duration::from_millis(self.animation_duration)
+        }
+    }
+
+    /// Whether the selection glides to its place, instead of teleporting there
+    pub const fn animates_selection(&self) -> bool {
+        self.animate_selection && !self.reduced_motion
+    }
+
+    /// How long to hover over an icon before its tooltip shows
+    pub fn tooltip_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.tooltip_delay)
+    }
+
+    /// How long ferrishot stays open without any input. `None` if it never closes by itself
+    pub const fn auto_exit_after(&self) -> Option<std::time::Duration> {
+        self.auto_exit_after.0
+    }
+
+    /// Smallest width and height of a selection that can be copied, saved or uploaded.
+    /// Even with `min-selection-size 0`, the image must not be empty
+    pub fn min_selection_size(&self) -> u32 {
+        self.min_selection_size.max(1)
+    }
+
+    /// How the screen is captured. At least 1 frame is captured, and at most
+    /// [`MAX_FRAMES`](crate::image::average::MAX_FRAMES)
+    pub fn capture_options(&self) -> crate::image::screenshot::CaptureOptions {
+        crate::image::screenshot::CaptureOptions {
+            on_next_frame: self.capture_on_next_frame,
+            frames: self
+                .capture_frames
+                .clamp(1, crate::image::average::MAX_FRAMES),
+        }
     }
 }
@@ -9,4 +9,92 @@ mod kdl_config_backward_compatibility {
         ))
         .expect("ferrishot v0.3: The first released version of the config must never break");
     }
+
+    #[test]
+    fn renamed_options_are_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ferrishot.kdl");
+        std::fs::write(&path, "version 1\nupload #false").unwrap();
+
+        let (config, warnings) = super::Config::parse(&path.to_string_lossy())
+            .expect("`upload` is migrated to `allow-upload`");
+
+        assert!(!config.allow_upload);
+        assert_eq!(warnings.len(), 1, "the user is told about the migration");
+    }
+}
+
+#[test]
+fn default_keybindings_do_not_conflict() {
+    let default_config =
+        ferrishot_knus::parse::<DefaultKdlConfig>("<default-config>", DEFAULT_KDL_CONFIG_STR)
+            .expect("default config is valid");
+
+    assert_eq!(
+        key_map::duplicates(&default_config.keys.keys),
+        vec![],
+        "the default config binds the same keys twice"
+    );
+
+    let config = Config::try_from(default_config).expect("default config is valid");
+    assert_eq!(
+        config.keys.conflicts(),
+        vec![],
+        "a keybinding of the default config is never triggered"
+    );
+}
+
+#[test]
+fn initial_selection_is_decoded() {
+    let user_config =
+        ferrishot_knus::parse::<UserKdlConfig>("<user-config>", "initial-selection \"full\"")
+            .expect("a region is a valid initial selection");
+    assert_eq!(
+        user_config.initial_selection,
+        Some(InitialSelection::Region(
+            crate::lazy_rect::LazyRectangle::FULL
+        ))
+    );
+
+    assert!(
+        ferrishot_knus::parse::<UserKdlConfig>("<user-config>", "initial-selection \"everything\"")
+            .is_err(),
+        "not a region"
+    );
+}
+
+#[test]
+fn auto_exit_after_is_decoded() {
+    let user_config =
+        ferrishot_knus::parse::<UserKdlConfig>("<user-config>", "auto-exit-after \"90s\"")
+            .expect("a duration is valid");
+    assert_eq!(
+        user_config.auto_exit_after,
+        Some(AutoExitAfter(Some(std::time::Duration::from_secs(90))))
+    );
+
+    assert!(
+        ferrishot_knus::parse::<UserKdlConfig>("<user-config>", "auto-exit-after \"0s\"").is_err(),
+        "ferrishot would close right away"
+    );
+}
+
+#[test]
+fn theme_preset_is_overridden_by_the_palette() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("ferrishot.kdl");
+
+    std::fs::write(&path, "theme-preset light").unwrap();
+    let (config, _) = Config::parse(&path.to_string_lossy()).expect("valid config");
+    assert_eq!(config.theme.tooltip_fg, iced::Color::BLACK);
+    assert_eq!(config.theme.tooltip_bg, iced::Color::WHITE);
+
+    std::fs::write(
+        &path,
+        "theme-preset light\ntheme {\n  palette fg=0xff_ff_ff\n}",
+    )
+    .unwrap();
+    let (config, _) = Config::parse(&path.to_string_lossy()).expect("valid config");
+    assert_eq!(config.theme.tooltip_fg, iced::Color::WHITE);
+    assert_eq!(config.theme.tooltip_bg, iced::Color::WHITE);
 }
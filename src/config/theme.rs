@@ -83,6 +83,43 @@ pub struct Color {
     pub opacity: f32,
 }
 
+/// Colors of the palette which the default theme is built from
+#[derive(DecodeScalar, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    /// Light text on dark backgrounds, with an orange accent
+    #[default]
+    Dark,
+    /// Dark text on light backgrounds, with a blue accent
+    Light,
+    /// White text on black backgrounds, with a blue accent
+    HighContrast,
+}
+
+impl Preset {
+    /// Every preset, in the order they are listed in the settings
+    pub const ALL: [Self; 3] = [Self::Dark, Self::Light, Self::HighContrast];
+
+    /// Name of the preset in the config
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::HighContrast => "high-contrast",
+        }
+    }
+
+    /// Colors of the `accent`, `fg` and `bg` of the palette
+    pub const fn palette(self) -> [(&'static str, u32); 3] {
+        let (accent, fg, bg) = match self {
+            Self::Dark => (0xab_61_37, 0xff_ff_ff, 0x00_00_00),
+            Self::Light => (0x4a_90_e2, 0x00_00_00, 0xff_ff_ff),
+            Self::HighContrast => (0x00_3c_c8, 0xff_ff_ff, 0x00_00_00),
+        };
+
+        [("accent", accent), ("fg", fg), ("bg", bg)]
+    }
+}
+
 /// Declare theme keys
 ///
 /// `UserKdlTheme` is merged into `DefaultKdlTheme` before being processed
@@ -132,6 +169,16 @@ macro_rules! declare_theme_options {
         }
 
         impl DefaultKdlTheme {
+            /// Replace the colors of the palette with the ones of the `preset`
+            pub fn apply_preset(&mut self, preset: Preset) {
+                self.palette.extend(
+                    preset
+                        .palette()
+                        .into_iter()
+                        .map(|(name, color)| (name.to_owned(), color)),
+                );
+            }
+
             /// If the user theme specifies a color, it will override the color in the
             /// default theme.
             pub fn merge_user_theme(mut self, user_theme: UserKdlTheme) -> Self {
@@ -194,6 +241,8 @@ crate::declare_theme_options! {
     selection_frame,
     /// Color of the region outside of the selected area
     non_selected_region,
+    /// Tint of the selected area, when `shade-style` is `highlight`
+    selection_highlight,
     /// Color of drop shadow, used for stuff like:
     ///
     /// - drop shadow of icons
@@ -202,6 +251,16 @@ crate::declare_theme_options! {
     drop_shadow,
     /// Background color of selected text
     text_selection,
+    /// Color of the lines when `crosshair` is `guides`
+    crosshair,
+
+    //
+    // --- Drawing ---
+    //
+    /// Color of the strokes drawn with the pen
+    pen,
+    /// Color of the strokes drawn with the highlighter, which should be semi-transparent
+    highlighter,
 
     //
     // --- Side Indicator ---
@@ -211,6 +270,14 @@ crate::declare_theme_options! {
     /// Background color of the size indicator
     size_indicator_bg,
 
+    //
+    // --- Dimensions Label ---
+    //
+    /// Text color of the `W × H` label next to the selection
+    dimensions_label_fg,
+    /// Background color of the `W × H` label next to the selection
+    dimensions_label_bg,
+
     //
     // --- Tooltip ---
     //
@@ -226,6 +293,10 @@ crate::declare_theme_options! {
     error_fg,
     /// Background color of the error boxes
     error_bg,
+    /// Color of the text on warnings, which are shown like errors
+    warning_fg,
+    /// Background color of the warning boxes
+    warning_bg,
 
     //
     // --- Info Box ---
@@ -244,6 +315,8 @@ crate::declare_theme_options! {
     icon_bg,
     /// Color of icons around the selection
     icon_fg,
+    /// Background color of the toolbar, when the `toolbar` option is enabled
+    toolbar_bg,
 
     //
     // --- Debug Menu ---
@@ -268,11 +341,45 @@ crate::declare_theme_options! {
     //
     // --- Image uploaded popup ---
     //
-    /// Foreground color of the image_uploaded popup
+    /// Foreground color of the `image_uploaded` popup
     image_uploaded_fg,
-    /// Background color of the image_uploaded popup
+    /// Background color of the `image_uploaded` popup
     image_uploaded_bg,
 
+    //
+    // --- Pixel inspector ---
+    //
+    /// Color of the coordinates and hex color of the inspected pixel
+    pixel_inspector_fg,
+    /// Background color of the pixel inspector
+    pixel_inspector_bg,
+    /// Color of the grid separating each magnified pixel
+    pixel_inspector_grid,
+
+    //
+    // --- File picker ---
+    //
+    /// Foreground color of the file picker, used to pick where to save the screenshot
+    file_picker_fg,
+    /// Background color of the file picker
+    file_picker_bg,
+
+    //
+    // --- Screen recording permission ---
+    //
+    /// Foreground color of the popup asking for the Screen Recording permission on macOS
+    permission_fg,
+    /// Background color of the popup asking for the permission
+    permission_bg,
+
+    //
+    // --- Settings ---
+    //
+    /// Foreground color of the settings popup
+    settings_fg,
+    /// Background color of the settings popup
+    settings_bg,
+
     /// Color of success, e.g. green check mark when copying text to clipboard
     success,
 }
@@ -0,0 +1,213 @@
+//! Change options of the user's config file, keeping everything else as the user wrote it
+//!
+//! Parsing the config and serializing it again would lose the comments and the formatting,
+//! so only the lines of the changed options are rewritten.
+
+use std::{
+    fs,
+    io::{self, Write as _},
+    path::Path,
+};
+
+/// Keeps track of where in the config we are, from one line to the next
+#[derive(Debug, Default)]
+struct Scanner {
+    /// How many blocks `{ ... }` we are inside of
+    depth: usize,
+    /// Whether we are inside of a `/* ... */` comment
+    in_block_comment: bool,
+}
+
+impl Scanner {
+    /// Scan a single `line`, returning where its `//` comment starts
+    fn line(&mut self, line: &str) -> Option<usize> {
+        let mut chars = line.char_indices().peekable();
+        let mut in_string = false;
+
+        while let Some((i, ch)) = chars.next() {
+            if self.in_block_comment {
+                if ch == '*' && chars.next_if(|(_, ch)| *ch == '/').is_some() {
+                    self.in_block_comment = false;
+                }
+            } else if in_string {
+                match ch {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '"' => in_string = false,
+                    _ => (),
+                }
+            } else {
+                match ch {
+                    '"' => in_string = true,
+                    '/' if chars.next_if(|(_, ch)| *ch == '/').is_some() => return Some(i),
+                    '/' if chars.next_if(|(_, ch)| *ch == '*').is_some() => {
+                        self.in_block_comment = true;
+                    }
+                    '{' => self.depth += 1,
+                    '}' => self.depth = self.depth.saturating_sub(1),
+                    _ => (),
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Set the top-level option `key` of the `config` to `value`, which is written as-is.
+///
+/// The comment after the option is kept. If the option is not in the config yet, it is
+/// added to the end
+pub fn set_option(config: &str, key: &str, value: &str) -> String {
+    let mut scanner = Scanner::default();
+    let mut found = false;
+
+    let mut lines = config
+        .lines()
+        .map(|line| {
+            let is_top_level = scanner.depth == 0 && !scanner.in_block_comment;
+            let comment = scanner.line(line);
+
+            let indent_len = line.len() - line.trim_start().len();
+            let is_option = is_top_level
+                && line[indent_len..]
+                    .strip_prefix(key)
+                    .is_some_and(|after| after.is_empty() || after.starts_with([' ', '\t', ';']));
+
+            if !is_option {
+                return line.to_owned();
+            }
+
+            found = true;
+            let comment = comment
+                .map(|start| format!(" {}", &line[start..]))
+                .unwrap_or_default();
+
+            format!("{}{key} {value}{comment}", &line[..indent_len])
+        })
+        .collect::<Vec<_>>();
+
+    if !found {
+        lines.push(format!("{key} {value}"));
+    }
+
+    let mut config = lines.join("\n");
+    config.push('\n');
+    config
+}
+
+/// Set the option `key` of the config file at `path` to `value`.
+///
+/// If the config file does not exist yet, it is created. The new config is written to a
+/// temporary file next to it, which then replaces it, so the config is never left
+/// half-written. If `path` is a symlink, the file it points to is replaced
+pub fn write_option(path: &Path, key: &str, value: &str) -> io::Result<()> {
+    let (path, config) = match fs::read_to_string(path) {
+        Ok(config) => (fs::canonicalize(path)?, config),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => (
+            path.to_path_buf(),
+            format!("version {}\n", super::migrations::CONFIG_VERSION),
+        ),
+        Err(err) => return Err(err),
+    };
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    temp.write_all(set_option(&config, key, value).as_bytes())?;
+    // the temporary file is only readable by the user, unlike the config may be
+    if let Ok(metadata) = fs::metadata(&path) {
+        temp.as_file().set_permissions(metadata.permissions())?;
+    }
+    temp.persist(&path)?;
+
+    Ok(())
+}
+
+/// How a `bool` is written in the config
+pub const fn kdl_bool(value: bool) -> &'static str {
+    if value { "#true" } else { "#false" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn keeps_comments() {
+        let config = "\
+// show the size of the selection
+size-indicator #true // in the corner
+tooltips #true
+";
+
+        assert_eq!(
+            set_option(config, "size-indicator", "#false"),
+            "\
+// show the size of the selection
+size-indicator #false // in the corner
+tooltips #true
+"
+        );
+    }
+
+    #[test]
+    fn only_top_level_options() {
+        let config = "\
+theme {
+  toolbar 0xff_00_00
+}
+/*
+toolbar #true
+*/
+toolbar-bg \"{\"
+";
+
+        assert_eq!(
+            set_option(config, "toolbar", "#true"),
+            format!("{config}toolbar #true\n"),
+            "options in blocks and comments are not touched, nor prefixes of other options"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn keeps_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("dotfiles.kdl");
+        let link = dir.path().join("ferrishot.kdl");
+        fs::write(&target, "toolbar #true\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_option(&link, "toolbar", kdl_bool(false)).unwrap();
+
+        assert!(
+            fs::symlink_metadata(&link).unwrap().is_symlink(),
+            "the file it points to is replaced, not the link"
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "toolbar #false\n");
+    }
+
+    #[test]
+    fn writes_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ferrishot.kdl");
+
+        write_option(&path, "toolbar", kdl_bool(true)).unwrap();
+        write_option(&path, "toolbar", kdl_bool(false)).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!(
+                "version {}\ntoolbar #false\n",
+                super::super::migrations::CONFIG_VERSION
+            )
+        );
+    }
+}
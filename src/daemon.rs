@@ -0,0 +1,272 @@
+//! Run in the background, taking screenshots without ever launching the app
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::{App, Cli, Config, lazy_rect::LazyRectangle};
+
+/// How often to check whether the trigger file was touched
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Failed to take a screenshot in the background
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Could not take the screenshot
+    #[error(transparent)]
+    GetImage(#[from] crate::image::GetImageError),
+    /// Could not read the last region
+    #[error(transparent)]
+    LastRegion(#[from] crate::last_region::Error),
+    /// Could not save the screenshot
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+    /// The save path contains an invalid `strftime` placeholder
+    #[error("invalid placeholder in the save path: {}", .0.display())]
+    InvalidSavePath(PathBuf),
+    /// The screenshots take up too much space
+    #[error(
+        "screenshots in {} take up {} bytes, which is more than `--max-disk-usage`",
+        directory.display(),
+        disk_usage
+    )]
+    DiskUsageExceeded {
+        /// Directory containing the screenshots
+        directory: PathBuf,
+        /// How many bytes the screenshots take up
+        disk_usage: u64,
+    },
+    /// IO error
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Take a screenshot of the region passed with `--region` or `--last-region` (or the full screen),
+/// saving it to `save_path`.
+///
+/// `save_path` can contain `strftime` placeholders such as `%Y-%m-%d_%H-%M-%S`, which
+/// are replaced with the current time.
+///
+/// Returns the path the screenshot was saved to
+pub fn capture(cli: &Cli, config: &Config, save_path: &Path) -> Result<PathBuf, Error> {
+    let image = crate::get_image(None, config.preserve_transparency, config.capture_options())?;
+    let bounds = image.bounds();
+
+    let region = if cli.last_region {
+        crate::last_region::read(bounds)?.unwrap_or(bounds)
+    } else {
+        cli.region.unwrap_or(LazyRectangle::FULL).init(bounds)
+    };
+
+    let path = format_save_path(save_path)?;
+    fs::create_dir_all(directory(&path))?;
+
+    Ok(crate::save_image(
+        &App::process_image(region, &image),
+        &path,
+        config.on_duplicate,
+        config.on_conflict,
+        config.parallel_encoding,
+    )?)
+}
+
+/// Replace the `strftime` placeholders in the `save_path` with the current time
+fn format_save_path(save_path: &Path) -> Result<PathBuf, Error> {
+    let mut path = String::new();
+    write!(
+        path,
+        "{}",
+        chrono::Local::now().format(&save_path.to_string_lossy())
+    )
+    .map_err(|_| Error::InvalidSavePath(save_path.to_path_buf()))?;
+
+    Ok(PathBuf::from(path))
+}
+
+/// Directory which contains the file at `path`
+fn directory(path: &Path) -> &Path {
+    path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+/// Take a screenshot every `interval`, until the time of day `--until` (or forever)
+///
+/// Stops once the files in the directory that the next screenshot would be saved to
+/// take up more than `--max-disk-usage`. As `save_path` can contain placeholders such as `%Y-%m-%d`,
+/// that directory can change from one screenshot to the next
+pub fn capture_periodically(
+    cli: &Cli,
+    config: &Config,
+    save_path: &Path,
+    interval: Duration,
+) -> Result<(), Error> {
+    let max_disk_usage = cli.max_disk_usage.map(|megabytes| megabytes * 1024 * 1024);
+
+    loop {
+        if let Some(until) = cli.until
+            && chrono::Local::now().time() >= until
+        {
+            log::info!("Reached {until}, no longer taking screenshots");
+            return Ok(());
+        }
+
+        if let Some(max_disk_usage) = max_disk_usage {
+            let path = format_save_path(save_path)?;
+            let directory = directory(&path);
+            let disk_usage = disk_usage(directory)?;
+            if disk_usage >= max_disk_usage {
+                return Err(Error::DiskUsageExceeded {
+                    directory: directory.to_path_buf(),
+                    disk_usage,
+                });
+            }
+        }
+
+        match capture(cli, config, save_path) {
+            Ok(path) => log::info!("Saved screenshot to {}", path.display()),
+            Err(err) => log::error!("Failed to take screenshot: {err}"),
+        }
+
+        thread::sleep(interval);
+    }
+}
+
+/// How many bytes the files directly inside of `directory` take up
+fn disk_usage(directory: &Path) -> Result<u64, Error> {
+    if !directory.exists() {
+        return Ok(0);
+    }
+
+    let mut total = 0;
+    for entry in fs::read_dir(directory)? {
+        let meta = entry?.metadata()?;
+        if meta.is_file() {
+            total += meta.len();
+        }
+    }
+
+    Ok(total)
+}
+
+/// Parse a duration like `30s`, `10m` or `2h`. Without a unit, it is in seconds
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let (number, seconds_per_unit) = match s.strip_suffix('s') {
+        Some(number) => (number, 1),
+        None => match s.strip_suffix('m') {
+            Some(number) => (number, 60),
+            None => match s.strip_suffix('h') {
+                Some(number) => (number, 60 * 60),
+                None => (s, 1),
+            },
+        },
+    };
+
+    let number = number
+        .parse::<u64>()
+        .map_err(|err| format!("invalid interval `{s}`: {err}"))?;
+
+    if number == 0 {
+        return Err("interval must be greater than zero".to_owned());
+    }
+
+    Ok(Duration::from_secs(number * seconds_per_unit))
+}
+
+/// Run ferrishot again with the same arguments and `--foreground`, in the background.
+///
+/// The background process is detached from the terminal, so it keeps running when the
+/// terminal is closed and isn't stopped by Ctrl+C. Returns its ID
+pub fn detach() -> Result<u32, Error> {
+    use std::process;
+
+    let mut command = process::Command::new(std::env::current_exe()?);
+    command
+        .args(std::env::args_os().skip(1))
+        .arg("--foreground")
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt as _;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt as _;
+        /// Run without the console of ferrishot
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        command.creation_flags(DETACHED_PROCESS);
+    }
+
+    Ok(command.spawn()?.id())
+}
+
+/// Take a screenshot every time the `trigger` file is created or touched, forever
+pub fn watch_trigger_file(
+    cli: &Cli,
+    config: &Config,
+    trigger: &Path,
+    save_path: &Path,
+) -> Result<(), Error> {
+    /// When the trigger file was last modified, `None` if it does not exist
+    fn modified(trigger: &Path) -> Option<SystemTime> {
+        fs::metadata(trigger).and_then(|meta| meta.modified()).ok()
+    }
+
+    let mut last_modified = modified(trigger);
+
+    loop {
+        thread::sleep(POLL_INTERVAL);
+
+        let modified = modified(trigger);
+        if modified.is_none() || modified == last_modified {
+            last_modified = modified;
+            continue;
+        }
+        last_modified = modified;
+
+        // A failed capture should not stop the daemon. It might succeed next time
+        match capture(cli, config, save_path) {
+            Ok(path) => log::info!("Saved screenshot to {}", path.display()),
+            Err(err) => log::error!("Failed to take screenshot: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn interval() {
+        assert_eq!(parse_interval("45").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_interval("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_interval("10m").unwrap(), Duration::from_secs(10 * 60));
+        assert_eq!(
+            parse_interval("2h").unwrap(),
+            Duration::from_secs(2 * 60 * 60)
+        );
+        assert!(parse_interval("0m").is_err(), "zero interval");
+        assert!(parse_interval("10d").is_err(), "unknown unit");
+        assert!(parse_interval("m").is_err(), "missing number");
+    }
+
+    #[test]
+    fn directory_of_formatted_path() {
+        let path = format_save_path(Path::new("shots/%Y/shot.png")).unwrap();
+
+        assert_eq!(
+            directory(&path),
+            Path::new("shots").join(chrono::Local::now().format("%Y").to_string())
+        );
+        assert_eq!(directory(Path::new("shot.png")), Path::new("."));
+    }
+}
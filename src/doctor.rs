@@ -0,0 +1,149 @@
+//! Diagnose common problems with the environment ferrishot runs in
+//!
+//! Prints a report which is useful to attach when reporting issues
+
+use std::{fmt::Write as _, path::Path, time::Instant};
+
+/// Result of a single check
+enum Status {
+    /// Everything works
+    Ok(String),
+    /// Might cause problems
+    Warn(String),
+    /// Will not work
+    Fail(String),
+}
+
+/// Run all of the checks, returning the report
+pub fn run(preserve_transparency: bool) -> String {
+    let checks = [
+        ("session", session()),
+        ("graphics", graphics()),
+        ("clipboard", clipboard()),
+        ("portal", portal()),
+        ("screenshot", screenshot(preserve_transparency)),
+    ];
+
+    let mut report = String::new();
+    for (name, status) in checks {
+        let (label, message) = match status {
+            Status::Ok(message) => ("ok", message),
+            Status::Warn(message) => ("warn", message),
+            Status::Fail(message) => ("fail", message),
+        };
+        let _ = writeln!(report, "[{label:>4}] {name:<10} {message}");
+    }
+
+    report
+}
+
+/// The display server we are running under
+fn session() -> Status {
+    let session_type = std::env::var("XDG_SESSION_TYPE").ok();
+    let wayland = std::env::var("WAYLAND_DISPLAY").ok();
+    let x11 = std::env::var("DISPLAY").ok();
+
+    match (session_type.as_deref(), wayland, x11) {
+        (_, Some(wayland), x11) => Status::Ok(format!(
+            "wayland ({wayland}){}",
+            x11.map(|x11| format!(", xwayland ({x11})"))
+                .unwrap_or_default()
+        )),
+        (_, None, Some(x11)) => Status::Ok(format!("x11 ({x11})")),
+        (Some(session_type), None, None) => Status::Warn(format!(
+            "session type is {session_type}, but neither WAYLAND_DISPLAY nor DISPLAY are set"
+        )),
+        (None, None, None) if cfg!(target_os = "linux") => {
+            Status::Fail("neither WAYLAND_DISPLAY nor DISPLAY are set".to_owned())
+        }
+        (None, None, None) => Status::Ok(std::env::consts::OS.to_owned()),
+    }
+}
+
+/// Graphics adapters which can render the app, on the backends chosen with `WGPU_BACKEND`
+fn graphics() -> Status {
+    let descriptor = wgpu::InstanceDescriptor::from_env_or_default();
+    let adapters = wgpu::Instance::new(&descriptor)
+        .enumerate_adapters(descriptor.backends)
+        .into_iter()
+        .map(|adapter| adapter.get_info())
+        .collect::<Vec<_>>();
+
+    let names = adapters
+        .iter()
+        .map(|info| format!("{} ({})", info.name, info.backend))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    if adapters.is_empty() {
+        Status::Warn("no graphics adapter was found. Try ICED_BACKEND=tiny-skia".to_owned())
+    } else if adapters
+        .iter()
+        .all(|info| info.device_type == wgpu::DeviceType::Cpu)
+    {
+        Status::Warn(format!(
+            "only software adapters were found, which can be slow: {names}"
+        ))
+    } else {
+        Status::Ok(names)
+    }
+}
+
+/// Whether we can access the clipboard
+fn clipboard() -> Status {
+    match arboard::Clipboard::new() {
+        Ok(_) => Status::Ok("available".to_owned()),
+        Err(err) => Status::Fail(err.to_string()),
+    }
+}
+
+/// Whether the desktop portal, used to take screenshots on some Wayland compositors, is reachable
+fn portal() -> Status {
+    if !cfg!(target_os = "linux") {
+        return Status::Ok("not needed".to_owned());
+    }
+
+    if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() {
+        return Status::Warn(
+            "DBUS_SESSION_BUS_ADDRESS is not set, the portal is unreachable".to_owned(),
+        );
+    }
+
+    let is_installed = ["/usr/libexec", "/usr/lib", "/usr/lib/xdg-desktop-portal"]
+        .iter()
+        .any(|dir| Path::new(dir).join("xdg-desktop-portal").exists());
+
+    if is_installed {
+        Status::Ok("xdg-desktop-portal is installed".to_owned())
+    } else {
+        Status::Warn("xdg-desktop-portal was not found".to_owned())
+    }
+}
+
+/// Whether we can take a screenshot, and how long it takes
+fn screenshot(preserve_transparency: bool) -> Status {
+    let start = Instant::now();
+
+    match crate::get_image(
+        None,
+        preserve_transparency,
+        crate::image::screenshot::CaptureOptions::default(),
+    ) {
+        Ok(image) => {
+            let elapsed = start.elapsed();
+            let message = format!(
+                "took a {}x{} screenshot in {elapsed:.2?}",
+                image.width(),
+                image.height()
+            );
+
+            // it should be near instant
+            if elapsed.as_millis() > 500 {
+                Status::Warn(format!("{message}, which is slow"))
+            } else {
+                Status::Ok(message)
+            }
+        }
+        Err(err) => Status::Fail(err.to_string()),
+    }
+}
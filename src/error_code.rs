@@ -0,0 +1,184 @@
+//! Catalog of errors shown in the app, each with a code like `FER-0001`
+//!
+//! The app shows the code next to the error, and `ferrishot --explain FER-0001`
+//! prints what went wrong and how to fix it.
+
+use std::{fmt, str::FromStr};
+
+/// Prefix of every error code
+const PREFIX: &str = "FER-";
+
+/// An error which can be explained with `--explain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumIter)]
+pub enum ErrorCode {
+    /// A command needs a selection, but nothing is selected
+    NoSelection,
+    /// A register was used before anything was stored in it
+    EmptyRegister,
+    /// Uploading was attempted while it is disabled
+    UploadDisabled,
+    /// The clipboard could not be written to
+    Clipboard,
+    /// Every upload service failed
+    UploadFailed,
+    /// The screenshot could not be saved to a file
+    SaveFailed,
+    /// The config file could not be written or read again after changing a setting
+    ConfigWrite,
+    /// The selection is smaller than `min-selection-size`
+    SelectionTooSmall,
+}
+
+impl ErrorCode {
+    /// Number of the code, which never changes once released
+    pub const fn number(self) -> u16 {
+        match self {
+            Self::NoSelection => 1,
+            Self::EmptyRegister => 2,
+            Self::UploadDisabled => 3,
+            Self::Clipboard => 4,
+            Self::UploadFailed => 5,
+            Self::SaveFailed => 6,
+            Self::ConfigWrite => 7,
+            Self::SelectionTooSmall => 8,
+        }
+    }
+
+    /// One-line hint for fixing the error, shown in the app with `detailed-errors`
+    pub const fn hint(self) -> &'static str {
+        match self {
+            Self::NoSelection => "Drag with the left mouse button to select a region first",
+            Self::EmptyRegister => "Store a region in the register first, for example with `\"ay`",
+            Self::UploadDisabled => "Enable the `allow-upload` option in the config",
+            Self::Clipboard => "Make sure a clipboard manager or desktop session is running",
+            Self::UploadFailed => "Check your internet connection, then try again",
+            Self::SaveFailed => "Check that the directory exists and that you can write to it",
+            Self::ConfigWrite => "Check that the config file is writable and valid",
+            Self::SelectionTooSmall => "Drag to make the selection larger",
+        }
+    }
+
+    /// What went wrong and the steps to fix it, printed by `--explain`
+    pub const fn explanation(self) -> &'static str {
+        match self {
+            Self::NoSelection => {
+                "The command acts on the selected region, but nothing is selected.\n\
+                \n\
+                - Drag with the left mouse button to select a region.\n\
+                - Or pass `--region` or `--last-region` to start with a selection.\n\
+                - Or restore a stored region with `\"<register>p`."
+            }
+            Self::EmptyRegister => {
+                "A region or a macro was read from a register, but nothing was stored in it.\n\
+                \n\
+                - Regions are stored with `\"<register>y`, like `\"ay`, and restored with `\"ap`.\n\
+                - Macros are recorded with `q<register>`, stopped with `q`, and replayed with `@<register>`."
+            }
+            Self::UploadDisabled => {
+                "Uploading screenshots to the internet is turned off.\n\
+                \n\
+                - Set `allow-upload #true` in the config, or enable it in the settings (ctrl + ,).\n\
+                - If ferrishot was built without the `upload` feature, rebuild it with the feature enabled."
+            }
+            Self::Clipboard => {
+                "The screenshot or text could not be written to the clipboard.\n\
+                \n\
+                - On Wayland, the compositor must support the `wlr-data-control` protocol.\n\
+                - On X11, ferrishot keeps the clipboard alive in the background. Make sure it is not killed.\n\
+                - Run `ferrishot doctor` to check the clipboard."
+            }
+            Self::UploadFailed => {
+                "None of the upload services accepted the screenshot.\n\
+                \n\
+                - Check your internet connection, and whether a proxy or firewall blocks the services.\n\
+                - The services limit the size of uploads. Try a smaller region.\n\
+                - The services may be down. Try again later."
+            }
+            Self::SaveFailed => {
+                "The screenshot could not be saved to the chosen file.\n\
+                \n\
+                - Make sure the directory exists and you are allowed to write to it.\n\
+                - The format is picked from the extension of the file. Use one such as `.png` or `.jpg`."
+            }
+            Self::ConfigWrite => {
+                "A changed setting could not be written to the config file, or the config file\n\
+                could not be read again afterwards.\n\
+                \n\
+                - Make sure the config file and its directory are writable.\n\
+                - Run `ferrishot config edit` to fix errors in the config file."
+            }
+            Self::SelectionTooSmall => {
+                "The selection is too small to be copied, saved or uploaded. Usually it was made\n\
+                by clicking without dragging, which selects nothing.\n\
+                \n\
+                - Drag with the left mouse button to make the selection larger.\n\
+                - Lower `min-selection-size` in the config to allow smaller selections."
+            }
+        }
+    }
+
+    /// Everything `--explain` prints about the error
+    pub fn explain(self) -> String {
+        format!("{self}\n\n{}\n", self.explanation())
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{PREFIX}{:04}", self.number())
+    }
+}
+
+/// The error code is not in the catalog
+#[derive(thiserror::Error, miette::Diagnostic, Debug, Clone, PartialEq, Eq)]
+#[error("unknown error code `{0}`, expected a code like `{PREFIX}0001`")]
+pub struct UnknownCode(String);
+
+impl FromStr for ErrorCode {
+    type Err = UnknownCode;
+
+    /// Parse `FER-0001`, `fer-1` or just `1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = s
+            .get(..PREFIX.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(PREFIX))
+            .map_or(s, |_| &s[PREFIX.len()..]);
+
+        number
+            .parse::<u16>()
+            .ok()
+            .and_then(|number| {
+                <Self as strum::IntoEnumIterator>::iter().find(|code| code.number() == number)
+            })
+            .ok_or_else(|| UnknownCode(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use strum::IntoEnumIterator as _;
+
+    #[test]
+    fn parses_codes() {
+        assert_eq!("FER-0001".parse(), Ok(ErrorCode::NoSelection));
+        assert_eq!("fer-2".parse(), Ok(ErrorCode::EmptyRegister));
+        assert_eq!("3".parse(), Ok(ErrorCode::UploadDisabled));
+        assert_eq!(
+            "FER-9999".parse::<ErrorCode>(),
+            Err(UnknownCode("FER-9999".to_owned()))
+        );
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        for code in ErrorCode::iter() {
+            assert_eq!(
+                code.to_string().parse(),
+                Ok(code),
+                "every code round-trips to a single error"
+            );
+        }
+    }
+}
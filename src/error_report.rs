@@ -0,0 +1,153 @@
+//! Present crashes to the user in a friendly way, and help them report bugs
+//!
+//! The GUI can panic in ways we cannot recover from, for instance when the graphics backend
+//! fails to initialize. Instead of a cryptic panic message, we show what went wrong along with
+//! hints that might fix it.
+
+use std::{
+    fmt::Write as _,
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    thread,
+};
+
+/// Failed to write the bug report
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Can't find the directory to write the bug report to
+    #[error(transparent)]
+    Paths(#[from] crate::paths::Error),
+    /// Failed to write the bug report
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Environment variables which are relevant for debugging issues with ferrishot
+const RELEVANT_ENV_VARS: &[&str] = &[
+    "XDG_SESSION_TYPE",
+    "XDG_CURRENT_DESKTOP",
+    "WAYLAND_DISPLAY",
+    "DISPLAY",
+    "WGPU_BACKEND",
+    "ICED_BACKEND",
+];
+
+/// Hints for fixing the panic, based on its message
+fn hints(message: &str) -> Vec<&'static str> {
+    let message = message.to_lowercase();
+    let mut hints = vec![];
+
+    if message.contains("egl") || message.contains("wgpu") || message.contains("adapter") {
+        hints.push("The graphics backend failed. Try running with `WGPU_BACKEND=vulkan`, or `WGPU_BACKEND=gl`");
+        hints.push("Try forcing software rendering with `ICED_BACKEND=tiny-skia`");
+    }
+    if message.contains("wayland") {
+        hints.push("Try running under XWayland by unsetting `WAYLAND_DISPLAY`");
+    }
+    if message.contains("screenshot") || message.contains("xcap") {
+        hints.push("Make sure ferrishot has permission to take screenshots");
+    }
+
+    hints
+}
+
+/// Show panics to the user in a dialog window, in addition to printing them
+/// to standard error and the log file.
+///
+/// Must be called from the main thread. Only panics on the main thread take down the app,
+/// and windowing toolkits can only create the dialog there, so panics on other threads
+/// are just printed and logged.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    let main_thread = thread::current().id();
+
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let message = panic_message(info);
+        log::error!("ferrishot panicked: {message}");
+
+        let mut description = format!("ferrishot crashed:\n\n{message}\n");
+        let hints = hints(&message);
+        if !hints.is_empty() {
+            description.push_str("\nThese might help:\n");
+            for hint in hints {
+                let _ = writeln!(description, "- {hint}");
+            }
+        }
+        description.push_str(
+            "\nRun `ferrishot --bug-report` to create a file with information that helps us fix this,\
+            \nand attach it to an issue at https://github.com/nik-rev/ferrishot/issues",
+        );
+
+        #[expect(
+            clippy::print_stderr,
+            reason = "the GUI has crashed, so this is all we have"
+        )]
+        {
+            eprintln!("\n{description}");
+        }
+
+        if thread::current().id() == main_thread {
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("ferrishot crashed")
+                .set_description(description)
+                .show();
+        }
+    }));
+}
+
+/// The message the panic was created with
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned());
+
+    info.location().map_or(payload.clone(), |location| {
+        format!("{payload} (at {location})")
+    })
+}
+
+/// Write a file with logs and information about the system, to attach to bug reports,
+/// into the data directory.
+///
+/// Returns where it was written
+pub fn write_bug_report(cli: &crate::Cli) -> Result<PathBuf, Error> {
+    let mut report = String::new();
+
+    let _ = writeln!(report, "# ferrishot bug report\n");
+    let _ = writeln!(report, "version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "os: {}", std::env::consts::OS);
+    let _ = writeln!(report, "arch: {}", std::env::consts::ARCH);
+    let _ = writeln!(report, "config file: {}", cli.config_file);
+
+    let _ = writeln!(report, "\n## Environment\n");
+    for var in RELEVANT_ENV_VARS {
+        let value = std::env::var(var).unwrap_or_else(|_| "<unset>".to_owned());
+        let _ = writeln!(report, "{var}={value}");
+    }
+
+    let _ = writeln!(report, "\n## Config\n");
+    report.push_str(
+        &std::fs::read_to_string(&cli.config_file)
+            .unwrap_or_else(|err| format!("<could not read the config file: {err}>")),
+    );
+
+    let _ = writeln!(report, "\n## Logs ({})\n", cli.log_file);
+    report.push_str(
+        &std::fs::read_to_string(&cli.log_file)
+            .unwrap_or_else(|err| format!("<could not read the log file: {err}>")),
+    );
+
+    let path = crate::paths::data_file(&format!(
+        "ferrishot-bug-report-{}.md",
+        chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ))?;
+    std::fs::write(&path, report)?;
+
+    Ok(path)
+}
@@ -0,0 +1,138 @@
+//! Control the selection with a gamepad
+//!
+//! - Left stick: Move the selection
+//! - Right trigger: Extend the selection to the bottom-right
+//! - Left trigger: Shrink the selection from the bottom-right
+//! - South button (A on Xbox controllers): Copy to clipboard
+//! - East button (B on Xbox controllers): Exit
+//!
+//! Events from the gamepad are mapped into the same `Command`s that keybindings trigger.
+
+use std::{thread, time::Duration};
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+use iced::{
+    Subscription,
+    futures::{SinkExt as _, Stream, channel::mpsc},
+};
+
+use crate::{
+    Command, Message,
+    geometry::Direction,
+    image::action::Command as ImageCommand,
+    ui::{app::Command as AppCommand, selection::Command as SelectionCommand},
+};
+
+/// How often the state of the sticks and triggers is read
+const TICK: Duration = Duration::from_millis(16);
+/// Values of the sticks and triggers below this are ignored, as they are
+/// rarely exactly `0.0` even when not touched
+const DEAD_ZONE: f32 = 0.2;
+/// How many pixels the selection moves or resizes each tick, when a stick or
+/// trigger is fully pressed
+const MAX_SPEED: f32 = 20.0;
+
+/// Listen for gamepad input
+pub fn subscription() -> Subscription<Message> {
+    Subscription::run(events)
+}
+
+/// Stream of messages produced by the gamepad
+fn events() -> impl Stream<Item = Message> {
+    iced::stream::channel(100, async |mut output| {
+        let (sender, mut receiver) = mpsc::channel(100);
+
+        // `Gilrs` must be polled in a loop, so do that on its own thread
+        thread::spawn(move || poll(sender));
+
+        while let Some(message) = iced::futures::StreamExt::next(&mut receiver).await {
+            if output.send(message).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Poll the gamepads until the app is closed
+fn poll(mut sender: mpsc::Sender<Message>) {
+    let mut gilrs = match Gilrs::new() {
+        Ok(gilrs) => gilrs,
+        Err(err) => {
+            log::error!("Failed to initialize gamepad input: {err}");
+            return;
+        }
+    };
+
+    while !sender.is_closed() {
+        let mut messages = vec![];
+
+        while let Some(event) = gilrs.next_event() {
+            if let EventType::ButtonPressed(button, _) = event.event {
+                match button {
+                    Button::South => {
+                        messages.push(Command::ImageUpload(ImageCommand::CopyToClipboard));
+                    }
+                    Button::East => messages.push(Command::App(AppCommand::Exit)),
+                    _ => (),
+                }
+            }
+        }
+
+        for (_, gamepad) in gilrs.gamepads() {
+            let x = gamepad.value(Axis::LeftStickX);
+            // the y-axis of the stick points up, but it points down on the screen
+            let y = -gamepad.value(Axis::LeftStickY);
+
+            for (value, negative, positive) in [
+                (x, Direction::Left, Direction::Right),
+                (y, Direction::Up, Direction::Down),
+            ] {
+                if let Some(amount) = speed(value) {
+                    messages.push(Command::Selection(SelectionCommand::Move {
+                        direction: if value < 0.0 { negative } else { positive },
+                        amount,
+                    }));
+                }
+            }
+
+            let trigger = |button| gamepad.button_data(button).map_or(0.0, |data| data.value());
+
+            for (value, resize) in [
+                (trigger(Button::RightTrigger2), true),
+                (trigger(Button::LeftTrigger2), false),
+            ] {
+                let Some(amount) = speed(value) else {
+                    continue;
+                };
+
+                for direction in [Direction::Right, Direction::Down] {
+                    messages.push(Command::Selection(if resize {
+                        SelectionCommand::Extend { direction, amount }
+                    } else {
+                        SelectionCommand::Shrink { direction, amount }
+                    }));
+                }
+            }
+        }
+
+        for action in messages {
+            if sender
+                .try_send(Message::Command { action, count: 1 })
+                .is_err()
+            {
+                // the app is lagging behind. Skip this input instead of
+                // letting it pile up
+                break;
+            }
+        }
+
+        thread::sleep(TICK);
+    }
+}
+
+/// How many pixels to move or resize by in a single tick,
+/// if the stick or trigger is pressed enough
+fn speed(value: f32) -> Option<u32> {
+    (value.abs() > DEAD_ZONE)
+        .then(|| ((value.abs() - DEAD_ZONE) / (1.0 - DEAD_ZONE) * MAX_SPEED).ceil() as u32)
+}
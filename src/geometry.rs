@@ -154,6 +154,16 @@ impl Side {
             Self::Left => ("h", "🡰"),
         }
     }
+
+    /// The side on the other end of the rectangle
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Right => Self::Left,
+            Self::Bottom => Self::Top,
+            Self::Left => Self::Right,
+        }
+    }
 }
 
 /// Where to resize / shrink / extend rectangle
@@ -200,6 +210,106 @@ impl SideOrCorner {
             .collect::<Vec<_>>()
             .join(", ")
     }
+
+    /// Where this ends up after the rectangle was flipped `horizontally` and `vertically`,
+    /// such as by resizing a side past the opposite one
+    pub const fn flipped(self, horizontally: bool, vertically: bool) -> Self {
+        match self {
+            Self::Side(side @ (Side::Left | Side::Right)) if horizontally => {
+                Self::Side(side.opposite())
+            }
+            Self::Side(side @ (Side::Top | Side::Bottom)) if vertically => {
+                Self::Side(side.opposite())
+            }
+            Self::Side(side) => Self::Side(side),
+            Self::Corner(corner) => {
+                let is_top = matches!(corner, Corner::TopLeft | Corner::TopRight) != vertically;
+                let is_left =
+                    matches!(corner, Corner::TopLeft | Corner::BottomLeft) != horizontally;
+
+                Self::Corner(match (is_top, is_left) {
+                    (true, true) => Corner::TopLeft,
+                    (true, false) => Corner::TopRight,
+                    (false, true) => Corner::BottomLeft,
+                    (false, false) => Corner::BottomRight,
+                })
+            }
+        }
+    }
+
+    /// The sides of the rectangle which move when resizing from here
+    pub fn sides(self) -> Vec<Side> {
+        match self {
+            Self::Side(side) => vec![side],
+            Self::Corner(corner) => match corner {
+                Corner::TopLeft => vec![Side::Top, Side::Left],
+                Corner::TopRight => vec![Side::Top, Side::Right],
+                Corner::BottomLeft => vec![Side::Bottom, Side::Left],
+                Corner::BottomRight => vec![Side::Bottom, Side::Right],
+            },
+        }
+    }
+}
+
+/// What stays in place while resizing the selection
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ResizeAnchor {
+    /// The opposite side or corner
+    #[default]
+    Opposite,
+    /// The center. The opposite side mirrors the change of the side being resized
+    Center,
+    /// The center, and the aspect ratio is preserved
+    CenterKeepAspectRatio,
+}
+
+impl ResizeAnchor {
+    /// Which anchor the held modifiers choose. Alt resizes symmetrically,
+    /// and Shift + Alt also preserves the aspect ratio
+    pub const fn of_modifiers(is_alt_down: bool, is_shift_down: bool) -> Self {
+        match (is_alt_down, is_shift_down) {
+            (false, _) => Self::Opposite,
+            (true, false) => Self::Center,
+            (true, true) => Self::CenterKeepAspectRatio,
+        }
+    }
+
+    /// Anchor the `resized` rectangle, which was resized from `initial` by dragging
+    /// the `resize_side` while the opposite side stayed in place
+    pub fn apply(
+        self,
+        initial: Rectangle,
+        resized: Rectangle,
+        resize_side: SideOrCorner,
+    ) -> Rectangle {
+        if self == Self::Opposite {
+            return resized;
+        }
+
+        // the opposite side moves by as much, in the other direction
+        let mut size = Size::new(
+            2.0f32.mul_add(resized.width - initial.width, initial.width),
+            2.0f32.mul_add(resized.height - initial.height, initial.height),
+        );
+
+        if self == Self::CenterKeepAspectRatio && initial.width != 0.0 && initial.height != 0.0 {
+            let scale_x = size.width / initial.width;
+            let scale_y = size.height / initial.height;
+            let scale = match resize_side {
+                SideOrCorner::Side(Side::Left | Side::Right) => scale_x,
+                SideOrCorner::Side(Side::Top | Side::Bottom) => scale_y,
+                // follow whichever side was dragged further
+                SideOrCorner::Corner(_) if (scale_x - 1.0).abs() > (scale_y - 1.0).abs() => scale_x,
+                SideOrCorner::Corner(_) => scale_y,
+            };
+            size = initial.size() * scale;
+        }
+
+        Rectangle::new(
+            initial.center() - Vector::new(size.width, size.height) * 0.5,
+            size,
+        )
+    }
 }
 
 impl FromStr for SideOrCorner {
@@ -268,6 +378,24 @@ impl SideOrCorner {
     }
 }
 
+/// How close the cursor has to be to the frame of the selection to grab it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrabArea {
+    /// The area around each side of the frame which allows that side to be hovered over and resized
+    pub sides: f32,
+    /// Distance from each corner of the frame which allows that corner to be hovered over and resized
+    pub corner_radius: f32,
+}
+
+impl Default for GrabArea {
+    fn default() -> Self {
+        Self {
+            sides: 35.0,
+            corner_radius: 25.0,
+        }
+    }
+}
+
 /// Corners of an `iced::Rectangle`
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Corners {
@@ -282,6 +410,26 @@ pub struct Corners {
 }
 
 impl Corners {
+    /// Point of the `corner`
+    pub const fn corner(&self, corner: Corner) -> Point {
+        match corner {
+            Corner::TopLeft => self.top_left,
+            Corner::TopRight => self.top_right,
+            Corner::BottomLeft => self.bottom_left,
+            Corner::BottomRight => self.bottom_right,
+        }
+    }
+
+    /// Both ends of the `side`
+    pub const fn side(&self, side: Side) -> (Point, Point) {
+        match side {
+            Side::Top => (self.top_left, self.top_right),
+            Side::Right => (self.top_right, self.bottom_right),
+            Side::Bottom => (self.bottom_left, self.bottom_right),
+            Side::Left => (self.top_left, self.bottom_left),
+        }
+    }
+
     /// Finds the nearest corner to this point
     pub fn nearest_corner(&self, point: Point) -> (Point, Corner) {
         let corners = [
@@ -302,65 +450,48 @@ impl Corners {
     }
 
     /// Return the interaction side for a point, if exists
-    pub fn side_at(&self, point: Point) -> Option<SideOrCorner> {
-        /// Shadow to apply to elements
-        /// The area around each side of the frame which allows that side to be hovered over and resized
-        const FRAME_INTERACTION_AREA: f32 = 35.0;
+    pub fn side_at(&self, point: Point, grab_area: GrabArea) -> Option<SideOrCorner> {
+        let half = grab_area.sides / 2.;
         let top = Rectangle {
             x: self.top_left.x,
-            y: self.top_left.y - FRAME_INTERACTION_AREA / 2.,
+            y: self.top_left.y - half,
             width: self.top_right.x - self.top_left.x,
-            height: FRAME_INTERACTION_AREA,
+            height: grab_area.sides,
         };
         let bottom = Rectangle {
             x: self.bottom_left.x,
-            y: self.bottom_left.y - FRAME_INTERACTION_AREA / 2.,
+            y: self.bottom_left.y - half,
             width: self.bottom_right.x - self.bottom_left.x,
-            height: FRAME_INTERACTION_AREA,
+            height: grab_area.sides,
         };
         let left = Rectangle {
-            x: self.top_left.x - FRAME_INTERACTION_AREA / 2.,
+            x: self.top_left.x - half,
             y: self.top_left.y,
-            width: FRAME_INTERACTION_AREA,
+            width: grab_area.sides,
             height: self.bottom_left.y - self.top_left.y,
         };
         let right = Rectangle {
-            x: self.top_right.x - FRAME_INTERACTION_AREA / 2.,
+            x: self.top_right.x - half,
             y: self.top_right.y,
-            width: FRAME_INTERACTION_AREA,
+            width: grab_area.sides,
             height: self.bottom_right.y - self.top_right.y,
         };
-        let top_left = Rectangle {
-            x: self.top_left.x - FRAME_INTERACTION_AREA / 2.,
-            y: self.top_left.y - FRAME_INTERACTION_AREA / 2.,
-            width: FRAME_INTERACTION_AREA,
-            height: FRAME_INTERACTION_AREA,
-        };
-        let top_right = Rectangle {
-            x: self.top_right.x - FRAME_INTERACTION_AREA / 2.,
-            y: self.top_right.y - FRAME_INTERACTION_AREA / 2.,
-            width: FRAME_INTERACTION_AREA,
-            height: FRAME_INTERACTION_AREA,
-        };
-        let bottom_left = Rectangle {
-            x: self.bottom_left.x - FRAME_INTERACTION_AREA / 2.,
-            y: self.bottom_left.y - FRAME_INTERACTION_AREA / 2.,
-            width: FRAME_INTERACTION_AREA,
-            height: FRAME_INTERACTION_AREA,
-        };
-        let bottom_right = Rectangle {
-            x: self.bottom_right.x - FRAME_INTERACTION_AREA / 2.,
-            y: self.bottom_right.y - FRAME_INTERACTION_AREA / 2.,
-            width: FRAME_INTERACTION_AREA,
-            height: FRAME_INTERACTION_AREA,
-        };
+
+        let corners = [
+            (self.top_left, Corner::TopLeft),
+            (self.top_right, Corner::TopRight),
+            (self.bottom_left, Corner::BottomLeft),
+            (self.bottom_right, Corner::BottomRight),
+        ];
+
+        // NOTE: the corners shall come first since the corners and sides will intersect
+        if let Some(corner) = corners.into_iter().find_map(|(vertex, corner)| {
+            (point.distance(vertex) <= grab_area.corner_radius).then_some(corner)
+        }) {
+            return Some(SideOrCorner::Corner(corner));
+        }
 
         [
-            // NOTE: the corners shall come first since the corners and sides will intersect
-            (top_left, SideOrCorner::Corner(Corner::TopLeft)),
-            (top_right, SideOrCorner::Corner(Corner::TopRight)),
-            (bottom_left, SideOrCorner::Corner(Corner::BottomLeft)),
-            (bottom_right, SideOrCorner::Corner(Corner::BottomRight)),
             // the sides will also intersect at the vertices, but that's fine since the vertices
             // will take priority
             (top, SideOrCorner::Side(Side::Top)),
@@ -399,8 +530,8 @@ pub impl Point<f32> {
     /// Find the midpoint of two points
     fn mid(self, other: Self) -> Self {
         Self {
-            x: (self.x + other.x) / 2.0,
-            y: (self.y + other.y) / 2.0,
+            x: f32::midpoint(self.x, other.x),
+            y: f32::midpoint(self.y, other.y),
         }
     }
 }
@@ -418,7 +549,7 @@ pub impl Rectangle<f32> {
         self.y += dy;
 
         self.height = (self.height - dy).min((container.y + container.height) - self.y);
-        self.width = (self.width - dx).min((container.x + container.width) - self.y);
+        self.width = (self.width - dx).min((container.x + container.width) - self.x);
 
         self
     }
@@ -437,7 +568,6 @@ pub impl Rectangle<f32> {
 
     /// point for which the `size` would be centered
     /// relative to the `Rectangle`
-    #[allow(dead_code, reason = "use later")]
     fn center_for(self, size: Size) -> Point {
         Point {
             x: self.center_x_for(size),
@@ -724,77 +854,104 @@ mod tests {
         let corners = rect.corners();
 
         assert_eq!(
-            corners.side_at(Point::new(100.0, 100.0)),
+            corners.side_at(Point::new(100.0, 100.0), GrabArea::default()),
             Some(SideOrCorner::Corner(Corner::TopLeft))
         );
         assert_eq!(
-            corners.side_at(Point::new(
-                100.0 - HALF_INTERACTION + 1.0,
-                100.0 - HALF_INTERACTION + 1.0
-            )),
+            corners.side_at(
+                Point::new(
+                    100.0 - HALF_INTERACTION + 1.0,
+                    100.0 - HALF_INTERACTION + 1.0
+                ),
+                GrabArea::default(),
+            ),
             Some(SideOrCorner::Corner(Corner::TopLeft))
         );
         assert_eq!(
-            corners.side_at(Point::new(300.0, 100.0)),
+            corners.side_at(Point::new(300.0, 100.0), GrabArea::default()),
             Some(SideOrCorner::Corner(Corner::TopRight))
         );
         assert_eq!(
-            corners.side_at(Point::new(100.0, 250.0)),
+            corners.side_at(Point::new(100.0, 250.0), GrabArea::default()),
             Some(SideOrCorner::Corner(Corner::BottomLeft))
         );
         assert_eq!(
-            corners.side_at(Point::new(300.0, 250.0)),
+            corners.side_at(Point::new(300.0, 250.0), GrabArea::default()),
             Some(SideOrCorner::Corner(Corner::BottomRight))
         );
 
         assert_eq!(
-            corners.side_at(Point::new(200.0, 100.0)),
+            corners.side_at(Point::new(200.0, 100.0), GrabArea::default()),
             Some(SideOrCorner::Side(Side::Top))
         );
         assert_eq!(
-            corners.side_at(Point::new(200.0, 100.0 - HALF_INTERACTION + 1.0)),
+            corners.side_at(
+                Point::new(200.0, 100.0 - HALF_INTERACTION + 1.0),
+                GrabArea::default()
+            ),
             Some(SideOrCorner::Side(Side::Top))
         );
         assert_eq!(
-            corners.side_at(Point::new(200.0, 100.0 + HALF_INTERACTION - 1.0)),
+            corners.side_at(
+                Point::new(200.0, 100.0 + HALF_INTERACTION - 1.0),
+                GrabArea::default()
+            ),
             Some(SideOrCorner::Side(Side::Top))
         );
 
         assert_eq!(
-            corners.side_at(Point::new(200.0, 250.0)),
+            corners.side_at(Point::new(200.0, 250.0), GrabArea::default()),
             Some(SideOrCorner::Side(Side::Bottom))
         );
         assert_eq!(
-            corners.side_at(Point::new(200.0, 250.0 - HALF_INTERACTION + 1.0)),
+            corners.side_at(
+                Point::new(200.0, 250.0 - HALF_INTERACTION + 1.0),
+                GrabArea::default()
+            ),
             Some(SideOrCorner::Side(Side::Bottom))
         );
 
         assert_eq!(
-            corners.side_at(Point::new(100.0, 150.0)),
+            corners.side_at(Point::new(100.0, 150.0), GrabArea::default()),
             Some(SideOrCorner::Side(Side::Left))
         );
         assert_eq!(
-            corners.side_at(Point::new(100.0 - HALF_INTERACTION + 1.0, 150.0)),
+            corners.side_at(
+                Point::new(100.0 - HALF_INTERACTION + 1.0, 150.0),
+                GrabArea::default()
+            ),
             Some(SideOrCorner::Side(Side::Left))
         );
 
         assert_eq!(
-            corners.side_at(Point::new(300.0, 150.0)),
+            corners.side_at(Point::new(300.0, 150.0), GrabArea::default()),
             Some(SideOrCorner::Side(Side::Right))
         );
         assert_eq!(
-            corners.side_at(Point::new(300.0 + HALF_INTERACTION - 1.0, 150.0)),
+            corners.side_at(
+                Point::new(300.0 + HALF_INTERACTION - 1.0, 150.0),
+                GrabArea::default()
+            ),
             Some(SideOrCorner::Side(Side::Right))
         );
 
         // Test point outside any interaction area
-        assert_eq!(corners.side_at(Point::new(0.0, 0.0)), None);
         assert_eq!(
-            corners.side_at(Point::new(200.0, 100.0 + HALF_INTERACTION + 1.0)),
+            corners.side_at(Point::new(0.0, 0.0), GrabArea::default()),
+            None
+        );
+        assert_eq!(
+            corners.side_at(
+                Point::new(200.0, 100.0 + HALF_INTERACTION + 1.0),
+                GrabArea::default()
+            ),
             None
         );
         // Just below top interaction
-        assert_eq!(corners.side_at(Point::new(200.0, 200.0)), None);
+        assert_eq!(
+            corners.side_at(Point::new(200.0, 200.0), GrabArea::default()),
+            None
+        );
 
         // Point within
         // - top-left corner rect
@@ -805,9 +962,35 @@ mod tests {
             100.0 - HALF_INTERACTION / 2.0,
         );
         assert_eq!(
-            corners.side_at(point_in_top_left_corner_interaction),
+            corners.side_at(point_in_top_left_corner_interaction, GrabArea::default()),
+            Some(SideOrCorner::Corner(Corner::TopLeft))
+        );
+    }
+
+    #[test]
+    fn test_corners_side_at_custom_grab_area() {
+        let rect = Rectangle::new(Point::new(100.0, 100.0), Size::new(200.0, 150.0));
+        let corners = rect.corners();
+        let grab_area = GrabArea {
+            sides: 10.0,
+            corner_radius: 40.0,
+        };
+
+        // outside of the sides, but within the radius of the corner
+        assert_eq!(
+            corners.side_at(Point::new(130.0, 120.0), grab_area),
             Some(SideOrCorner::Corner(Corner::TopLeft))
         );
+        assert_eq!(
+            corners.side_at(Point::new(200.0, 104.0), grab_area),
+            Some(SideOrCorner::Side(Side::Top))
+        );
+        // would be grabbed with the default grab area
+        assert_eq!(corners.side_at(Point::new(200.0, 110.0), grab_area), None);
+        assert_eq!(
+            corners.side_at(Point::new(200.0, 110.0), GrabArea::default()),
+            Some(SideOrCorner::Side(Side::Top))
+        );
     }
 
     #[test]
@@ -972,5 +1155,67 @@ mod tests {
                 width: 50.0,
             }
         );
+
+        assert_eq!(
+            Rectangle {
+                x: 700.0,
+                y: 100.0,
+                width: 200.0,
+                height: 100.0,
+            }
+            .clipped_in_bounds_of(Rectangle {
+                x: 0.0,
+                y: 0.0,
+                width: 800.0,
+                height: 600.0,
+            }),
+            Rectangle {
+                x: 700.0,
+                y: 100.0,
+                width: 100.0,
+                height: 100.0,
+            },
+            "overflowing to the right"
+        );
+    }
+
+    #[test]
+    fn flipped_side_or_corner() {
+        assert_eq!(
+            SideOrCorner::Side(Side::Left).flipped(true, false),
+            SideOrCorner::Side(Side::Right)
+        );
+        assert_eq!(
+            SideOrCorner::Side(Side::Left).flipped(false, true),
+            SideOrCorner::Side(Side::Left),
+            "flipping vertically does not move the left side"
+        );
+        assert_eq!(
+            SideOrCorner::Corner(Corner::TopLeft).flipped(true, true),
+            SideOrCorner::Corner(Corner::BottomRight)
+        );
+    }
+
+    #[test]
+    fn resize_anchor() {
+        let initial = Rectangle::new(Point::new(100.0, 100.0), Size::new(200.0, 100.0));
+        // the right side was dragged 20px to the right
+        let resized = Rectangle::new(initial.position(), Size::new(220.0, 100.0));
+        let right = SideOrCorner::Side(Side::Right);
+
+        assert_eq!(
+            ResizeAnchor::Opposite.apply(initial, resized, right),
+            resized
+        );
+        assert_eq!(
+            ResizeAnchor::Center.apply(initial, resized, right),
+            Rectangle::new(Point::new(80.0, 100.0), Size::new(240.0, 100.0)),
+            "the left side mirrors the right side"
+        );
+        assert_eq!(
+            ResizeAnchor::CenterKeepAspectRatio.apply(initial, resized, right),
+            Rectangle::new(Point::new(80.0, 90.0), Size::new(240.0, 120.0)),
+            "the height grows with the width"
+        );
     }
 }
@@ -73,6 +73,8 @@ load_icons! {
     Pen,
     /// Draw a square
     Square,
+    /// Blur or pixelate parts of the image
+    Redact,
     /// Add text
     Text,
     /// Upload image to the internet
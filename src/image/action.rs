@@ -9,13 +9,18 @@ use iced::Rectangle;
 use iced::Task;
 use image::DynamicImage;
 
-use crate::image::upload::ImageUploaded;
-use crate::{App, geometry::RectangleExt as _, ui::popup::image_uploaded};
+use crate::image::{conflict::OnConflict, upload::ImageUploaded};
+use crate::{
+    App,
+    error_code::ErrorCode,
+    geometry::RectangleExt as _,
+    ui::popup::{Popup, file_picker, image_uploaded},
+};
 use iced::widget;
 
 // INFO: Documentation comments for the enum are used in `--help`
 crate::declare_commands! {
-    #[derive(clap::ValueEnum)]
+    #[derive(clap::ValueEnum, Copy)]
     /// Action to take with the image
     enum Command {
         /// Copy image to the clipboard
@@ -30,23 +35,90 @@ crate::declare_commands! {
 impl crate::command::Handler for Command {
     fn handle(self, app: &mut App, _count: u32) -> Task<crate::Message> {
         let Some(rect) = app.selection.map(|sel| sel.rect.norm()) else {
-            app.errors.push(match self {
-                Self::CopyToClipboard => "There is no selection to copy",
-                Self::UploadScreenshot => "There is no selection to upload",
-                Self::SaveScreenshot => "There is no selection to save",
-            });
+            app.errors.push_code(
+                ErrorCode::NoSelection,
+                match self {
+                    Self::CopyToClipboard => "There is no selection to copy",
+                    Self::UploadScreenshot => "There is no selection to upload",
+                    Self::SaveScreenshot => "There is no selection to save",
+                },
+            );
             return Task::none();
         };
 
+        if let Err(err) = check_size(rect, app.config.min_selection_size()) {
+            app.errors
+                .push_code(ErrorCode::SelectionTooSmall, err.to_string());
+
+            // let the next selection be accepted instead, like the accidental one never happened
+            if app.cli.accept_on_select.is_some() {
+                app.selection = None;
+                app.selections_created = 0;
+            }
+            return Task::none();
+        }
+
         if self == Self::UploadScreenshot {
+            if !app.config.can_upload() {
+                app.errors
+                    .push_code(ErrorCode::UploadDisabled, UPLOAD_DISABLED);
+                return Task::none();
+            }
             app.is_uploading_image = true;
         }
 
-        let image = App::process_image(rect, &app.image);
+        if app.config.statistics {
+            crate::stats::record(|stats| stats.record_action(self));
+        }
+
+        let image = app.annotations.apply(
+            app.redactions.apply(
+                app.adjustments.apply(App::process_image(rect, &app.image)),
+                rect,
+            ),
+            rect,
+        );
+        let autosave = app.config.autosave();
+        let upload = app.config.upload();
+
+        if self == Self::SaveScreenshot && app.cli.picks_save_path() {
+            remember(&image, rect, autosave);
+            app.popup = Some(Popup::FilePicker(file_picker::State::new(
+                image,
+                app.config.image_format,
+            )));
+            return Task::none();
+        }
+
+        // the screenshot is saved once the app exits, so ask about the existing file before that
+        if self == Self::SaveScreenshot
+            && !app.cli.raw
+            && app.config.on_conflict == OnConflict::Ask
+            && let Some(path) = app.cli.save_path.as_ref().filter(|path| path.exists())
+        {
+            remember(&image, rect, autosave);
+            app.popup = Some(Popup::FilePicker(file_picker::State::conflicting(
+                image,
+                path.clone(),
+            )));
+            return Task::none();
+        }
+
+        let outcomes = app.outcomes.clone();
+        let notifications = app.config.notifications();
 
         Task::future(async move {
-            match self.execute(image, rect).await {
-                Ok((Output::Saved | Output::Copied, _)) => crate::message::Message::Exit,
+            match self.execute(image, rect, autosave, upload).await {
+                Ok((Output::Saved(image), _)) => {
+                    if outcomes.send(crate::Outcome::Saved(image)).is_err() {
+                        log::error!("Nothing is waiting for the screenshot to save it");
+                    }
+                    crate::message::Message::Exit
+                }
+                Ok((Output::Copied(image), _)) => {
+                    notifications.copied(&image).await;
+                    crate::message::Message::Exit
+                }
                 Ok((
                     Output::Uploaded {
                         path,
@@ -54,16 +126,23 @@ impl crate::command::Handler for Command {
                         file_size,
                     },
                     ImageData { height, width },
-                )) => crate::Message::ImageUploaded(image_uploaded::Message::ImageUploaded(
-                    image_uploaded::ImageUploadedData {
-                        image_uploaded: data,
-                        uploaded_image: widget::image::Handle::from_path(&path),
-                        height,
-                        width,
-                        file_size,
-                    },
-                )),
-                Err(err) => crate::Message::Error(err.to_string()),
+                )) => {
+                    notifications.uploaded(&path, &data.link).await;
+
+                    crate::Message::ImageUploaded(image_uploaded::Message::ImageUploaded(
+                        image_uploaded::ImageUploadedData {
+                            image_uploaded: data,
+                            uploaded_image: widget::image::Handle::from_path(&path),
+                            height,
+                            width,
+                            file_size,
+                        },
+                    ))
+                }
+                Err(err) => match err.code() {
+                    Some(code) => crate::Message::ErrorWithCode(code, err.to_string()),
+                    None => crate::Message::Error(err.to_string()),
+                },
             }
         })
     }
@@ -80,11 +159,9 @@ pub struct ImageData {
 /// The output of an image action
 pub enum Output {
     /// Copied to the clipboard
-    Copied,
-    /// Saved to a path
-    ///
-    /// We don't know the path yet. We'll find out at the end of `main`.
-    Saved,
+    Copied(DynamicImage),
+    /// To be saved to the `--save-path`, by whoever launched the app
+    Saved(DynamicImage),
     /// Uploaded to the internet
     Uploaded {
         /// information about the uploaded image
@@ -114,9 +191,43 @@ pub enum Error {
     /// Could not get the image
     #[error(transparent)]
     GetImage(#[from] crate::image::GetImageError),
+    /// The selection is smaller than `min-selection-size`
+    #[error("the selection is {width}x{height} pixels, it must be at least {min}x{min} pixels")]
+    SelectionTooSmall {
+        /// Width of the image the selection would produce
+        width: u32,
+        /// Height of the image the selection would produce
+        height: u32,
+        /// Smallest allowed width and height
+        min: u32,
+    },
 }
 
+impl Error {
+    /// Code of the error, which `--explain` explains
+    pub const fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Self::Clipboard(_) => Some(ErrorCode::Clipboard),
+            Self::ImageUpload(_) => Some(ErrorCode::UploadFailed),
+            Self::SelectionTooSmall { .. } => Some(ErrorCode::SelectionTooSmall),
+            Self::Io(_) | Self::SaveImage(_) | Self::GetImage(_) => None,
+        }
+    }
+}
+
+/// Shown when trying to upload, but uploading is disabled
+const UPLOAD_DISABLED: &str = if cfg!(feature = "upload") {
+    "Uploading is disabled by the `allow-upload` option in the config"
+} else {
+    "Uploading is not available, ferrishot was built without the `upload` feature"
+};
+
 impl Command {
+    /// Whether this action sends the screenshot over the network
+    pub const fn needs_network(self) -> bool {
+        matches!(self, Self::UploadScreenshot)
+    }
+
     /// Convert this into a key action
     pub fn into_key_action(self) -> crate::Command {
         match self {
@@ -127,51 +238,59 @@ impl Command {
     }
 
     /// Execute the action
+    ///
+    /// With `autosave`, the image is also written to the spool directory, whichever the action.
+    /// `upload` decides where the image is uploaded, and in which format
     pub async fn execute(
         self,
         image: DynamicImage,
         region: Rectangle,
+        autosave: Option<crate::autosave::Autosave>,
+        upload: crate::image::upload::Upload,
     ) -> Result<(Output, ImageData), Error> {
         let image_data = ImageData {
             height: image.height(),
             width: image.width(),
         };
 
-        // NOTE: Not a hard error, so no need to abort the main action
-        if let Err(failed_to_write) = crate::last_region::write(region) {
-            log::error!(
-                "Failed to save the current rectangle selection, for possible re-use: {failed_to_write}"
-            );
-        }
+        remember(&image, region, autosave);
 
         let out = match self {
-            Self::CopyToClipboard => crate::clipboard::set_image(arboard::ImageData {
-                width: image.width() as usize,
-                height: image.height() as usize,
-                bytes: std::borrow::Cow::Borrowed(image.as_bytes()),
-            })
-            .map(|_| (Output::Copied, image_data))?,
-            Self::SaveScreenshot => {
-                let _ = SAVED_IMAGE.set(image);
-                (Output::Saved, image_data)
+            Self::CopyToClipboard => {
+                crate::clipboard::set_image(arboard::ImageData {
+                    width: image.width() as usize,
+                    height: image.height() as usize,
+                    bytes: std::borrow::Cow::Borrowed(image.as_bytes()),
+                })?;
+
+                (Output::Copied(image), image_data)
             }
+            Self::SaveScreenshot => (Output::Saved(image), image_data),
             Self::UploadScreenshot => {
-                let path = tempfile::TempDir::new()?
-                    .into_path()
-                    .join("ferrishot-screenshot.png");
+                let path = tempfile::TempDir::new()?.into_path().join(format!(
+                    "ferrishot-screenshot.{}",
+                    upload.format.extension()
+                ));
 
-                // TODO: allow configuring the upload format
-                // in-app
-                image.save_with_format(&path, image::ImageFormat::Png)?;
+                std::fs::write(
+                    &path,
+                    crate::image::encode::encode(
+                        &image,
+                        upload.format.image_format(),
+                        upload.parallel_encoding,
+                    )?,
+                )?;
 
                 (
                     Output::Uploaded {
-                        data: crate::image::upload::upload(&path).await.map_err(|err| {
-                            err.into_iter()
-                                .next()
-                                .map(Error::ImageUpload)
-                                .expect("at least 1 image upload provider")
-                        })?,
+                        data: crate::image::upload::upload(&path, upload.provider)
+                            .await
+                            .map_err(|err| {
+                                err.into_iter()
+                                    .next()
+                                    .map(Error::ImageUpload)
+                                    .expect("at least 1 image upload provider")
+                            })?,
                         file_size: path.metadata().map(|meta| meta.len()).unwrap_or(0),
                         path,
                     },
@@ -184,37 +303,51 @@ impl Command {
     }
 }
 
-/// The image to save to a file, chosen by the user in a file picker.
-///
-/// Unfortunately, there is simply no way to communicate something from
-/// the inside of an iced application to the outside: i.e. "Return" something
-/// from an iced program exiting. So we have to use a global variable for this.
+/// Reject a `region` whose image would be narrower or shorter than `min` pixels.
 ///
-/// This global is mutated just *once* at the end of the application's lifetime,
-/// when the window closes.
-///
-/// It is then accessed just *once* to open the file dialog and let the user pick
-/// where they want to save their image.
-///
-/// Yes, at the moment we want this when using Ctrl + S to save as file:
-/// 1. Close the application to save the file and generate the image we'll save
-/// 2. Open the file explorer, and save the image to the specified path
-///
-/// When the file explorer is spawned from the inside of an iced window, closing
-/// this window will then also close the file explorer. It means that we can't
-/// close the window and then spawn an explorer.
-///
-/// The other option is to have both windows open at the same time. But this
-/// would be really odd. First of all, we will need to un-fullscreen the App
-/// because the file explorer can spawn under the app.
-///
-/// This is going to be sub-optimal. Currently, we give off the illusion of
-/// drawing shapes and rectangles on top of the desktop. It is not immediately
-/// obvious that the app is just another window which is full-screen.
-/// Doing the above would break that illusion.
-///
-/// Ideally, we would be able to spawn a file explorer *above* the window without
-/// having to close this. But this seems to not be possible. Perhaps in the
-/// future there will be some kind of file explorer Iced widget that we
-/// can use instead of the native file explorer.
-pub static SAVED_IMAGE: std::sync::OnceLock<DynamicImage> = std::sync::OnceLock::new();
+/// Sub-pixel parts of the `region` are cut off, same as when the image is cropped
+pub fn check_size(region: Rectangle, min: u32) -> Result<(), Error> {
+    let (width, height) = (region.width as u32, region.height as u32);
+
+    if width < min || height < min {
+        return Err(Error::SelectionTooSmall { width, height, min });
+    }
+
+    Ok(())
+}
+
+/// Keep the `region` for `--last-region`, and the `image` if autosave is enabled
+fn remember(image: &DynamicImage, region: Rectangle, autosave: Option<crate::autosave::Autosave>) {
+    // NOTE: Not a hard error, so no need to abort the main action
+    if let Err(failed_to_write) = crate::last_region::write(region) {
+        log::error!(
+            "Failed to save the current rectangle selection, for possible re-use: {failed_to_write}"
+        );
+    }
+
+    if let Some(autosave) = autosave {
+        autosave.run(image);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced::{Point, Size};
+
+    #[test]
+    fn rejects_degenerate_selections() {
+        let rect = |width, height| Rectangle::new(Point::new(10.0, 10.0), Size::new(width, height));
+
+        assert!(
+            check_size(rect(0.0, 0.0), 1).is_err(),
+            "a click selects nothing"
+        );
+        assert!(
+            check_size(rect(0.6, 40.0), 1).is_err(),
+            "sub-pixel width is cut off when cropping"
+        );
+        assert!(check_size(rect(1.0, 1.0), 1).is_ok());
+        assert!(check_size(rect(8.0, 20.0), 10).is_err());
+    }
+}
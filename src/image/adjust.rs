@@ -0,0 +1,230 @@
+//! Adjust the brightness, contrast and saturation of the screenshot
+//!
+//! Adjustments are applied to the image which is copied, saved or uploaded.
+//! While adjusting, the selection shows a preview of the adjusted image.
+
+use std::fmt;
+
+use iced::{Rectangle, Task, widget::image::Handle};
+use image::DynamicImage;
+
+use crate::{App, geometry::RectangleExt as _};
+
+use super::RgbaHandle;
+
+crate::declare_commands! {
+    enum Command {
+        /// Make the screenshot brighter, or darker with a negative amount
+        AdjustBrightness {
+            /// How much brightness to add, from `-1.0` to `1.0`
+            amount: f32 = 0.05,
+        },
+        /// Increase the contrast of the screenshot, or decrease it with a negative amount
+        AdjustContrast {
+            /// How much contrast to add, from `-1.0` to `1.0`
+            amount: f32 = 0.05,
+        },
+        /// Make the colors of the screenshot more vivid, or duller with a negative amount
+        AdjustSaturation {
+            /// How much saturation to add, from `-1.0` to `1.0`
+            amount: f32 = 0.05,
+        },
+        /// Undo all adjustments
+        ResetAdjustments,
+    }
+}
+
+impl crate::command::Handler for Command {
+    fn handle(self, app: &mut App, count: u32) -> Task<crate::Message> {
+        // rounded to whole percents, so that adjusting back and forth returns to exactly `0.0`
+        let adjust = |value: f32, amount: f32| {
+            ((value + amount * count as f32) * 100.0)
+                .round()
+                .clamp(-100.0, 100.0)
+                / 100.0
+        };
+        let adjustments = &mut app.adjustments;
+
+        match self {
+            Self::AdjustBrightness { amount } => {
+                adjustments.brightness = adjust(adjustments.brightness, amount);
+            }
+            Self::AdjustContrast { amount } => {
+                adjustments.contrast = adjust(adjustments.contrast, amount);
+            }
+            Self::AdjustSaturation { amount } => {
+                adjustments.saturation = adjust(adjustments.saturation, amount);
+            }
+            Self::ResetAdjustments => {
+                *adjustments = Adjustments::default();
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Adjustments to the colors of the screenshot. Each is from `-1.0` to `1.0`,
+/// and `0.0` leaves the image unchanged
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Adjustments {
+    /// Added to each channel
+    pub brightness: f32,
+    /// How far each channel is pushed away from the middle gray
+    pub contrast: f32,
+    /// How far each channel is pushed away from the gray of the same luminance
+    pub saturation: f32,
+}
+
+impl Adjustments {
+    /// Whether the adjustments leave the image unchanged
+    pub fn is_identity(self) -> bool {
+        self == Self::default()
+    }
+
+    /// Adjust the `image`
+    pub fn apply(self, image: DynamicImage) -> DynamicImage {
+        if self.is_identity() {
+            return image;
+        }
+
+        let mut image = image.into_rgba8();
+        for pixel in image.pixels_mut() {
+            self.apply_to_pixel(&mut pixel.0);
+        }
+
+        DynamicImage::from(image)
+    }
+
+    /// Adjust a single RGBA `pixel`. The alpha channel is unchanged
+    fn apply_to_pixel(self, pixel: &mut [u8; 4]) {
+        let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|channel| {
+            let channel = f32::from(channel) / 255.0 + self.brightness;
+            (channel - 0.5) * (1.0 + self.contrast) + 0.5
+        });
+
+        let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+
+        let [r, g, b] = [r, g, b].map(|channel| {
+            let channel = luminance + (channel - luminance) * (1.0 + self.saturation);
+            (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+        });
+
+        pixel[..3].copy_from_slice(&[r, g, b]);
+    }
+}
+
+impl fmt::Display for Adjustments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = |value: f32| (value * 100.0).round() as i32;
+
+        write!(
+            f,
+            "BRIGHTNESS {:+}% | CONTRAST {:+}% | SATURATION {:+}%",
+            percent(self.brightness),
+            percent(self.contrast),
+            percent(self.saturation)
+        )
+    }
+}
+
+/// The selected part of the screenshot with the adjustments applied,
+/// which is drawn on top of the selection
+#[derive(Debug, Clone)]
+pub struct Preview {
+    /// Area of the screenshot that was adjusted
+    pub rect: Rectangle,
+    /// Adjustments that were applied
+    pub adjustments: Adjustments,
+    /// The adjusted image
+    pub handle: Handle,
+}
+
+impl Preview {
+    /// Preview of the `adjustments` to the `rect` of the `image`. The `previous` preview
+    /// is kept if neither changed, since adjusting the image is slow
+    pub fn refresh(
+        previous: Option<Self>,
+        image: &RgbaHandle,
+        rect: Option<Rectangle>,
+        adjustments: Adjustments,
+    ) -> Option<Self> {
+        let rect = rect.filter(|_| !adjustments.is_identity())?.norm();
+
+        if let Some(previous) = previous
+            && previous.rect == rect
+            && previous.adjustments == adjustments
+        {
+            return Some(previous);
+        }
+
+        let adjusted = adjustments
+            .apply(App::process_image(rect, image))
+            .into_rgba8();
+
+        Some(Self {
+            rect,
+            adjustments,
+            handle: Handle::from_rgba(adjusted.width(), adjusted.height(), adjusted.into_raw()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Adjust a single pixel
+    fn adjust(adjustments: Adjustments, pixel: [u8; 4]) -> [u8; 4] {
+        let mut pixel = pixel;
+        adjustments.apply_to_pixel(&mut pixel);
+        pixel
+    }
+
+    #[test]
+    fn identity_keeps_pixels() {
+        for pixel in [[0, 0, 0, 255], [12, 200, 99, 128], [255, 255, 255, 0]] {
+            assert_eq!(adjust(Adjustments::default(), pixel), pixel);
+        }
+    }
+
+    #[test]
+    fn adjusts_pixels() {
+        let brighter = Adjustments {
+            brightness: 0.2,
+            ..Default::default()
+        };
+        assert_eq!(adjust(brighter, [100, 100, 100, 7]), [151, 151, 151, 7]);
+
+        let more_contrast = Adjustments {
+            contrast: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(
+            adjust(more_contrast, [100, 200, 128, 255]),
+            [86, 236, 128, 255]
+        );
+
+        let grayscale = Adjustments {
+            saturation: -1.0,
+            ..Default::default()
+        };
+        let [r, g, b, _] = adjust(grayscale, [255, 0, 0, 255]);
+        assert_eq!([r, g, b], [54, 54, 54]);
+    }
+
+    #[test]
+    fn shows_percentages() {
+        let adjustments = Adjustments {
+            brightness: 0.1,
+            contrast: -0.25,
+            saturation: 0.0,
+        };
+
+        assert_eq!(
+            adjustments.to_string(),
+            "BRIGHTNESS +10% | CONTRAST -25% | SATURATION +0%"
+        );
+    }
+}
@@ -0,0 +1,585 @@
+//! Draw on the screenshot with a pen, or mark parts of it with a semi-transparent highlighter
+//!
+//! While drawing, dragging inside of the selection draws a stroke instead of moving the
+//! selection. Strokes are drawn on top of the selection, and baked into the image which is
+//! copied, saved or uploaded.
+//!
+//! Until then, each stroke stays editable: while drawing, clicking a stroke selects it.
+//! The selected stroke can be dragged to move it, resized with the handles at the corners
+//! of its outline, or deleted.
+//!
+//! Strokes can also be exported to, and imported from, Flameshot and `ShareX`.
+//! See [`super::interop`]
+
+use std::path::Path;
+
+use iced::{Color, Point, Rectangle, Size, Task, Vector, widget::canvas};
+use image::{DynamicImage, Pixel as _, RgbaImage};
+
+use crate::{App, Config};
+
+/// How far from a stroke a click still selects it, in pixels
+const HIT_DISTANCE: f32 = 4.0;
+
+/// Width and height of the handles at the corners of the selected stroke, in pixels
+const HANDLE_SIZE: f32 = 8.0;
+
+/// What strokes are drawn with
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tool {
+    /// Thin line in the `pen` color of the theme
+    #[default]
+    Pen,
+    /// Wide line in the `highlighter` color of the theme, which should be semi-transparent
+    Highlighter,
+}
+
+impl Tool {
+    /// Color and width of the strokes drawn with this tool
+    pub fn style(self, config: &Config) -> (Color, f32) {
+        match self {
+            Self::Pen => (config.theme.pen, config.pen_width),
+            Self::Highlighter => (config.theme.highlighter, config.highlighter_width),
+        }
+    }
+}
+
+crate::declare_commands! {
+    enum Command {
+        /// Drag inside of the selection to draw on it, instead of moving it.
+        /// Toggling again with the other tool switches to it
+        ToggleDrawing {
+            /// What to draw with
+            tool: Tool = Tool::Pen,
+        },
+        /// Remove the stroke that was drawn last
+        UndoStroke,
+        /// Remove the selected stroke
+        DeleteStroke,
+        /// Remove all strokes
+        ClearStrokes,
+        /// Write the strokes to a `.flameshot` file for Flameshot, or a `.json` file for `ShareX`
+        ExportStrokes {
+            /// Where to write the strokes
+            path: String,
+        },
+        /// Draw the strokes of a `.flameshot` file from Flameshot, or a `.json` file from `ShareX`
+        ImportStrokes {
+            /// Where to read the strokes from
+            path: String,
+        },
+    }
+}
+
+impl crate::command::Handler for Command {
+    fn handle(self, app: &mut App, _count: u32) -> Task<crate::Message> {
+        let annotations = &mut app.annotations;
+
+        match self {
+            Self::ToggleDrawing { tool } => {
+                annotations.tool = (annotations.tool != Some(tool)).then_some(tool);
+                // dragging can either draw or redact, not both
+                if annotations.is_active() {
+                    app.redactions.style = None;
+                } else {
+                    annotations.selected = None;
+                }
+            }
+            Self::UndoStroke => {
+                annotations.selected = None;
+                if annotations.strokes.pop().is_none() {
+                    app.errors.push("There is nothing to undo");
+                }
+            }
+            Self::DeleteStroke => {
+                if let Some(index) = annotations.selected.take() {
+                    annotations.strokes.remove(index);
+                } else {
+                    app.errors
+                        .push("No stroke is selected. Click a stroke while drawing");
+                }
+            }
+            Self::ClearStrokes => {
+                annotations.selected = None;
+                annotations.strokes.clear();
+            }
+            Self::ExportStrokes { path } => {
+                if annotations.strokes.is_empty() {
+                    app.errors.push("There are no strokes to export");
+                } else if let Err(err) =
+                    super::interop::export(&annotations.strokes, Path::new(&path))
+                {
+                    app.errors
+                        .push(format!("Failed to export the strokes to {path}: {err}"));
+                }
+            }
+            Self::ImportStrokes { path } => match super::interop::import(Path::new(&path)) {
+                Ok((strokes, skipped)) => {
+                    annotations.selected = None;
+                    annotations.strokes.extend(strokes);
+                    if skipped > 0 {
+                        app.errors.warn(format!(
+                            "Skipped {skipped} shapes of {path}, only freehand lines can be drawn"
+                        ));
+                    }
+                }
+                Err(err) => app
+                    .errors
+                    .push(format!("Failed to import the strokes from {path}: {err}")),
+            },
+        }
+
+        Task::none()
+    }
+}
+
+/// Message for drawing
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Draw a stroke through the points, in the coordinates of the screenshot
+    Add(Vec<Point>),
+    /// Select the stroke at the index, or stop selecting any
+    Select(Option<usize>),
+    /// Replace the stroke at the index with the one that was moved or resized
+    Edit(usize, Stroke),
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut App) -> Task<crate::Message> {
+        match self {
+            Self::Add(points) => {
+                let Some(tool) = app.annotations.tool else {
+                    return Task::none();
+                };
+                let (color, width) = tool.style(&app.config);
+
+                if !points.is_empty() {
+                    app.annotations.strokes.push(Stroke {
+                        points,
+                        color,
+                        width,
+                    });
+                }
+            }
+            Self::Select(index) => {
+                app.annotations.selected =
+                    index.filter(|index| *index < app.annotations.strokes.len());
+            }
+            Self::Edit(index, stroke) => {
+                if let Some(old) = app.annotations.strokes.get_mut(index) {
+                    *old = stroke;
+                }
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Draw a stroke through the `points` on the `frame`, with each point shifted by `offset`
+pub fn draw_stroke(
+    frame: &mut canvas::Frame,
+    points: &[Point],
+    color: Color,
+    width: f32,
+    offset: Vector,
+) {
+    // a path through a single point has no length, so it would not be visible
+    if let [point] = points {
+        frame.fill(&canvas::Path::circle(*point - offset, width / 2.0), color);
+        return;
+    }
+
+    let path = canvas::Path::new(|builder| {
+        let mut points = points.iter().map(|point| *point - offset);
+        if let Some(first) = points.next() {
+            builder.move_to(first);
+        }
+        for point in points {
+            builder.line_to(point);
+        }
+    });
+
+    frame.stroke(
+        &path,
+        canvas::Stroke {
+            line_cap: canvas::LineCap::Round,
+            line_join: canvas::LineJoin::Round,
+            ..canvas::Stroke::default()
+                .with_color(color)
+                .with_width(width)
+        },
+    );
+}
+
+/// Distance from `point` to the closest point on the line segment from `start` to `end`
+fn distance_to_segment(point: Point, start: Point, end: Point) -> f32 {
+    let segment = end - start;
+    let to_point = point - start;
+    let length_squared = segment.x * segment.x + segment.y * segment.y;
+
+    // how far along the segment the closest point is, from `0.0` to `1.0`
+    let t = if length_squared <= f32::EPSILON {
+        0.0
+    } else {
+        ((to_point.x * segment.x + to_point.y * segment.y) / length_squared).clamp(0.0, 1.0)
+    };
+
+    point.distance(start + segment * t)
+}
+
+/// A line drawn freehand on the screenshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    /// Points that the line goes through, in the coordinates of the screenshot
+    pub points: Vec<Point>,
+    /// Color of the line
+    pub color: Color,
+    /// Width of the line, in pixels
+    pub width: f32,
+}
+
+impl Stroke {
+    /// Draw the stroke on the `frame`, with each point shifted by `offset`
+    pub fn draw(&self, frame: &mut canvas::Frame, offset: Vector) {
+        draw_stroke(frame, &self.points, self.color, self.width, offset);
+    }
+
+    /// Smallest rectangle which contains the whole line, including its width
+    pub fn bounds(&self) -> Rectangle {
+        let radius = self.width / 2.0;
+        let (min, max) = self.points.iter().fold(
+            (
+                Point::new(f32::INFINITY, f32::INFINITY),
+                Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            ),
+            |(min, max), point| {
+                (
+                    Point::new(min.x.min(point.x), min.y.min(point.y)),
+                    Point::new(max.x.max(point.x), max.y.max(point.y)),
+                )
+            },
+        );
+
+        Rectangle::new(
+            Point::new(min.x - radius, min.y - radius),
+            Size::new(max.x - min.x + self.width, max.y - min.y + self.width),
+        )
+    }
+
+    /// Whether clicking the `point` clicks this stroke
+    fn is_hit(&self, point: Point) -> bool {
+        let radius = self.width / 2.0 + HIT_DISTANCE;
+
+        match self.points.as_slice() {
+            [only] => only.distance(point) <= radius,
+            points => points
+                .windows(2)
+                .any(|segment| distance_to_segment(point, segment[0], segment[1]) <= radius),
+        }
+    }
+
+    /// Draw a dashed outline around the stroke, with a handle at each corner to resize it
+    pub fn draw_outline(&self, frame: &mut canvas::Frame, color: Color, offset: Vector) {
+        let bounds = self.bounds();
+        frame.stroke_rectangle(
+            bounds.position() - offset,
+            bounds.size(),
+            canvas::Stroke {
+                line_dash: canvas::LineDash {
+                    segments: &[4.0, 4.0],
+                    offset: 0,
+                },
+                ..canvas::Stroke::default().with_color(color).with_width(1.0)
+            },
+        );
+
+        for corner in corners(bounds) {
+            frame.fill_rectangle(
+                corner - offset - Vector::new(HANDLE_SIZE / 2.0, HANDLE_SIZE / 2.0),
+                Size::new(HANDLE_SIZE, HANDLE_SIZE),
+                color,
+            );
+        }
+    }
+
+    /// Paint the stroke onto the `image`, whose top-left corner is at `origin` of the screenshot.
+    ///
+    /// Each pixel is blended with the color once, even where the line crosses itself,
+    /// so a semi-transparent stroke has the same opacity everywhere
+    fn paint(&self, image: &mut RgbaImage, origin: Point) {
+        let (width, height) = image.dimensions();
+        let radius = self.width / 2.0;
+        let mut is_covered = vec![false; width as usize * height as usize];
+
+        let points = self
+            .points
+            .iter()
+            .map(|point| Point::new(point.x - origin.x, point.y - origin.y))
+            .collect::<Vec<_>>();
+        // a stroke through a single point is a dot
+        let segments = points
+            .windows(2)
+            .map(|segment| (segment[0], segment[1]))
+            .chain((points.len() == 1).then(|| (points[0], points[0])));
+
+        for (start, end) in segments {
+            let x_range = (start.x.min(end.x) - radius)
+                .floor()
+                .clamp(0.0, width as f32) as u32
+                ..(start.x.max(end.x) + radius)
+                    .ceil()
+                    .clamp(0.0, width as f32) as u32;
+            let y_range = (start.y.min(end.y) - radius)
+                .floor()
+                .clamp(0.0, height as f32) as u32
+                ..(start.y.max(end.y) + radius)
+                    .ceil()
+                    .clamp(0.0, height as f32) as u32;
+
+            for y in y_range {
+                for x in x_range.clone() {
+                    let center = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                    if distance_to_segment(center, start, end) <= radius {
+                        is_covered[(y * width + x) as usize] = true;
+                    }
+                }
+            }
+        }
+
+        let color = image::Rgba(self.color.into_rgba8());
+        for (pixel, is_covered) in image.pixels_mut().zip(is_covered) {
+            if is_covered {
+                pixel.blend(&color);
+            }
+        }
+    }
+}
+
+/// Top-left, top-right, bottom-right and bottom-left corners of the `rect`
+fn corners(rect: Rectangle) -> [Point; 4] {
+    [
+        rect.position(),
+        Point::new(rect.x + rect.width, rect.y),
+        Point::new(rect.x + rect.width, rect.y + rect.height),
+        Point::new(rect.x, rect.y + rect.height),
+    ]
+}
+
+/// A stroke being moved or resized by dragging it
+#[derive(Debug, Clone, Copy)]
+pub struct Edit {
+    /// Index of the stroke
+    pub index: usize,
+    /// Where the drag started
+    pub start: Point,
+    /// While resizing, the corner of the outline which stays in place.
+    /// `None` while moving
+    pub anchor: Option<Point>,
+}
+
+impl Edit {
+    /// The `stroke` after dragging it from the start to the `cursor`
+    pub fn apply(&self, stroke: &Stroke, cursor: Point) -> Stroke {
+        let points = match self.anchor {
+            None => {
+                let by = cursor - self.start;
+                stroke.points.iter().map(|point| *point + by).collect()
+            }
+            Some(anchor) => {
+                // how much each side grows. An outline without width or height can't grow
+                let scale = |from: f32, to: f32| {
+                    if from.abs() <= f32::EPSILON {
+                        1.0
+                    } else {
+                        to / from
+                    }
+                };
+                let scale_x = scale(self.start.x - anchor.x, cursor.x - anchor.x);
+                let scale_y = scale(self.start.y - anchor.y, cursor.y - anchor.y);
+
+                stroke
+                    .points
+                    .iter()
+                    .map(|point| {
+                        Point::new(
+                            anchor.x + (point.x - anchor.x) * scale_x,
+                            anchor.y + (point.y - anchor.y) * scale_y,
+                        )
+                    })
+                    .collect()
+            }
+        };
+
+        Stroke {
+            points,
+            ..stroke.clone()
+        }
+    }
+}
+
+/// Strokes drawn on the screenshot
+#[derive(Debug, Clone, Default)]
+pub struct Annotations {
+    /// Tool which dragging inside of the selection draws with.
+    /// `None` if dragging moves the selection as usual
+    pub tool: Option<Tool>,
+    /// Strokes which are drawn, from oldest to newest
+    pub strokes: Vec<Stroke>,
+    /// Index of the stroke which is selected, to move, resize or delete it
+    pub selected: Option<usize>,
+}
+
+impl Annotations {
+    /// Whether dragging inside of the selection draws instead of moving it
+    pub const fn is_active(&self) -> bool {
+        self.tool.is_some()
+    }
+
+    /// What pressing the mouse at `point` starts to edit: the selected stroke if one of its
+    /// handles is pressed, otherwise the topmost stroke under the `point`
+    pub fn edit_at(&self, point: Point) -> Option<Edit> {
+        let resize = self.selected.and_then(|index| {
+            let [top_left, top_right, bottom_right, bottom_left] =
+                corners(self.strokes.get(index)?.bounds());
+            // each corner is resized around the opposite one
+            let (_, anchor) = [
+                (top_left, bottom_right),
+                (top_right, bottom_left),
+                (bottom_right, top_left),
+                (bottom_left, top_right),
+            ]
+            .into_iter()
+            .find(|(corner, _)| corner.distance(point) <= HANDLE_SIZE)?;
+
+            Some(Edit {
+                index,
+                start: point,
+                anchor: Some(anchor),
+            })
+        });
+
+        resize.or_else(|| {
+            let index = self
+                .strokes
+                .iter()
+                .rposition(|stroke| stroke.is_hit(point))?;
+            Some(Edit {
+                index,
+                start: point,
+                anchor: None,
+            })
+        })
+    }
+
+    /// Shown in the status bar while drawing
+    pub fn status(&self) -> Option<String> {
+        self.tool.map(|tool| match tool {
+            Tool::Pen => "DRAW (PEN)".to_owned(),
+            Tool::Highlighter => "DRAW (HIGHLIGHTER)".to_owned(),
+        })
+    }
+
+    /// Paint the strokes onto the `image`, which was cropped to the `region` of the screenshot
+    pub fn apply(&self, image: DynamicImage, region: Rectangle) -> DynamicImage {
+        if self.strokes.is_empty() {
+            return image;
+        }
+
+        let mut image = image.into_rgba8();
+        for stroke in &self.strokes {
+            stroke.paint(&mut image, region.position());
+        }
+
+        DynamicImage::from(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    #[expect(clippy::float_cmp, reason = "exact distances")]
+    fn distance_to_the_closest_point_of_the_segment() {
+        let start = Point::new(0.0, 0.0);
+        let end = Point::new(10.0, 0.0);
+
+        assert_eq!(distance_to_segment(Point::new(5.0, 3.0), start, end), 3.0);
+        assert_eq!(distance_to_segment(Point::new(14.0, 3.0), start, end), 5.0);
+        assert_eq!(distance_to_segment(Point::new(0.0, 2.0), start, start), 2.0);
+    }
+
+    #[test]
+    fn moves_and_resizes_strokes() {
+        let stroke = Stroke {
+            points: vec![Point::new(10.0, 10.0), Point::new(20.0, 30.0)],
+            color: Color::BLACK,
+            width: 2.0,
+        };
+        let annotations = Annotations {
+            tool: Some(Tool::Pen),
+            strokes: vec![stroke.clone()],
+            selected: Some(0),
+        };
+
+        let grab = annotations
+            .edit_at(Point::new(15.0, 20.0))
+            .expect("the middle of the stroke");
+        assert_eq!(grab.anchor, None);
+        assert_eq!(
+            grab.apply(&stroke, Point::new(20.0, 25.0)).points,
+            vec![Point::new(15.0, 15.0), Point::new(25.0, 35.0)]
+        );
+
+        // the bottom-right handle, resized around the top-left corner of the outline
+        let resize = annotations
+            .edit_at(Point::new(21.0, 31.0))
+            .expect("the handle of the selected stroke");
+        assert_eq!(resize.anchor, Some(Point::new(9.0, 9.0)));
+        assert_eq!(
+            resize.apply(&stroke, Point::new(33.0, 53.0)).points,
+            vec![Point::new(11.0, 11.0), Point::new(31.0, 51.0)]
+        );
+
+        assert!(annotations.edit_at(Point::new(40.0, 10.0)).is_none());
+    }
+
+    #[test]
+    fn blends_each_pixel_once() {
+        let white = image::Rgba([255, 255, 255, 255]);
+        let annotations = Annotations {
+            tool: None,
+            strokes: vec![Stroke {
+                // goes back over itself
+                points: vec![
+                    Point::new(15.0, 15.0),
+                    Point::new(25.0, 15.0),
+                    Point::new(15.0, 15.0),
+                ],
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.5),
+                width: 4.0,
+            }],
+            selected: None,
+        };
+
+        // the image is the `10..30` part of the screenshot
+        let region = Rectangle::new(Point::new(10.0, 10.0), iced::Size::new(20.0, 20.0));
+        let painted = annotations
+            .apply(
+                DynamicImage::from(RgbaImage::from_pixel(20, 20, white)),
+                region,
+            )
+            .into_rgba8();
+
+        assert_eq!(painted.get_pixel(0, 0), &white, "outside of the stroke");
+        assert_eq!(painted.get_pixel(10, 5), painted.get_pixel(5, 5));
+        assert_ne!(painted.get_pixel(10, 5), &white);
+        assert_ne!(
+            painted.get_pixel(10, 5),
+            &image::Rgba([0, 0, 0, 255]),
+            "semi-transparent"
+        );
+    }
+}
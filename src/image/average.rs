@@ -0,0 +1,107 @@
+//! Average several frames of the screen into one, to reduce noise
+//!
+//! Camera feeds and remote desktops flicker a little from frame to frame. Each pixel of the
+//! average is the mean of that pixel across the frames, so the noise mostly cancels out.
+
+use image::RgbaImage;
+
+/// Most frames which are averaged into one screenshot.
+///
+/// Each frame takes as long to capture as a screenshot does, so more frames would keep the
+/// user waiting for longer while barely reducing the noise any further
+pub const MAX_FRAMES: u32 = 64;
+
+/// Running average of frames, pixel by pixel.
+///
+/// Only the sums of the channels are kept, so frames can be added as soon as they are
+/// captured and dropped right after.
+#[derive(Debug, Default)]
+pub struct Average {
+    /// Width and height of the first frame
+    dimensions: (u32, u32),
+    /// Sum of each channel of each pixel, across the frames
+    sums: Vec<u32>,
+    /// How many frames were added
+    count: u32,
+}
+
+impl Average {
+    /// Add the `frame` to the average.
+    ///
+    /// Frames with a different size than the first are skipped, e.g. if the resolution
+    /// changed while capturing
+    pub fn add(&mut self, frame: &RgbaImage) {
+        if self.count == 0 {
+            self.dimensions = frame.dimensions();
+            self.sums = frame
+                .as_raw()
+                .iter()
+                .map(|&channel| u32::from(channel))
+                .collect();
+        } else if frame.dimensions() == self.dimensions {
+            for (sum, &channel) in self.sums.iter_mut().zip(frame.as_raw()) {
+                *sum += u32::from(channel);
+            }
+        } else {
+            return;
+        }
+
+        self.count += 1;
+    }
+
+    /// The averaged image. `None` if no frames were added
+    pub fn finish(self) -> Option<RgbaImage> {
+        let count = self.count;
+        if count == 0 {
+            return None;
+        }
+
+        // rounded to the nearest value
+        let channels = self
+            .sums
+            .into_iter()
+            .map(|sum| ((sum + count / 2) / count) as u8)
+            .collect();
+
+        RgbaImage::from_raw(self.dimensions.0, self.dimensions.1, channels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn averages_each_pixel() {
+        let mut average = Average::default();
+        for value in [10, 20, 31] {
+            average.add(&RgbaImage::from_pixel(
+                2,
+                2,
+                image::Rgba([value, 0, 255, 255]),
+            ));
+        }
+
+        let averaged = average.finish().expect("there are frames");
+
+        assert_eq!(averaged.get_pixel(1, 1), &image::Rgba([20, 0, 255, 255]));
+    }
+
+    #[test]
+    fn skips_frames_of_another_size() {
+        let mut average = Average::default();
+        average.add(&RgbaImage::from_pixel(2, 2, image::Rgba([10, 10, 10, 255])));
+        average.add(&RgbaImage::from_pixel(
+            3,
+            3,
+            image::Rgba([200, 200, 200, 255]),
+        ));
+
+        let averaged = average.finish().expect("there are frames");
+
+        assert_eq!(averaged.dimensions(), (2, 2));
+        assert_eq!(averaged.get_pixel(0, 0), &image::Rgba([10, 10, 10, 255]));
+        assert_eq!(Average::default().finish(), None);
+    }
+}
@@ -0,0 +1,81 @@
+//! Decide what happens when a screenshot is saved to a file which already exists
+//!
+//! Unlike duplicates, the existing file can have any contents. It is usually an older
+//! screenshot saved under the same name.
+use std::path::{Path, PathBuf};
+
+/// What to do when saving a screenshot to a path which already exists
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnConflict {
+    /// Replace the existing file
+    #[default]
+    Overwrite,
+    /// Save the screenshot with a number added to the name of the file
+    Rename,
+    /// Ask whether to overwrite or rename, when saving from the app. Without the app
+    /// there is no one to ask, so the screenshot is renamed
+    Ask,
+}
+
+impl OnConflict {
+    /// Where to save the screenshot which should be saved to `path`
+    pub fn resolve(self, path: &Path) -> PathBuf {
+        match self {
+            Self::Overwrite => path.to_path_buf(),
+            Self::Rename | Self::Ask if path.exists() => numbered(path),
+            Self::Rename | Self::Ask => path.to_path_buf(),
+        }
+    }
+}
+
+/// Add the lowest number to the name of the file for which no file exists.
+///
+/// `screenshot.png` becomes `screenshot-1.png`, then `screenshot-2.png`, ...
+pub fn numbered(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| path.with_file_name(format!("{stem}-{n}{extension}")))
+        .find(|path| !path.exists())
+        .expect("there are infinitely many candidates")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+
+    #[test]
+    fn renames_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("screenshot.png");
+
+        assert_eq!(
+            OnConflict::Rename.resolve(&path),
+            path,
+            "nothing to rename yet"
+        );
+
+        fs::write(&path, b"").unwrap();
+        assert_eq!(
+            OnConflict::Rename.resolve(&path),
+            dir.path().join("screenshot-1.png")
+        );
+        assert_eq!(OnConflict::Overwrite.resolve(&path), path);
+
+        fs::write(dir.path().join("screenshot-1.png"), b"").unwrap();
+        assert_eq!(
+            OnConflict::Ask.resolve(&path),
+            dir.path().join("screenshot-2.png"),
+            "can't ask, so it is renamed"
+        );
+    }
+}
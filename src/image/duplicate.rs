@@ -0,0 +1,122 @@
+//! Detect screenshots which are identical to one that was already saved
+//!
+//! When taking lots of screenshots, it is common to capture the exact same thing several times.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// What to do when saving a screenshot which is identical to one already in the same directory
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnDuplicate {
+    /// Save the screenshot anyways
+    #[default]
+    Save,
+    /// Do not save the screenshot
+    Skip,
+    /// Save the screenshot, with `-duplicate` added to the name of the file
+    Suffix,
+}
+
+/// Find a file in `directory` with the same extension as `path`, whose contents are `bytes`.
+///
+/// Files which cannot be read are skipped, as they can't be compared
+pub fn find(bytes: &[u8], path: &Path) -> io::Result<Option<PathBuf>> {
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    if !directory.exists() {
+        return Ok(None);
+    }
+
+    for entry in fs::read_dir(directory)?.filter_map(Result::ok) {
+        let candidate = entry.path();
+
+        // cheap checks first, to avoid reading every file in the directory
+        if candidate.extension() != path.extension()
+            || !entry
+                .metadata()
+                .is_ok_and(|meta| meta.is_file() && meta.len() == bytes.len() as u64)
+        {
+            continue;
+        }
+
+        if fs::read(&candidate).is_ok_and(|contents| contents == bytes) {
+            return Ok(Some(candidate));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Add `-duplicate` to the name of the file, and a number if that file exists as well.
+///
+/// `screenshot.png` becomes `screenshot-duplicate.png`, then `screenshot-duplicate-2.png`, ...
+pub fn suffixed(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    (1..)
+        .map(|n| {
+            let suffix = if n == 1 {
+                "-duplicate".to_owned()
+            } else {
+                format!("-duplicate-{n}")
+            };
+            path.with_file_name(format!("{stem}{suffix}{extension}"))
+        })
+        .find(|path| !path.exists())
+        .expect("there are infinitely many candidates")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn finds_identical_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.png"), b"first").unwrap();
+        fs::write(dir.path().join("b.png"), b"second").unwrap();
+        fs::write(dir.path().join("c.jpg"), b"third").unwrap();
+
+        let path = dir.path().join("new.png");
+
+        assert_eq!(
+            find(b"second", &path).unwrap(),
+            Some(dir.path().join("b.png"))
+        );
+        // different extension
+        assert_eq!(find(b"third", &path).unwrap(), None);
+        // same length, different contents
+        assert_eq!(find(b"fifth", &path).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_directory_has_no_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("new.png");
+
+        assert_eq!(find(b"anything", &path).unwrap(), None);
+    }
+
+    #[test]
+    fn suffixes_until_unique() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shot.png");
+
+        assert_eq!(suffixed(&path), dir.path().join("shot-duplicate.png"));
+
+        fs::write(dir.path().join("shot-duplicate.png"), b"").unwrap();
+        assert_eq!(suffixed(&path), dir.path().join("shot-duplicate-2.png"));
+    }
+}
@@ -0,0 +1,381 @@
+//! Encode the screenshot into the bytes of an image file
+//!
+//! The PNG encoder of `image` compresses on a single thread, which takes hundreds of
+//! milliseconds for a 4K screenshot. With `parallel-encoding`, large PNGs are instead split
+//! into bands of rows which are filtered and compressed on every core, and then stitched
+//! back together into a single zlib stream.
+
+use std::{
+    borrow::Cow,
+    io::{Cursor, Write as _},
+    num::NonZeroUsize,
+    ops::Range,
+    thread,
+};
+
+use flate2::{Compression, write::DeflateEncoder};
+use image::{DynamicImage, ImageFormat};
+
+/// Images with fewer pixels than this are always encoded on a single thread,
+/// as spreading them over the cores would not save any time
+const PARALLEL_MIN_PIXELS: u64 = 1024 * 1024;
+
+/// Every PNG file starts with these bytes
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Largest amount of bytes in a single chunk of a PNG file
+const PNG_MAX_CHUNK: usize = 1 << 30;
+
+/// Header of the zlib stream, for deflate with a 32K window and the fastest compression
+const ZLIB_HEADER: [u8; 2] = [0x78, 0x01];
+
+/// Largest prime smaller than 65536, the modulus of Adler-32 checksums
+const ADLER_MOD: u32 = 65521;
+
+/// Format of screenshots which are uploaded, or saved to a file picked in the app
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Lossless, and keeps the alpha channel
+    #[default]
+    Png,
+    /// Lossy and much smaller, without the alpha channel
+    Jpeg,
+    /// Lossless and smaller than PNG, but not supported everywhere
+    Webp,
+}
+
+impl Format {
+    /// Every format, in the order they are listed in the settings
+    pub const ALL: [Self; 3] = [Self::Png, Self::Jpeg, Self::Webp];
+
+    /// Name of the format in the config
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpeg",
+            Self::Webp => "webp",
+        }
+    }
+
+    /// Extension of files in this format
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpg",
+            Self::Webp => "webp",
+        }
+    }
+
+    /// The format, as the `image` crate calls it
+    pub const fn image_format(self) -> ImageFormat {
+        match self {
+            Self::Png => ImageFormat::Png,
+            Self::Jpeg => ImageFormat::Jpeg,
+            Self::Webp => ImageFormat::WebP,
+        }
+    }
+}
+
+/// Encode the `image` in the `format`.
+///
+/// Formats which cannot store an alpha channel (such as JPEG) will receive
+/// the image without it, instead of failing to encode.
+///
+/// With `parallel`, large PNGs are compressed on multiple threads
+pub fn encode(
+    image: &DynamicImage,
+    format: ImageFormat,
+    parallel: bool,
+) -> image::ImageResult<Vec<u8>> {
+    if parallel
+        && format == ImageFormat::Png
+        && u64::from(image.width()) * u64::from(image.height()) >= PARALLEL_MIN_PIXELS
+    {
+        return Ok(png_parallel(image)?);
+    }
+
+    let mut bytes = Cursor::new(Vec::new());
+    if format == ImageFormat::Jpeg {
+        DynamicImage::from(image.to_rgb8()).write_to(&mut bytes, format)?;
+    } else {
+        image.write_to(&mut bytes, format)?;
+    }
+
+    Ok(bytes.into_inner())
+}
+
+/// A band of rows of the image, compressed on its own thread
+struct Band {
+    /// Raw deflate data. Ends with a sync flush, so the next band can follow it
+    deflate: Vec<u8>,
+    /// Adler-32 checksum of the filtered rows, before compression
+    adler: u32,
+    /// Amount of bytes of the filtered rows, before compression
+    len: usize,
+}
+
+/// Encode the `image` as PNG, compressing bands of it on every core
+fn png_parallel(image: &DynamicImage) -> std::io::Result<Vec<u8>> {
+    // PNG color types
+    const RGB: u8 = 2;
+    const RGBA: u8 = 6;
+
+    let (color_type, pixels) = match image {
+        DynamicImage::ImageRgb8(image) => (RGB, Cow::Borrowed(image.as_raw())),
+        DynamicImage::ImageRgba8(image) => (RGBA, Cow::Borrowed(image.as_raw())),
+        image => (RGBA, Cow::Owned(image.to_rgba8().into_raw())),
+    };
+    let bytes_per_pixel = if color_type == RGB { 3 } else { 4 };
+
+    let rows = pixels
+        .chunks_exact(image.width() as usize * bytes_per_pixel)
+        .collect::<Vec<_>>();
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let band_height = rows.len().div_ceil(threads).max(1);
+
+    let bands = thread::scope(|scope| {
+        (0..rows.len())
+            .step_by(band_height)
+            .map(|start| {
+                let rows = &rows;
+                let end = (start + band_height).min(rows.len());
+                scope.spawn(move || compress_band(rows, start..end, bytes_per_pixel))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|band| band.join().expect("compressing a band does not panic"))
+            .collect::<std::io::Result<Vec<_>>>()
+    })?;
+
+    let adler = bands.iter().fold(adler32(&[]), |adler, band| {
+        adler32_combine(adler, band.adler, band.len)
+    });
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width().to_be_bytes());
+    header.extend_from_slice(&image.height().to_be_bytes());
+    // bit depth, color type, compression, filter method and no interlacing
+    header.extend_from_slice(&[8, color_type, 0, 0, 0]);
+
+    // the bands are stitched into a single zlib stream, which is then split into chunks
+    let mut data = ZLIB_HEADER.to_vec();
+    for band in bands {
+        data.extend_from_slice(&band.deflate);
+    }
+    // a final block which is empty, as every band only flushes its own blocks
+    data.extend_from_slice(&DeflateEncoder::new(Vec::new(), Compression::none()).finish()?);
+    data.extend_from_slice(&adler.to_be_bytes());
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, *b"IHDR", &header);
+    for chunk in data.chunks(PNG_MAX_CHUNK) {
+        write_chunk(&mut png, *b"IDAT", chunk);
+    }
+    write_chunk(&mut png, *b"IEND", &[]);
+
+    Ok(png)
+}
+
+/// Filter and compress the `rows` in `range`
+fn compress_band(
+    rows: &[&[u8]],
+    range: Range<usize>,
+    bytes_per_pixel: usize,
+) -> std::io::Result<Band> {
+    let mut filtered = Vec::with_capacity(range.len() * (rows[0].len() + 1));
+    for index in range {
+        let previous = index.checked_sub(1).map(|previous| rows[previous]);
+        filter_row(rows[index], previous, bytes_per_pixel, &mut filtered);
+    }
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(&filtered)?;
+    // ends the band on a byte boundary without marking the block as the last one
+    encoder.flush()?;
+
+    Ok(Band {
+        deflate: std::mem::take(encoder.get_mut()),
+        adler: adler32(&filtered),
+        len: filtered.len(),
+    })
+}
+
+/// Append the `row` to `out` with the PNG filter which is likely to compress the best,
+/// preceded by the type of the filter.
+///
+/// Like `libpng`, picks the filter whose bytes have the smallest sum when read as signed
+fn filter_row(row: &[u8], previous: Option<&[u8]>, bytes_per_pixel: usize, out: &mut Vec<u8>) {
+    let above = previous.map_or_else(|| Cow::Owned(vec![0; row.len()]), Cow::Borrowed);
+    // the bytes of the pixel to the left, and of the pixel above it. The first pixel has none
+    let (first, rest) = row.split_at(bytes_per_pixel);
+    let (first_above, rest_above) = above.split_at(bytes_per_pixel);
+    let left = &row[..rest.len()];
+    let upper_left = &above[..rest.len()];
+
+    // in the order of their types: None, Sub, Up, Average and Paeth
+    let filtered: [Vec<u8>; 5] = [
+        row.to_vec(),
+        first
+            .iter()
+            .copied()
+            .chain(rest.iter().zip(left).map(|(&x, &a)| x.wrapping_sub(a)))
+            .collect(),
+        row.iter()
+            .zip(above.iter())
+            .map(|(&x, &b)| x.wrapping_sub(b))
+            .collect(),
+        first
+            .iter()
+            .zip(first_above)
+            .map(|(&x, &b)| x.wrapping_sub(b / 2))
+            .chain(
+                rest.iter()
+                    .zip(left)
+                    .zip(rest_above)
+                    .map(|((&x, &a), &b)| x.wrapping_sub(u8::midpoint(a, b))),
+            )
+            .collect(),
+        first
+            .iter()
+            .zip(first_above)
+            .map(|(&x, &b)| x.wrapping_sub(b))
+            .chain(
+                rest.iter()
+                    .zip(left)
+                    .zip(rest_above)
+                    .zip(upper_left)
+                    .map(|(((&x, &a), &b), &c)| x.wrapping_sub(paeth(a, b, c))),
+            )
+            .collect(),
+    ];
+
+    let (filter, bytes) = filtered
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, bytes)| {
+            bytes
+                .iter()
+                .map(|byte| u64::from(byte.cast_signed().unsigned_abs()))
+                .sum::<u64>()
+        })
+        .expect("there are 5 filters");
+
+    out.push(filter as u8);
+    out.extend_from_slice(bytes);
+}
+
+/// The Paeth predictor of PNG: Whichever of the neighbouring bytes is closest to
+/// `left + above - upper_left`
+fn paeth(left: u8, above: u8, upper_left: u8) -> u8 {
+    let estimate = i16::from(left) + i16::from(above) - i16::from(upper_left);
+    let distance = |byte: u8| (estimate - i16::from(byte)).abs();
+
+    if distance(left) <= distance(above) && distance(left) <= distance(upper_left) {
+        left
+    } else if distance(above) <= distance(upper_left) {
+        above
+    } else {
+        upper_left
+    }
+}
+
+/// Write a chunk of a PNG file
+fn write_chunk(png: &mut Vec<u8>, kind: [u8; 4], data: &[u8]) {
+    png.extend_from_slice(
+        &u32::try_from(data.len())
+            .expect("chunks are at most `PNG_MAX_CHUNK` long")
+            .to_be_bytes(),
+    );
+    let start = png.len();
+    png.extend_from_slice(&kind);
+    png.extend_from_slice(data);
+    let crc = crc32fast::hash(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Adler-32 checksum of the `bytes`, which ends a zlib stream
+fn adler32(bytes: &[u8]) -> u32 {
+    // most bytes which can be summed before `b` could overflow
+    const MAX_RUN: usize = 5552;
+
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for run in bytes.chunks(MAX_RUN) {
+        for &byte in run {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= ADLER_MOD;
+        b %= ADLER_MOD;
+    }
+
+    (b << 16) | a
+}
+
+/// Adler-32 checksum of two sequences of bytes, one after another, from their checksums
+/// and the length of the `second` sequence. Ported from zlib's `adler32_combine`
+fn adler32_combine(first: u32, second: u32, second_len: usize) -> u32 {
+    let rem = u32::try_from(second_len % ADLER_MOD as usize).expect("smaller than the modulus");
+
+    let mut sum1 = first & 0xFFFF;
+    let mut sum2 = (rem * sum1) % ADLER_MOD;
+    sum1 += (second & 0xFFFF) + ADLER_MOD - 1;
+    sum2 += (first >> 16) + (second >> 16) + ADLER_MOD - rem;
+
+    if sum1 >= ADLER_MOD {
+        sum1 -= ADLER_MOD;
+    }
+    if sum1 >= ADLER_MOD {
+        sum1 -= ADLER_MOD;
+    }
+    if sum2 >= ADLER_MOD << 1 {
+        sum2 -= ADLER_MOD << 1;
+    }
+    if sum2 >= ADLER_MOD {
+        sum2 -= ADLER_MOD;
+    }
+
+    (sum2 << 16) | sum1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parallel_png_decodes() {
+        let image = DynamicImage::from(image::RgbaImage::from_fn(200, 100, |x, y| {
+            image::Rgba([u8::try_from(x).unwrap(), u8::try_from(y).unwrap(), 42, 200])
+        }));
+
+        let decoded =
+            image::load_from_memory_with_format(&png_parallel(&image).unwrap(), ImageFormat::Png)
+                .unwrap();
+
+        assert_eq!(decoded.to_rgba8(), image.to_rgba8());
+    }
+
+    #[test]
+    fn combines_checksums() {
+        let bytes = (0..100_000)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect::<Vec<_>>();
+        let (first, second) = bytes.split_at(31_337);
+
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(
+            adler32_combine(adler32(first), adler32(second), second.len()),
+            adler32(&bytes)
+        );
+    }
+
+    #[test]
+    fn jpeg_without_alpha() {
+        let image = DynamicImage::from(image::RgbaImage::new(10, 10));
+
+        assert!(
+            encode(&image, ImageFormat::Jpeg, true).is_ok(),
+            "the alpha channel is dropped"
+        );
+    }
+}
@@ -0,0 +1,344 @@
+//! Move strokes between ferrishot and other screenshot tools
+//!
+//! Strokes are exported to, and imported from, JSON files in the shape of the
+//! annotations of other tools. The format is picked by the extension of the file:
+//!
+//! - `.flameshot`: Flameshot's `pencil` and `marker` tools. Colors are `#AARRGGBB`,
+//!   as Qt writes them.
+//! - `.json`: `ShareX`'s `DrawingFreehand` shapes. Colors and points are written as
+//!   `"A, R, G, B"` and `"X, Y"`, as .NET writes them.
+//!
+//! Only freehand lines can be drawn by ferrishot, so other shapes are skipped when
+//! importing. Points are in the coordinates of the screenshot in every format.
+
+use std::{fs, path::Path};
+
+use iced::{Color, Point};
+
+use super::annotate::Stroke;
+
+/// Flameshot's tool for thin lines
+const FLAMESHOT_PENCIL: &str = "pencil";
+/// Flameshot's tool for wide, semi-transparent lines
+const FLAMESHOT_MARKER: &str = "marker";
+/// `ShareX`'s shape for lines drawn freehand
+const SHAREX_FREEHAND: &str = "DrawingFreehand";
+
+/// Could not import or export the strokes
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// The extension doesn't belong to any format
+    #[error("unknown format of {0}, expected a `.flameshot` or `.json` file")]
+    UnknownFormat(String),
+    /// Failed to read or write the file
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The file is not valid JSON, or not in the shape of the format
+    #[error("the file is malformed: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A color or point could not be read
+    #[error("the file is malformed: invalid {0} `{1}`")]
+    Value(&'static str, String),
+}
+
+/// Format of a file with strokes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Flameshot's `.flameshot` files
+    Flameshot,
+    /// `ShareX`'s annotation JSON
+    ShareX,
+}
+
+impl Format {
+    /// Format of the file at `path`, from its extension
+    pub fn of(path: &Path) -> Result<Self, Error> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("flameshot") => Ok(Self::Flameshot),
+            Some("json") => Ok(Self::ShareX),
+            _ => Err(Error::UnknownFormat(path.display().to_string())),
+        }
+    }
+}
+
+/// A `.flameshot` file
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct Flameshot {
+    /// What was drawn, from oldest to newest
+    tools: Vec<FlameshotTool>,
+}
+
+/// Something drawn with one of Flameshot's tools
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+struct FlameshotTool {
+    /// Name of the tool, such as `pencil`
+    tool: String,
+    /// Color as `#AARRGGBB`
+    color: String,
+    /// Width of the line, in pixels
+    thickness: f32,
+    /// Points which the line goes through
+    #[serde(default)]
+    points: Vec<[f32; 2]>,
+}
+
+/// `ShareX`'s annotation JSON
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct ShareX {
+    /// What was drawn, from oldest to newest
+    shapes: Vec<ShareXShape>,
+}
+
+/// A shape drawn with `ShareX`'s image editor
+#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+struct ShareXShape {
+    /// Kind of the shape, such as `DrawingFreehand`
+    shape_type: String,
+    /// Color as `A, R, G, B`
+    border_color: String,
+    /// Width of the line, in pixels
+    border_size: f32,
+    /// Points which the line goes through, each as `X, Y`
+    #[serde(default)]
+    points: Vec<String>,
+}
+
+/// Write the `strokes` to the file at `path`, in the format of its extension
+pub fn export(strokes: &[Stroke], path: &Path) -> Result<(), Error> {
+    let json = match Format::of(path)? {
+        Format::Flameshot => serde_json::to_string_pretty(&Flameshot {
+            tools: strokes
+                .iter()
+                .map(|stroke| {
+                    let [r, g, b, a] = stroke.color.into_rgba8();
+                    FlameshotTool {
+                        // the marker is Flameshot's semi-transparent pen
+                        tool: if a == u8::MAX {
+                            FLAMESHOT_PENCIL
+                        } else {
+                            FLAMESHOT_MARKER
+                        }
+                        .to_owned(),
+                        color: format!("#{a:02x}{r:02x}{g:02x}{b:02x}"),
+                        thickness: stroke.width,
+                        points: stroke
+                            .points
+                            .iter()
+                            .map(|point| [point.x, point.y])
+                            .collect(),
+                    }
+                })
+                .collect(),
+        })?,
+        Format::ShareX => serde_json::to_string_pretty(&ShareX {
+            shapes: strokes
+                .iter()
+                .map(|stroke| {
+                    let [r, g, b, a] = stroke.color.into_rgba8();
+                    ShareXShape {
+                        shape_type: SHAREX_FREEHAND.to_owned(),
+                        border_color: format!("{a}, {r}, {g}, {b}"),
+                        border_size: stroke.width,
+                        points: stroke
+                            .points
+                            .iter()
+                            .map(|point| format!("{}, {}", point.x, point.y))
+                            .collect(),
+                    }
+                })
+                .collect(),
+        })?,
+    };
+
+    fs::write(path, json)?;
+    Ok(())
+}
+
+/// Read the strokes from the file at `path`, in the format of its extension.
+///
+/// Also returns how many shapes were skipped, because ferrishot can't draw them
+pub fn import(path: &Path) -> Result<(Vec<Stroke>, usize), Error> {
+    let json = fs::read_to_string(path)?;
+
+    let (strokes, total) = match Format::of(path)? {
+        Format::Flameshot => {
+            let file: Flameshot = serde_json::from_str(&json)?;
+            let total = file.tools.len();
+            let strokes = file
+                .tools
+                .into_iter()
+                .filter(|tool| [FLAMESHOT_PENCIL, FLAMESHOT_MARKER].contains(&tool.tool.as_str()))
+                .map(|tool| {
+                    Ok(Stroke {
+                        color: flameshot_color(&tool.color)?,
+                        width: tool.thickness,
+                        points: tool
+                            .points
+                            .into_iter()
+                            .map(|[x, y]| Point::new(x, y))
+                            .collect(),
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            (strokes, total)
+        }
+        Format::ShareX => {
+            let file: ShareX = serde_json::from_str(&json)?;
+            let total = file.shapes.len();
+            let strokes = file
+                .shapes
+                .into_iter()
+                .filter(|shape| shape.shape_type == SHAREX_FREEHAND)
+                .map(|shape| {
+                    Ok(Stroke {
+                        color: sharex_color(&shape.border_color)?,
+                        width: shape.border_size,
+                        points: shape
+                            .points
+                            .iter()
+                            .map(String::as_str)
+                            .map(sharex_point)
+                            .collect::<Result<_, _>>()?,
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            (strokes, total)
+        }
+    };
+
+    // a line without any points can't be drawn, or selected to delete it
+    let strokes = strokes
+        .into_iter()
+        .filter(|stroke| !stroke.points.is_empty())
+        .collect::<Vec<_>>();
+    let skipped = total - strokes.len();
+
+    Ok((strokes, skipped))
+}
+
+/// Parse a color written as `#AARRGGBB` or `#RRGGBB`
+fn flameshot_color(color: &str) -> Result<Color, Error> {
+    let invalid = || Error::Value("color", color.to_owned());
+
+    let hex = color.strip_prefix('#').ok_or_else(invalid)?;
+    let value = u32::from_str_radix(hex, 16).map_err(|_| invalid())?;
+    let [a, r, g, b] = match hex.len() {
+        8 => value.to_be_bytes(),
+        6 => (value | 0xFF00_0000).to_be_bytes(),
+        _ => return Err(invalid()),
+    };
+
+    Ok(Color::from_rgba8(r, g, b, f32::from(a) / 255.0))
+}
+
+/// Parse a color written as `A, R, G, B` or `R, G, B`
+fn sharex_color(color: &str) -> Result<Color, Error> {
+    let invalid = || Error::Value("color", color.to_owned());
+
+    let channels = color
+        .split(',')
+        .map(|channel| channel.trim().parse::<u8>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+    let (a, r, g, b) = match *channels.as_slice() {
+        [a, r, g, b] => (a, r, g, b),
+        [r, g, b] => (u8::MAX, r, g, b),
+        _ => return Err(invalid()),
+    };
+
+    Ok(Color::from_rgba8(r, g, b, f32::from(a) / 255.0))
+}
+
+/// Parse a point written as `X, Y`
+fn sharex_point(point: &str) -> Result<Point, Error> {
+    let invalid = || Error::Value("point", point.to_owned());
+
+    let (x, y) = point.split_once(',').ok_or_else(invalid)?;
+    Ok(Point::new(
+        x.trim().parse().map_err(|_| invalid())?,
+        y.trim().parse().map_err(|_| invalid())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn strokes_round_trip() {
+        let strokes = vec![
+            Stroke {
+                points: vec![Point::new(10.0, 10.5), Point::new(20.0, 30.0)],
+                color: Color::from_rgb8(255, 0, 0),
+                width: 2.0,
+            },
+            Stroke {
+                points: vec![Point::new(5.0, 5.0)],
+                color: Color::from_rgba8(255, 255, 0, 0.4),
+                width: 16.0,
+            },
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        for name in ["strokes.flameshot", "strokes.json"] {
+            let path = dir.path().join(name);
+            export(&strokes, &path).unwrap();
+            let (imported, skipped) = import(&path).unwrap();
+
+            assert_eq!(skipped, 0);
+            assert_eq!(imported.len(), strokes.len());
+            for (imported, stroke) in imported.iter().zip(&strokes) {
+                assert_eq!(imported.points, stroke.points);
+                assert_eq!(imported.color.into_rgba8(), stroke.color.into_rgba8());
+            }
+        }
+    }
+
+    #[test]
+    fn skips_other_shapes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sharex.json");
+        fs::write(
+            &path,
+            r#"{"Shapes": [
+                {"ShapeType": "DrawingRectangle", "BorderColor": "255, 0, 0", "BorderSize": 2},
+                {"ShapeType": "DrawingFreehand", "BorderColor": "0, 0, 255",
+                 "BorderSize": 4, "Points": ["1, 2", "3, 4"]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let (strokes, skipped) = import(&path).unwrap();
+
+        assert_eq!(skipped, 1);
+        assert_eq!(
+            strokes,
+            vec![Stroke {
+                points: vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)],
+                color: Color::from_rgb8(0, 0, 255),
+                width: 4.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(
+            flameshot_color("#80ff0000").unwrap().into_rgba8(),
+            [255, 0, 0, 128]
+        );
+        assert_eq!(
+            flameshot_color("#00ff00").unwrap().into_rgba8(),
+            [0, 255, 0, 255]
+        );
+        assert!(flameshot_color("red").is_err());
+        assert_eq!(
+            sharex_color("128, 0, 0, 255").unwrap().into_rgba8(),
+            [0, 0, 255, 128]
+        );
+        assert!(sharex_color("0, 0").is_err());
+    }
+}
@@ -2,16 +2,46 @@
 
 pub mod action;
 
+pub mod adjust;
+
+pub mod annotate;
+
+pub mod average;
+
+pub mod conflict;
+
+pub mod duplicate;
+
+pub mod encode;
+
+pub mod interop;
+
+pub mod qr_code;
+
+pub mod redact;
+
+pub mod segment;
+
+pub mod text_lines;
+
+pub mod trim;
+
 pub mod upload;
 
-mod screenshot;
-use std::path::PathBuf;
+pub mod screenshot;
 
-use image::ImageReader;
+pub mod staleness;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{DynamicImage, ImageFormat, ImageReader};
+
+use crate::geometry::RectangleExt as _;
 
 mod rgba_handle;
 pub use rgba_handle::RgbaHandle;
-use tap::Pipe as _;
 
 /// Failed to get the image
 #[derive(thiserror::Error, miette::Diagnostic, Debug)]
@@ -27,19 +57,135 @@ pub enum GetImageError {
     Screenshot(#[from] screenshot::ScreenshotError),
 }
 
+impl GetImageError {
+    /// Whether the screenshot could not be taken because ferrishot is not allowed to
+    pub const fn is_missing_permission(&self) -> bool {
+        matches!(
+            self,
+            Self::Screenshot(screenshot::ScreenshotError::Permission)
+        )
+    }
+}
+
 /// Returns handle of the image that will be edited
 ///
 /// If path is passed, use that as the image to edit.
-/// Otherwise take a screenshot of the desktop and use that to edit.
-pub fn get_image(file: Option<&PathBuf>) -> Result<RgbaHandle, GetImageError> {
-    file.map(ImageReader::open)
-        .transpose()?
-        .map(ImageReader::decode)
-        .transpose()?
-        .map_or_else(
-            // no path passed = take image of the monitor
-            screenshot::take,
-            |img| RgbaHandle::new(img.width(), img.height(), img.into_rgba8().into_raw()).pipe(Ok),
-        )?
-        .pipe(Ok)
+/// Otherwise take a screenshot of the desktop and use that to edit. On Linux, if the
+/// desktop does not let us capture the screen, it is asked to take the screenshot instead.
+///
+/// Unless `preserve_transparency` is set, the alpha channel of the screenshot is flattened
+/// so every pixel of it is fully opaque. An image opened from a file is edited as it is.
+pub fn get_image(
+    file: Option<&PathBuf>,
+    preserve_transparency: bool,
+    capture_options: screenshot::CaptureOptions,
+) -> Result<RgbaHandle, GetImageError> {
+    let handle = match file {
+        Some(file) => return open(file),
+        // no path passed = take image of the monitor
+        None => match screenshot::take(capture_options) {
+            Ok(handle) => handle,
+            // The desktop can refuse to let us capture the screen, but take the screenshot itself
+            #[cfg(target_os = "linux")]
+            Err(
+                err @ (screenshot::ScreenshotError::Monitor(_)
+                | screenshot::ScreenshotError::Screenshot(_)),
+            ) => {
+                log::warn!("{err}, asking the desktop for a screenshot instead");
+                let path =
+                    screenshot::take_with_portal().map_err(screenshot::ScreenshotError::from)?;
+                log::info!("The desktop saved the screenshot to {}", path.display());
+                open(&path)?
+            }
+            Err(err) => return Err(err.into()),
+        },
+    };
+
+    if preserve_transparency {
+        Ok(handle)
+    } else {
+        Ok(handle.flattened())
+    }
+}
+
+/// Returns handle of a screenshot of the `region` of the whole desktop, which can span
+/// multiple monitors
+///
+/// The region is relative to the area covered by all monitors, so `0,0` is its top-left
+/// corner. Unless `preserve_transparency` is set, the alpha channel is flattened, which
+/// makes parts of the region outside of every monitor black.
+pub fn get_desktop_image(
+    region: crate::lazy_rect::LazyRectangle,
+    preserve_transparency: bool,
+    capture_options: screenshot::CaptureOptions,
+) -> Result<RgbaHandle, GetImageError> {
+    let desktop = screenshot::desktop_bounds()?;
+    let region = region
+        .init(iced::Rectangle::with_size(desktop.size()))
+        .norm();
+    let handle = screenshot::take_desktop(
+        iced::Rectangle::new(
+            region.position() + (desktop.position() - iced::Point::ORIGIN),
+            region.size(),
+        ),
+        capture_options,
+    )?;
+
+    if preserve_transparency {
+        Ok(handle)
+    } else {
+        Ok(handle.flattened())
+    }
+}
+
+/// Decode the image file at `path`
+fn open(path: &Path) -> Result<RgbaHandle, GetImageError> {
+    let img = ImageReader::open(path)?.decode()?;
+
+    Ok(RgbaHandle::new(
+        img.width(),
+        img.height(),
+        img.into_rgba8().into_raw(),
+    ))
+}
+
+/// Save the image to the given path. The format is inferred from the extension.
+///
+/// Formats which cannot store an alpha channel (such as JPEG) will receive
+/// the image without it, instead of failing to encode. With `parallel_encoding`,
+/// large PNGs are compressed on multiple threads.
+///
+/// If an identical screenshot already exists next to `path`, `on_duplicate` decides what happens.
+/// Otherwise, if a file already exists at the path, `on_conflict` decides what happens.
+/// Returns the path where the screenshot can be found, which may be the existing file
+pub fn save(
+    image: &DynamicImage,
+    path: &Path,
+    on_duplicate: duplicate::OnDuplicate,
+    on_conflict: conflict::OnConflict,
+    parallel_encoding: bool,
+) -> image::ImageResult<PathBuf> {
+    let bytes = encode::encode(image, ImageFormat::from_path(path)?, parallel_encoding)?;
+
+    let path = match on_duplicate {
+        duplicate::OnDuplicate::Save => path.to_path_buf(),
+        duplicate::OnDuplicate::Skip | duplicate::OnDuplicate::Suffix => {
+            match duplicate::find(&bytes, path)? {
+                Some(existing) if on_duplicate == duplicate::OnDuplicate::Skip => {
+                    log::info!(
+                        "Not saving the screenshot, it is identical to {}",
+                        existing.display()
+                    );
+                    return Ok(existing);
+                }
+                Some(_) => duplicate::suffixed(path),
+                None => path.to_path_buf(),
+            }
+        }
+    };
+
+    let path = on_conflict.resolve(&path);
+    fs::write(&path, bytes)?;
+
+    Ok(path)
 }
@@ -0,0 +1,57 @@
+//! Render the link to an uploaded image as a QR code image, to paste into slides and documents
+
+use image::{Rgba, RgbaImage};
+use qrcode::{Color, QrCode, types::QrError};
+
+/// Width and height of each module (square) of the QR code, in pixels
+const MODULE_SIZE: u32 = 10;
+
+/// Width of the light border around the QR code, in modules. Scanners need it to find the code
+const QUIET_ZONE: u32 = 4;
+
+/// Render the `link` as a black and white QR code
+pub fn render(link: &str) -> Result<RgbaImage, QrError> {
+    let code = QrCode::new(link)?;
+    let width = code.width() as u32;
+    let colors = code.to_colors();
+
+    let size = (width + QUIET_ZONE * 2) * MODULE_SIZE;
+
+    Ok(RgbaImage::from_fn(size, size, |x, y| {
+        let module_x = (x / MODULE_SIZE).checked_sub(QUIET_ZONE);
+        let module_y = (y / MODULE_SIZE).checked_sub(QUIET_ZONE);
+
+        let is_dark = module_x
+            .zip(module_y)
+            .filter(|(x, y)| *x < width && *y < width)
+            .is_some_and(|(x, y)| colors[(y * width + x) as usize] == Color::Dark);
+
+        if is_dark {
+            Rgba([0, 0, 0, 255])
+        } else {
+            Rgba([255, 255, 255, 255])
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn renders_with_quiet_zone() {
+        let image = render("https://example.com/image.png").unwrap();
+        let modules = QrCode::new("https://example.com/image.png")
+            .unwrap()
+            .width() as u32;
+
+        assert_eq!(image.width(), (modules + QUIET_ZONE * 2) * MODULE_SIZE);
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 255, 255, 255]));
+        assert_eq!(
+            image.get_pixel(QUIET_ZONE * MODULE_SIZE, QUIET_ZONE * MODULE_SIZE),
+            &Rgba([0, 0, 0, 255]),
+            "the finder pattern starts in the corner"
+        );
+    }
+}
@@ -0,0 +1,284 @@
+//! Hide parts of the screenshot, such as passwords and emails, by blurring or pixelating them
+//!
+//! While redacting, dragging inside of the selection marks a region to redact instead of
+//! moving the selection. Redactions are applied to the image which is copied, saved or uploaded,
+//! and a preview of each is drawn on top of the selection.
+
+use iced::{Point, Rectangle, Size, Task, widget::image::Handle};
+use image::{DynamicImage, RgbaImage, imageops};
+
+use crate::{App, geometry::RectangleExt as _};
+
+use super::RgbaHandle;
+
+/// Width and height of each block of a pixelated region, in pixels
+const PIXEL_SIZE: u32 = 12;
+
+/// Standard deviation of the blur, in pixels. Large enough that text can't be read
+const BLUR_SIGMA: f32 = 12.0;
+
+/// How a region is redacted
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RedactionStyle {
+    /// Replace the region with large blocks of its average color
+    #[default]
+    Pixelate,
+    /// Blur the region
+    Blur,
+}
+
+impl RedactionStyle {
+    /// Redact the whole `image`
+    pub fn redact(self, image: &RgbaImage) -> RgbaImage {
+        match self {
+            Self::Pixelate => {
+                let small = imageops::resize(
+                    image,
+                    image.width().div_ceil(PIXEL_SIZE),
+                    image.height().div_ceil(PIXEL_SIZE),
+                    imageops::FilterType::Triangle,
+                );
+                imageops::resize(
+                    &small,
+                    image.width(),
+                    image.height(),
+                    imageops::FilterType::Nearest,
+                )
+            }
+            Self::Blur => imageops::fast_blur(image, BLUR_SIGMA),
+        }
+    }
+}
+
+crate::declare_commands! {
+    enum Command {
+        /// Drag inside of the selection to redact regions of it, instead of moving it.
+        /// Toggling again with the other style switches to it
+        ToggleRedaction {
+            /// How the regions are redacted
+            style: RedactionStyle = RedactionStyle::Pixelate,
+        },
+        /// Remove the region that was redacted last
+        UndoRedaction,
+        /// Remove all redacted regions
+        ClearRedactions,
+    }
+}
+
+impl crate::command::Handler for Command {
+    fn handle(self, app: &mut App, _count: u32) -> Task<crate::Message> {
+        let redactions = &mut app.redactions;
+
+        match self {
+            Self::ToggleRedaction { style } => {
+                redactions.style = (redactions.style != Some(style)).then_some(style);
+                // dragging can either redact or draw, not both
+                if redactions.is_active() {
+                    app.annotations.tool = None;
+                }
+            }
+            Self::UndoRedaction => {
+                if redactions.regions.pop().is_none() {
+                    app.errors.push("There is nothing to undo");
+                }
+            }
+            Self::ClearRedactions => {
+                redactions.regions.clear();
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Message for redacting
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Redact the region between 2 points, in the coordinates of the screenshot
+    Add(Point, Point),
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut App) -> Task<crate::Message> {
+        match self {
+            Self::Add(start, end) => {
+                let Some(style) = app.redactions.style else {
+                    return Task::none();
+                };
+
+                if let Some(redaction) = Redaction::new(
+                    Rectangle::new(start, Size::new(end.x - start.x, end.y - start.y)).norm(),
+                    style,
+                    app.selection.map(|sel| sel.rect.norm()),
+                    &app.image,
+                ) {
+                    app.redactions.regions.push(redaction);
+                }
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// A redacted region of the screenshot
+#[derive(Debug, Clone)]
+pub struct Redaction {
+    /// Area of the screenshot that is redacted
+    pub rect: Rectangle,
+    /// How the area is redacted
+    pub style: RedactionStyle,
+    /// The redacted area, drawn on top of the screenshot
+    pub preview: Handle,
+}
+
+impl Redaction {
+    /// Redact the part of the `rect` of the `image` which is inside of the `selection`.
+    ///
+    /// `None` if that part does not cover a single pixel
+    pub fn new(
+        rect: Rectangle,
+        style: RedactionStyle,
+        selection: Option<Rectangle>,
+        image: &RgbaHandle,
+    ) -> Option<Self> {
+        let rect = rect
+            .intersection(&selection?)?
+            .intersection(&image.bounds())?;
+        // whole pixels, so the preview covers exactly the pixels that are redacted
+        let rect = Rectangle {
+            x: rect.x.floor(),
+            y: rect.y.floor(),
+            width: rect.width.round(),
+            height: rect.height.round(),
+        };
+
+        if rect.width < 1.0 || rect.height < 1.0 {
+            return None;
+        }
+
+        let redacted = style.redact(&App::process_image(rect, image).into_rgba8());
+
+        Some(Self {
+            rect,
+            style,
+            preview: Handle::from_rgba(redacted.width(), redacted.height(), redacted.into_raw()),
+        })
+    }
+}
+
+/// Regions of the screenshot which are redacted
+#[derive(Debug, Clone, Default)]
+pub struct Redactions {
+    /// Style of the regions made by dragging inside of the selection.
+    /// `None` if dragging moves the selection as usual
+    pub style: Option<RedactionStyle>,
+    /// Regions which are redacted, from oldest to newest
+    pub regions: Vec<Redaction>,
+}
+
+impl Redactions {
+    /// Whether dragging inside of the selection redacts instead of moving it
+    pub const fn is_active(&self) -> bool {
+        self.style.is_some()
+    }
+
+    /// Shown in the status bar while redacting
+    pub fn status(&self) -> Option<String> {
+        self.style.map(|style| match style {
+            RedactionStyle::Pixelate => "REDACT (PIXELATE)".to_owned(),
+            RedactionStyle::Blur => "REDACT (BLUR)".to_owned(),
+        })
+    }
+
+    /// Redact the `image`, which was cropped to the `region` of the screenshot
+    pub fn apply(&self, image: DynamicImage, region: Rectangle) -> DynamicImage {
+        let overlapping = self
+            .regions
+            .iter()
+            .filter_map(|redaction| Some((redaction.style, redaction.rect.intersection(&region)?)))
+            .collect::<Vec<_>>();
+
+        if overlapping.is_empty() {
+            return image;
+        }
+
+        let mut image = image.into_rgba8();
+        for (style, rect) in overlapping {
+            let x = (rect.x - region.x) as u32;
+            let y = (rect.y - region.y) as u32;
+            let part =
+                imageops::crop_imm(&image, x, y, rect.width as u32, rect.height as u32).to_image();
+
+            imageops::replace(&mut image, &style.redact(&part), x.into(), y.into());
+        }
+
+        DynamicImage::from(image)
+    }
+
+    /// Redact the regions again after the screenshot was taken again, so the previews
+    /// show the new `image`
+    pub fn refresh(&mut self, image: &RgbaHandle) {
+        self.regions = self
+            .regions
+            .iter()
+            .filter_map(|redaction| {
+                Redaction::new(redaction.rect, redaction.style, Some(redaction.rect), image)
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn pixelates_in_blocks() {
+        let image = RgbaImage::from_fn(PIXEL_SIZE * 2, PIXEL_SIZE, |x, _| {
+            if x % 2 == 0 {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            }
+        });
+
+        let pixelated = RedactionStyle::Pixelate.redact(&image);
+
+        assert_eq!(pixelated.dimensions(), image.dimensions());
+        assert_eq!(
+            pixelated.get_pixel(0, 0),
+            pixelated.get_pixel(PIXEL_SIZE - 1, PIXEL_SIZE - 1),
+            "each block has a single color"
+        );
+    }
+
+    #[test]
+    fn only_redacts_inside_of_the_region() {
+        let white = image::Rgba([255, 255, 255, 255]);
+        let black = image::Rgba([0, 0, 0, 255]);
+        let image = RgbaImage::from_fn(40, 40, |x, y| if (x + y) % 2 == 0 { white } else { black });
+        let redactions = Redactions {
+            style: None,
+            regions: vec![Redaction {
+                rect: Rectangle::new(Point::new(30.0, 30.0), Size::new(20.0, 20.0)),
+                style: RedactionStyle::Blur,
+                preview: Handle::from_rgba(1, 1, vec![0; 4]),
+            }],
+        };
+
+        // the image is the `10..50` part of the screenshot
+        let region = Rectangle::new(Point::new(10.0, 10.0), Size::new(40.0, 40.0));
+        let redacted = redactions
+            .apply(DynamicImage::from(image.clone()), region)
+            .into_rgba8();
+
+        assert_eq!(
+            redacted.get_pixel(19, 19),
+            image.get_pixel(19, 19),
+            "outside of the redacted region"
+        );
+        assert_ne!(redacted.get_pixel(30, 30), image.get_pixel(30, 30));
+    }
+}
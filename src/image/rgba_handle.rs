@@ -39,6 +39,43 @@ impl RgbaHandle {
         self.raw().2
     }
 
+    /// RGBA color of the pixel at the given coordinates, if it is inside of the image
+    pub fn pixel(&self, x: u32, y: u32) -> Option<[u8; 4]> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let start = (y as usize * self.width() as usize + x as usize) * 4;
+        self.bytes()
+            .get(start..start + 4)
+            .and_then(|pixel| pixel.try_into().ok())
+    }
+
+    /// Composite every pixel onto a black background, making the image fully opaque
+    ///
+    /// Screenshots of translucent windows have an alpha channel, which we only
+    /// want to keep if the user explicitly asked for it.
+    pub fn flattened(self) -> Self {
+        if self
+            .bytes()
+            .chunks_exact(4)
+            .all(|pixel| pixel[3] == u8::MAX)
+        {
+            return self;
+        }
+
+        let pixels = self
+            .bytes()
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]].map(u16::from);
+                let premultiply = |channel: u16| (channel * a / u16::from(u8::MAX)) as u8;
+                [premultiply(r), premultiply(g), premultiply(b), u8::MAX]
+            })
+            .collect::<Vec<_>>();
+
+        Self::new(self.width(), self.height(), pixels)
+    }
+
     /// Returns the width, height and RGBA pixels
     fn raw(&self) -> (u32, u32, &Bytes) {
         let Handle::Rgba {
@@ -1,4 +1,39 @@
-//! Take screenshot of the current monitor
+//! Take screenshot of the current monitor, or of a region of the desktop spanning several monitors
+
+use std::time::{Duration, Instant};
+
+use iced::Rectangle;
+
+/// How long to wait for a window to appear with `--wait-for-window`
+const WINDOW_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often to check whether the window appeared
+const WINDOW_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait after the window appeared, so that it has time to draw itself
+const WINDOW_SETTLE: Duration = Duration::from_millis(250);
+
+/// How long to wait for the desktop to present the next frame with `capture-on-next-frame`,
+/// before capturing immediately instead. Nothing is presented while the screen does not change
+const NEXT_FRAME_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// How the screen is captured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureOptions {
+    /// Capture the next frame which the desktop presents, instead of capturing right away
+    pub on_next_frame: bool,
+    /// How many frames to capture and average, to reduce noise
+    pub frames: u32,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            on_next_frame: false,
+            frames: 1,
+        }
+    }
+}
 
 /// Could not retrieve the screenshot
 #[derive(thiserror::Error, Debug)]
@@ -9,24 +44,173 @@ pub enum ScreenshotError {
     #[error("Could not get the active monitor: {0}")]
     /// There is no active monitor
     Monitor(xcap::XCapError),
+    /// Could not list the windows
+    #[error("Could not list the windows: {0}")]
+    Windows(xcap::XCapError),
+    /// The window to wait for did not appear
+    #[error("No window with a title matching `{0}` appeared within {WINDOW_TIMEOUT:?}")]
+    WindowTimeout(regex::Regex),
+    /// There are no monitors to capture
+    #[error("Could not find any monitors")]
+    NoMonitors,
     /// Could not capture the screenshot for some reason
     #[error("Could not take a screenshot: {0}")]
     Screenshot(xcap::XCapError),
+    /// Screenshots are not allowed. They would only contain the wallpaper
+    #[error(
+        "ferrishot does not have the Screen Recording permission. Grant it in System Settings > Privacy & Security > Screen Recording, then restart ferrishot"
+    )]
+    Permission,
+    /// The desktop could not take the screenshot either
+    #[cfg(target_os = "linux")]
+    #[error("Could not take a screenshot through the desktop portal: {0}")]
+    Portal(#[from] PortalError),
 }
 
-/// Take a screenshot and return a handle to the image
-pub fn take() -> Result<super::RgbaHandle, ScreenshotError> {
+/// Could not take a screenshot through the XDG Desktop Portal
+#[cfg(target_os = "linux")]
+#[derive(thiserror::Error, Debug)]
+pub enum PortalError {
+    /// Could not start the runtime to talk to the portal
+    #[error(transparent)]
+    Runtime(std::io::Error),
+    /// The portal is unavailable, or the user cancelled the screenshot
+    #[error(transparent)]
+    Request(#[from] ashpd::Error),
+    /// The screenshot is not stored in a file
+    #[error("the screenshot is not a file: {0}")]
+    NotAFile(String),
+}
+
+/// Ask the desktop to take a screenshot through the XDG Desktop Portal, returning the
+/// path of the screenshot. The user picks what to capture in a dialog of the desktop
+///
+/// Some desktops, like GNOME on Wayland, do not let other apps capture the screen
+#[cfg(target_os = "linux")]
+pub fn take_with_portal() -> Result<std::path::PathBuf, PortalError> {
+    // The portal is async. A thread of its own can block on it, even when called from a runtime
+    std::thread::spawn(|| {
+        let uri = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(PortalError::Runtime)?
+            .block_on(async {
+                ashpd::desktop::screenshot::Screenshot::request()
+                    .interactive(true)
+                    .modal(true)
+                    .send()
+                    .await?
+                    .response()
+            })?
+            .uri()
+            .clone();
+
+        uri.to_file_path()
+            .map_err(|()| PortalError::NotAFile(uri.to_string()))
+    })
+    .join()
+    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+/// Check and ask for the Screen Recording permission, which is needed to take screenshots on macOS
+#[cfg(target_os = "macos")]
+mod macos {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    unsafe extern "C" {
+        /// Whether the Screen Recording permission is granted, without asking for it
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        /// Ask for the Screen Recording permission, which adds ferrishot to the list
+        /// of apps in the System Settings
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    /// Whether ferrishot is allowed to take screenshots
+    pub fn has_permission() -> bool {
+        // SAFETY: Takes no arguments, and is available since macOS 10.15
+        unsafe { CGPreflightScreenCaptureAccess() }
+    }
+
+    /// Open the page of the System Settings where the permission to take screenshots is granted
+    pub fn open_permission_settings() -> std::io::Result<()> {
+        // without asking for it first, ferrishot would not be on the list
+        //
+        // SAFETY: Takes no arguments, and is available since macOS 10.15
+        unsafe {
+            CGRequestScreenCaptureAccess();
+        }
+
+        std::process::Command::new("open")
+            .arg("x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture")
+            .status()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub use macos::{has_permission, open_permission_settings};
+
+/// Whether ferrishot is allowed to take screenshots. Only macOS needs a permission
+#[cfg(not(target_os = "macos"))]
+pub const fn has_permission() -> bool {
+    true
+}
+
+/// Open the page of the System Settings where the permission to take screenshots is granted.
+/// Only macOS needs a permission
+#[cfg(not(target_os = "macos"))]
+pub fn open_permission_settings() -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "only macOS needs a permission to take screenshots",
+    ))
+}
+
+/// Position of the mouse on the desktop
+fn mouse_position() -> Result<(i32, i32), ScreenshotError> {
     let mouse_position::mouse_position::Mouse::Position { x, y } =
         mouse_position::mouse_position::Mouse::get_mouse_position()
     else {
         return Err(ScreenshotError::MousePosition);
     };
 
+    Ok((x, y))
+}
+
+/// Take a screenshot of the monitor the mouse is on and return a handle to the image
+pub fn take(options: CaptureOptions) -> Result<super::RgbaHandle, ScreenshotError> {
+    let (x, y) = mouse_position()?;
+
+    take_at(x, y, options)
+}
+
+/// A black image the size of the monitor the mouse is on, for when a screenshot can't be taken
+pub fn blank() -> Result<super::RgbaHandle, ScreenshotError> {
+    let (x, y) = mouse_position()?;
+    let monitor = xcap::Monitor::from_point(x, y).map_err(ScreenshotError::Monitor)?;
+    let width = monitor.width().map_err(ScreenshotError::Monitor)?;
+    let height = monitor.height().map_err(ScreenshotError::Monitor)?;
+
+    Ok(super::RgbaHandle::new(
+        width,
+        height,
+        [0, 0, 0, u8::MAX].repeat(width as usize * height as usize),
+    ))
+}
+
+/// Take a screenshot of the monitor containing the point and return a handle to the image
+pub fn take_at(
+    x: i32,
+    y: i32,
+    options: CaptureOptions,
+) -> Result<super::RgbaHandle, ScreenshotError> {
+    if !has_permission() {
+        return Err(ScreenshotError::Permission);
+    }
+
     let monitor = xcap::Monitor::from_point(x, y).map_err(ScreenshotError::Monitor)?;
 
-    let screenshot = monitor
-        .capture_image()
-        .map_err(ScreenshotError::Screenshot)?;
+    let screenshot = capture(&monitor, options)?;
 
     Ok(super::RgbaHandle::new(
         screenshot.width(),
@@ -34,3 +218,257 @@ pub fn take() -> Result<super::RgbaHandle, ScreenshotError> {
         screenshot.into_raw(),
     ))
 }
+
+/// Capture the `monitor`, averaging as many frames as the `options` say.
+///
+/// Capturing right away can catch the desktop in the middle of presenting a frame, so a
+/// playing video comes out torn. With `on_next_frame`, the frames are received as soon as
+/// they are presented, in the same way as recording the screen. When the desktop can't be
+/// recorded, or does not present enough frames in time, the rest are captured right away.
+///
+/// Each frame is added to the average as soon as it is captured, so only one frame is kept
+/// in memory at a time
+fn capture(
+    monitor: &xcap::Monitor,
+    options: CaptureOptions,
+) -> Result<image::RgbaImage, ScreenshotError> {
+    let count = options.frames.clamp(1, super::average::MAX_FRAMES);
+    let mut average = super::average::Average::default();
+
+    let mut captured = if options.on_next_frame {
+        match next_frames(monitor, count, &mut average) {
+            Ok(presented) => {
+                if presented < count {
+                    log::info!(
+                        "Only {presented} of {count} frames were presented in time, capturing the rest right away"
+                    );
+                }
+                presented
+            }
+            Err(err) => {
+                log::warn!(
+                    "Could not wait for the next frame, capturing right away instead: {err}"
+                );
+                0
+            }
+        }
+    } else {
+        0
+    };
+
+    while captured < count {
+        average.add(
+            &monitor
+                .capture_image()
+                .map_err(ScreenshotError::Screenshot)?,
+        );
+        captured += 1;
+    }
+
+    Ok(average.finish().expect("at least 1 frame is captured"))
+}
+
+/// Add up to `count` of the next frames which the desktop presents on the `monitor` to the
+/// `average`, returning how many were presented.
+/// Stops early if no frame is presented within [`NEXT_FRAME_TIMEOUT`]
+fn next_frames(
+    monitor: &xcap::Monitor,
+    count: u32,
+    average: &mut super::average::Average,
+) -> Result<u32, xcap::XCapError> {
+    let (recorder, frames) = monitor.video_recorder()?;
+
+    recorder.start()?;
+    let presented = std::iter::from_fn(|| frames.recv_timeout(NEXT_FRAME_TIMEOUT).ok())
+        .take(count as usize)
+        .filter_map(|frame| image::RgbaImage::from_raw(frame.width, frame.height, frame.raw))
+        .fold(0, |presented, frame| {
+            average.add(&frame);
+            presented + 1
+        });
+    recorder.stop()?;
+
+    Ok(presented)
+}
+
+/// Area covered by all of the monitors, in the coordinates of the desktop
+pub fn desktop_bounds() -> Result<Rectangle, ScreenshotError> {
+    xcap::Monitor::all()
+        .map_err(ScreenshotError::Monitor)?
+        .iter()
+        .map(monitor_bounds)
+        .try_fold(None, |union: Option<Rectangle>, bounds| {
+            let bounds = bounds?;
+            Ok(Some(union.map_or(bounds, |union| union.union(&bounds))))
+        })?
+        .ok_or(ScreenshotError::NoMonitors)
+}
+
+/// Take a screenshot of the `region` of the desktop, in the coordinates of the desktop.
+/// Each monitor the region covers is captured, and the parts are stitched together
+///
+/// Parts of the region which are not on any monitor are transparent
+pub fn take_desktop(
+    region: Rectangle,
+    options: CaptureOptions,
+) -> Result<super::RgbaHandle, ScreenshotError> {
+    if !has_permission() {
+        return Err(ScreenshotError::Permission);
+    }
+
+    let mut parts = vec![];
+    for monitor in xcap::Monitor::all().map_err(ScreenshotError::Monitor)? {
+        let bounds = monitor_bounds(&monitor)?;
+        if bounds.intersection(&region).is_none() {
+            continue;
+        }
+
+        let screenshot = capture(&monitor, options)?;
+
+        parts.push((bounds, screenshot));
+    }
+
+    let image = stitch(region, parts);
+
+    Ok(super::RgbaHandle::new(
+        image.width(),
+        image.height(),
+        image.into_raw(),
+    ))
+}
+
+/// Where the `monitor` is on the desktop
+fn monitor_bounds(monitor: &xcap::Monitor) -> Result<Rectangle, ScreenshotError> {
+    Ok(Rectangle {
+        x: monitor.x().map_err(ScreenshotError::Monitor)? as f32,
+        y: monitor.y().map_err(ScreenshotError::Monitor)? as f32,
+        width: monitor.width().map_err(ScreenshotError::Monitor)? as f32,
+        height: monitor.height().map_err(ScreenshotError::Monitor)? as f32,
+    })
+}
+
+/// Stitch screenshots of monitors at the given bounds into a single image of the `region`
+///
+/// A monitor with a scale factor captures more pixels than its size on the desktop,
+/// so its screenshot is scaled down to fit its bounds
+fn stitch(
+    region: Rectangle,
+    parts: impl IntoIterator<Item = (Rectangle, image::RgbaImage)>,
+) -> image::RgbaImage {
+    let mut image = image::RgbaImage::new(region.width as u32, region.height as u32);
+
+    for (bounds, screenshot) in parts {
+        let (width, height) = (bounds.width as u32, bounds.height as u32);
+        let screenshot = if screenshot.dimensions() == (width, height) {
+            screenshot
+        } else {
+            image::imageops::resize(
+                &screenshot,
+                width,
+                height,
+                image::imageops::FilterType::Triangle,
+            )
+        };
+
+        image::imageops::replace(
+            &mut image,
+            &screenshot,
+            (bounds.x - region.x) as i64,
+            (bounds.y - region.y) as i64,
+        );
+    }
+
+    image
+}
+
+/// Wait until a window with a title matching `title` is shown, and has had a moment to draw
+/// itself. Useful for scripts which launch an app and take a screenshot of it
+pub fn wait_for_window(title: &regex::Regex) -> Result<(), ScreenshotError> {
+    let started = Instant::now();
+
+    loop {
+        let is_shown = xcap::Window::all()
+            .map_err(ScreenshotError::Windows)?
+            .iter()
+            .any(|window| {
+                window
+                    .title()
+                    .is_ok_and(|window_title| title.is_match(&window_title))
+                    && !window.is_minimized().unwrap_or(false)
+            });
+
+        if is_shown {
+            std::thread::sleep(WINDOW_SETTLE);
+            return Ok(());
+        }
+
+        if started.elapsed() >= WINDOW_TIMEOUT {
+            return Err(ScreenshotError::WindowTimeout(title.clone()));
+        }
+
+        std::thread::sleep(WINDOW_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn stitches_monitors() {
+        let white = image::RgbaImage::from_pixel(4, 2, image::Rgba([255, 255, 255, 255]));
+        let black = image::RgbaImage::from_pixel(4, 2, image::Rgba([0, 0, 0, 255]));
+
+        // 2 monitors side by side, and a region in the middle of them
+        let image = stitch(
+            Rectangle::new(iced::Point::new(2.0, 0.0), iced::Size::new(4.0, 2.0)),
+            [
+                (
+                    Rectangle::new(iced::Point::ORIGIN, iced::Size::new(4.0, 2.0)),
+                    white,
+                ),
+                (
+                    Rectangle::new(iced::Point::new(4.0, 0.0), iced::Size::new(4.0, 2.0)),
+                    black,
+                ),
+            ],
+        );
+
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(
+            image
+                .rows()
+                .next()
+                .unwrap()
+                .map(|pixel| pixel.0[0])
+                .collect::<Vec<_>>(),
+            [255, 255, 0, 0],
+            "half of each monitor"
+        );
+    }
+
+    #[test]
+    fn scales_down_monitors_with_scale_factor() {
+        let hidpi = image::RgbaImage::from_pixel(8, 4, image::Rgba([255, 255, 255, 255]));
+
+        let image = stitch(
+            Rectangle::new(iced::Point::ORIGIN, iced::Size::new(6.0, 2.0)),
+            [(
+                Rectangle::new(iced::Point::ORIGIN, iced::Size::new(4.0, 2.0)),
+                hidpi,
+            )],
+        );
+
+        assert_eq!(
+            image.get_pixel(3, 1).0,
+            [255, 255, 255, 255],
+            "on the monitor"
+        );
+        assert_eq!(
+            image.get_pixel(4, 1).0,
+            [0, 0, 0, 0],
+            "outside of any monitor"
+        );
+    }
+}
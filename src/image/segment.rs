@@ -0,0 +1,123 @@
+//! Find the UI element under the cursor, such as a button, a panel or an image
+//!
+//! UI elements usually have a background of a single color, with a clear edge where they end.
+//! Starting from the cursor, we fill the area of the same color as the pixel under it.
+//! Text and icons inside of the element are surrounded by its background, so the
+//! bounding box of the filled area is the entire element.
+
+use iced::{Point, Rectangle};
+
+use super::{RgbaHandle, trim::is_similar};
+
+/// Elements smaller than this in either dimension are most likely text or
+/// thin lines, rather than something worth selecting
+const MIN_ELEMENT_SIZE: u32 = 8;
+
+/// Bounding box of the element at `point`, or `None` if there is no element there
+pub fn element_at(image: &RgbaHandle, point: Point) -> Option<Rectangle> {
+    let (width, height) = (image.width(), image.height());
+    let (x, y) = (point.x as u32, point.y as u32);
+    let color = image.pixel(x, y)?;
+
+    let mut visited = vec![false; width as usize * height as usize];
+    let mut stack = vec![(x, y)];
+
+    let (mut left, mut top, mut right, mut bottom) = (x, y, x, y);
+
+    while let Some((x, y)) = stack.pop() {
+        let index = y as usize * width as usize + x as usize;
+        if visited[index]
+            || !image
+                .pixel(x, y)
+                .is_some_and(|pixel| is_similar(pixel, color))
+        {
+            continue;
+        }
+        visited[index] = true;
+
+        left = left.min(x);
+        top = top.min(y);
+        right = right.max(x);
+        bottom = bottom.max(y);
+
+        if x > 0 {
+            stack.push((x - 1, y));
+        }
+        if x + 1 < width {
+            stack.push((x + 1, y));
+        }
+        if y > 0 {
+            stack.push((x, y - 1));
+        }
+        if y + 1 < height {
+            stack.push((x, y + 1));
+        }
+    }
+
+    let element = Rectangle {
+        x: left as f32,
+        y: top as f32,
+        width: (right - left + 1) as f32,
+        height: (bottom - top + 1) as f32,
+    };
+
+    (right - left + 1 >= MIN_ELEMENT_SIZE && bottom - top + 1 >= MIN_ELEMENT_SIZE)
+        .then_some(element)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const GRAY: [u8; 4] = [128, 128, 128, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+    /// White 40x40 image with a gray 20x10 "button" at (10, 10),
+    /// with a black 3x3 "letter" at (15, 13) on it
+    fn image() -> RgbaHandle {
+        let pixels = (0..40)
+            .flat_map(|y| {
+                (0..40).flat_map(move |x| {
+                    if (15..18).contains(&x) && (13..16).contains(&y) {
+                        BLACK
+                    } else if (10..30).contains(&x) && (10..20).contains(&y) {
+                        GRAY
+                    } else {
+                        WHITE
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        RgbaHandle::new(40, 40, pixels)
+    }
+
+    #[test]
+    fn finds_element() {
+        let button = Rectangle::new(Point::new(10.0, 10.0), iced::Size::new(20.0, 10.0));
+
+        assert_eq!(element_at(&image(), Point::new(25.0, 18.0)), Some(button));
+    }
+
+    #[test]
+    fn background_is_the_whole_image() {
+        let image = image();
+
+        assert_eq!(
+            element_at(&image, Point::new(2.0, 2.0)),
+            Some(image.bounds())
+        );
+    }
+
+    #[test]
+    fn ignores_small_elements() {
+        assert_eq!(element_at(&image(), Point::new(16.0, 14.0)), None);
+    }
+
+    #[test]
+    fn outside_of_image() {
+        assert_eq!(element_at(&image(), Point::new(100.0, 2.0)), None);
+    }
+}
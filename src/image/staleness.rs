@@ -0,0 +1,138 @@
+//! Detect when the screenshot no longer matches what is on the screen
+//!
+//! If taking the screenshot is slow, or the user switches to another window before
+//! ferrishot shows up, the screenshot is of something the user is no longer looking at.
+//!
+//! When the app starts, the screen is captured again and compared against the screenshot.
+//! Comparing fingerprints instead of pixels ignores small changes, like a blinking cursor
+//! or a ticking clock.
+
+use iced::{
+    Subscription,
+    futures::{SinkExt as _, Stream},
+};
+
+use crate::Message;
+
+use super::{RgbaHandle, screenshot};
+
+/// Fingerprints are computed from a grid of this many cells on each side
+const GRID: u32 = 8;
+
+/// Each cell of the grid is sampled in this many points on each side
+const SAMPLES: u32 = 4;
+
+/// How many cells of the grid can change before the screenshot is considered stale
+const MAX_CHANGED_CELLS: u32 = 6;
+
+/// A fingerprint of the `image`, which barely changes when the image barely changes
+///
+/// Each bit is a cell of a grid over the image, which is set if the cell
+/// is brighter than the average of all cells
+pub fn fingerprint(image: &RgbaHandle) -> u64 {
+    let cell_width = image.width() / GRID;
+    let cell_height = image.height() / GRID;
+
+    let brightness = (0..GRID * GRID)
+        .map(|cell| {
+            let (cell_x, cell_y) = (cell % GRID * cell_width, cell / GRID * cell_height);
+
+            (0..SAMPLES * SAMPLES)
+                .filter_map(|sample| {
+                    image.pixel(
+                        cell_x + sample % SAMPLES * cell_width / SAMPLES,
+                        cell_y + sample / SAMPLES * cell_height / SAMPLES,
+                    )
+                })
+                .map(|[r, g, b, _]| u32::from(r) + u32::from(g) + u32::from(b))
+                .sum::<u32>()
+        })
+        .collect::<Vec<_>>();
+
+    let average = brightness.iter().sum::<u32>() / (GRID * GRID);
+
+    brightness
+        .iter()
+        .enumerate()
+        .filter(|(_, brightness)| **brightness > average)
+        .fold(0, |fingerprint, (cell, _)| fingerprint | (1 << cell))
+}
+
+/// Whether the screenshot with the `captured` fingerprint is too different from the
+/// `current` fingerprint of the screen
+pub const fn is_stale(captured: u64, current: u64) -> bool {
+    (captured ^ current).count_ones() > MAX_CHANGED_CELLS
+}
+
+/// Capture the screen once more and tell the app if it no longer matches the screenshot
+/// with this `fingerprint`
+///
+/// This starts together with the app, so the screen is usually captured
+/// before the window of ferrishot covers it
+pub fn subscription(fingerprint: u64) -> Subscription<Message> {
+    Subscription::run_with(fingerprint, |&fingerprint| check(fingerprint))
+}
+
+/// Stream which sends a message if the screenshot is stale, then ends
+fn check(captured: u64) -> impl Stream<Item = Message> {
+    iced::stream::channel(1, async move |mut output| {
+        let current = tokio::task::spawn_blocking(|| {
+            screenshot::take(screenshot::CaptureOptions::default()).map(|image| fingerprint(&image))
+        })
+        .await;
+
+        match current {
+            Ok(Ok(current)) if is_stale(captured, current) => {
+                let _ = output.send(Message::CaptureStale).await;
+            }
+            Ok(Ok(_)) => (),
+            Ok(Err(err)) => {
+                log::warn!("Failed to check whether the screenshot is stale: {err}");
+            }
+            Err(err) => log::error!("Failed to check whether the screenshot is stale: {err}"),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `width` x `height` image, white to the left of `split` and black after it
+    fn image(width: u32, height: u32, split: u32) -> RgbaHandle {
+        let pixels = (0..width * height)
+            .flat_map(|i| {
+                if i % width < split {
+                    [u8::MAX; 4]
+                } else {
+                    [0, 0, 0, u8::MAX]
+                }
+            })
+            .collect::<Vec<_>>();
+
+        RgbaHandle::new(width, height, pixels)
+    }
+
+    #[test]
+    fn small_changes_are_not_stale() {
+        let captured = fingerprint(&image(160, 90, 80));
+        let current = fingerprint(&image(160, 90, 82));
+
+        assert!(
+            !is_stale(captured, current),
+            "moving the edge by 2 pixels barely changes the image"
+        );
+        assert!(!is_stale(captured, captured), "identical images");
+    }
+
+    #[test]
+    fn large_changes_are_stale() {
+        let captured = fingerprint(&image(160, 90, 80));
+        let current = fingerprint(&image(160, 90, 20));
+
+        assert!(
+            is_stale(captured, current),
+            "most of the image changed from white to black"
+        );
+    }
+}
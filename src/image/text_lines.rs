@@ -0,0 +1,163 @@
+//! Snap the sides of the selection to where lines of text start and end
+//!
+//! Between lines of text (such as in terminal output or tables) there are rows of pixels
+//! of a single color. A line starts on the first row which is not a single color, and ends
+//! on the last such row. The same goes for columns, which separate the cells of a table.
+
+use std::ops::Range;
+
+use iced::Rectangle;
+
+use super::{RgbaHandle, trim::is_similar};
+use crate::geometry::Side;
+
+/// How far away a side of the selection can be from the start or end of a line, to snap to it
+const SNAP_DISTANCE: u32 = 12;
+
+/// Whether all of the pixels are the same color, so there is no text on them
+fn is_blank(mut pixels: impl Iterator<Item = [u8; 4]>) -> bool {
+    pixels
+        .next()
+        .is_none_or(|first| pixels.all(|pixel| is_similar(pixel, first)))
+}
+
+/// Position in `range` and within `SNAP_DISTANCE` of `pos` which is nearest to `pos`, where
+/// content starts (if `is_start`) or ends. Lines are indexed by position, and `is_blank`
+/// tells whether a line has no content on it
+fn nearest_boundary(
+    pos: u32,
+    range: Range<u32>,
+    is_start: bool,
+    is_blank: impl Fn(u32) -> bool,
+) -> Option<u32> {
+    (pos.saturating_sub(SNAP_DISTANCE)..=pos + SNAP_DISTANCE)
+        .filter(|candidate| range.contains(candidate))
+        .filter(|&candidate| {
+            if is_start {
+                is_blank(candidate - 1) && !is_blank(candidate)
+            } else {
+                !is_blank(candidate - 1) && is_blank(candidate)
+            }
+        })
+        .min_by_key(|candidate| candidate.abs_diff(pos))
+}
+
+/// Move the `sides` of the `rect` to the nearest start or end of a line of text
+/// inside of the `image`, if there is one close enough
+pub fn snap(image: &RgbaHandle, rect: Rectangle, sides: &[Side]) -> Rectangle {
+    let pixel = |x: u32, y: u32| image.pixel(x, y).unwrap_or_default();
+
+    // exclusive bounds of the rectangle
+    let mut left = rect.x.max(0.0) as u32;
+    let mut top = rect.y.max(0.0) as u32;
+    let mut right = ((rect.x + rect.width).max(0.0) as u32).min(image.width());
+    let mut bottom = ((rect.y + rect.height).max(0.0) as u32).min(image.height());
+
+    if left >= right || top >= bottom {
+        return rect;
+    }
+
+    // top and bottom first, they separate lines of text
+    let is_blank_row = |y: u32| is_blank((left..right).map(|x| pixel(x, y)));
+
+    for side in sides {
+        match side {
+            Side::Top => {
+                top = nearest_boundary(top, 1..bottom, true, is_blank_row).unwrap_or(top);
+            }
+            Side::Bottom => {
+                bottom = nearest_boundary(bottom, top + 1..image.height(), false, is_blank_row)
+                    .unwrap_or(bottom);
+            }
+            Side::Left | Side::Right => (),
+        }
+    }
+
+    let is_blank_column = |x: u32| is_blank((top..bottom).map(|y| pixel(x, y)));
+
+    for side in sides {
+        match side {
+            Side::Left => {
+                left = nearest_boundary(left, 1..right, true, is_blank_column).unwrap_or(left);
+            }
+            Side::Right => {
+                right = nearest_boundary(right, left + 1..image.width(), false, is_blank_column)
+                    .unwrap_or(right);
+            }
+            Side::Top | Side::Bottom => (),
+        }
+    }
+
+    Rectangle {
+        x: left as f32,
+        y: top as f32,
+        width: (right - left) as f32,
+        height: (bottom - top) as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+    /// 60x60 white image, with black "text" in lines from y=10..20 and y=30..40,
+    /// with "characters" from x=10..20 and x=30..40
+    fn image() -> RgbaHandle {
+        let is_text = |n: u32| (10..20).contains(&n) || (30..40).contains(&n);
+
+        let pixels = (0..60)
+            .flat_map(|y| {
+                (0..60).flat_map(move |x| {
+                    if is_text(x) && is_text(y) {
+                        BLACK
+                    } else {
+                        WHITE
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        RgbaHandle::new(60, 60, pixels)
+    }
+
+    #[test]
+    fn snaps_to_lines() {
+        let rect = Rectangle::new(iced::Point::new(5.0, 7.0), iced::Size::new(50.0, 15.0));
+
+        assert_eq!(
+            snap(&image(), rect, &[Side::Top, Side::Bottom]),
+            Rectangle::new(iced::Point::new(5.0, 10.0), iced::Size::new(50.0, 10.0))
+        );
+    }
+
+    #[test]
+    fn snaps_to_columns() {
+        let rect = Rectangle::new(iced::Point::new(5.0, 5.0), iced::Size::new(37.0, 50.0));
+
+        assert_eq!(
+            snap(&image(), rect, &[Side::Left, Side::Right]),
+            Rectangle::new(iced::Point::new(10.0, 5.0), iced::Size::new(30.0, 50.0))
+        );
+    }
+
+    #[test]
+    fn only_snaps_moved_sides() {
+        let rect = Rectangle::new(iced::Point::new(5.0, 7.0), iced::Size::new(50.0, 15.0));
+
+        assert_eq!(
+            snap(&image(), rect, &[Side::Bottom]),
+            Rectangle::new(iced::Point::new(5.0, 7.0), iced::Size::new(50.0, 13.0))
+        );
+    }
+
+    #[test]
+    fn too_far_to_snap() {
+        let rect = Rectangle::new(iced::Point::new(0.0, 50.0), iced::Size::new(60.0, 10.0));
+
+        assert_eq!(snap(&image(), rect, &[Side::Top, Side::Bottom]), rect);
+    }
+}
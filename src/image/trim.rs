@@ -0,0 +1,135 @@
+//! Find the content inside of a region, ignoring uniform-colored margins around it
+//!
+//! For example, a screenshot of a web page usually has lots of empty space on the sides.
+
+use iced::Rectangle;
+
+use super::RgbaHandle;
+
+/// How much each channel of a pixel may differ from the color of the margin,
+/// for the pixel to still be considered part of the margin
+const TOLERANCE: u8 = 10;
+
+/// Whether the 2 colors are the same, give or take a little noise (such as from compression)
+pub fn is_similar(a: [u8; 4], b: [u8; 4]) -> bool {
+    a.into_iter()
+        .zip(b)
+        .all(|(a, b)| a.abs_diff(b) <= TOLERANCE)
+}
+
+/// Bounding box of the content inside `region` of the `image`, without the margins
+/// which have the same color as the top-left corner of the region.
+///
+/// Returns `None` if the entire region is a single color
+pub fn content_bounds(image: &RgbaHandle, region: Rectangle) -> Option<Rectangle> {
+    let pixel = |x: u32, y: u32| image.pixel(x, y).unwrap_or_default();
+
+    // exclusive bounds of the region, so an empty region has `left == right`
+    let mut left = region.x as u32;
+    let mut top = region.y as u32;
+    let mut right = ((region.x + region.width) as u32).min(image.width());
+    let mut bottom = ((region.y + region.height) as u32).min(image.height());
+
+    if left >= right || top >= bottom {
+        return None;
+    }
+
+    // the corner is most likely part of the margin
+    let background = pixel(left, top);
+
+    // whether every pixel in the row, from `left` until `right`, is the background
+    let is_row =
+        |y: u32, left: u32, right: u32| (left..right).all(|x| is_similar(pixel(x, y), background));
+    // whether every pixel in the column, from `top` until `bottom`, is the background
+    let is_column =
+        |x: u32, top: u32, bottom: u32| (top..bottom).all(|y| is_similar(pixel(x, y), background));
+
+    while top < bottom && is_row(top, left, right) {
+        top += 1;
+    }
+
+    if top == bottom {
+        return None;
+    }
+
+    // there is at least 1 pixel that is not the background, so none of these
+    // loops can go past each other
+    while is_row(bottom - 1, left, right) {
+        bottom -= 1;
+    }
+
+    while is_column(left, top, bottom) {
+        left += 1;
+    }
+
+    while is_column(right - 1, top, bottom) {
+        right -= 1;
+    }
+
+    Some(Rectangle {
+        x: left as f32,
+        y: top as f32,
+        width: (right - left) as f32,
+        height: (bottom - top) as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+    const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+    /// Image of the given size which is white, except for black pixels inside of `content`
+    fn image(width: u32, height: u32, content: Rectangle) -> RgbaHandle {
+        let pixels = (0..height)
+            .flat_map(|y| {
+                (0..width).flat_map(move |x| {
+                    let x = x as f32;
+                    let y = y as f32;
+                    if (content.x..content.x + content.width).contains(&x)
+                        && (content.y..content.y + content.height).contains(&y)
+                    {
+                        BLACK
+                    } else {
+                        WHITE
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        RgbaHandle::new(width, height, pixels)
+    }
+
+    #[test]
+    fn trims_margins() {
+        let content = Rectangle::new(iced::Point::new(3.0, 2.0), iced::Size::new(4.0, 5.0));
+        let image = image(10, 10, content);
+
+        assert_eq!(content_bounds(&image, image.bounds()), Some(content));
+    }
+
+    #[test]
+    fn trims_within_region() {
+        let content = Rectangle::new(iced::Point::new(3.0, 2.0), iced::Size::new(4.0, 5.0));
+        let image = image(10, 10, content);
+        let region = Rectangle::new(iced::Point::new(1.0, 1.0), iced::Size::new(5.0, 5.0));
+
+        assert_eq!(
+            content_bounds(&image, region),
+            Some(Rectangle::new(
+                iced::Point::new(3.0, 2.0),
+                iced::Size::new(3.0, 4.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn uniform_region_has_no_content() {
+        let image = image(10, 10, Rectangle::default());
+
+        assert_eq!(content_bounds(&image, image.bounds()), None);
+    }
+}
@@ -1,20 +1,90 @@
 //! Upload images to free services
+//!
+//! Without the `upload` feature, nothing is uploaded and ferrishot makes no web requests
 
 use std::path::Path;
 
+#[cfg(feature = "upload")]
 use ferrishot_knus::DecodeScalar;
+#[cfg(feature = "upload")]
 use iced::futures::future::join_all;
+#[cfg(feature = "upload")]
 use reqwest::multipart::{Form, Part};
+#[cfg(feature = "upload")]
 use serde::{Deserialize, Serialize};
-use strum::{EnumCount as _, IntoEnumIterator as _};
+#[cfg(feature = "upload")]
+use strum::IntoEnumIterator as _;
+#[cfg(feature = "upload")]
 use tokio::sync::oneshot;
 
 /// A single client for HTTP requests
+#[cfg(feature = "upload")]
 static HTTP_CLIENT: std::sync::LazyLock<reqwest::Client> =
     std::sync::LazyLock::new(reqwest::Client::new);
 
-/// Upload an image to multiple services. As soon as the first service succeeds,
-/// cancel the other uploads.
+/// Which service screenshots are uploaded to
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UploadProvider {
+    /// Upload to every service at once, and keep the link of the first one to succeed
+    #[default]
+    Fastest,
+    /// `https://litterbox.catbox.moe`, which keeps images for 3 days
+    Litterbox,
+    /// `https://catbox.moe`, which keeps images for a few weeks
+    Catbox,
+    /// `https://0x0.st`, which keeps images for at least 30 days
+    TheNullPointer,
+    /// `https://uguu.se`, which keeps images for 3 hours
+    Uguu,
+}
+
+impl UploadProvider {
+    /// Every provider, in the order they are listed in the settings
+    pub const ALL: [Self; 5] = [
+        Self::Fastest,
+        Self::Litterbox,
+        Self::Catbox,
+        Self::TheNullPointer,
+        Self::Uguu,
+    ];
+
+    /// Name of the provider in the config
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Fastest => "fastest",
+            Self::Litterbox => "litterbox",
+            Self::Catbox => "catbox",
+            Self::TheNullPointer => "the-null-pointer",
+            Self::Uguu => "uguu",
+        }
+    }
+
+    /// Services which the screenshot is uploaded to
+    #[cfg(feature = "upload")]
+    fn services(self) -> Vec<ImageUploadService> {
+        match self {
+            Self::Fastest => ImageUploadService::iter().collect(),
+            Self::Litterbox => vec![ImageUploadService::Litterbox],
+            Self::Catbox => vec![ImageUploadService::Catbox],
+            Self::TheNullPointer => vec![ImageUploadService::TheNullPointer],
+            Self::Uguu => vec![ImageUploadService::Uguu],
+        }
+    }
+}
+
+/// How screenshots are uploaded
+#[derive(Debug, Clone, Copy)]
+pub struct Upload {
+    /// Service to upload to
+    pub provider: UploadProvider,
+    /// Format which the screenshot is uploaded in
+    pub format: super::encode::Format,
+    /// Compress large PNGs on multiple threads
+    pub parallel_encoding: bool,
+}
+
+/// Upload an image to the services of the `provider`. As soon as the first service
+/// succeeds, cancel the other uploads.
 ///
 /// # Returns
 ///
@@ -23,7 +93,12 @@ static HTTP_CLIENT: std::sync::LazyLock<reqwest::Client> =
 /// # Errors
 ///
 /// If none succeed, return error for all the services
-pub async fn upload(file_path: &Path) -> Result<ImageUploaded, Vec<String>> {
+#[cfg(feature = "upload")]
+pub async fn upload(
+    file_path: &Path,
+    provider: UploadProvider,
+) -> Result<ImageUploaded, Vec<String>> {
+    let services = provider.services();
     let mut handles = Vec::new();
 
     // Channel for results
@@ -37,7 +112,7 @@ pub async fn upload(file_path: &Path) -> Result<ImageUploaded, Vec<String>> {
     let cancel_rx = std::sync::Arc::new(tokio::sync::Mutex::new(Some(cancel_rx)));
 
     // launch an Upload task for each service
-    for (i, service) in ImageUploadService::iter().enumerate() {
+    for (i, service) in services.iter().copied().enumerate() {
         let tx = tx.clone();
         let path = file_path.to_path_buf();
         let cancel_rx = cancel_rx.clone();
@@ -70,7 +145,7 @@ pub async fn upload(file_path: &Path) -> Result<ImageUploaded, Vec<String>> {
     // receiver stops waiting if no senders remain
     drop(tx);
 
-    let mut errors = vec![None; ImageUploadService::COUNT];
+    let mut errors = vec![None; services.len()];
 
     while let Some((i, result)) = rx.recv().await {
         match result {
@@ -96,6 +171,26 @@ pub async fn upload(file_path: &Path) -> Result<ImageUploaded, Vec<String>> {
     Err(errors.into_iter().flatten().collect())
 }
 
+/// Uploading is not available, as ferrishot was built without the `upload` feature
+///
+/// # Errors
+///
+/// Always
+#[cfg(not(feature = "upload"))]
+#[expect(
+    clippy::unused_async,
+    reason = "same signature as with the `upload` feature"
+)]
+pub async fn upload(
+    _file_path: &Path,
+    _provider: UploadProvider,
+) -> Result<ImageUploaded, Vec<String>> {
+    Err(vec![
+        "ferrishot was built without the `upload` feature".to_owned(),
+    ])
+}
+
+#[cfg(feature = "upload")]
 #[derive(
     Copy,
     Clone,
@@ -108,10 +203,9 @@ pub async fn upload(file_path: &Path) -> Result<ImageUploaded, Vec<String>> {
     Deserialize,
     DecodeScalar,
     strum::EnumIter,
-    strum::EnumCount,
 )]
 #[serde(rename_all = "kebab-case")]
-/// Choose which image upload service should be used by default when pressing "Upload Online"
+/// A service which images can be uploaded to
 pub enum ImageUploadService {
     /// - Website: `https://litterbox.catbox.moe`
     /// - Max upload size: 1 GB
@@ -134,9 +228,12 @@ pub struct ImageUploaded {
     pub link: String,
     /// How long until the image expires (rough estimate - purely for visualization)
     pub expires_in: &'static str,
+    /// Name of the service the image was uploaded to
+    pub provider: &'static str,
 }
 
 /// Image upload error
+#[cfg(feature = "upload")]
 #[derive(thiserror::Error, miette::Diagnostic, Debug)]
 pub enum Error {
     /// IO error
@@ -150,7 +247,18 @@ pub enum Error {
     InvalidResponse(String),
 }
 
+#[cfg(feature = "upload")]
 impl ImageUploadService {
+    /// Name of the service, as shown to the user
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Litterbox => "Litterbox",
+            Self::Catbox => "Catbox",
+            Self::TheNullPointer => "0x0.st",
+            Self::Uguu => "Uguu",
+        }
+    }
+
     /// Conservative estimate for how long until images expire
     fn expires_in(self) -> &'static str {
         match self {
@@ -248,6 +356,7 @@ impl ImageUploadService {
         Ok(ImageUploaded {
             link,
             expires_in: self.expires_in(),
+            provider: self.name(),
         })
     }
 }
@@ -0,0 +1,139 @@
+//! Keep the screen from locking or turning off while ferrishot is open
+//!
+//! When the screen locks in the middle of making a selection, the selection is lost.
+//! With `inhibit-idle`, the screen is kept awake for as long as the [`Inhibitor`] lives,
+//! which is as long as the app. Otherwise, no `Inhibitor` is created at all.
+//! If ferrishot exits without dropping it, the operating system releases it anyway.
+//!
+//! - Linux: `org.freedesktop.ScreenSaver` through D-Bus
+//! - Windows: `SetThreadExecutionState`
+//! - Other platforms: nothing is done
+
+/// Why the screen is kept awake, shown by some desktops
+#[cfg(target_os = "linux")]
+const REASON: &str = "Taking a screenshot";
+
+pub use platform::Inhibitor;
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use ashpd::zbus;
+    use tokio::sync::oneshot;
+
+    /// Keeps the screen awake until it is dropped
+    #[derive(Debug)]
+    pub struct Inhibitor {
+        /// Dropping it tells the thread that holds the inhibition to release it
+        _release: oneshot::Sender<()>,
+    }
+
+    impl Inhibitor {
+        /// Keep the screen awake. Failing to do so is only logged
+        pub fn new() -> Self {
+            let (release, released) = oneshot::channel();
+
+            // The app does not give us a runtime that lives as long as it does,
+            // and the inhibition is released when the D-Bus connection closes
+            std::thread::spawn(move || {
+                let result = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(zbus::Error::from)
+                    .and_then(|runtime| runtime.block_on(hold(released)));
+
+                if let Err(err) = result {
+                    log::warn!("Failed to keep the screen awake: {err}");
+                }
+            });
+
+            Self { _release: release }
+        }
+    }
+
+    /// Keep the screen awake until `released` resolves
+    async fn hold(released: oneshot::Receiver<()>) -> zbus::Result<()> {
+        let connection = zbus::Connection::session().await?;
+        let screensaver = zbus::Proxy::new(
+            &connection,
+            "org.freedesktop.ScreenSaver",
+            "/org/freedesktop/ScreenSaver",
+            "org.freedesktop.ScreenSaver",
+        )
+        .await?;
+
+        let cookie: u32 = screensaver
+            .call("Inhibit", &("ferrishot", super::REASON))
+            .await?;
+
+        // resolves with an error once the `Inhibitor` is dropped
+        _ = released.await;
+
+        screensaver.call("UnInhibit", &(cookie,)).await
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    /// The state stays until it is changed again
+    const ES_CONTINUOUS: u32 = 0x8000_0000;
+    /// Don't go to sleep
+    const ES_SYSTEM_REQUIRED: u32 = 0x0000_0001;
+    /// Don't turn off the display, which also keeps the screen from locking
+    const ES_DISPLAY_REQUIRED: u32 = 0x0000_0002;
+
+    #[link(name = "kernel32")]
+    unsafe extern "system" {
+        /// Tell Windows that the thread is in use, so the system should stay awake.
+        /// Returns the previous state, or `0` on failure
+        fn SetThreadExecutionState(flags: u32) -> u32;
+    }
+
+    /// Keeps the screen awake until it is dropped
+    #[derive(Debug)]
+    pub struct Inhibitor {
+        /// Only created with `new`
+        _private: (),
+    }
+
+    impl Inhibitor {
+        /// Keep the screen awake. Failing to do so is only logged
+        pub fn new() -> Self {
+            // SAFETY: Takes flags by value, and is available since Windows XP
+            let previous = unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED)
+            };
+
+            if previous == 0 {
+                log::warn!("Failed to keep the screen awake");
+            }
+
+            Self { _private: () }
+        }
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            // SAFETY: Takes flags by value, and is available since Windows XP
+            unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS);
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    /// Does nothing on this platform
+    #[derive(Debug)]
+    pub struct Inhibitor {
+        /// Only created with `new`
+        _private: (),
+    }
+
+    impl Inhibitor {
+        /// Does nothing on this platform
+        pub const fn new() -> Self {
+            Self { _private: () }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Integrate ferrishot with the Windows Explorer
+//!
+//! Adds entries to the context menu of Explorer:
+//!
+//! - Right-clicking the desktop or the background of a folder: "Capture region with ferrishot"
+//! - Right-clicking an image file: "Crop with ferrishot", which opens the image in ferrishot
+//!
+//! The entries are registered for the current user only, so no administrator rights are needed.
+//! They are written with the `reg` command which comes with Windows.
+
+use std::{io, path::Path, process::Command};
+
+/// Registry key of the entry in the context menu of the desktop and of folders
+const CAPTURE_KEY: &str = r"HKCU\Software\Classes\Directory\Background\shell\ferrishot";
+
+/// Registry key of the entry in the context menu of every type of image file
+const CROP_KEY: &str = r"HKCU\Software\Classes\SystemFileAssociations\image\shell\ferrishot";
+
+/// Add or remove ferrishot from the Explorer
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Integration {
+    /// Add ferrishot to the context menu of the Explorer
+    Install,
+    /// Remove ferrishot from the context menu of the Explorer
+    Uninstall,
+}
+
+/// Could not integrate ferrishot with the Explorer
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Could not find the path to the ferrishot executable
+    #[error("could not find the path to ferrishot: {0}")]
+    CurrentExe(io::Error),
+    /// Could not run `reg`
+    #[error("failed to run `reg`: {0}")]
+    Io(#[from] io::Error),
+    /// `reg` exited with an error
+    #[error("`reg {}` failed", .0.join(" "))]
+    Reg(Vec<String>),
+}
+
+/// Arguments for each invocation of `reg`, which integrate the executable at `exe`
+fn reg_commands(integration: Integration, exe: &Path) -> Vec<Vec<String>> {
+    let exe = exe.display().to_string();
+    let args = |parts: &[&str]| {
+        parts
+            .iter()
+            .map(|part| (*part).to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    // (registry key, label in the context menu, command to run)
+    let entries = [
+        (
+            CAPTURE_KEY,
+            "Capture region with ferrishot",
+            format!(r#""{exe}""#),
+        ),
+        (CROP_KEY, "Crop with ferrishot", format!(r#""{exe}" "%1""#)),
+    ];
+
+    entries
+        .into_iter()
+        .flat_map(|(key, label, command)| match integration {
+            Integration::Install => vec![
+                args(&["add", key, "/ve", "/d", label, "/f"]),
+                args(&["add", key, "/v", "Icon", "/d", &exe, "/f"]),
+                args(&[
+                    "add",
+                    &format!(r"{key}\command"),
+                    "/ve",
+                    "/d",
+                    &command,
+                    "/f",
+                ]),
+            ],
+            Integration::Uninstall => vec![args(&["delete", key, "/f"])],
+        })
+        .collect()
+}
+
+/// Add ferrishot to, or remove it from, the context menu of the Explorer
+pub fn run(integration: Integration) -> Result<(), Error> {
+    let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+
+    for args in reg_commands(integration, &exe) {
+        let status = Command::new("reg").args(&args).status()?;
+
+        // removing an entry which does not exist is not an error
+        if !status.success() && integration == Integration::Install {
+            return Err(Error::Reg(args));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn install_opens_images() {
+        let commands = reg_commands(
+            Integration::Install,
+            Path::new(r"C:\Program Files\ferrishot\ferrishot.exe"),
+        );
+
+        assert_eq!(commands.len(), 6);
+        assert_eq!(
+            commands[5],
+            vec![
+                "add",
+                r"HKCU\Software\Classes\SystemFileAssociations\image\shell\ferrishot\command",
+                "/ve",
+                "/d",
+                r#""C:\Program Files\ferrishot\ferrishot.exe" "%1""#,
+                "/f",
+            ]
+        );
+    }
+
+    #[test]
+    fn uninstall_removes_every_entry() {
+        assert_eq!(
+            reg_commands(Integration::Uninstall, Path::new("ferrishot.exe")),
+            vec![
+                vec!["delete", CAPTURE_KEY, "/f"],
+                vec!["delete", CROP_KEY, "/f"],
+            ]
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! An opt-in HTTP endpoint for automation, such as test suites or AI assistants
+//!
+//! It speaks JSON-RPC 2.0 over `POST` requests, and exposes a single method:
+//!
+//! ```json
+//! { "jsonrpc": "2.0", "id": 1, "method": "capture", "params": { "region": "full" } }
+//! ```
+//!
+//! Which takes a screenshot, crops it to the `region` (same syntax as `--region`)
+//! and returns the PNG encoded as base64:
+//!
+//! ```json
+//! { "jsonrpc": "2.0", "id": 1, "result": { "png": "iVBORw0...", "width": 1920, "height": 1080 } }
+//! ```
+//!
+//! Every request must contain the `Authorization: Bearer <token>` header, where the token is
+//! the value of the [`TOKEN_ENV_VAR`] environment variable. The server refuses to start without it.
+//!
+//! The token is sent in plain text, so by default the server only listens on loopback addresses.
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use base64::Engine as _;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader},
+    net::{TcpListener, TcpStream},
+};
+
+use crate::{App, Config, lazy_rect::LazyRectangle};
+
+/// Environment variable which contains the token that clients must authenticate with
+pub const TOKEN_ENV_VAR: &str = "FERRISHOT_AUTOMATION_TOKEN";
+
+/// Requests with a larger body are rejected
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Requests with a longer request line or header are rejected
+const MAX_LINE_LEN: usize = 8 * 1024;
+
+/// Requests with more headers are rejected
+const MAX_HEADERS: usize = 100;
+
+/// Requests which take longer to send are rejected, so clients can't keep
+/// connections open forever by sending them slowly
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Failed to run the automation server
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// The token is not set
+    #[error("the {TOKEN_ENV_VAR} environment variable must be set to use the automation server")]
+    MissingToken,
+    /// Listening on an address which other machines can reach, without `allow_remote`
+    #[error(
+        "refusing to serve automation requests on {0}, which is not a loopback address. \
+         Pass `--serve-remote` to allow it"
+    )]
+    NotLoopback(SocketAddr),
+    /// IO error
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// A JSON-RPC request
+#[derive(Deserialize)]
+struct Request {
+    /// Identifies the request, sent back in the response
+    #[serde(default)]
+    id: Value,
+    /// Name of the method to call
+    method: String,
+    /// Arguments to the method
+    #[serde(default)]
+    params: Value,
+}
+
+/// Status line and body of a response
+type Response = (&'static str, Value);
+
+/// Parameters of the `capture` method
+#[derive(Deserialize)]
+struct CaptureParams {
+    /// Region to capture, `full` when omitted
+    region: Option<String>,
+}
+
+/// Serve automation requests on the given address until the process is killed.
+///
+/// Unless `allow_remote` is set, the address must be a loopback address
+pub async fn serve(addr: SocketAddr, allow_remote: bool, config: Arc<Config>) -> Result<(), Error> {
+    if !allow_remote && !addr.ip().is_loopback() {
+        return Err(Error::NotLoopback(addr));
+    }
+
+    let token = std::env::var(TOKEN_ENV_VAR)
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or(Error::MissingToken)?;
+
+    let listener = TcpListener::bind(addr).await?;
+    log::info!("Automation server listening on {addr}");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let token = token.clone();
+        let config = Arc::clone(&config);
+
+        tokio::spawn(async move {
+            if let Err(err) = handle_connection(stream, &token, config).await {
+                log::error!("Automation request from {peer} failed: {err}");
+            }
+        });
+    }
+}
+
+/// Read a single HTTP request from the stream, and respond to it
+async fn handle_connection(
+    stream: TcpStream,
+    token: &str,
+    config: Arc<Config>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+
+    let (status, body) =
+        match tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut reader, token)).await {
+            Ok(request) => match request? {
+                Ok(body) => ("200 OK", respond(&body, config).await),
+                Err(rejected) => rejected,
+            },
+            Err(_) => (
+                "408 Request Timeout",
+                json!({ "error": "the request took too long to send" }),
+            ),
+        };
+
+    let body = body.to_string();
+    reader
+        .into_inner()
+        .write_all(
+            format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .as_bytes(),
+        )
+        .await
+}
+
+/// Read the request, returning its body. Requests which are not allowed are rejected
+/// with the response to send instead
+async fn read_request(
+    reader: &mut BufReader<TcpStream>,
+    token: &str,
+) -> std::io::Result<Result<Vec<u8>, Response>> {
+    let mut request_line = String::new();
+    read_line(reader, &mut request_line).await?;
+
+    let mut content_length = 0;
+    let mut is_authorized = false;
+    let mut headers = 0;
+    loop {
+        let mut header = String::new();
+        if read_line(reader, &mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+
+        headers += 1;
+        if headers > MAX_HEADERS {
+            return Ok(Err((
+                "431 Request Header Fields Too Large",
+                json!({ "error": "too many headers" }),
+            )));
+        }
+
+        let Some((name, value)) = header.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().unwrap_or(0);
+        } else if name.eq_ignore_ascii_case("authorization") {
+            is_authorized = value
+                .strip_prefix("Bearer ")
+                .is_some_and(|sent| constant_time_eq(sent.as_bytes(), token.as_bytes()));
+        }
+    }
+
+    if !request_line.starts_with("POST ") {
+        return Ok(Err((
+            "405 Method Not Allowed",
+            json!({ "error": "only POST is supported" }),
+        )));
+    }
+    if !is_authorized {
+        return Ok(Err((
+            "401 Unauthorized",
+            json!({ "error": "invalid or missing token" }),
+        )));
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Ok(Err((
+            "413 Payload Too Large",
+            json!({ "error": "request is too large" }),
+        )));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Ok(body))
+}
+
+/// Read a line of the request into `line`, failing if it is longer than [`MAX_LINE_LEN`]
+async fn read_line(reader: &mut BufReader<TcpStream>, line: &mut String) -> std::io::Result<usize> {
+    let read = (&mut *reader)
+        .take(MAX_LINE_LEN as u64)
+        .read_line(line)
+        .await?;
+
+    if read == MAX_LINE_LEN && !line.ends_with('\n') {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "request line or header is too long",
+        ));
+    }
+
+    Ok(read)
+}
+
+/// Whether the bytes are equal, taking the same time no matter where they differ,
+/// so the token cannot be guessed byte by byte from how long the comparison takes
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Create the JSON-RPC response to the request
+async fn respond(body: &[u8], config: Arc<Config>) -> Value {
+    let request = match serde_json::from_slice::<Request>(body) {
+        Ok(request) => request,
+        Err(err) => return error(&Value::Null, -32700, &format!("parse error: {err}")),
+    };
+
+    if request.method != "capture" {
+        return error(
+            &request.id,
+            -32601,
+            &format!("method not found: {}", request.method),
+        );
+    }
+
+    let params = match serde_json::from_value::<Option<CaptureParams>>(request.params) {
+        Ok(params) => params,
+        Err(err) => return error(&request.id, -32602, &format!("invalid params: {err}")),
+    };
+
+    let region = match params.and_then(|params| params.region) {
+        Some(region) => match region.parse::<LazyRectangle>() {
+            Ok(region) => region,
+            Err(err) => return error(&request.id, -32602, &err.to_string()),
+        },
+        None => LazyRectangle::FULL,
+    };
+
+    match tokio::task::spawn_blocking(move || capture(region, &config)).await {
+        Ok(Ok((png, width, height))) => json!({
+            "jsonrpc": "2.0",
+            "id": request.id,
+            "result": { "png": png, "width": width, "height": height },
+        }),
+        Ok(Err(err)) => error(&request.id, -32000, &err),
+        Err(err) => error(&request.id, -32603, &err.to_string()),
+    }
+}
+
+/// Take a screenshot of the region, returning it as base64-encoded PNG with its width and height
+fn capture(region: LazyRectangle, config: &Config) -> Result<(String, u32, u32), String> {
+    let image = crate::get_image(None, config.preserve_transparency, config.capture_options())
+        .map_err(|err| err.to_string())?;
+    let image = App::process_image(region.init(image.bounds()), &image);
+
+    let png =
+        crate::image::encode::encode(&image, image::ImageFormat::Png, config.parallel_encoding)
+            .map_err(|err| err.to_string())?;
+
+    Ok((
+        base64::engine::general_purpose::STANDARD.encode(png),
+        image.width(),
+        image.height(),
+    ))
+}
+
+/// A JSON-RPC error response
+fn error(id: &Value, code: i32, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn compares_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[tokio::test]
+    async fn rejects_too_many_headers() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        let headers = "X-Filler: 1\r\n".repeat(MAX_HEADERS + 1);
+        client
+            .write_all(format!("POST / HTTP/1.1\r\n{headers}\r\n").as_bytes())
+            .await
+            .unwrap();
+
+        let (status, _) = read_request(&mut BufReader::new(server), "secret")
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(status, "431 Request Header Fields Too Large");
+    }
+}
@@ -0,0 +1,132 @@
+//! Control a running instance of ferrishot from other programs
+
+use crate::{
+    Command,
+    geometry::Direction,
+    image::action::Command as ImageCommand,
+    lazy_rect::LazyRectangle,
+    ui::{app::Command as AppCommand, selection::Command as SelectionCommand},
+};
+
+pub mod http;
+pub mod script;
+pub mod session;
+#[cfg(unix)]
+pub mod socket;
+
+/// Parse a single request into the `Command` it represents
+///
+/// - `set-region <WxH+X+Y>` or `select <WxH+X+Y>`: Select a region, same syntax as `--region`
+/// - `move|extend|shrink <left|right|up|down> [pixels]`: Move or resize the selection, by 1px by default
+/// - `accept <copy|save|upload>`: Perform an action on the selection
+/// - `cancel`: Exit ferrishot
+pub(crate) fn parse_command(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+
+    let command = match words.next() {
+        Some(name @ ("set-region" | "select")) => {
+            let region = words
+                .next()
+                .ok_or_else(|| format!("`{name}` requires a region"))?;
+
+            Command::Selection(SelectionCommand::SelectRegion {
+                selection: region
+                    .parse::<LazyRectangle>()
+                    .map_err(|err| err.to_string())?,
+            })
+        }
+        Some(name @ ("move" | "extend" | "shrink")) => {
+            let direction = words
+                .next()
+                .ok_or_else(|| format!("`{name}` requires one of: left, right, up, down"))?
+                .parse::<Direction>()
+                .map_err(|_| format!("`{name}` requires one of: left, right, up, down"))?;
+
+            let amount = words
+                .next()
+                .map(str::parse::<u32>)
+                .transpose()
+                .map_err(|err| format!("invalid amount of pixels: {err}"))?
+                .unwrap_or(1);
+
+            Command::Selection(match name {
+                "move" => SelectionCommand::Move { direction, amount },
+                "extend" => SelectionCommand::Extend { direction, amount },
+                _ => SelectionCommand::Shrink { direction, amount },
+            })
+        }
+        Some("accept") => Command::ImageUpload(match words.next() {
+            Some("copy") => ImageCommand::CopyToClipboard,
+            Some("save") => ImageCommand::SaveScreenshot,
+            Some("upload") => ImageCommand::UploadScreenshot,
+            Some(other) => return Err(format!("unknown action: {other}")),
+            None => return Err("`accept` requires one of: copy, save, upload".to_owned()),
+        }),
+        Some("cancel") => Command::App(AppCommand::Exit),
+        Some(other) => return Err(format!("unknown command: {other}")),
+        None => return Err("empty command".to_owned()),
+    };
+
+    if let Some(extra) = words.next() {
+        return Err(format!("unexpected argument: {extra}"));
+    }
+
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_requests() {
+        assert_eq!(
+            parse_command("set-region full").unwrap(),
+            Command::Selection(SelectionCommand::SelectRegion {
+                selection: LazyRectangle::FULL
+            })
+        );
+        assert_eq!(
+            parse_command("select full").unwrap(),
+            Command::Selection(SelectionCommand::SelectRegion {
+                selection: LazyRectangle::FULL
+            })
+        );
+        assert_eq!(
+            parse_command("move right 50").unwrap(),
+            Command::Selection(SelectionCommand::Move {
+                direction: Direction::Right,
+                amount: 50
+            })
+        );
+        assert_eq!(
+            parse_command("shrink up").unwrap(),
+            Command::Selection(SelectionCommand::Shrink {
+                direction: Direction::Up,
+                amount: 1
+            })
+        );
+        assert_eq!(
+            parse_command("accept copy").unwrap(),
+            Command::ImageUpload(ImageCommand::CopyToClipboard)
+        );
+        assert_eq!(
+            parse_command("  cancel ").unwrap(),
+            Command::App(AppCommand::Exit)
+        );
+    }
+
+    #[test]
+    fn parse_invalid_requests() {
+        assert!(parse_command("").is_err(), "empty request");
+        assert!(parse_command("accept").is_err(), "missing action");
+        assert!(parse_command("accept print").is_err(), "unknown action");
+        assert!(parse_command("cancel now").is_err(), "extra argument");
+        assert!(parse_command("set-region").is_err(), "missing region");
+        assert!(parse_command("move").is_err(), "missing direction");
+        assert!(parse_command("move sideways").is_err(), "unknown direction");
+        assert!(parse_command("move left far").is_err(), "invalid amount");
+        assert!(parse_command("launch").is_err(), "unknown command");
+    }
+}
@@ -0,0 +1,117 @@
+//! Drive the app with a script of commands passed on startup with `--script`
+//!
+//! Commands are separated by `;` or new lines, and are the same as the ones accepted
+//! by the control socket (see [`parse_command`](super::parse_command)). Additionally,
+//! `wait <milliseconds>` pauses the script. Lines starting with `#` are comments.
+//!
+//! ```text
+//! select 100x100+10+10; move right 50; wait 500; accept copy
+//! ```
+//!
+//! Commands are sent one after another in the same order, so running a script
+//! always does the same thing. This is useful for demos and for testing the app.
+
+use std::time::Duration;
+
+use iced::{
+    Subscription,
+    futures::{SinkExt as _, Stream},
+};
+
+use crate::{Command, Message};
+
+/// A single step of the script
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// Send the command to the app
+    Command(Command),
+    /// Wait before running the next step
+    Wait(Duration),
+}
+
+/// Parse the script into its steps
+fn parse(script: &str) -> Result<Vec<Step>, String> {
+    script
+        .split(['\n', ';'])
+        .map(str::trim)
+        .filter(|statement| !statement.is_empty() && !statement.starts_with('#'))
+        .map(|statement| {
+            let step = if let Some(milliseconds) = statement.strip_prefix("wait ") {
+                milliseconds
+                    .trim()
+                    .parse::<u64>()
+                    .map(|milliseconds| Step::Wait(Duration::from_millis(milliseconds)))
+                    .map_err(|err| format!("invalid duration: {err}"))
+            } else {
+                super::parse_command(statement).map(Step::Command)
+            };
+
+            step.map_err(|err| format!("`{statement}`: {err}"))
+        })
+        .collect()
+}
+
+/// Check that the script is valid, before the app launches
+pub fn validate(script: &str) -> Result<(), String> {
+    parse(script).map(|_| ())
+}
+
+/// Run the script, once the app launches
+pub fn subscription(script: String) -> Subscription<Message> {
+    Subscription::run_with(script, |script| run(script.clone()))
+}
+
+/// Stream of messages for each command in the script, in order
+fn run(script: String) -> impl Stream<Item = Message> {
+    iced::stream::channel(100, async move |mut output| {
+        let steps = match parse(&script) {
+            Ok(steps) => steps,
+            Err(err) => {
+                let _ = output
+                    .send(Message::Error(format!("Invalid script: {err}")))
+                    .await;
+                return;
+            }
+        };
+
+        for step in steps {
+            match step {
+                Step::Wait(duration) => tokio::time::sleep(duration).await,
+                Step::Command(action) => {
+                    if output
+                        .send(Message::Command { action, count: 1 })
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{image::action::Command as ImageCommand, ui::app::Command as AppCommand};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_script() {
+        assert_eq!(
+            parse("# copy after a second\nwait 1000; accept copy;\n\ncancel").unwrap(),
+            vec![
+                Step::Wait(Duration::from_secs(1)),
+                Step::Command(Command::ImageUpload(ImageCommand::CopyToClipboard)),
+                Step::Command(Command::App(AppCommand::Exit)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid_script() {
+        assert!(parse("wait soon").is_err(), "invalid duration");
+        assert!(parse("cancel; launch").is_err(), "unknown command");
+    }
+}
@@ -0,0 +1,154 @@
+//! Record what the user does with `--record-session`, to replay it later with `--replay-session`
+//!
+//! A session is a `--script`: every change of the selection becomes a `select` command,
+//! and the time between changes becomes a `wait`. Replaying it shows the same selection
+//! changing in the same way, which is useful for bug reports.
+
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::Write as _,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use iced::Rectangle;
+
+use crate::{
+    Command, Message, geometry::RectangleExt as _, image::action::Command as ImageCommand,
+    ui::app::Command as AppCommand,
+};
+
+/// Written at the start of every session
+const HEADER: &str = "# ferrishot session. Replay it with `ferrishot --replay-session <FILE>`\n";
+
+/// Records messages received by the app into a session file
+#[derive(Debug)]
+pub struct Recorder {
+    /// Where the session is saved
+    path: PathBuf,
+    /// The session file, which each command is appended to.
+    /// `None` once it could not be written, so we don't spam the logs
+    file: Option<File>,
+    /// When the last command was recorded
+    last_recorded: Instant,
+    /// Selection as of the last recorded command
+    last_selection: Option<Rectangle>,
+}
+
+impl Recorder {
+    /// Start recording a session into `path`, replacing the file if it exists
+    pub fn new(path: PathBuf) -> Self {
+        let file = File::create(&path)
+            .and_then(|mut file| file.write_all(HEADER.as_bytes()).map(|()| file))
+            .inspect_err(|err| log_failure(&path, err))
+            .ok();
+
+        Self {
+            path,
+            file,
+            last_recorded: Instant::now(),
+            last_selection: None,
+        }
+    }
+
+    /// Record the `message` which is about to be handled
+    ///
+    /// Actions are recorded before the message is handled, because they can exit the app
+    pub fn record_message(&mut self, message: &Message) {
+        let statement = match message {
+            Message::Command {
+                action: Command::ImageUpload(action),
+                ..
+            } => match action {
+                ImageCommand::CopyToClipboard => "accept copy",
+                ImageCommand::SaveScreenshot => "accept save",
+                ImageCommand::UploadScreenshot => "accept upload",
+            },
+            Message::Exit
+            | Message::Command {
+                action: Command::App(AppCommand::Exit),
+                ..
+            } => "cancel",
+            _ => return,
+        };
+
+        self.record(statement);
+    }
+
+    /// Record the `selection` after a message was handled, if it changed
+    pub fn record_selection(&mut self, selection: Option<Rectangle>) {
+        let selection = selection.map(|rect| rect.norm());
+        if selection == self.last_selection {
+            return;
+        }
+        self.last_selection = selection;
+
+        if let Some(rect) = selection {
+            self.record(&format!("select {}", rect.as_str()));
+        }
+    }
+
+    /// Add the `statement` to the session, after waiting for as long as the user did
+    fn record(&mut self, statement: &str) {
+        let now = Instant::now();
+        let wait = now.duration_since(self.last_recorded).as_millis();
+        self.last_recorded = now;
+
+        let Some(file) = &mut self.file else {
+            return;
+        };
+
+        let mut lines = String::new();
+        if wait > 0 {
+            let _ = writeln!(lines, "wait {wait}");
+        }
+        let _ = writeln!(lines, "{statement}");
+
+        // The app can exit at any moment, so each statement is appended to the file right away
+        if let Err(err) = file.write_all(lines.as_bytes()) {
+            log_failure(&self.path, &err);
+            self.file = None;
+        }
+    }
+}
+
+/// Log that the session could not be written to `path`
+fn log_failure(path: &Path, err: &std::io::Error) {
+    log::error!("Failed to write the session to {}: {err}", path.display());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn records_a_replayable_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session");
+        let mut recorder = Recorder::new(path.clone());
+
+        let rect = Rectangle::new(iced::Point::new(10.0, 20.0), iced::Size::new(30.0, 40.0));
+        recorder.record_selection(Some(rect));
+        // unchanged
+        recorder.record_selection(Some(rect));
+        recorder.record_message(&Message::NoOp);
+        recorder.record_message(&Message::Command {
+            action: Command::ImageUpload(ImageCommand::CopyToClipboard),
+            count: 1,
+        });
+
+        let session = std::fs::read_to_string(path).unwrap();
+        let statements = session
+            .lines()
+            .filter(|line| !line.starts_with("wait") && !line.starts_with('#'))
+            .collect::<Vec<_>>();
+
+        assert_eq!(statements, vec!["select 30x40+10+20", "accept copy"]);
+        assert!(
+            crate::ipc::script::validate(&session).is_ok(),
+            "session is a valid script"
+        );
+    }
+}
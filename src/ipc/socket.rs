@@ -0,0 +1,168 @@
+//! A control socket which lets external tools send commands to a running ferrishot
+//!
+//! The protocol is line-based. Each line is a single request, and gets a single
+//! line in response: either `ok` or `error: <reason>`.
+//!
+//! See [`parse_command`](super::parse_command) for the requests which can be sent.
+//!
+//! Only the user that started ferrishot can connect to the socket, as it is created
+//! with permissions `0600` inside of a directory which only that user can access, and
+//! only then moved to its path.
+
+use std::{
+    fs, io,
+    os::unix::{
+        fs::{FileTypeExt as _, PermissionsExt as _},
+        net::UnixStream,
+    },
+    path::{Path, PathBuf},
+};
+
+use iced::{
+    Subscription,
+    futures::{SinkExt as _, Stream},
+};
+use tokio::{
+    io::{AsyncBufReadExt as _, AsyncWriteExt as _, BufReader},
+    net::UnixListener,
+};
+
+use crate::Message;
+
+/// Listen for commands on the socket at the given path
+pub fn subscription(path: PathBuf) -> Subscription<Message> {
+    Subscription::run_with(path, |path| listen(path.clone()))
+}
+
+/// Stream of messages received from all clients connected to the socket
+fn listen(path: PathBuf) -> impl Stream<Item = Message> {
+    iced::stream::channel(100, async move |mut output| {
+        let listener = match bind(&path) {
+            Ok(listener) => listener,
+            Err(err) => {
+                let _ = output
+                    .send(Message::Error(format!(
+                        "Failed to create control socket at {}: {err}",
+                        path.display()
+                    )))
+                    .await;
+                return;
+            }
+        };
+
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    log::error!("Failed to accept connection on the control socket: {err}");
+                    continue;
+                }
+            };
+
+            let mut output = output.clone();
+            tokio::spawn(async move {
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = BufReader::new(reader).lines();
+
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let response = match super::parse_command(&line) {
+                        Ok(action) => {
+                            if output
+                                .send(Message::Command { action, count: 1 })
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
+                            "ok\n".to_owned()
+                        }
+                        Err(err) => format!("error: {err}\n"),
+                    };
+
+                    if writer.write_all(response.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    })
+}
+
+/// Create the socket at `path`, which only the current user can connect to
+fn bind(path: &Path) -> io::Result<UnixListener> {
+    // removing the socket would take it over from the instance which listens on it
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!(
+                "another ferrishot is already listening on {}",
+                path.display()
+            ),
+        ));
+    }
+    // a socket left over from a previous instance which did not exit cleanly.
+    // Anything else at the path is not ours to remove
+    remove(path)?;
+    if fs::symlink_metadata(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "the path already exists, and is not a socket",
+        ));
+    }
+
+    // Until its permissions are restricted, anyone could connect to the socket. So it is
+    // created in a directory that only we can access, and moved to the `path` afterwards
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let dir = tempfile::Builder::new()
+        .prefix(".ferrishot-socket-")
+        .permissions(fs::Permissions::from_mode(0o700))
+        .tempdir_in(parent)?;
+    let private_path = dir.path().join("socket");
+
+    let listener = UnixListener::bind(&private_path)?;
+    fs::set_permissions(&private_path, fs::Permissions::from_mode(0o600))?;
+    fs::rename(&private_path, path)?;
+
+    Ok(listener)
+}
+
+/// Remove the socket at `path`, if there is one. Does nothing if the path
+/// does not exist, is not a socket, or another instance is listening on it
+pub fn remove(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() && is_stale(path) => fs::remove_file(path),
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Whether nothing listens on the socket at `path` anymore
+fn is_stale(path: &Path) -> bool {
+    UnixStream::connect(path).is_err_and(|err| err.kind() == io::ErrorKind::ConnectionRefused)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn does_not_take_over_a_listening_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ferrishot.sock");
+
+        let listener = bind(&path).unwrap();
+        let err = bind(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+
+        // once the first instance is gone, its socket is stale and can be replaced
+        remove(&path).unwrap();
+        assert!(path.exists(), "the first instance is still listening");
+        drop(listener);
+        bind(&path).unwrap();
+    }
+}
@@ -1,4 +1,6 @@
 //! The ferrishot app
+//!
+//! To take screenshots from other programs without launching the app, use [`capture()`]
 #![cfg_attr(
     test,
     allow(
@@ -15,9 +17,12 @@ mod command {
 
 mod clipboard;
 mod config;
+#[cfg(feature = "gamepad")]
+mod gamepad;
 mod geometry;
 mod icons;
 mod image;
+mod inhibit;
 mod lazy_rect;
 mod message;
 mod ui;
@@ -27,13 +32,49 @@ use config::commands::Command;
 use config::Theme;
 use message::Message;
 
+pub mod autosave;
+pub mod batch;
+pub mod capture;
+pub mod daemon;
+pub mod doctor;
+pub mod error_code;
+pub mod error_report;
+pub mod integrate;
+pub mod ipc;
 pub mod last_region;
 pub mod logging;
+pub mod notify;
+pub mod paths;
+pub mod registers;
+pub mod stats;
+
+/// Internals used by the benchmarks in `benches/`. Not a stable API
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench {
+    pub use crate::config::key_map::KeyMap;
+    pub use crate::image::{RgbaHandle, conflict::OnConflict, duplicate::OnDuplicate};
+}
 
 #[cfg(target_os = "linux")]
 pub use clipboard::{CLIPBOARD_DAEMON_ID, run_clipboard_daemon};
 
-pub use config::{Cli, Config, DEFAULT_KDL_CONFIG_STR, DEFAULT_LOG_FILE_PATH};
-pub use image::action::SAVED_IMAGE;
-pub use image::get_image;
-pub use ui::App;
+// capturing screenshots, without the app
+pub use capture::capture;
+pub use image::{
+    GetImageError,
+    encode::encode as encode_image,
+    get_desktop_image, get_image, save as save_image,
+    screenshot::{CaptureOptions, blank as blank_screenshot, wait_for_window},
+};
+pub use lazy_rect::{LazyRectangle, ParseRectError};
+
+// the app
+pub use config::contrast::check as check_contrast;
+pub use config::edit as edit_config;
+pub use config::{
+    Action, Cli, Config, ConfigAction, DEFAULT_KDL_CONFIG_STR, DEFAULT_LOG_FILE_PATH,
+    InitialSelection,
+};
+pub use ui::popup::keybindings_cheatsheet::export as export_cheatsheet;
+pub use ui::{App, Outcome};
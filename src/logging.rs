@@ -12,7 +12,14 @@ pub fn initialize(cli: &crate::Cli) {
     } else {
         use std::io::Write as _;
 
-        match std::fs::File::create(std::path::PathBuf::from(&*cli.log_file)) {
+        let path = std::path::PathBuf::from(&*cli.log_file);
+
+        // missing the first time in portable mode
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+
+        match std::fs::File::create(path) {
             Ok(file) => env_logger::Builder::new()
                 .format(|buf, record| {
                     writeln!(
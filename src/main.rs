@@ -41,21 +41,61 @@ fn main() -> miette::Result<()> {
         return Ok(());
     }
 
+    // Waits for the notification to be clicked, after ferrishot has exited
+    #[cfg(target_os = "linux")]
+    if std::env::args().nth(1).as_deref() == Some(ferrishot::notify::NOTIFICATION_DAEMON_ID) {
+        ferrishot::notify::run_notification_daemon()?;
+        return Ok(());
+    }
+
     // Parse command line arguments
-    let cli = Arc::new(Cli::parse());
+    let mut cli = Cli::parse();
+
+    // a session is just a script
+    if let Some(session) = &cli.replay_session {
+        cli.script = Some(
+            std::fs::read_to_string(session)
+                .map_err(|err| miette!("Failed to read the session: {err}"))?,
+        );
+    }
+
+    if cli.script.as_deref() == Some("-") {
+        cli.script = Some(std::io::read_to_string(std::io::stdin()).into_diagnostic()?);
+    }
+
+    if let Some(script) = &cli.script {
+        ferrishot::ipc::script::validate(script).map_err(|err| miette!("Invalid script: {err}"))?;
+    }
+
+    let cli = Arc::new(cli);
 
     // Setup logging
     ferrishot::logging::initialize(&cli);
 
-    if cli.dump_default_config {
-        std::fs::create_dir_all(
-            std::path::PathBuf::from(&cli.config_file)
-                .parent()
-                .ok_or_else(|| miette!("Could not get parent path of {}", cli.config_file))?,
-        )
-        .into_diagnostic()?;
+    if let Some(code) = cli.explain {
+        print!("{}", code.explain());
+        return Ok(());
+    }
+
+    if cli.bug_report {
+        let path = ferrishot::error_report::write_bug_report(&cli)
+            .map_err(|err| miette!("Failed to write the bug report: {err}"))?;
+
+        if !cli.silent {
+            println!("Wrote the bug report to {}", path.display());
+        }
 
-        std::fs::write(&cli.config_file, ferrishot::DEFAULT_KDL_CONFIG_STR).into_diagnostic()?;
+        return Ok(());
+    }
+
+    ferrishot::error_report::install_panic_hook();
+
+    if let Some(ferrishot::Action::Config(ferrishot::ConfigAction::Edit)) = cli.action {
+        return edit_config(&cli.config_file);
+    }
+
+    if cli.dump_default_config {
+        ferrishot::edit_config::write_default(std::path::Path::new(&cli.config_file))?;
 
         if !cli.silent {
             println!("Wrote the default config file to {}", cli.config_file);
@@ -64,52 +104,306 @@ fn main() -> miette::Result<()> {
         return Ok(());
     }
 
+    #[cfg(windows)]
+    if let Some(integration) = cli.integrate {
+        ferrishot::integrate::run(integration)
+            .map_err(|err| miette!("Failed to integrate with the Explorer: {err}"))?;
+
+        if !cli.silent {
+            println!("Done! The context menu of the Explorer is updated");
+        }
+
+        return Ok(());
+    }
+
     // these variables need to be re-used after the `iced::application` ends
     let cli_save_path = cli.save_path.clone();
-    let is_silent = cli.silent;
+    // the screenshot goes to stdout, so nothing else can be printed there
+    let is_raw = cli.raw;
+    let is_silent = cli.silent || is_raw;
+
+    if let Some(title) = &cli.wait_for_window {
+        if !is_silent {
+            println!("Waiting for a window matching `{title}`...");
+        }
+        ferrishot::wait_for_window(title).map_err(|err| miette!("{err}"))?;
+    }
 
     if let Some(delay) = cli.delay {
-        if !cli.silent {
+        if !is_silent {
             println!("Sleeping for {delay:?}...");
         }
         std::thread::sleep(delay);
     }
 
     // Parse user's `ferrishot.kdl` config file
-    let config = Arc::new(ferrishot::Config::parse(&cli.config_file)?);
+    let (config, warnings) = ferrishot::Config::parse(&cli.config_file)?;
+    if !cli.silent {
+        for warning in warnings {
+            eprintln!("{warning:?}");
+        }
+    }
+    let config = Arc::new(config);
+    let on_duplicate = config.on_duplicate;
+    let on_conflict = config.on_conflict;
+    let parallel_encoding = config.parallel_encoding;
+    let notifications = config.notifications();
+
+    if cli.action == Some(ferrishot::Action::Doctor) {
+        print!("{}", ferrishot::doctor::run(config.preserve_transparency));
+        return Ok(());
+    }
+
+    if let Some(ferrishot::Action::Config(ferrishot::ConfigAction::Check { contrast })) = cli.action
+    {
+        if !cli.silent {
+            println!("{} is valid", cli.config_file);
+        }
+
+        if !contrast {
+            return Ok(());
+        }
+
+        let report = ferrishot::check_contrast(&config.theme);
+        print!("{report}");
+
+        return match report.failures() {
+            0 => Ok(()),
+            failures => Err(miette!(
+                "{failures} pairs of colors of the theme do not have enough contrast"
+            )),
+        };
+    }
+
+    if let Some(path) = &cli.export_cheatsheet {
+        tokio::runtime::Runtime::new()
+            .into_diagnostic()?
+            .block_on(ferrishot::export_cheatsheet(&config, path))
+            .map_err(|err| miette!("Failed to export the cheatsheet: {err}"))?;
+
+        if !cli.silent {
+            println!("Exported the cheatsheet to {}", path.display());
+        }
+
+        return Ok(());
+    }
+
+    if cli.action == Some(ferrishot::Action::Stats) {
+        let stats = ferrishot::stats::read()
+            .map_err(|err| miette!("Failed to read the statistics: {err}"))?;
+
+        print!("{}", stats.report());
+
+        if !config.statistics {
+            println!("\nStatistics are not being collected. Enable them with `statistics #true`");
+        }
+
+        return Ok(());
+    }
+
+    if let Some(ferrishot::Action::Batch {
+        files,
+        region,
+        out_dir,
+    }) = &cli.action
+    {
+        let batch = ferrishot::batch::Batch {
+            region: *region,
+            out_dir,
+            on_duplicate,
+            on_conflict,
+            parallel_encoding,
+        };
+
+        let results = batch
+            .run(files)
+            .map_err(|err| miette!("Failed to create the output directory: {err}"))?;
+
+        let mut failed = 0;
+        for (file, result) in files.iter().zip(results) {
+            match result {
+                Ok(path) => {
+                    if !cli.silent {
+                        println!("{} -> {}", file.display(), path.display());
+                    }
+                }
+                Err(err) => {
+                    failed += 1;
+                    eprintln!("Failed to crop {}: {err}", file.display());
+                }
+            }
+        }
+
+        if failed > 0 {
+            return Err(miette!("Failed to crop {failed} of {} images", files.len()));
+        }
+
+        return Ok(());
+    }
+
+    if let Some(trigger_file) = &cli.trigger_file {
+        let save_path = cli
+            .save_path
+            .as_ref()
+            .ok_or_else(|| miette!("`--trigger-file` requires `--save-path`"))?;
+
+        if !cli.foreground {
+            let pid = ferrishot::daemon::detach()
+                .map_err(|err| miette!("Failed to start in the background: {err}"))?;
+
+            if !cli.silent {
+                let stop = if cfg!(windows) {
+                    format!("taskkill /PID {pid}")
+                } else {
+                    format!("kill {pid}")
+                };
+                println!(
+                    "Taking a screenshot whenever {} is touched, in the background. Stop it with `{stop}`",
+                    trigger_file.display()
+                );
+            }
+
+            return Ok(());
+        }
+
+        if !cli.silent {
+            println!(
+                "Taking a screenshot whenever {} is touched...",
+                trigger_file.display()
+            );
+        }
+
+        return ferrishot::daemon::watch_trigger_file(&cli, &config, trigger_file, save_path)
+            .map_err(|err| miette!("Failed to watch the trigger file: {err}"));
+    }
+
+    if let Some(interval) = cli.every {
+        let save_path = cli
+            .save_path
+            .as_ref()
+            .ok_or_else(|| miette!("`--every` requires `--save-path`"))?;
+
+        if !cli.silent {
+            println!("Taking a screenshot every {interval:?}...");
+        }
+
+        return ferrishot::daemon::capture_periodically(&cli, &config, save_path, interval)
+            .map_err(|err| miette!("Failed to take periodic screenshots: {err}"));
+    }
+
+    if let Some(addr) = cli.serve {
+        if !cli.silent {
+            println!("Serving automation requests on {addr}...");
+        }
+
+        return tokio::runtime::Runtime::new()
+            .into_diagnostic()?
+            .block_on(ferrishot::ipc::http::serve(
+                addr,
+                cli.serve_remote,
+                Arc::clone(&config),
+            ))
+            .map_err(|err| miette!("Automation server failed: {err}"));
+    }
+
+    let initial_selection = config.initial_selection;
+
+    if cli
+        .accept_on_select
+        .is_some_and(|action| action.needs_network())
+        && !config.can_upload()
+    {
+        return Err(miette!(
+            "Can't upload the screenshot: uploading is disabled by the config, or ferrishot was built without the `upload` feature"
+        ));
+    }
 
     // The image that we are going to be editing
-    let image = Arc::new(ferrishot::get_image(cli.file.as_ref())?);
+    let image = match cli.region.filter(|_| cli.desktop) {
+        Some(region) => ferrishot::get_desktop_image(
+            region,
+            config.preserve_transparency,
+            config.capture_options(),
+        ),
+        None => ferrishot::get_image(
+            cli.file.as_ref(),
+            config.preserve_transparency,
+            config.capture_options(),
+        ),
+    };
+    let (image, is_missing_permission) = match image {
+        Ok(image) => (image, false),
+        // Instead of capturing a black screen, the app guides the user to allow screenshots.
+        // Without the app there is no one to guide, so it is an error
+        Err(err) if err.is_missing_permission() && cli.accept_on_select.is_none() => (
+            ferrishot::blank_screenshot().map_err(|err| miette!("{err}"))?,
+            true,
+        ),
+        Err(err) => return Err(err.into()),
+    };
+    let image = Arc::new(image);
+
+    if config.statistics {
+        ferrishot::stats::record(|stats| stats.captures += 1);
+    }
 
     // start the app with an initial selection of the image
     let initial_region = if cli.last_region {
         ferrishot::last_region::read(image.bounds())?
     } else {
-        cli.region.map(|lazy_rect| lazy_rect.init(image.bounds()))
+        // the screenshot of the desktop is already of the region
+        cli.region.map(|lazy_rect| {
+            if cli.desktop {
+                image.bounds()
+            } else {
+                lazy_rect.init(image.bounds())
+            }
+        })
     };
 
-    let generate_output = match (cli.accept_on_select, initial_region) {
-        // If we want to do an action as soon as we have a selection,
-        // AND we start the app with the selection: Then don't even launch a window.
-        //
-        // Run in 'headless' mode and perform the action instantly
-        (Some(accept_on_select), Some(region)) => {
+    // What the app produced, such as screenshots to save, is received after it exits
+    let (outcomes, received_outcomes) = std::sync::mpsc::channel();
+
+    // If we want to do an action as soon as we have a selection,
+    // AND we start the app with the selection: Then don't even launch a window.
+    //
+    // Run in 'headless' mode and perform the action instantly
+    let generate_output =
+        if let (Some(accept_on_select), Some(region)) = (cli.headless_action(), initial_region) {
             let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
 
-            App::headless(accept_on_select, region, image, cli.json)
-                .pipe(|fut| runtime.block_on(fut))
-                .map_err(|err| miette!("Failed to start ferrishot (headless): {err}"))?
-                .pipe(Some)
-        }
-        // Launch full ferrishot app
-        _ => {
-            iced::application(
+            if config.statistics {
+                ferrishot::stats::record(|stats| stats.record_action(accept_on_select));
+            }
+
+            App::headless(
+                accept_on_select,
+                region,
+                image,
+                cli.json,
+                &config,
+                &outcomes,
+            )
+            .pipe(|fut| runtime.block_on(fut))
+            .map_err(|err| miette!("Failed to start ferrishot (headless): {err}"))?
+            .pipe(Some)
+        } else {
+            // Without a region from the command line, the config decides what to start with
+            let initial_region = initial_region.or_else(|| initial_selection.init(image.bounds()));
+            let windowed = cli.windowed;
+            #[cfg(unix)]
+            let control_socket = cli.control_socket.clone();
+
+            // Launch full ferrishot app
+            let ran = iced::application(
                 move || {
                     App::builder()
                         .cli(Arc::clone(&cli))
                         .config(Arc::clone(&config))
                         .maybe_initial_region(initial_region)
                         .image(Arc::clone(&image))
+                        .outcomes(outcomes.clone())
+                        .is_missing_permission(is_missing_permission)
                         .build()
                 },
                 App::update,
@@ -118,7 +412,10 @@ fn main() -> miette::Result<()> {
             .subscription(App::subscription)
             .window(iced::window::Settings {
                 level: iced::window::Level::Normal,
-                fullscreen: true,
+                fullscreen: windowed.is_none(),
+                size: windowed.map_or(iced::window::Settings::default().size, |size| {
+                    iced::Size::new(size.width as f32, size.height as f32)
+                }),
                 icon: Some(
                     iced::window::icon::from_rgba(LOGO.to_vec(), 64, 64)
                         .expect("Icon to be valid RGBA bytes"),
@@ -127,37 +424,50 @@ fn main() -> miette::Result<()> {
             })
             .title("ferrishot")
             .default_font(iced::Font::MONOSPACE)
-            .run()
-            .map_err(|err| miette!("Failed to start ferrishot: {err}"))?;
-
-            None
-        }
-    };
+            .run();
 
-    let saved_path = if let Some(saved_image) = ferrishot::SAVED_IMAGE.get() {
-        if let Some(save_path) = cli_save_path.or_else(|| {
-            // Open file explorer to choose where to save the image
-            let dialog = rfd::FileDialog::new()
-                .set_title("Save Screenshot")
-                .save_file();
-
-            if dialog.is_none() {
-                log::info!("The file dialog was closed before a file was chosen");
+            #[cfg(unix)]
+            if let Some(socket) = control_socket {
+                ferrishot::ipc::socket::remove(&socket)
+                    .map_err(|err| miette!("Failed to remove the control socket: {err}"))?;
             }
 
-            dialog
-        }) {
-            saved_image
-                .save(&save_path)
-                .map_err(|err| miette!("Failed to save the screenshot: {err}"))?;
+            ran.map_err(|err| miette!("Failed to start ferrishot: {err}"))?;
 
-            Some(save_path)
-        } else {
             None
+        };
+
+    // Without `--save-path` or `--raw`, the file picker inside of the app has already saved it
+    let mut saved_path = None;
+    for outcome in received_outcomes.try_iter() {
+        match outcome {
+            ferrishot::Outcome::Saved(image) => {
+                if is_raw {
+                    let png =
+                        ferrishot::encode_image(&image, image::ImageFormat::Png, parallel_encoding)
+                            .map_err(|err| miette!("Failed to encode the screenshot: {err}"))?;
+                    std::io::Write::write_all(&mut std::io::stdout().lock(), &png).map_err(
+                        |err| miette!("Failed to write the screenshot to stdout: {err}"),
+                    )?;
+                } else if let Some(save_path) = &cli_save_path {
+                    let path = ferrishot::save_image(
+                        &image,
+                        save_path,
+                        on_duplicate,
+                        on_conflict,
+                        parallel_encoding,
+                    )
+                    .map_err(|err| miette!("Failed to save the screenshot: {err}"))?;
+                    if notifications.on_save {
+                        tokio::runtime::Runtime::new()
+                            .into_diagnostic()?
+                            .block_on(notifications.saved(&image, &path));
+                    }
+                    saved_path = Some(path);
+                }
+            }
         }
-    } else {
-        None
-    };
+    }
 
     if let Some(print_output) = generate_output {
         let output = print_output(saved_path);
@@ -167,3 +477,56 @@ fn main() -> miette::Result<()> {
     }
     Ok(())
 }
+
+/// Open the config file at `path` in the editor of the user, then check it for mistakes.
+/// While it has mistakes, ask whether to open the editor again
+#[allow(
+    clippy::print_stderr,
+    clippy::print_stdout,
+    reason = "the user is editing the config interactively"
+)]
+fn edit_config(path: &str) -> miette::Result<()> {
+    let file = std::path::Path::new(path);
+
+    if !file.exists() {
+        ferrishot::edit_config::write_default(file)?;
+        println!("Created {path} with the default config");
+    }
+
+    let editor = ferrishot::edit_config::editor();
+
+    loop {
+        ferrishot::edit_config::open(&editor, file)?;
+
+        let err = match ferrishot::Config::parse(path) {
+            Ok((config, warnings)) => {
+                for warning in warnings {
+                    eprintln!("{warning:?}");
+                }
+                println!("{path} is valid");
+
+                let failures = ferrishot::check_contrast(&config.theme).failures();
+                if failures > 0 {
+                    println!(
+                        "{failures} pairs of colors of the theme do not have enough contrast. \
+                        See `ferrishot config check --contrast`"
+                    );
+                }
+
+                return Ok(());
+            }
+            Err(err) => err,
+        };
+
+        eprintln!("{err:?}");
+        print!("Edit the config again? [Y/n] ");
+        std::io::Write::flush(&mut std::io::stdout()).into_diagnostic()?;
+
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer).into_diagnostic()?;
+
+        if matches!(answer.trim(), "n" | "N" | "no") {
+            return Err(miette!("{path} has mistakes"));
+        }
+    }
+}
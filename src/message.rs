@@ -21,6 +21,8 @@ pub enum Message {
     ImageUploaded(ui::popup::image_uploaded::Message),
     /// A certain moment. This message is used for animations
     Tick(Instant),
+    /// A key was pressed, or the mouse was moved or clicked
+    Activity,
     /// Letters message
     Letters(ui::popup::letters::Message),
     /// Size indicator message
@@ -29,10 +31,45 @@ pub enum Message {
     Selection(Box<ui::selection::Message>),
     /// Keybinding cheatsheet message
     KeyCheatsheet(ui::popup::keybindings_cheatsheet::Message),
+    /// Pixel inspector message
+    PixelInspector(ui::popup::pixel_inspector::Message),
+    /// File picker message
+    FilePicker(ui::popup::file_picker::Message),
+    /// Screen recording permission message
+    ScreenRecordingPermission(ui::popup::screen_recording_permission::Message),
+    /// Settings message
+    Settings(ui::popup::settings::Message),
+    /// Tutorial message
+    Tutorial(ui::tutorial::Message),
+    /// Toolbar message
+    Toolbar(ui::toolbar::Message),
+    /// Key macros message
+    KeyMacros(ui::key_macros::Message),
+    /// Redaction message
+    Redact(crate::image::redact::Message),
+    /// Drawing message
+    Annotate(crate::image::annotate::Message),
     /// An error occured, display to the user
     Error(String),
+    /// An error which `--explain` explains occured, display to the user
+    ErrorWithCode(crate::error_code::ErrorCode, String),
+    /// The screen changed since the screenshot was taken
+    CaptureStale,
+    /// The window was resized to this size
+    WindowResized(iced::Size),
+    /// The screenshot was taken again
+    CaptureRefreshed(std::sync::Arc<crate::image::RgbaHandle>),
     /// Do nothing
     NoOp,
+    /// A command triggered by a keybind, after choosing a register with `"`
+    RegisterCommand {
+        /// Register chosen for the command
+        register: char,
+        /// What to do when this keybind is pressed
+        action: Command,
+        /// How many times it was pressed
+        count: u32,
+    },
     /// A command can be triggered by a keybind
     ///
     /// It can also be triggered through other means, such as pressing a button
@@ -49,3 +86,42 @@ pub enum Message {
         count: u32,
     },
 }
+
+impl Message {
+    /// Whether handling the message can change the image, the selection, the adjustments,
+    /// or the redactions and strokes on it.
+    ///
+    /// Only after these messages is the change recorded in the history, and the preview
+    /// of the adjustments refreshed. Ticks only change the selection while it is animated,
+    /// which the caller has to check
+    pub const fn can_change_image(&self) -> bool {
+        match self {
+            Self::Letters(_)
+            | Self::SizeIndicator(_)
+            | Self::Selection(_)
+            | Self::KeyMacros(_)
+            | Self::Redact(_)
+            | Self::Annotate(_)
+            | Self::CaptureRefreshed(_)
+            | Self::RegisterCommand { .. }
+            | Self::Command { .. } => true,
+            Self::Exit
+            | Self::ClosePopup
+            | Self::ImageUploaded(_)
+            | Self::Tick(_)
+            | Self::Activity
+            | Self::KeyCheatsheet(_)
+            | Self::PixelInspector(_)
+            | Self::FilePicker(_)
+            | Self::ScreenRecordingPermission(_)
+            | Self::Settings(_)
+            | Self::Tutorial(_)
+            | Self::Toolbar(_)
+            | Self::Error(_)
+            | Self::ErrorWithCode(..)
+            | Self::CaptureStale
+            | Self::WindowResized(_)
+            | Self::NoOp => false,
+        }
+    }
+}
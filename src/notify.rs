@@ -0,0 +1,278 @@
+//! Desktop notifications for when a screenshot is copied, saved or uploaded
+//!
+//! Which notifications are shown is decided by the `notify-on-copy`, `notify-on-save`
+//! and `notify-on-upload` options of the config. Showing a notification never blocks
+//! the app, and failing to show one is only logged.
+//!
+//! On Linux, clicking the notification of a saved screenshot shows it in the file manager,
+//! and clicking the notification of an uploaded screenshot opens the link. ferrishot exits
+//! right after the screenshot is taken, so a daemon waits for the click instead, similar to
+//! the clipboard daemon. Other platforms can't tell us when a notification is clicked.
+
+use std::path::{Path, PathBuf};
+
+use image::DynamicImage;
+
+/// Name of the thumbnail of copied screenshots, in the temporary directory.
+/// Saved screenshots are their own thumbnail
+const THUMBNAIL_FILENAME: &str = "ferrishot-notification.png";
+
+/// Thumbnails are scaled down to fit into a square of this size
+const THUMBNAIL_SIZE: u32 = 256;
+
+/// An argument that can be passed into the program to signal that it should become
+/// the daemon which shows a notification and waits for it to be clicked
+#[cfg(target_os = "linux")]
+pub const NOTIFICATION_DAEMON_ID: &str = "__ferrishot_notification_daemon";
+
+/// Could not show the notification
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// The desktop refused to show the notification
+    #[error(transparent)]
+    Notify(#[from] notify_rust::error::Error),
+    /// The thread showing the notification stopped
+    #[error(transparent)]
+    Join(#[from] tokio::task::JoinError),
+    /// Could not start the daemon, or run what happens on click
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The notification could not be passed to the daemon
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// The file manager could not show the file
+    #[cfg(target_os = "linux")]
+    #[error(transparent)]
+    Portal(#[from] ashpd::Error),
+}
+
+/// What happens when the notification is clicked
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum OnClick {
+    /// Show the file in the file manager
+    Reveal(PathBuf),
+    /// Open the link in the browser
+    Open(String),
+}
+
+#[cfg(target_os = "linux")]
+impl OnClick {
+    /// Do what happens when the notification is clicked
+    async fn run(&self) -> Result<(), Error> {
+        match self {
+            Self::Reveal(path) => {
+                // The file manager opens the directory, with the file selected
+                ashpd::desktop::open_uri::OpenDirectoryRequest::default()
+                    .send(&std::fs::File::open(path)?)
+                    .await?;
+            }
+            Self::Open(link) => {
+                std::process::Command::new("xdg-open").arg(link).spawn()?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A notification to show
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Notification {
+    /// What happened
+    pub summary: String,
+    /// Details of what happened
+    pub body: String,
+    /// Image to show in the notification
+    pub thumbnail: Option<PathBuf>,
+    /// What happens when the notification is clicked
+    pub on_click: Option<OnClick>,
+}
+
+impl Notification {
+    /// Notification of the desktop, without what happens on click
+    fn desktop(&self) -> notify_rust::Notification {
+        let mut desktop = notify_rust::Notification::new();
+        desktop
+            .appname("ferrishot")
+            .summary(&self.summary)
+            .body(&self.body);
+
+        if let Some(thumbnail) = &self.thumbnail {
+            desktop.icon(&thumbnail.to_string_lossy());
+        }
+
+        desktop
+    }
+}
+
+/// Something which can show notifications
+pub trait Notifier {
+    /// Show the `notification`, without blocking
+    fn notify(&self, notification: Notification) -> impl Future<Output = Result<(), Error>> + Send;
+}
+
+/// Notifications of the operating system
+///
+/// - Linux: Through D-Bus, to the notification daemon
+/// - macOS: Notification Center
+/// - Windows: Toast notifications
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Desktop;
+
+impl Notifier for Desktop {
+    async fn notify(&self, notification: Notification) -> Result<(), Error> {
+        #[cfg(target_os = "linux")]
+        if notification.on_click.is_some() {
+            use std::process;
+            process::Command::new(std::env::current_exe()?)
+                .arg(NOTIFICATION_DAEMON_ID)
+                .arg(serde_json::to_string(&notification)?)
+                .stdin(process::Stdio::null())
+                .stdout(process::Stdio::null())
+                .stderr(process::Stdio::inherit())
+                .current_dir("/")
+                .spawn()?;
+
+            return Ok(());
+        }
+
+        tokio::task::spawn_blocking(move || notification.desktop().show().map(drop)).await??;
+
+        Ok(())
+    }
+}
+
+/// Shows a notification, and waits in the background until it is clicked or closed
+///
+/// # Panics
+///
+/// Will panic if the daemon was invoked incorrectly. That's fine because
+/// it should only be invoked from this app, never from the outside.
+///
+/// We expect that the daemon receives 2 arguments:
+///
+/// 1. ID of the daemon
+/// 2. the `Notification`, as JSON
+#[cfg(target_os = "linux")]
+pub fn run_notification_daemon() -> Result<(), Error> {
+    // skip program name
+    let mut args = std::env::args().skip(1);
+
+    assert_eq!(
+        args.next().as_deref(),
+        Some(NOTIFICATION_DAEMON_ID),
+        "this function must be invoked from a daemon process"
+    );
+
+    let notification = serde_json::from_str::<Notification>(&args.next().expect("notification"))?;
+
+    let mut clicked = false;
+    notification
+        .desktop()
+        .action("default", "Open")
+        .show()?
+        .wait_for_action(|action| clicked = action == "default");
+
+    if clicked && let Some(on_click) = &notification.on_click {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(on_click.run())?;
+    }
+
+    Ok(())
+}
+
+/// Which notifications to show, from the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Notifications {
+    /// Show a notification when a screenshot is copied to the clipboard
+    pub on_copy: bool,
+    /// Show a notification when a screenshot is saved to a file
+    pub on_save: bool,
+    /// Show a notification when a screenshot is uploaded to the internet
+    pub on_upload: bool,
+    /// Show the screenshot in the notification
+    pub thumbnail: bool,
+}
+
+impl Notifications {
+    /// Tell the user that the `image` was copied, if they want to know
+    pub async fn copied(self, image: &DynamicImage) {
+        if !self.on_copy {
+            return;
+        }
+
+        let thumbnail = if self.thumbnail {
+            thumbnail(image)
+        } else {
+            None
+        };
+
+        show(Notification {
+            summary: "Screenshot copied".to_owned(),
+            body: format!(
+                "{}x{} image copied to the clipboard",
+                image.width(),
+                image.height()
+            ),
+            thumbnail,
+            on_click: None,
+        })
+        .await;
+    }
+
+    /// Tell the user that the `image` was saved to `path`, if they want to know
+    pub async fn saved(self, image: &DynamicImage, path: &Path) {
+        if !self.on_save {
+            return;
+        }
+
+        show(Notification {
+            summary: "Screenshot saved".to_owned(),
+            body: format!(
+                "{}x{} image saved to {}",
+                image.width(),
+                image.height(),
+                path.display()
+            ),
+            thumbnail: self.thumbnail.then(|| path.to_path_buf()),
+            on_click: Some(OnClick::Reveal(path.to_path_buf())),
+        })
+        .await;
+    }
+
+    /// Tell the user that the image at `path` was uploaded to `link`, if they want to know
+    pub async fn uploaded(self, path: &Path, link: &str) {
+        if !self.on_upload {
+            return;
+        }
+
+        show(Notification {
+            summary: "Screenshot uploaded".to_owned(),
+            body: format!("Uploaded to {link}"),
+            thumbnail: self.thumbnail.then(|| path.to_path_buf()),
+            on_click: Some(OnClick::Open(link.to_owned())),
+        })
+        .await;
+    }
+}
+
+/// Show the `notification` on the desktop, logging if it fails
+async fn show(notification: Notification) {
+    if let Err(err) = Desktop.notify(notification).await {
+        log::error!("Failed to show the notification: {err}");
+    }
+}
+
+/// Write a small version of the `image` to a temporary file, for the notification to show
+fn thumbnail(image: &DynamicImage) -> Option<PathBuf> {
+    let path = std::env::temp_dir().join(THUMBNAIL_FILENAME);
+
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .save_with_format(&path, image::ImageFormat::Png)
+        .inspect_err(|err| log::error!("Failed to create the thumbnail: {err}"))
+        .ok()
+        .map(|()| path)
+}
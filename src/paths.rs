@@ -0,0 +1,154 @@
+//! Where ferrishot keeps its files
+//!
+//! Every file that ferrishot reads or writes on its own, like the config, the log and
+//! the remembered state, is found through this module.
+//!
+//! The directories follow the conventions of each platform:
+//!
+//! | Platform | Config                          | Cache                     | Data                            |
+//! | -------- | ------------------------------- | ------------------------- | ------------------------------- |
+//! | Linux    | `$XDG_CONFIG_HOME`              | `$XDG_CACHE_HOME`         | `$XDG_DATA_HOME`                |
+//! | Windows  | `{FOLDERID_RoamingAppData}`     | `{FOLDERID_LocalAppData}` | `{FOLDERID_RoamingAppData}`     |
+//! | macOS    | `~/Library/Application Support` | `~/Library/Caches`        | `~/Library/Application Support` |
+//!
+//! # Portable mode
+//!
+//! With `--portable`, or when the `FERRISHOT_PORTABLE` environment variable is set,
+//! everything is kept in a `ferrishot-data` directory next to the executable instead.
+//! That way ferrishot can be carried around on a USB stick, without leaving anything behind.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use etcetera::BaseStrategy as _;
+
+/// Environment variable which enables portable mode when set to anything but `0`
+pub const PORTABLE_ENV_VAR: &str = "FERRISHOT_PORTABLE";
+
+/// Command-line flag which enables portable mode
+pub const PORTABLE_FLAG: &str = "--portable";
+
+/// Name of the directory next to the executable, in portable mode
+pub const PORTABLE_DIRNAME: &str = "ferrishot-data";
+
+/// Name of the config file, in the config directory
+pub const CONFIG_FILENAME: &str = "ferrishot.kdl";
+
+/// Name of the log file, in the cache directory
+pub const LOG_FILENAME: &str = "ferrishot.log";
+
+/// Could not determine a directory
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Can't find home dir
+    #[error(transparent)]
+    HomeDir(#[from] etcetera::HomeDirError),
+    /// Can't find the executable, which the portable directory is next to
+    #[error("Could not find the location of the executable: {0}")]
+    CurrentExe(std::io::Error),
+}
+
+/// Whether ferrishot runs in portable mode.
+///
+/// The flag is checked before the arguments are parsed, because the default
+/// locations of the config and log files are shown in `--help`.
+static IS_PORTABLE: LazyLock<bool> = LazyLock::new(|| {
+    env::var_os(PORTABLE_ENV_VAR).is_some_and(|value| !value.is_empty() && value != "0")
+        || env::args_os().any(|arg| arg == PORTABLE_FLAG)
+});
+
+/// Whether everything is kept next to the executable
+pub fn is_portable() -> bool {
+    *IS_PORTABLE
+}
+
+/// Directory next to the executable, used for everything in portable mode
+pub fn portable_dir() -> Result<PathBuf, Error> {
+    let exe = env::current_exe().map_err(Error::CurrentExe)?;
+
+    Ok(exe
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(PORTABLE_DIRNAME))
+}
+
+/// Directory with the config file
+pub fn config_dir() -> Result<PathBuf, Error> {
+    if is_portable() {
+        return portable_dir();
+    }
+
+    Ok(etcetera::choose_native_strategy()?.config_dir())
+}
+
+/// Directory with files which are nice to keep around, but fine to lose.
+/// For example, the position of the toolbar
+pub fn cache_dir() -> Result<PathBuf, Error> {
+    if is_portable() {
+        return Ok(portable_dir()?.join("cache"));
+    }
+
+    Ok(etcetera::choose_native_strategy()?.cache_dir())
+}
+
+/// Directory with files which the user would not want to lose, like the statistics
+pub fn data_dir() -> Result<PathBuf, Error> {
+    if is_portable() {
+        return Ok(portable_dir()?.join("data"));
+    }
+
+    Ok(etcetera::choose_native_strategy()?.data_dir())
+}
+
+/// Path to the config file
+pub fn config_file() -> Result<PathBuf, Error> {
+    let path = config_dir()?.join(CONFIG_FILENAME);
+
+    // before Application Support was used on macOS, the config was in `~/.config`
+    #[cfg(target_os = "macos")]
+    if !is_portable() && !path.exists() {
+        let legacy = etcetera::choose_base_strategy()?
+            .config_dir()
+            .join(CONFIG_FILENAME);
+
+        if legacy.exists() {
+            return Ok(legacy);
+        }
+    }
+
+    Ok(path)
+}
+
+/// Path to the log file
+pub fn log_file() -> Result<PathBuf, Error> {
+    Ok(cache_dir()?.join(LOG_FILENAME))
+}
+
+/// Path to the file called `name` in the cache directory, creating the directory if
+/// it does not exist yet
+pub fn cache_file(name: &str) -> Result<PathBuf, Error> {
+    let dir = cache_dir()?;
+    create_dir(&dir);
+
+    Ok(dir.join(name))
+}
+
+/// Path to the file called `name` in the data directory, creating the directory if
+/// it does not exist yet
+pub fn data_file(name: &str) -> Result<PathBuf, Error> {
+    let dir = data_dir()?;
+    create_dir(&dir);
+
+    Ok(dir.join(name))
+}
+
+/// Create the `dir`. In portable mode it is usually missing the first time,
+/// and writing a file into it would fail
+fn create_dir(dir: &Path) {
+    if let Err(err) = std::fs::create_dir_all(dir) {
+        log::warn!("Failed to create directory {}: {err}", dir.display());
+    }
+}
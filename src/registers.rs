@@ -0,0 +1,109 @@
+//! Regions stored in registers, like vim. `"1y` stores the selected region into
+//! register `1`, and `"1p` selects it again, even in a later session
+//!
+//! Each line of the registers file is a register followed by its region, like `1 800x600+42+24`
+use crate::{
+    geometry::RectangleExt as _,
+    lazy_rect::{LazyRectangle, ParseRectError},
+};
+use iced::Rectangle;
+use std::{collections::BTreeMap, fs, io, str::FromStr as _};
+
+/// Name of the file which stores the registers
+pub const REGISTERS_FILENAME: &str = "ferrishot-registers.txt";
+
+/// Register used when no register is chosen
+pub const UNNAMED_REGISTER: char = '"';
+
+/// Layer of keybindings which is active after choosing a register
+pub const REGISTER_LAYER: &str = "register";
+
+/// Could not read or write a register
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Can't find the directory of the file
+    #[error(transparent)]
+    Paths(#[from] crate::paths::Error),
+    /// Failed to parse as rectangle
+    #[error(transparent)]
+    ParseRect(#[from] ParseRectError),
+    /// Failed to read or write the registers file
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Regions of each register, as they are written in the registers file
+fn parse(contents: &str) -> BTreeMap<char, &str> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut chars = line.chars();
+            let register = chars.next()?;
+            let region = chars.as_str().strip_prefix(' ')?;
+
+            Some((register, region.trim()))
+        })
+        .collect()
+}
+
+/// Contents of the registers file
+fn serialize(registers: &BTreeMap<char, &str>) -> String {
+    registers
+        .iter()
+        .map(|(register, region)| format!("{register} {region}\n"))
+        .collect()
+}
+
+/// Contents of the registers file, which is empty if no region was ever stored
+fn read_file() -> Result<String, Error> {
+    match fs::read_to_string(crate::paths::cache_file(REGISTERS_FILENAME)?) {
+        Ok(contents) => Ok(contents),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Read the region stored in the `register`
+pub fn read(register: char, image_bounds: Rectangle) -> Result<Option<Rectangle>, Error> {
+    let contents = read_file()?;
+
+    parse(&contents)
+        .get(&register)
+        .map(|region| LazyRectangle::from_str(region).map(|rect| rect.init(image_bounds)))
+        .transpose()
+        .map_err(Error::from)
+}
+
+/// Store the `region` in the `register`
+pub(crate) fn write(register: char, region: Rectangle) -> Result<(), Error> {
+    let contents = read_file()?;
+    let region = region.as_str();
+
+    let mut registers = parse(&contents);
+    registers.insert(register, &region);
+
+    fs::write(
+        crate::paths::cache_file(REGISTERS_FILENAME)?,
+        serialize(&registers),
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_registers() {
+        let registers = parse("1 800x600+42+24\n\" 10x10+0+0\na\n");
+
+        assert_eq!(
+            registers,
+            BTreeMap::from([('1', "800x600+42+24"), ('"', "10x10+0+0")]),
+            "lines without a region are skipped"
+        );
+        assert_eq!(serialize(&registers), "\" 10x10+0+0\n1 800x600+42+24\n");
+    }
+}
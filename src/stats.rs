@@ -0,0 +1,177 @@
+//! Usage statistics, such as how many screenshots were copied and which keybindings are used the most
+//!
+//! Statistics are only collected if the `statistics` option is enabled. They are stored in a
+//! local file, and never sent anywhere. Users can view them with `ferrishot stats`, and share them
+//! if they want to.
+use std::{collections::BTreeMap, fmt::Write as _, fs, path::PathBuf};
+
+/// Name of the file in which the statistics are stored
+pub const STATS_FILENAME: &str = "ferrishot-stats.json";
+
+/// How many of the most used keybindings to show
+const TOP_KEYBINDINGS: usize = 10;
+
+/// Could not read or write the statistics
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Can't find the directory of the file
+    #[error(transparent)]
+    Paths(#[from] crate::paths::Error),
+    /// Failed to read or write the statistics file
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The statistics file is malformed
+    #[error("the statistics file is malformed: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Usage statistics of ferrishot
+#[derive(serde::Serialize, serde::Deserialize, Default, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Stats {
+    /// How many screenshots were taken
+    pub captures: u64,
+    /// How many screenshots were copied to the clipboard
+    pub copies: u64,
+    /// How many screenshots were saved to a file
+    pub saves: u64,
+    /// How many screenshots were uploaded
+    pub uploads: u64,
+    /// How many times each command was triggered by a keybinding
+    pub keybindings: BTreeMap<String, u64>,
+}
+
+impl Stats {
+    /// Record that an action was taken with a screenshot
+    pub const fn record_action(&mut self, action: crate::image::action::Command) {
+        use crate::image::action::Command;
+
+        match action {
+            Command::CopyToClipboard => self.copies += 1,
+            Command::SaveScreenshot => self.saves += 1,
+            Command::UploadScreenshot => self.uploads += 1,
+        }
+    }
+
+    /// Record that a command was triggered by a keybinding
+    pub fn record_keybinding(&mut self, command: &crate::Command) {
+        *self
+            .keybindings
+            .entry(kebab_case(command.name()))
+            .or_default() += 1;
+    }
+
+    /// Human-readable summary of the statistics
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "Screenshots taken: {}\nCopied: {}\nSaved: {}\nUploaded: {}\n",
+            self.captures, self.copies, self.saves, self.uploads
+        );
+
+        let mut keybindings = self.keybindings.iter().collect::<Vec<_>>();
+        // most used first. Ties are in alphabetical order, since the sort is stable
+        keybindings.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        if !keybindings.is_empty() {
+            report.push_str("\nMost used keybindings:\n");
+            for (command, count) in keybindings.into_iter().take(TOP_KEYBINDINGS) {
+                let _ = writeln!(report, "{count:>8}  {command}");
+            }
+        }
+
+        report
+    }
+}
+
+/// Convert a name like `CopyToClipboard` into `copy-to-clipboard`, as it is written in the config
+fn kebab_case(name: &str) -> String {
+    let mut kebab = String::with_capacity(name.len() + 4);
+    for (i, ch) in name.chars().enumerate() {
+        if ch.is_ascii_uppercase() && i != 0 {
+            kebab.push('-');
+        }
+        kebab.push(ch.to_ascii_lowercase());
+    }
+    kebab
+}
+
+/// Path to the statistics file
+pub fn path() -> Result<PathBuf, Error> {
+    Ok(crate::paths::data_file(STATS_FILENAME)?)
+}
+
+/// Read the statistics. If there are none yet, they are all zero
+pub fn read() -> Result<Stats, Error> {
+    match fs::read_to_string(path()?) {
+        Ok(contents) => Ok(serde_json::from_str(&contents)?),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Stats::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Write the statistics
+fn write(stats: &Stats) -> Result<(), Error> {
+    let path = path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(stats)?)?;
+    Ok(())
+}
+
+/// Update the statistics. Failing to do so is not worth bothering the user about, so it is only logged
+pub fn record(f: impl FnOnce(&mut Stats)) {
+    let result = read().and_then(|mut stats| {
+        f(&mut stats);
+        write(&stats)
+    });
+
+    if let Err(err) = result {
+        log::warn!("Failed to record statistics: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn kebab_case_names() {
+        assert_eq!(kebab_case("CopyToClipboard"), "copy-to-clipboard");
+        assert_eq!(kebab_case("Move"), "move");
+        assert_eq!(kebab_case(""), "");
+    }
+
+    #[test]
+    fn report_sorts_keybindings() {
+        let stats = Stats {
+            captures: 3,
+            copies: 2,
+            saves: 1,
+            uploads: 0,
+            keybindings: [("move", 5), ("exit", 1), ("extend", 5)]
+                .into_iter()
+                .map(|(command, count)| (command.to_owned(), count))
+                .collect(),
+        };
+
+        assert_eq!(
+            stats.report(),
+            "Screenshots taken: 3\nCopied: 2\nSaved: 1\nUploaded: 0\n\
+            \nMost used keybindings:\n       5  extend\n       5  move\n       1  exit\n"
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_zero() {
+        let stats: Stats = serde_json::from_str(r#"{"copies": 4}"#).unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                copies: 4,
+                ..Stats::default()
+            }
+        );
+    }
+}
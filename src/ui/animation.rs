@@ -0,0 +1,273 @@
+//! Smoothly move the selection to where it ends up, instead of teleporting it there
+//!
+//! With `animate-selection`, `goto` glides the selection to its place, and releasing
+//! the selection while dragging it quickly lets it slide a bit further before settling
+//! on whole pixels. Popups also ease into view when they open.
+//!
+//! Animations are driven by `App::time_elapsed`, which is updated every frame,
+//! and are turned off entirely by `reduced-motion`.
+
+use std::{collections::VecDeque, time::Duration};
+
+use iced::{Point, Rectangle, Vector};
+
+/// Only the movement during this long before releasing the selection counts towards its speed
+const VELOCITY_WINDOW: Duration = Duration::from_millis(80);
+
+/// Releasing the selection slower than this many pixels per second does not make it slide
+const INERTIA_MIN_SPEED: f32 = 600.0;
+
+/// How far the selection slides after being released, as seconds of its speed on release
+const INERTIA_DISTANCE: f32 = 0.12;
+
+/// How quickly the animation approaches its end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Same speed from start to end
+    Linear,
+    /// Starts fast, then slows down towards the end
+    #[default]
+    EaseOutCubic,
+}
+
+impl Easing {
+    /// How far along the animation is, from `0.0` to `1.0`, when `t` of its duration has passed
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Self::Linear => t,
+            Self::EaseOutCubic => 1.0 - (1.0 - t).powi(3),
+        }
+    }
+}
+
+/// How far along an animation that `started` and takes `duration` is at `now`,
+/// from `0.0` to `1.0`, after `easing`
+pub fn progress(started: Duration, duration: Duration, now: Duration, easing: Easing) -> f32 {
+    if duration.is_zero() {
+        return 1.0;
+    }
+
+    easing.apply(now.saturating_sub(started).as_secs_f32() / duration.as_secs_f32())
+}
+
+/// Value which goes from one rectangle to another over time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Animation {
+    /// Where the animation starts
+    pub from: Rectangle,
+    /// Where the animation ends
+    pub to: Rectangle,
+    /// `App::time_elapsed` when the animation started
+    pub started: Duration,
+    /// How long the animation takes
+    pub duration: Duration,
+    /// How quickly the animation approaches its end
+    pub easing: Easing,
+}
+
+impl Animation {
+    /// How far along the animation is at `now`, from `0.0` to `1.0`, after easing
+    pub fn progress(&self, now: Duration) -> f32 {
+        progress(self.started, self.duration, now, self.easing)
+    }
+
+    /// Value of the animation at `now`
+    pub fn at(&self, now: Duration) -> Rectangle {
+        let progress = self.progress(now);
+        let lerp = |from: f32, to: f32| (to - from).mul_add(progress, from);
+
+        Rectangle {
+            x: lerp(self.from.x, self.to.x),
+            y: lerp(self.from.y, self.to.y),
+            width: lerp(self.from.width, self.to.width),
+            height: lerp(self.from.height, self.to.height),
+        }
+    }
+
+    /// Whether the animation has reached its end at `now`
+    pub fn is_finished(&self, now: Duration) -> bool {
+        now.saturating_sub(self.started) >= self.duration
+    }
+}
+
+/// Animation of the selection, and the recent positions of the selection being dragged
+#[derive(Debug, Default)]
+pub struct SelectionAnimation {
+    /// Animation in progress, and the rectangle it last set on the selection
+    animation: Option<(Animation, Rectangle)>,
+    /// Recent positions of the selection being dragged, with the time they were reached
+    drag: VecDeque<(Duration, Point)>,
+}
+
+impl SelectionAnimation {
+    /// Animate the selection from `from` to `to`
+    pub fn start(&mut self, from: Rectangle, to: Rectangle, now: Duration, duration: Duration) {
+        self.animation = Some((
+            Animation {
+                from,
+                to,
+                started: now,
+                duration,
+                easing: Easing::EaseOutCubic,
+            },
+            from,
+        ));
+    }
+
+    /// Remember that the selection being dragged reached `pos` at `now`
+    pub fn record_drag(&mut self, pos: Point, now: Duration) {
+        while self
+            .drag
+            .front()
+            .is_some_and(|(time, _)| now.saturating_sub(*time) > VELOCITY_WINDOW)
+        {
+            self.drag.pop_front();
+        }
+
+        self.drag.push_back((now, pos));
+    }
+
+    /// Speed of the selection as it is released at `now`, in pixels per second. Forgets the drag
+    pub fn release_velocity(&mut self, now: Duration) -> Vector {
+        let drag = std::mem::take(&mut self.drag);
+
+        let (Some((first_time, first_pos)), Some((last_time, last_pos))) =
+            (drag.front(), drag.back())
+        else {
+            return Vector::ZERO;
+        };
+
+        // the cursor stopped before releasing
+        if now.saturating_sub(*last_time) > VELOCITY_WINDOW {
+            return Vector::ZERO;
+        }
+
+        let secs = last_time.saturating_sub(*first_time).as_secs_f32();
+        if secs <= 0.0 {
+            return Vector::ZERO;
+        }
+
+        (*last_pos - *first_pos) * (1.0 / secs)
+    }
+
+    /// Where the `rect` released with `velocity` slides to, staying inside of the `bounds`.
+    ///
+    /// `None` if it was released too slowly to slide at all
+    pub fn inertia_target(
+        rect: Rectangle,
+        velocity: Vector,
+        bounds: Rectangle,
+    ) -> Option<Rectangle> {
+        if velocity.x.hypot(velocity.y) < INERTIA_MIN_SPEED {
+            return None;
+        }
+
+        let pos = rect.position() + velocity * INERTIA_DISTANCE;
+        let max = Point::new(
+            (bounds.width - rect.width).max(0.0),
+            (bounds.height - rect.height).max(0.0),
+        );
+
+        // settle on whole pixels, so the region is not blurry
+        Some(Rectangle::new(
+            Point::new(
+                pos.x.clamp(0.0, max.x).round(),
+                pos.y.clamp(0.0, max.y).round(),
+            ),
+            rect.size(),
+        ))
+    }
+
+    /// Advance the animation to `now`, returning the new rectangle of the `selection`.
+    ///
+    /// If something else changed the `selection` since the animation last did,
+    /// the animation stops so it does not fight with it
+    pub fn tick(&mut self, selection: Rectangle, now: Duration) -> Option<Rectangle> {
+        let (animation, last) = self.animation.as_mut()?;
+
+        if selection != *last {
+            self.animation = None;
+            return None;
+        }
+
+        let rect = animation.at(now);
+        *last = rect;
+
+        if animation.is_finished(now) {
+            self.animation = None;
+        }
+
+        Some(rect)
+    }
+
+    /// Whether the selection is being animated
+    pub const fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::RectangleExt as _;
+    use iced::Size;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn easing() {
+        assert_eq!(Easing::EaseOutCubic.apply(0.0), 0.0);
+        assert_eq!(Easing::EaseOutCubic.apply(0.5), 0.875);
+        assert_eq!(Easing::EaseOutCubic.apply(2.0), 1.0, "clamped to the end");
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+    }
+
+    #[test]
+    fn animates_until_interrupted() {
+        let from = Rectangle::new(Point::ORIGIN, Size::new(10.0, 10.0));
+        let to = Rectangle::new(Point::new(100.0, 0.0), Size::new(10.0, 10.0));
+        let mut anim = SelectionAnimation::default();
+        anim.start(from, to, Duration::ZERO, Duration::from_secs(1));
+
+        let halfway = anim.tick(from, Duration::from_millis(500)).unwrap();
+        assert_eq!(halfway.x, 87.5);
+
+        assert_eq!(
+            anim.tick(
+                Rectangle::new(Point::ORIGIN, Size::new(5.0, 5.0)),
+                Duration::from_millis(600)
+            ),
+            None,
+            "the selection was changed by something else"
+        );
+        assert!(!anim.is_animating(), "interrupted animation is dropped");
+    }
+
+    #[test]
+    fn inertia() {
+        let mut anim = SelectionAnimation::default();
+        let start = Duration::from_secs(1);
+        anim.record_drag(Point::ORIGIN, start);
+        anim.record_drag(
+            Point::new(100.0, 0.0),
+            start + Duration::from_micros(62_500),
+        );
+
+        let velocity = anim.release_velocity(start + Duration::from_millis(70));
+        assert_eq!(velocity, Vector::new(1600.0, 0.0));
+
+        let rect = Rectangle::new(Point::new(100.0, 0.0), Size::new(10.0, 10.0));
+        let bounds = Rectangle::with_size(Size::new(150.0, 100.0));
+        assert_eq!(
+            SelectionAnimation::inertia_target(rect, velocity, bounds),
+            Some(Rectangle::new(Point::new(140.0, 0.0), rect.size())),
+            "slides until the edge of the image"
+        );
+        assert_eq!(
+            SelectionAnimation::inertia_target(rect, Vector::new(10.0, 0.0), bounds),
+            None,
+            "too slow to slide"
+        );
+    }
+}
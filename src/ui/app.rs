@@ -2,6 +2,7 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::mpsc;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -12,6 +13,7 @@ use crate::image::action::ImageData;
 use crate::message::Message;
 use crate::ui;
 use crate::ui::popup;
+use iced::Color;
 use iced::Length::Fill;
 use iced::Renderer;
 use iced::Subscription;
@@ -22,20 +24,24 @@ use iced::widget::Canvas;
 use iced::widget::Stack;
 use iced::window;
 use iced::{
-    Rectangle,
+    Rectangle, Size, Vector,
     widget::{Action, canvas},
 };
 use image::DynamicImage;
 use indoc::formatdoc;
 use tap::Pipe as _;
 
+use crate::config::Crosshair;
 use crate::geometry::RectangleExt as _;
+use crate::ui::selection::FRAME_WIDTH;
 use crate::ui::selection::Selection;
+use crate::ui::selection::ShadeStyle;
 
 use super::Errors;
 use super::popup::Popup;
 use super::selection::OptionalSelectionExt as _;
 use super::selection::SelectionKeysState;
+use super::tutorial::Tutorial;
 
 crate::declare_commands! {
     enum Command {
@@ -43,18 +49,123 @@ crate::declare_commands! {
         NoOp,
         /// Exit the application
         Exit,
+        /// Make the shade around the selection darker
+        DarkenShade {
+            /// How much opacity to add to the shade
+            amount: f32 = 0.1,
+        },
+        /// Make the shade around the selection lighter
+        LightenShade {
+            /// How much opacity to remove from the shade
+            amount: f32 = 0.1,
+        },
+        /// Hide the shade around the selection, or show it if it is hidden
+        ToggleShade,
+        /// Show the screenshot with inverted colors, or as it is if it is inverted.
+        /// Only changes how it looks while selecting
+        ToggleInvert,
+        /// Show the screenshot in shades of gray, or in color if it is gray.
+        /// Only changes how it looks while selecting
+        ToggleGrayscale,
+        /// Activate a layer of keybindings, which take priority until the layer is exited
+        EnterLayer {
+            name: String,
+        },
+        /// Go back to the keybindings without a layer
+        ExitLayer,
+        /// Take the screenshot again, keeping the selection
+        RefreshCapture,
     }
 }
 
+/// How long the window stays hidden before taking the screenshot again,
+/// so the compositor has time to remove it from the screen
+const HIDE_BEFORE_REFRESH: Duration = Duration::from_millis(200);
+
 impl crate::command::Handler for Command {
-    fn handle(self, _app: &mut App, _count: u32) -> Task<Message> {
+    fn handle(self, app: &mut App, count: u32) -> Task<Message> {
         match self {
-            Self::NoOp => Task::none(),
-            Self::Exit => App::exit(),
+            Self::NoOp => return Task::none(),
+            Self::Exit => return App::exit(),
+            Self::DarkenShade { amount } => {
+                app.shade_opacity =
+                    Some((app.non_selected_region().a + amount * count as f32).clamp(0.0, 1.0));
+                app.is_shade_hidden = false;
+            }
+            Self::LightenShade { amount } => {
+                app.shade_opacity =
+                    Some((app.non_selected_region().a - amount * count as f32).clamp(0.0, 1.0));
+                app.is_shade_hidden = false;
+            }
+            Self::ToggleShade => {
+                app.is_shade_hidden = !app.is_shade_hidden;
+            }
+            Self::ToggleInvert => {
+                app.background_filter.invert = !app.background_filter.invert;
+                app.filtered_background = app.background_filter.apply(&app.image);
+            }
+            Self::ToggleGrayscale => {
+                app.background_filter.grayscale = !app.background_filter.grayscale;
+                app.filtered_background = app.background_filter.apply(&app.image);
+            }
+            Self::EnterLayer { name } => {
+                app.layer = Some(name);
+            }
+            Self::ExitLayer => {
+                app.layer = None;
+            }
+            Self::RefreshCapture => {
+                if app.cli.file.is_some() {
+                    app.errors
+                        .push("Can't refresh an image which was opened from a file");
+                    return Task::none();
+                }
+
+                let preserve_transparency = app.config.preserve_transparency;
+                let capture_options = app.config.capture_options();
+                let mode = if app.cli.windowed.is_some() {
+                    window::Mode::Windowed
+                } else {
+                    window::Mode::Fullscreen
+                };
+
+                // The window is hidden while taking the screenshot, so it won't capture itself
+                return window::get_latest().then(move |id| {
+                    let Some(id) = id else {
+                        return Task::none();
+                    };
+
+                    window::set_mode(id, window::Mode::Hidden)
+                        .chain(Task::future(async move {
+                            tokio::time::sleep(HIDE_BEFORE_REFRESH).await;
+
+                            match crate::get_image(None, preserve_transparency, capture_options) {
+                                Ok(image) => Message::CaptureRefreshed(Arc::new(image)),
+                                Err(err) => Message::Error(format!(
+                                    "Failed to refresh the screenshot: {err}"
+                                )),
+                            }
+                        }))
+                        .chain(window::set_mode(id, mode))
+                });
+            }
         }
+
+        Task::none()
     }
 }
 
+/// Something the app produced, to be handled by whoever launched it
+///
+/// Nothing can be returned from the app once its window closes, so these are sent
+/// through a channel instead, as soon as they happen. A single run of the app can
+/// send any number of them.
+#[derive(Debug)]
+pub enum Outcome {
+    /// Screenshot to save to the `--save-path`, or to write to stdout with `--raw`
+    Saved(DynamicImage),
+}
+
 /// Holds the state for ferrishot
 #[derive(Debug)]
 pub struct App {
@@ -64,6 +175,8 @@ pub struct App {
     pub time_started: Instant,
     /// How long has passed since starting ferrishot
     pub time_elapsed: Duration,
+    /// `time_elapsed` when a key was last pressed, or the mouse last moved or clicked
+    pub last_activity: Duration,
     /// Config of the app
     pub config: Arc<Config>,
     /// A list of messages which obtained while the debug overlay is active
@@ -76,6 +189,8 @@ pub struct App {
     /// screenshot as background, with a canvas rendered on top - giving the
     /// illusion that we are drawing shapes on top of the screen.
     pub image: Arc<RgbaHandle>,
+    /// Where the screenshot is shown inside of the window
+    pub viewport: super::viewport::Viewport,
     /// Area of the screen that is selected for capture
     pub selection: Option<Selection>,
     /// Errors to display to the user
@@ -84,9 +199,57 @@ pub struct App {
     pub show_debug_overlay: bool,
     /// Command line arguments passed
     pub cli: Arc<Cli>,
+    /// Opacity of the shade around the selection, changed at runtime.
+    /// Takes priority over the opacity of `theme.non_selected_region`
+    pub shade_opacity: Option<f32>,
+    /// Whether the shade around the selection is turned off
+    pub is_shade_hidden: bool,
+    /// Filters applied to the screenshot while it is shown
+    pub background_filter: super::background_image::Filter,
+    /// The screenshot with `background_filter` applied, if there are any filters
+    pub filtered_background: Option<iced::widget::image::Handle>,
+    /// Layer of keybindings which is currently active
+    pub layer: Option<String>,
+    /// Moving and resizing with the mouse is slower, and only by whole pixels
+    pub is_precision_mode: bool,
+    /// Hovering highlights the UI element under the cursor, and clicking selects it
+    pub is_smart_select: bool,
+    /// Resizing snaps the sides of the selection to lines of text
+    pub is_snap_to_text: bool,
+    /// Records the session into the file passed with `--record-session`
+    pub session: Option<crate::ipc::session::Recorder>,
+    /// Guided tour for new users, if it is shown
+    pub tutorial: Option<Tutorial>,
+    /// Floating toolbar with the icons, if the `toolbar` option is enabled
+    pub toolbar: super::toolbar::Toolbar,
+    /// Macros recorded with the keyboard
+    pub key_macros: super::key_macros::KeyMacros,
+    /// Register chosen with `"` for the command being handled
+    pub register: Option<char>,
+    /// Selection gliding to its place, with `animate-selection`
+    pub selection_animation: super::animation::SelectionAnimation,
 
     /// Currently opened popup
     pub popup: Option<Popup>,
+    /// `time_elapsed` when the current popup was opened, for its opening animation
+    pub popup_opened: Duration,
+    /// Where to send what the app produced
+    pub outcomes: mpsc::Sender<Outcome>,
+    /// Brightness, contrast and saturation of the output image
+    pub adjustments: crate::image::adjust::Adjustments,
+    /// The selection with `adjustments` applied, drawn on top of it
+    pub adjustments_preview: Option<crate::image::adjust::Preview>,
+    /// Regions of the output image which are blurred or pixelated
+    pub redactions: crate::image::redact::Redactions,
+    /// Strokes drawn on the output image with a pen or highlighter
+    pub annotations: crate::image::annotate::Annotations,
+    /// Changes to the selection, redactions and strokes which can be undone
+    pub history: super::history::History,
+    /// Fingerprint of the screenshot, to check that it still matches the screen.
+    /// Images opened from a file are not checked
+    pub capture_fingerprint: Option<u64>,
+    /// Keeps the screen from locking while the app is open, with `inhibit-idle`
+    pub inhibitor: Option<crate::inhibit::Inhibitor>,
 }
 
 #[bon::bon]
@@ -95,17 +258,23 @@ impl App {
     ///
     /// Returns a closure which takes path of the saved image. It has to be this way because we don't
     /// actually know where the image will be saved until the end of `main`.
+    ///
+    /// The image to save is sent to `outcomes`, same as when running the app.
     pub async fn headless(
         action: crate::image::action::Command,
         region: Rectangle,
         image: Arc<RgbaHandle>,
         is_json: bool,
+        config: &Config,
+        outcomes: &mpsc::Sender<Outcome>,
     ) -> Result<Box<dyn Fn(Option<PathBuf>) -> String>, crate::image::action::Error> {
         use crate::image::action::Output as O;
 
+        crate::image::action::check_size(region, config.min_selection_size())?;
+
         let (output, ImageData { height, width }) = image
             .pipe(|img| Self::process_image(region, &img))
-            .pipe(|img| action.execute(img, region))
+            .pipe(|img| action.execute(img, region, config.autosave(), config.upload()))
             .await?;
 
         let green = anstyle::AnsiColor::Green
@@ -116,23 +285,28 @@ impl App {
         let tick = format!("{green}✓{reset}");
 
         let closure: Box<dyn Fn(Option<PathBuf>) -> String> = match output {
-            O::Saved => Box::new(move |saved_path| {
-                let save_path = saved_path
-                    .as_ref()
-                    .map(|path| format!("{}", path.display()))
-                    .unwrap_or_default();
-
-                let file_size_bytes = saved_path
-                    .unwrap_or_default()
-                    .metadata()
-                    .map(|meta| meta.len())
-                    .unwrap_or(0);
-
-                let file_size = human_bytes::human_bytes(file_size_bytes as f64);
-
-                if is_json {
-                    formatdoc! {
-                        r#"
+            O::Saved(image) => {
+                if outcomes.send(Outcome::Saved(image)).is_err() {
+                    log::error!("Nothing is waiting for the screenshot to save it");
+                }
+
+                Box::new(move |saved_path| {
+                    let save_path = saved_path
+                        .as_ref()
+                        .map(|path| format!("{}", path.display()))
+                        .unwrap_or_default();
+
+                    let file_size_bytes = saved_path
+                        .unwrap_or_default()
+                        .metadata()
+                        .map(|meta| meta.len())
+                        .unwrap_or(0);
+
+                    let file_size = human_bytes::human_bytes(file_size_bytes as f64);
+
+                    if is_json {
+                        formatdoc! {
+                            r#"
                             {{
                                 "type": "save",
                                 "width": {width},
@@ -142,53 +316,61 @@ impl App {
                                 "savePath": "{save_path}"
                             }}
                         "#
-                    }
-                } else {
-                    formatdoc! {
-                        "
+                        }
+                    } else {
+                        formatdoc! {
+                            "
                             {tick} Image saved to {save_path}
 
                             width: {width} px
                             height: {height} px
                             file size: {file_size}
                         ",
+                        }
                     }
-                }
-            }),
-            O::Copied => Box::new(move |_| {
-                if is_json {
-                    formatdoc! {
-                        r#"
+                })
+            }
+            O::Copied(image) => {
+                config.notifications().copied(&image).await;
+
+                Box::new(move |_| {
+                    if is_json {
+                        formatdoc! {
+                            r#"
                             {{
                                 "type": "copy",
                                 "width": {width},
                                 "height": {height},
                             }}
                         "#
-                    }
-                } else {
-                    formatdoc! {
-                        "
+                        }
+                    } else {
+                        formatdoc! {
+                            "
                             {tick} Image copied to clipboard
 
                             width: {width} px
                             height: {height} px
                         "
+                        }
                     }
-                }
-            }),
+                })
+            }
             O::Uploaded {
                 data,
                 file_size: file_size_bytes,
-                ..
-            } => Box::new(move |_| {
-                let link = &data.link;
-                let expires = data.expires_in;
-                let file_size = human_bytes::human_bytes(file_size_bytes as f64);
-
-                if is_json {
-                    formatdoc! {
-                        r#"
+                path,
+            } => {
+                config.notifications().uploaded(&path, &data.link).await;
+
+                Box::new(move |_| {
+                    let link = &data.link;
+                    let expires = data.expires_in;
+                    let file_size = human_bytes::human_bytes(file_size_bytes as f64);
+
+                    if is_json {
+                        formatdoc! {
+                            r#"
                             {{
                                 "type": "upload",
                                 "width": {width},
@@ -199,10 +381,10 @@ impl App {
                                 "expiresIn": "{expires}"
                             }}
                         "#
-                    }
-                } else {
-                    formatdoc! {
-                        "
+                        }
+                    } else {
+                        formatdoc! {
+                            "
                             {tick} Image uploaded to {link}
 
                             width: {width} px
@@ -210,9 +392,10 @@ impl App {
                             file size: {file_size}
                             expires in: {expires}
                         "
+                        }
                     }
-                }
-            }),
+                })
+            }
         };
 
         Ok(closure)
@@ -225,15 +408,57 @@ impl App {
         config: Arc<Config>,
         initial_region: Option<Rectangle>,
         image: Arc<RgbaHandle>,
+        outcomes: mpsc::Sender<Outcome>,
+        /// The screenshot could not be taken because it is not allowed, so `image` is blank
+        #[builder(default)]
+        is_missing_permission: bool,
     ) -> Self {
-        Self {
+        let session = cli
+            .record_session
+            .clone()
+            .map(crate::ipc::session::Recorder::new);
+        let tutorial = (!cli.minimal && !config.minimal && !super::tutorial::is_done())
+            .then(Tutorial::default);
+        let capture_fingerprint = (cli.file.is_none() && !is_missing_permission)
+            .then(|| crate::image::staleness::fingerprint(&image));
+        let mut viewport =
+            super::viewport::Viewport::new(Size::new(image.width() as f32, image.height() as f32));
+        if let Some(size) = cli.windowed {
+            viewport.window = Size::new(size.width as f32, size.height as f32);
+        }
+        let inhibitor = config.inhibit_idle.then(crate::inhibit::Inhibitor::new);
+        let toolbar = if config.toolbar {
+            super::toolbar::Toolbar::restore()
+        } else {
+            super::toolbar::Toolbar::default()
+        };
+
+        // The region is known and it will be saved, so the only thing left is to pick where
+        let popup = initial_region
+            .filter(|rect| {
+                cli.accept_on_select == Some(crate::image::action::Command::SaveScreenshot)
+                    && cli.picks_save_path()
+                    && crate::image::action::check_size(rect.norm(), config.min_selection_size())
+                        .is_ok()
+            })
+            .map(|rect| {
+                Popup::FilePicker(popup::file_picker::State::new(
+                    Self::process_image(rect.norm(), &image),
+                    config.image_format,
+                ))
+            })
+            .or_else(|| is_missing_permission.then_some(Popup::ScreenRecordingPermission));
+
+        let mut app = Self {
             is_uploading_image: false,
             time_started: Instant::now(),
             time_elapsed: Duration::ZERO,
+            last_activity: Duration::ZERO,
             selection: initial_region.map(|rect| Selection {
                 is_first: true,
                 accept_on_select: cli.accept_on_select,
                 theme: config.theme,
+                grab_area: config.grab_area(),
                 rect,
                 status: ui::selection::SelectionStatus::default(),
             }),
@@ -242,12 +467,41 @@ impl App {
             // FIXME: Currently the app cannot handle when the resolution is very small
             // if a path was passed and the path contains a valid image
             image,
+            viewport,
             errors: Errors::default(),
             show_debug_overlay: cli.debug,
             config,
             cli,
-            popup: None,
-        }
+            shade_opacity: None,
+            is_shade_hidden: false,
+            background_filter: super::background_image::Filter::default(),
+            filtered_background: None,
+            layer: None,
+            is_precision_mode: false,
+            is_smart_select: false,
+            is_snap_to_text: false,
+            session,
+            tutorial,
+            toolbar,
+            key_macros: super::key_macros::KeyMacros::default(),
+            register: None,
+            selection_animation: super::animation::SelectionAnimation::default(),
+            popup,
+            popup_opened: Duration::ZERO,
+            outcomes,
+            adjustments: crate::image::adjust::Adjustments::default(),
+            adjustments_preview: None,
+            redactions: crate::image::redact::Redactions::default(),
+            annotations: crate::image::annotate::Annotations::default(),
+            history: super::history::History::default(),
+            capture_fingerprint,
+            inhibitor,
+        };
+
+        // the first change can be undone back to the initial state
+        super::history::History::record(&mut app);
+
+        app
     }
 
     /// Close the app
@@ -264,51 +518,200 @@ impl App {
 
     /// This method is used to keep track of time / how much time has passed since start
     /// of the program, using this for animations.
+    ///
+    /// It also listens for input from gamepads (with the `gamepad` feature),
+    /// for commands on the `--control-socket`, runs the `--script`, and checks
+    /// that the screenshot still matches the screen.
     pub fn subscription(&self) -> Subscription<Message> {
-        window::frames().map(Message::Tick)
+        Subscription::batch([
+            window::frames().map(Message::Tick),
+            window::resize_events().map(|(_, size)| Message::WindowResized(size)),
+            if self.config.auto_exit_after().is_some() {
+                iced::event::listen_with(|event, _, _| {
+                    matches!(
+                        event,
+                        iced::Event::Keyboard(_) | iced::Event::Mouse(_) | iced::Event::Touch(_)
+                    )
+                    .then_some(Message::Activity)
+                })
+            } else {
+                Subscription::none()
+            },
+            #[cfg(feature = "gamepad")]
+            crate::gamepad::subscription(),
+            #[cfg(unix)]
+            self.cli
+                .control_socket
+                .clone()
+                .map_or_else(Subscription::none, crate::ipc::socket::subscription),
+            self.cli
+                .script
+                .clone()
+                .map_or_else(Subscription::none, crate::ipc::script::subscription),
+            self.capture_fingerprint
+                .map_or_else(Subscription::none, crate::image::staleness::subscription),
+        ])
+    }
+
+    /// How long until ferrishot exits because nothing was pressed or moved, while it is
+    /// close enough to show a countdown
+    pub fn auto_exit_countdown(&self) -> Option<Duration> {
+        /// Show the countdown for this long before exiting
+        const COUNTDOWN: Duration = Duration::from_secs(10);
+
+        let after = self.config.auto_exit_after()?;
+        let remaining = (self.last_activity + after).saturating_sub(self.time_elapsed);
+
+        (remaining <= COUNTDOWN).then_some(remaining)
+    }
+
+    /// Color of the shade around the selection, taking into account
+    /// changes to it made at runtime
+    pub fn non_selected_region(&self) -> Color {
+        let color = self.config.theme.non_selected_region;
+
+        if self.is_shade_hidden {
+            Color::TRANSPARENT
+        } else if let Some(opacity) = self.shade_opacity {
+            Color {
+                a: opacity,
+                ..color
+            }
+        } else {
+            color
+        }
+    }
+
+    /// Type of the mouse cursor, before applying the `crosshair` style
+    fn cursor_interaction(&self, cursor: iced::advanced::mouse::Cursor) -> Interaction {
+        if let Some(
+            Popup::ImageUploaded(_)
+            | Popup::FilePicker(_)
+            | Popup::ScreenRecordingPermission
+            | Popup::Settings,
+        ) = self.popup
+        {
+            Interaction::default()
+        } else {
+            self.selection
+                .map(Selection::norm)
+                .map_or(Interaction::Crosshair, |sel| {
+                    // dragging inside of the selection redacts or draws instead of moving it
+                    if (self.redactions.is_active() || self.annotations.is_active())
+                        && let Some((pos, _)) = sel.cursor_in_selection(cursor)
+                    {
+                        // while drawing, strokes can be grabbed to move or resize them
+                        let edit = self
+                            .annotations
+                            .tool
+                            .and_then(|_| self.annotations.edit_at(pos));
+                        match edit.map(|edit| edit.anchor) {
+                            Some(Some(anchor)) if (pos.x - anchor.x) * (pos.y - anchor.y) > 0.0 => {
+                                Interaction::ResizingDiagonallyDown
+                            }
+                            Some(Some(_)) => Interaction::ResizingDiagonallyUp,
+                            Some(None) => Interaction::Grab,
+                            None => Interaction::Crosshair,
+                        }
+                    } else {
+                        sel.mouse_interaction(cursor)
+                    }
+                })
+        }
+    }
+
+    /// How far along the opening animation of the popup is, from `0.0` to `1.0`
+    pub fn popup_progress(&self) -> f32 {
+        super::animation::progress(
+            self.popup_opened,
+            self.config.animation_duration(),
+            self.time_elapsed,
+            super::animation::Easing::EaseOutCubic,
+        )
+    }
+
+    /// Whether to hide everything except for the crosshair and the selection frame,
+    /// either from the `--minimal` flag or from the config
+    pub fn is_minimal(&self) -> bool {
+        self.cli.minimal || self.config.minimal
+    }
+
+    /// Fit the viewport and the selection to the screenshot after it changed, since
+    /// the resolution of the monitor could be different now
+    fn fit_to_image(&mut self) {
+        self.viewport.image = Size::new(self.image.width() as f32, self.image.height() as f32);
+
+        let bounds = self.viewport.image_bounds();
+        self.selection = self.selection.map(Selection::norm).and_then(|mut sel| {
+            sel.rect = sel.rect.clipped_in_bounds_of(bounds);
+            // the selection can be entirely outside of a smaller screenshot
+            (sel.rect.width > 0.0 && sel.rect.height > 0.0).then_some(sel)
+        });
     }
 
     /// Renders the app
-    pub fn view(&self) -> iced::Element<Message> {
+    pub fn view(&self) -> iced::Element<'_, Message> {
         Stack::new()
             // taken screenshot in the background
             .push(super::BackgroundImage {
-                image_handle: RgbaHandle::clone(&self.image).into(),
+                image_handle: self
+                    .filtered_background
+                    .clone()
+                    .unwrap_or_else(|| RgbaHandle::clone(&self.image).into()),
             })
             // Shade in the background + global event handler + selection renderer
             .push(Canvas::new(self).width(Fill).height(Fill))
+            // guided tour for new users
+            .push_maybe(
+                self.tutorial
+                    .filter(|_| self.popup.is_none() && !self.is_minimal())
+                    .map(|tutorial| tutorial.view(self)),
+            )
             // information popup with basic tips
             .push_maybe(
-                (self.popup.is_none() && self.selection.is_none())
-                    .then(|| super::welcome_message(self)),
+                (self.tutorial.is_none()
+                    && self.popup.is_none()
+                    && self.selection.is_none()
+                    && !self.is_minimal())
+                .then(|| super::welcome_message(self)),
             )
             // errors
             .push(self.errors.view(self))
             // icons around the selection
             .push_maybe(
                 self.selection
-                    .filter(|sel| sel.is_idle() && self.config.selection_icons)
+                    .filter(|sel| {
+                        sel.is_idle()
+                            && self.config.selection_icons
+                            && !self.config.toolbar
+                            && !self.is_minimal()
+                    })
                     .map(|sel| {
                         super::SelectionIcons {
                             app: self,
-                            image_width: self.image.width() as f32,
-                            image_height: self.image.height() as f32,
-                            selection_rect: sel.rect.norm(),
+                            image_width: self.viewport.window.width,
+                            image_height: self.viewport.window.height,
+                            selection_rect: self.viewport.rect_to_window(sel.rect.norm()),
                         }
                         .view()
                     }),
             )
+            // toolbar with the icons, instead of around the selection
+            .push_maybe(
+                (self.config.toolbar && self.selection.is_some() && !self.is_minimal())
+                    .then(|| self.toolbar.view(self, self.viewport.window)),
+            )
             // size indicator
             .push_maybe(
                 self.selection
-                    .filter(|_| self.config.size_indicator)
+                    .filter(|_| self.config.size_indicator && !self.is_minimal())
                     .get()
                     .map(|(sel, sel_is_some)| {
                         super::size_indicator(self, sel.rect.norm(), sel_is_some)
                     }),
             )
             .push_maybe(self.popup.as_ref().map(|popup| {
-                match popup {
+                let element = match popup {
                     Popup::Letters(state) => popup::Letters {
                         app: self,
                         pick_corner: state.picking_corner,
@@ -319,14 +722,31 @@ impl App {
                         qr_code_data: &state.url.0,
                         data: &state.url.1,
                         url_copied: state.has_copied_link,
+                        qr_code_copied: state.has_copied_qr_code,
+                        preview_zoomed: state.is_preview_zoomed,
                     }
                     .view(),
                     Popup::KeyCheatsheet => popup::KeybindingsCheatsheet {
-                        theme: &self.config.theme,
+                        config: &self.config,
                     }
                     .view(),
+                    Popup::PixelInspector => popup::PixelInspector { app: self }.view(),
+                    Popup::FilePicker(state) => popup::FilePicker { app: self, state }.view(),
+                    Popup::ScreenRecordingPermission => {
+                        popup::ScreenRecordingPermission { app: self }.view()
+                    }
+                    Popup::Settings => popup::Settings { app: self }.view(),
+                };
+
+                // these cover the whole screen, so they fade in by themselves instead
+                if matches!(popup, Popup::Letters(_) | Popup::PixelInspector) {
+                    element
+                } else {
+                    popup::opening(element, self.popup_progress())
                 }
             }))
+            // active layer of keybindings, and other modes
+            .push_maybe(super::status_bar(self))
             // debug overlay
             .push_maybe(self.show_debug_overlay.then(|| super::debug_overlay(self)))
             .into()
@@ -353,6 +773,62 @@ impl App {
 
     /// Modifies the app's state
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        // copying the selection is the last step of the tutorial, and it exits the app
+        if self
+            .tutorial
+            .is_some_and(|tutorial| tutorial.step == super::tutorial::Step::Copy)
+            && matches!(
+                message,
+                Message::Command {
+                    action: crate::Command::ImageUpload(
+                        crate::image::action::Command::CopyToClipboard
+                    ),
+                    ..
+                }
+            )
+            && let Err(err) = super::tutorial::mark_done()
+        {
+            log::error!("Failed to mark the tutorial as completed: {err}");
+        }
+
+        if let Some(session) = &mut self.session {
+            session.record_message(&message);
+        }
+
+        let popup_before = self.popup.as_ref().map(std::mem::discriminant);
+        let can_change_image =
+            message.can_change_image() || self.selection_animation.is_animating();
+        let task = self.handle_message(message);
+
+        let popup_after = self.popup.as_ref().map(std::mem::discriminant);
+        if popup_after.is_some() && popup_after != popup_before {
+            self.popup_opened = self.time_elapsed;
+        }
+
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.advance(self.selection);
+        }
+
+        if let Some(session) = &mut self.session {
+            session.record_selection(self.selection.map(|sel| sel.rect));
+        }
+
+        if can_change_image {
+            super::history::History::record(self);
+
+            self.adjustments_preview = crate::image::adjust::Preview::refresh(
+                self.adjustments_preview.take(),
+                &self.image,
+                self.selection.map(|sel| sel.rect),
+                self.adjustments,
+            );
+        }
+
+        task
+    }
+
+    /// Mutate the state of the app in response to a message
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
         use crate::message::Handler as _;
 
         match message {
@@ -362,6 +838,30 @@ impl App {
             }
             Message::Tick(instant) => {
                 self.time_elapsed = instant.duration_since(self.time_started);
+
+                if let Some(selection) = self.selection.as_mut()
+                    && let Some(rect) = self
+                        .selection_animation
+                        .tick(selection.rect, self.time_elapsed)
+                {
+                    selection.rect = rect;
+                }
+
+                // an upload would be cancelled by exiting
+                if self.is_uploading_image {
+                    self.last_activity = self.time_elapsed;
+                }
+
+                if self
+                    .config
+                    .auto_exit_after()
+                    .is_some_and(|after| self.time_elapsed >= self.last_activity + after)
+                {
+                    return Self::exit();
+                }
+            }
+            Message::Activity => {
+                self.last_activity = self.time_elapsed;
             }
             Message::KeyCheatsheet(key_cheatsheet) => {
                 return key_cheatsheet.handle(self);
@@ -378,19 +878,94 @@ impl App {
             Message::Letters(letters) => {
                 return letters.handle(self);
             }
+            Message::PixelInspector(pixel_inspector) => {
+                return pixel_inspector.handle(self);
+            }
+            Message::FilePicker(file_picker) => {
+                return file_picker.handle(self);
+            }
+            Message::ScreenRecordingPermission(screen_recording_permission) => {
+                return screen_recording_permission.handle(self);
+            }
+            Message::Settings(settings) => {
+                return settings.handle(self);
+            }
+            Message::Tutorial(tutorial) => {
+                return tutorial.handle(self);
+            }
+            Message::Toolbar(toolbar) => {
+                return toolbar.handle(self);
+            }
+            Message::Redact(redact) => {
+                return redact.handle(self);
+            }
+            Message::Annotate(annotate) => {
+                return annotate.handle(self);
+            }
+            Message::KeyMacros(key_macros) => {
+                return key_macros.handle(self);
+            }
             Message::NoOp => (),
             Message::Command { action, count } => {
+                self.key_macros.record(&action, count);
                 return <crate::Command as crate::command::Handler>::handle(action, self, count);
             }
+            Message::RegisterCommand {
+                register,
+                action,
+                count,
+            } => {
+                self.register = Some(register);
+                let task = <crate::Command as crate::command::Handler>::handle(action, self, count);
+                self.register = None;
+
+                return task;
+            }
             Message::Error(err) => {
                 self.errors.push(err);
             }
+            Message::ErrorWithCode(code, err) => {
+                self.errors.push_code(code, err);
+            }
+            Message::CaptureStale => {
+                self.errors
+                    .warn("The screen changed since the screenshot was taken. Refresh it with F5");
+            }
+            Message::WindowResized(size) => {
+                self.viewport.window = size;
+            }
+            Message::CaptureRefreshed(image) => {
+                // `capture_fingerprint` stays the same, otherwise the screen would be checked
+                // again while the window covers it
+                self.image = image;
+                self.filtered_background = self.background_filter.apply(&self.image);
+                self.redactions.refresh(&self.image);
+                self.fit_to_image();
+            }
         }
 
         Task::none()
     }
 }
 
+/// Whether holding down the key of this command should repeat it
+fn is_repeatable(action: &crate::Command) -> bool {
+    use ui::selection::Command as Selection;
+
+    matches!(
+        action,
+        crate::Command::Selection(
+            Selection::Move { .. } | Selection::Extend { .. } | Selection::Shrink { .. }
+        )
+    )
+}
+
+/// How far apart the 2 clicks of a double-click can be, in pixels
+const DOUBLE_CLICK_MAX_DISTANCE: f32 = 4.0;
+
+/// How long the keys typed so far are shown after the last key press
+const PENDING_KEYS_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Holds information about the mouse
 #[derive(Default, Debug, Clone)]
 pub struct AppKeysState {
@@ -398,8 +973,71 @@ pub struct AppKeysState {
     pub is_left_down: bool,
     /// How many times to execute the next motion
     pub motion_count: Option<u32>,
+    /// `"` was pressed, so the next key chooses the register
+    pub is_choosing_register: bool,
+    /// Register for the next keybinding
+    pub register: Option<char>,
     /// The last key that was pressed
     pub last_key_pressed: Option<iced::keyboard::Key>,
+    /// When and where the left mouse button was last pressed, used to detect double-clicks
+    pub last_click: Option<(Instant, iced::Point)>,
+    /// Key which is being held down, repeating its command
+    pub held_key: Option<HeldKey>,
+    /// In smart select mode, the pixel under the cursor and the UI element at that pixel
+    pub hovered_element: Option<((u32, u32), Option<Rectangle>)>,
+    /// Where the left mouse button was pressed to start redacting a region
+    pub redaction_start: Option<iced::Point>,
+    /// Points of the stroke being drawn, while the left mouse button is held down
+    pub stroke: Option<Vec<iced::Point>>,
+    /// Stroke being moved or resized, while the left mouse button is held down
+    pub stroke_edit: Option<crate::image::annotate::Edit>,
+    /// When a key was last pressed, to hide the keys typed so far after a while
+    pub last_key_at: Option<Instant>,
+}
+
+impl AppKeysState {
+    /// Keys typed so far which did not trigger a keybinding yet, like the `"1200g`
+    /// of `"1200gg`. `None` if there are none, or if no key was pressed for a while
+    fn pending_keys(&self, keys: &crate::config::key_map::KeyMap) -> Option<String> {
+        if self
+            .last_key_at
+            .is_none_or(|at| at.elapsed() > PENDING_KEYS_TIMEOUT)
+        {
+            return None;
+        }
+
+        let register = if self.is_choosing_register {
+            Some("\"".to_owned())
+        } else {
+            self.register.map(|register| format!("\"{register}"))
+        };
+        let count = self.motion_count.map(|count| count.to_string());
+        let sequence = self
+            .last_key_pressed
+            .clone()
+            .filter(|key| keys.starts_sequence(key))
+            .map(|key| crate::config::key_map::KeySequence((key, None)).to_string());
+
+        let pending = [register, count, sequence]
+            .into_iter()
+            .flatten()
+            .collect::<String>();
+
+        (!pending.is_empty()).then_some(pending)
+    }
+}
+
+/// A key held down, which repeats its command at the configured rate
+#[derive(Debug, Clone)]
+pub struct HeldKey {
+    /// The key that is held
+    key: iced::keyboard::Key,
+    /// Command to repeat
+    action: crate::Command,
+    /// Count that the command was first invoked with
+    count: u32,
+    /// When to repeat the command next
+    next_repeat: Instant,
 }
 
 impl canvas::Program<Message> for App {
@@ -407,23 +1045,182 @@ impl canvas::Program<Message> for App {
 
     fn draw(
         &self,
-        _state: &Self::State,
+        (state, _): &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
-        _cursor: iced::advanced::mouse::Cursor,
+        cursor: iced::advanced::mouse::Cursor,
     ) -> Vec<canvas::Geometry> {
         let mut frame = canvas::Frame::new(renderer, bounds.size());
 
+        // everything is drawn in the coordinates of the screenshot
+        frame.translate(self.viewport.offset());
+        frame.scale(self.viewport.scale());
+        let bounds = self.viewport.image_bounds();
+        let cursor = self.viewport.cursor(cursor);
+
+        let mut theme = self.config.theme;
+        theme.non_selected_region = self.non_selected_region();
+
+        // the side or corner being resized, or else the one which clicking would grab
+        let handle = self.selection.and_then(|sel| {
+            sel.resizing_side().or_else(|| {
+                let pos = cursor.position().filter(|_| sel.is_idle())?;
+                sel.norm().corners().side_at(pos, sel.grab_area)
+            })
+        });
+
         if let Some(sel) = self.selection.map(Selection::norm) {
-            sel.draw(&mut frame, bounds);
-        } else {
+            if let Some(preview) = &self.adjustments_preview {
+                frame.draw_image(preview.rect, canvas::Image::new(preview.handle.clone()));
+            }
+
+            // only the parts of the redactions inside of the selection end up in the image
+            frame.with_clip(sel.rect, |frame| {
+                for redaction in &self.redactions.regions {
+                    frame.draw_image(
+                        redaction.rect - Vector::new(sel.rect.x, sel.rect.y),
+                        canvas::Image::new(redaction.preview.clone()),
+                    );
+                }
+
+                if let Some(start) = state.redaction_start
+                    && let Some(end) = cursor.position()
+                {
+                    let rect =
+                        Rectangle::new(start, Size::new(end.x - start.x, end.y - start.y)).norm();
+                    frame.stroke_rectangle(
+                        rect.position() - Vector::new(sel.rect.x, sel.rect.y),
+                        rect.size(),
+                        canvas::Stroke {
+                            line_dash: canvas::LineDash {
+                                segments: &[6.0, 4.0],
+                                offset: 0,
+                            },
+                            ..canvas::Stroke::default()
+                                .with_color(theme.selection_frame)
+                                .with_width(FRAME_WIDTH)
+                        },
+                    );
+                }
+
+                let offset = Vector::new(sel.rect.x, sel.rect.y);
+                // the stroke being moved or resized follows the cursor
+                let edited = state
+                    .stroke_edit
+                    .zip(cursor.position())
+                    .and_then(|(edit, pos)| {
+                        Some((
+                            edit.index,
+                            edit.apply(self.annotations.strokes.get(edit.index)?, pos),
+                        ))
+                    });
+                let strokes =
+                    self.annotations.strokes.iter().enumerate().map(
+                        |(index, stroke)| match &edited {
+                            Some((edited_index, edited)) if *edited_index == index => edited,
+                            _ => stroke,
+                        },
+                    );
+                for stroke in strokes.clone() {
+                    stroke.draw(frame, offset);
+                }
+
+                if let Some(selected) = self
+                    .annotations
+                    .selected
+                    .and_then(|index| strokes.clone().nth(index))
+                {
+                    selected.draw_outline(frame, theme.selection_frame, offset);
+                }
+
+                if let Some(points) = &state.stroke
+                    && let Some(tool) = self.annotations.tool
+                {
+                    let (color, width) = tool.style(&self.config);
+                    crate::image::annotate::draw_stroke(frame, points, color, width, offset);
+                }
+            });
+
+            sel.with_theme(&theme)
+                .draw(&mut frame, bounds, self.config.shade_style, handle);
+
+            if self.config.dimensions_label && !self.is_minimal() {
+                sel.draw_dimensions_label(&mut frame, bounds);
+            }
+
+            if self.config.cursor_offset
+                && sel.is_resize()
+                && let Some(pos) = cursor.position_in(bounds)
+            {
+                sel.draw_cursor_offset(&mut frame, bounds, pos);
+            }
+        } else if self.config.shade_style == ShadeStyle::Dim {
             // usually the selection is responsible for drawing shade around itself
             // However here we don't have selection, so just draw the shade on the entire screen
+            frame.fill_rectangle(bounds.position(), bounds.size(), theme.non_selected_region);
+        }
+
+        if self.config.crosshair == Crosshair::Guides
+            && self.cursor_interaction(cursor) == Interaction::Crosshair
+            && let Some(pos) = cursor.position_in(bounds)
+        {
+            let guides = canvas::Path::new(|p| {
+                p.move_to(iced::Point::new(pos.x, 0.0));
+                p.line_to(iced::Point::new(pos.x, bounds.height));
+                p.move_to(iced::Point::new(0.0, pos.y));
+                p.line_to(iced::Point::new(bounds.width, pos.y));
+            });
+
+            frame.stroke(
+                &guides,
+                canvas::Stroke::default()
+                    .with_color(self.config.theme.crosshair)
+                    .with_width(self.config.crosshair_thickness),
+            );
+        }
+
+        if let Some(pending) = state.pending_keys(&self.config.keys) {
+            /// Distance between the label and the cursor, so the cursor does not cover it
+            const CURSOR_GAP: f32 = 16.0;
+
+            ui::selection::draw_label(&mut frame, bounds, &theme, pending, |size| {
+                cursor.position_in(bounds).map_or_else(
+                    || iced::Point::new(bounds.x, bounds.y + bounds.height - size.height),
+                    |pos| pos + Vector::new(CURSOR_GAP, -CURSOR_GAP - size.height),
+                )
+            });
+        }
+
+        if let Some(remaining) = self.auto_exit_countdown() {
+            ui::selection::draw_label(
+                &mut frame,
+                bounds,
+                &theme,
+                format!(
+                    "Closing in {}s. Move the mouse to stay",
+                    remaining.as_secs_f32().ceil() as u32
+                ),
+                |size| iced::Point::new(bounds.center_x_for(size), bounds.y),
+            );
+        }
+
+        // preview of what clicking will select
+        if self.is_smart_select
+            && self.cursor_interaction(cursor) == Interaction::Crosshair
+            && let Some((_, Some(element))) = state.hovered_element
+        {
             frame.fill_rectangle(
-                bounds.position(),
-                bounds.size(),
-                self.config.theme.non_selected_region,
+                element.position(),
+                element.size(),
+                self.config.theme.selection_highlight,
+            );
+            frame.stroke_rectangle(
+                element.position(),
+                element.size(),
+                canvas::Stroke::default()
+                    .with_color(self.config.theme.selection_frame)
+                    .with_width(ui::selection::FRAME_WIDTH),
             );
         }
 
@@ -434,18 +1231,19 @@ impl canvas::Program<Message> for App {
         &self,
         state: &mut Self::State,
         event: &iced::Event,
-        bounds: Rectangle,
+        _bounds: Rectangle,
         cursor: iced::advanced::mouse::Cursor,
     ) -> Option<Action<Message>> {
         use iced::Event::{Keyboard, Mouse, Touch};
-        use iced::keyboard::Event::KeyPressed;
+        use iced::keyboard::Event::{KeyPressed, KeyReleased};
         use iced::keyboard::Key::Named;
         use iced::keyboard::Modifiers;
         use iced::keyboard::key::Named::{ArrowDown, ArrowLeft, ArrowRight, ArrowUp, Shift};
-        use iced::mouse::Button::Left;
+        use iced::mouse::Button::{Left, Middle};
         use iced::mouse::Event::ButtonPressed;
         use iced::mouse::Event::ButtonReleased;
-        use iced::touch::Event::{FingerLifted, FingerPressed};
+        use iced::mouse::Event::CursorMoved;
+        use iced::touch::Event::{FingerLifted, FingerMoved, FingerPressed};
 
         // Handle popups. Esc = close popup
         //
@@ -464,9 +1262,281 @@ impl canvas::Program<Message> for App {
 
         let (state, selection_state) = state;
 
-        if let Some(sel) = self.selection {
-            if let Some(action) = sel.update(selection_state, event, bounds, cursor) {
-                return Some(action);
+        // the selection is in the coordinates of the screenshot, so the cursor must be too
+        let bounds = self.viewport.image_bounds();
+        let cursor = self.viewport.cursor(cursor);
+
+        // the toolbar follows the cursor anywhere while it is dragged, even over other widgets
+        if self.toolbar.is_dragging {
+            match event {
+                Mouse(CursorMoved { position }) => {
+                    return Some(Action::publish(Message::Toolbar(
+                        ui::toolbar::Message::Drag(*position),
+                    )));
+                }
+                Mouse(ButtonReleased(Left)) => {
+                    return Some(Action::publish(Message::Toolbar(
+                        ui::toolbar::Message::EndDrag,
+                    )));
+                }
+                _ => (),
+            }
+        }
+
+        // while redacting, dragging inside of the selection redacts instead of moving it
+        if self.redactions.is_active()
+            && let Some(sel) = self.selection.map(Selection::norm)
+        {
+            match event {
+                Touch(FingerPressed { .. }) | Mouse(ButtonPressed(Left)) => {
+                    if let Some((pos, _)) = sel.cursor_in_selection(cursor) {
+                        state.redaction_start = Some(pos);
+                        return Some(Action::capture());
+                    }
+                }
+                Touch(FingerLifted { .. }) | Mouse(ButtonReleased(Left)) => {
+                    if let Some(start) = state.redaction_start.take()
+                        && let Some(end) = cursor.position()
+                    {
+                        return Some(Action::publish(Message::Redact(
+                            crate::image::redact::Message::Add(start, end),
+                        )));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // middle-click pastes a region, like pasting text on Linux
+        if let Mouse(ButtonPressed(Middle)) = event {
+            return Some(Action::publish(Message::Selection(Box::new(
+                ui::selection::Message::PasteRegion { primary: true },
+            ))));
+        }
+
+        // while drawing, dragging inside of the selection draws instead of moving it
+        if self.annotations.is_active()
+            && let Some(sel) = self.selection.map(Selection::norm)
+        {
+            match event {
+                Touch(FingerPressed { .. }) | Mouse(ButtonPressed(Left)) => {
+                    if let Some((pos, _)) = sel.cursor_in_selection(cursor) {
+                        // pressing a stroke selects it to move or resize it, instead of drawing
+                        if let Some(edit) = self.annotations.edit_at(pos) {
+                            state.stroke_edit = Some(edit);
+                            return Some(
+                                Action::publish(Message::Annotate(
+                                    crate::image::annotate::Message::Select(Some(edit.index)),
+                                ))
+                                .and_capture(),
+                            );
+                        }
+
+                        state.stroke = Some(vec![pos]);
+                        return Some(if self.annotations.selected.is_some() {
+                            Action::publish(Message::Annotate(
+                                crate::image::annotate::Message::Select(None),
+                            ))
+                            .and_capture()
+                        } else {
+                            Action::capture()
+                        });
+                    }
+                }
+                Touch(FingerMoved { .. }) | Mouse(CursorMoved { .. })
+                    if state.stroke_edit.is_some() =>
+                {
+                    return Some(Action::request_redraw());
+                }
+                Touch(FingerLifted { .. }) | Mouse(ButtonReleased(Left))
+                    if state.stroke_edit.is_some() =>
+                {
+                    if let Some(edit) = state.stroke_edit.take()
+                        && let Some(pos) = cursor.position()
+                        && let Some(stroke) = self.annotations.strokes.get(edit.index)
+                    {
+                        return Some(Action::publish(Message::Annotate(
+                            crate::image::annotate::Message::Edit(
+                                edit.index,
+                                edit.apply(stroke, pos),
+                            ),
+                        )));
+                    }
+                }
+                Touch(FingerMoved { .. }) | Mouse(CursorMoved { .. }) => {
+                    if let Some(points) = state.stroke.as_mut()
+                        && let Some(pos) = cursor.position()
+                    {
+                        if points.last() != Some(&pos) {
+                            points.push(pos);
+                        }
+                        return Some(Action::request_redraw());
+                    }
+                }
+                Touch(FingerLifted { .. }) | Mouse(ButtonReleased(Left)) => {
+                    if let Some(points) = state.stroke.take() {
+                        return Some(Action::publish(Message::Annotate(
+                            crate::image::annotate::Message::Add(points),
+                        )));
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // handle double-clicks before the selection, because the selection
+        // would otherwise treat the 2nd click as the start of moving or resizing
+        if let Mouse(ButtonPressed(Left)) = event
+            && let Some(cursor_pos) = cursor.position()
+        {
+            let now = Instant::now();
+            let is_double_click = state.last_click.is_some_and(|(time, pos)| {
+                now.duration_since(time) <= self.config.mouse.double_click_interval()
+                    && pos.distance(cursor_pos) <= DOUBLE_CLICK_MAX_DISTANCE
+            });
+
+            if is_double_click {
+                state.last_click = None;
+
+                let action = if self
+                    .selection
+                    .is_some_and(|sel| sel.cursor_in_selection(cursor).is_some())
+                {
+                    self.config.mouse.double_click_inside
+                } else {
+                    self.config.mouse.double_click_outside
+                };
+
+                if let Some(action) = action.command() {
+                    return Some(Action::publish(Message::Command { action, count: 1 }));
+                }
+            } else {
+                state.last_click = Some((now, cursor_pos));
+            }
+        }
+
+        // In smart select mode, clicking selects the UI element under the cursor
+        if self.is_smart_select
+            && !state.is_left_down
+            && self.cursor_interaction(cursor) == Interaction::Crosshair
+            && let Some(cursor_pos) = cursor.position()
+        {
+            // finding the element is expensive, so only do it when the cursor moves to another pixel
+            let pixel = (cursor_pos.x as u32, cursor_pos.y as u32);
+            if state
+                .hovered_element
+                .is_none_or(|(hovered, _)| hovered != pixel)
+            {
+                state.hovered_element = Some((
+                    pixel,
+                    crate::image::segment::element_at(&self.image, cursor_pos),
+                ));
+            }
+
+            if let Mouse(ButtonPressed(Left)) = event
+                && let Some((_, Some(element))) = state.hovered_element
+            {
+                return Some(Action::publish(Message::Selection(Box::new(
+                    ui::selection::Message::SelectElement(element),
+                ))));
+            }
+        } else {
+            state.hovered_element = None;
+        }
+
+        // repeat the command of the key being held down
+        match event {
+            iced::Event::Window(window::Event::RedrawRequested(now)) => {
+                if let Some(held) = state.held_key.as_mut()
+                    && *now >= held.next_repeat
+                {
+                    held.next_repeat = *now + self.config.key_repeat_interval();
+                    return Some(Action::publish(Message::Command {
+                        action: held.action.clone(),
+                        count: held.count,
+                    }));
+                }
+            }
+            Keyboard(KeyPressed { key, .. }) => {
+                // we repeat the key ourselves, at a consistent rate.
+                // So ignore the repeats of the operating system
+                if state.held_key.as_ref().is_some_and(|held| held.key == *key) {
+                    return Some(Action::capture());
+                }
+            }
+            Keyboard(KeyReleased { key, .. }) => {
+                if state.held_key.as_ref().is_some_and(|held| held.key == *key) {
+                    state.held_key = None;
+                }
+            }
+            // the key could be released while another window has the focus,
+            // in which case we would never receive the release and keep repeating
+            iced::Event::Window(window::Event::Unfocused) => state.held_key = None,
+            _ => (),
+        }
+
+        // after starting to record or replay a macro, the next key names its register
+        if self.key_macros.pending.is_some()
+            && let Keyboard(KeyPressed { modified_key, .. }) = event
+        {
+            match modified_key {
+                iced::keyboard::Key::Character(ch) => {
+                    if let Some(register) = ch.chars().next() {
+                        return Some(Action::publish(Message::KeyMacros(
+                            ui::key_macros::Message::Register(register),
+                        )));
+                    }
+                }
+                Named(iced::keyboard::key::Named::Escape) => {
+                    return Some(Action::publish(Message::KeyMacros(
+                        ui::key_macros::Message::Cancel,
+                    )));
+                }
+                // for example, Shift is pressed before an uppercase register
+                _ => return Some(Action::capture()),
+            }
+        }
+
+        // the count snaps dragging with the mouse to multiples of it,
+        // until the mouse is released
+        if let Mouse(ButtonReleased(Left)) = event {
+            state.motion_count = None;
+        }
+
+        if let Some(sel) = self.selection
+            && let Some(action) = sel.update(
+                selection_state,
+                &self.viewport.event(event.clone()),
+                bounds,
+                cursor,
+                state.motion_count,
+                self.is_precision_mode,
+                self.config.mouse.drag_threshold,
+            )
+        {
+            return Some(action);
+        }
+
+        // the keys typed so far are shown until no key is pressed for a while
+        if let Keyboard(KeyPressed { .. }) = event {
+            state.last_key_at = Some(Instant::now());
+        }
+
+        // `"` chooses the register for the next keybinding, like `"1y`
+        if let Keyboard(KeyPressed {
+            modified_key: iced::keyboard::Key::Character(ch),
+            ..
+        }) = event
+        {
+            if state.is_choosing_register {
+                state.is_choosing_register = false;
+                state.register = ch.chars().next();
+                return Some(Action::capture());
+            }
+
+            if ch.as_str() == "\"" {
+                state.is_choosing_register = true;
+                return Some(Action::capture());
             }
         }
 
@@ -478,13 +1548,12 @@ impl canvas::Program<Message> for App {
             key: iced::keyboard::Key::Character(ch),
             ..
         }) = event
+            && let Ok(number_pressed) = ch.parse::<u32>()
         {
-            if let Ok(number_pressed) = ch.parse::<u32>() {
-                if let Some(motion_count) = state.motion_count.as_mut() {
-                    *motion_count = *motion_count * 10 + number_pressed;
-                } else {
-                    state.motion_count = Some(number_pressed);
-                }
+            if let Some(motion_count) = state.motion_count.as_mut() {
+                *motion_count = *motion_count * 10 + number_pressed;
+            } else {
+                state.motion_count = Some(number_pressed);
             }
         }
 
@@ -508,6 +1577,13 @@ impl canvas::Program<Message> for App {
                 modifiers.remove(Modifiers::SHIFT);
             }
 
+            // after choosing a register, keybindings which use it take priority
+            let layer = if state.register.is_some() {
+                Some(crate::registers::REGISTER_LAYER)
+            } else {
+                self.layer.as_deref()
+            };
+
             if let Some(action) = state
                 .last_key_pressed
                 .as_ref()
@@ -516,9 +1592,14 @@ impl canvas::Program<Message> for App {
                         last_key_pressed.clone(),
                         Some(modified_key.clone()),
                         modifiers,
+                        layer,
                     )
                 })
-                .or_else(|| self.config.keys.get(modified_key.clone(), None, modifiers))
+                .or_else(|| {
+                    self.config
+                        .keys
+                        .get(modified_key.clone(), None, modifiers, layer)
+                })
             {
                 // the last key pressed needs to be reset for it to be
                 // correct in future invocations
@@ -533,6 +1614,27 @@ impl canvas::Program<Message> for App {
                 let count = state.motion_count.unwrap_or(1);
                 state.motion_count = None;
 
+                if self.config.statistics {
+                    crate::stats::record(|stats| stats.record_keybinding(action));
+                }
+
+                if is_repeatable(action) {
+                    state.held_key = Some(HeldKey {
+                        key: key.clone(),
+                        action: action.clone(),
+                        count,
+                        next_repeat: Instant::now() + self.config.key_repeat_delay(),
+                    });
+                }
+
+                if let Some(register) = state.register.take() {
+                    return Some(Action::publish(Message::RegisterCommand {
+                        register,
+                        action: action.clone(),
+                        count,
+                    }));
+                }
+
                 return Some(Action::publish(Message::Command {
                     action: action.clone(),
                     count,
@@ -574,12 +1676,13 @@ impl canvas::Program<Message> for App {
         _bounds: Rectangle,
         cursor: iced::advanced::mouse::Cursor,
     ) -> Interaction {
-        if let Some(Popup::ImageUploaded(_)) = self.popup {
-            Interaction::default()
-        } else {
-            self.selection
-                .map(Selection::norm)
-                .map_or(Interaction::Crosshair, |sel| sel.mouse_interaction(cursor))
+        match self.cursor_interaction(self.viewport.cursor(cursor)) {
+            Interaction::Crosshair => match self.config.crosshair {
+                Crosshair::Native => Interaction::Crosshair,
+                // the guides replace the cursor
+                Crosshair::Guides | Crosshair::Hidden => Interaction::Hidden,
+            },
+            interaction => interaction,
         }
     }
 }
@@ -1,10 +1,78 @@
-//! Renders the full, unprocessed desktop screenshot on the screen
+//! Renders the full desktop screenshot on the screen
+//!
+//! The screenshot can be shown inverted or in grayscale, which makes it more comfortable
+//! to look at for users with light sensitivity or color blindness. This only changes
+//! what is shown while selecting, never the image which is copied, saved or uploaded.
+use std::fmt;
+
 use iced::Length::Fill;
 use iced::advanced::widget::Tree;
 use iced::advanced::{Layout, Widget, layout, renderer};
 use iced::widget::image;
 use iced::{Element, Length, Rectangle, Size, Theme, mouse};
 
+use crate::image::RgbaHandle;
+
+/// Filters applied to the screenshot while it is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Show the screenshot with inverted colors
+    pub invert: bool,
+    /// Show the screenshot in shades of gray
+    pub grayscale: bool,
+}
+
+impl Filter {
+    /// Whether the screenshot is shown as it is
+    pub fn is_none(self) -> bool {
+        self == Self::default()
+    }
+
+    /// The `image` with the filters applied, or `None` if there are no filters
+    pub fn apply(self, image: &RgbaHandle) -> Option<image::Handle> {
+        if self.is_none() {
+            return None;
+        }
+
+        let mut pixels = image.bytes().to_vec();
+        for pixel in pixels.chunks_exact_mut(4) {
+            self.apply_to_pixel(pixel);
+        }
+
+        Some(image::Handle::from_rgba(
+            image.width(),
+            image.height(),
+            pixels,
+        ))
+    }
+
+    /// Filter a single RGBA `pixel`. The alpha channel is unchanged
+    fn apply_to_pixel(self, pixel: &mut [u8]) {
+        if self.grayscale {
+            let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+            let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+            pixel[..3].fill(luminance.round() as u8);
+        }
+
+        if self.invert {
+            for channel in &mut pixel[..3] {
+                *channel = u8::MAX - *channel;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let filters = [(self.invert, "INVERTED"), (self.grayscale, "GRAYSCALE")]
+            .into_iter()
+            .filter_map(|(is_on, name)| is_on.then_some(name))
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", filters.join(" | "))
+    }
+}
+
 #[derive(Debug)]
 /// A widget that draws an image on the entire screen
 pub struct BackgroundImage {
@@ -72,3 +140,47 @@ where
         Self::new(widget)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Filter a single pixel
+    fn filter(filter: Filter, pixel: [u8; 4]) -> [u8; 4] {
+        let mut pixel = pixel;
+        filter.apply_to_pixel(&mut pixel);
+        pixel
+    }
+
+    #[test]
+    fn filters_pixels() {
+        let invert = Filter {
+            invert: true,
+            grayscale: false,
+        };
+        assert_eq!(filter(invert, [0, 100, 255, 7]), [255, 155, 0, 7]);
+
+        let grayscale = Filter {
+            invert: false,
+            grayscale: true,
+        };
+        assert_eq!(filter(grayscale, [255, 0, 0, 255]), [54, 54, 54, 255]);
+
+        let both = Filter {
+            invert: true,
+            grayscale: true,
+        };
+        assert_eq!(filter(both, [255, 0, 0, 255]), [201, 201, 201, 255]);
+    }
+
+    #[test]
+    fn no_filter_shows_screenshot() {
+        let image = RgbaHandle::new(1, 1, vec![1, 2, 3, 4]);
+
+        assert!(
+            Filter::default().apply(&image).is_none(),
+            "the screenshot is shown as it is"
+        );
+    }
+}
@@ -8,6 +8,7 @@ use iced::{
 };
 
 crate::declare_commands! {
+    #[derive(Copy)]
     enum Command {
         /// Toggle the overlay showing various information for debugging
         ToggleDebugOverlay,
@@ -30,7 +31,7 @@ impl crate::command::Handler for Command {
 const LABEL_SPACE: f32 = 25.0;
 
 /// Debug overlay shows useful information when pressing F12
-pub fn debug_overlay(app: &crate::App) -> Element<crate::Message> {
+pub fn debug_overlay(app: &crate::App) -> Element<'_, crate::Message> {
     let container_style = |_: &Theme| container::Style {
         text_color: Some(app.config.theme.debug_fg),
         background: Some(Background::Color(app.config.theme.debug_bg)),
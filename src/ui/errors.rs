@@ -17,6 +17,10 @@ pub struct ErrorMessage {
     pub message: Cow<'static, str>,
     /// When the error was created
     pub timestamp: Instant,
+    /// Something may be wrong, but nothing failed
+    pub is_warning: bool,
+    /// Code of the error, which `--explain` explains
+    pub code: Option<ErrorCode>,
 }
 
 impl ErrorMessage {
@@ -25,11 +29,29 @@ impl ErrorMessage {
         Self {
             message: message.into(),
             timestamp: Instant::now(),
+            is_warning: false,
+            code: None,
+        }
+    }
+
+    /// Create a new error message which has a `code`
+    pub fn with_code<T: Into<Cow<'static, str>>>(code: ErrorCode, message: T) -> Self {
+        Self {
+            code: Some(code),
+            ..Self::new(message)
+        }
+    }
+
+    /// Create a new warning message
+    pub fn warning<T: Into<Cow<'static, str>>>(message: T) -> Self {
+        Self {
+            is_warning: true,
+            ..Self::new(message)
         }
     }
 }
 
-use crate::message::Message;
+use crate::{error_code::ErrorCode, message::Message};
 
 /// Width of error message
 const ERROR_WIDTH: u32 = 300;
@@ -50,25 +72,56 @@ impl Errors {
         self.errors.push(ErrorMessage::new(error));
     }
 
+    /// Add a new error which has a `code` to the list of errors
+    pub fn push_code<T: Into<Cow<'static, str>>>(&mut self, code: ErrorCode, error: T) {
+        self.errors.push(ErrorMessage::with_code(code, error));
+    }
+
+    /// Add a new warning to the list of errors
+    pub fn warn<T: Into<Cow<'static, str>>>(&mut self, warning: T) {
+        self.errors.push(ErrorMessage::warning(warning));
+    }
+
     /// Show errors on the screen
     pub fn view<'app>(&self, app: &'app super::App) -> Element<'app, Message> {
-        let image_width = app.image.width();
+        let window_width = app.viewport.window.width;
         let errors = self
             .errors
             .iter()
             .rev()
             // don't display more than the most recent 3 errors
             .take(3)
-            .filter(|&error| (error.timestamp.elapsed() < ERROR_DURATION))
+            .filter(|&error| error.timestamp.elapsed() < ERROR_DURATION)
             .map(|error| {
-                container(widget::text!("Error: {}", error.message))
-                    .height(80)
+                let theme = &app.config.theme;
+                let (label, fg, bg) = if error.is_warning {
+                    ("Warning", theme.warning_fg, theme.warning_bg)
+                } else {
+                    ("Error", theme.error_fg, theme.error_bg)
+                };
+
+                let content = match error.code {
+                    Some(code) if app.config.detailed_errors => format!(
+                        "{label} {code}: {}\n{}\nSee `ferrishot --explain {code}`",
+                        error.message,
+                        code.hint()
+                    ),
+                    Some(code) => format!("{label} {code}: {}", error.message),
+                    None => format!("{label}: {}", error.message),
+                };
+
+                container(widget::text(content))
+                    .height(if app.config.detailed_errors {
+                        iced::Length::Shrink
+                    } else {
+                        iced::Length::Fixed(80.0)
+                    })
                     .width(ERROR_WIDTH)
-                    .style(|_| container::Style {
-                        text_color: Some(app.config.theme.error_fg),
-                        background: Some(Background::Color(app.config.theme.error_bg)),
+                    .style(move |_| container::Style {
+                        text_color: Some(fg),
+                        background: Some(Background::Color(bg)),
                         border: iced::Border {
-                            color: app.config.theme.drop_shadow,
+                            color: theme.drop_shadow,
                             width: 4.0,
                             radius: 2.0.into(),
                         },
@@ -81,6 +134,10 @@ impl Errors {
             .width(ERROR_WIDTH)
             .spacing(30);
 
-        row![Space::with_width(image_width - ERROR_WIDTH), errors].into()
+        row![
+            Space::with_width((window_width - ERROR_WIDTH as f32).max(0.0)),
+            errors
+        ]
+        .into()
     }
 }
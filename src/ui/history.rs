@@ -0,0 +1,152 @@
+//! Undo and redo changes to the selection, redactions and strokes
+//!
+//! After each message which can change the state, it is compared with the state after the
+//! last recorded change.
+//! Nothing is recorded while the selection is being dragged or animated, so a whole drag
+//! is undone at once.
+
+use std::collections::VecDeque;
+
+use iced::{Rectangle, Task};
+
+use crate::{
+    App,
+    geometry::RectangleExt as _,
+    image::{annotate::Stroke, redact::Redaction},
+};
+
+/// How many changes can be undone. Older changes are forgotten
+const MAX_CHANGES: usize = 100;
+
+crate::declare_commands! {
+    #[derive(Copy)]
+    enum Command {
+        /// Undo the last change to the selection, redactions or strokes
+        Undo,
+        /// Redo the last change that was undone
+        Redo,
+    }
+}
+
+impl crate::command::Handler for Command {
+    fn handle(self, app: &mut App, count: u32) -> Task<crate::Message> {
+        for i in 0..count {
+            let history = &mut app.history;
+            let (from, to) = match self {
+                Self::Undo => (&mut history.undo, &mut history.redo),
+                Self::Redo => (&mut history.redo, &mut history.undo),
+            };
+
+            let Some(snapshot) = from.pop_back() else {
+                if i == 0 {
+                    app.errors.push(match self {
+                        Self::Undo => "There is nothing to undo",
+                        Self::Redo => "There is nothing to redo",
+                    });
+                }
+                break;
+            };
+
+            if app
+                .selection
+                .is_some_and(|sel| sel.is_locked && Some(sel.rect.norm()) != snapshot.selection)
+            {
+                from.push_back(snapshot);
+                app.errors.warn("The selection is locked");
+                break;
+            }
+
+            if let Some(current) = history.current.replace(snapshot.clone()) {
+                to.push_back(current);
+            }
+            snapshot.restore(app);
+        }
+
+        Task::none()
+    }
+}
+
+/// State of the app which can be undone
+#[derive(Debug, Clone)]
+struct Snapshot {
+    /// Region of the selection
+    selection: Option<Rectangle>,
+    /// Regions which are redacted
+    redactions: Vec<Redaction>,
+    /// Strokes which are drawn
+    strokes: Vec<Stroke>,
+}
+
+impl Snapshot {
+    /// Current state of the `app`
+    fn of(app: &App) -> Self {
+        Self {
+            selection: app.selection.map(|sel| sel.rect.norm()),
+            redactions: app.redactions.regions.clone(),
+            strokes: app.annotations.strokes.clone(),
+        }
+    }
+
+    /// Whether the state of the `app` is different.
+    ///
+    /// Redactions are only ever added or removed, so comparing how many there are is enough.
+    /// Strokes can also be moved and resized
+    fn differs_from(&self, app: &App) -> bool {
+        self.selection != app.selection.map(|sel| sel.rect.norm())
+            || self.redactions.len() != app.redactions.regions.len()
+            || self.strokes != app.annotations.strokes
+    }
+
+    /// Change the state of the `app` to this one
+    fn restore(self, app: &mut App) {
+        match (app.selection.as_mut(), self.selection) {
+            (Some(selection), Some(rect)) => selection.rect = rect,
+            (_, None) => app.selection = None,
+            (None, Some(rect)) => super::selection::select(app, rect),
+        }
+        app.redactions.regions = self.redactions;
+        app.annotations.strokes = self.strokes;
+        app.annotations.selected = None;
+    }
+}
+
+/// Changes which can be undone and redone
+#[derive(Debug, Default)]
+pub struct History {
+    /// State after the last recorded change. `None` until the app is created
+    current: Option<Snapshot>,
+    /// States before each change, from oldest to newest
+    undo: VecDeque<Snapshot>,
+    /// States which were undone. The last one was undone most recently
+    redo: VecDeque<Snapshot>,
+}
+
+impl History {
+    /// Record the state of the `app` if it changed since the last recorded change,
+    /// so that the change can be undone. Called when the app is created, and after each
+    /// message which can change the state
+    pub fn record(app: &mut App) {
+        let is_moving = app.selection.is_some_and(|sel| !sel.is_idle())
+            || app.selection_animation.is_animating();
+
+        if is_moving
+            || app
+                .history
+                .current
+                .as_ref()
+                .is_some_and(|current| !current.differs_from(app))
+        {
+            return;
+        }
+
+        let snapshot = Snapshot::of(app);
+        let history = &mut app.history;
+        if let Some(previous) = history.current.replace(snapshot) {
+            history.undo.push_back(previous);
+            if history.undo.len() > MAX_CHANGES {
+                history.undo.pop_front();
+            }
+            history.redo.clear();
+        }
+    }
+}
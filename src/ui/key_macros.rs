@@ -0,0 +1,160 @@
+//! Record a sequence of commands into a register, and replay it later
+//!
+//! Works like macros in vim: `q` followed by a register, like `a`, starts recording.
+//! Every command invoked by a keybinding is recorded, until `q` is pressed again.
+//! `@a` then replays the commands, and `3@a` replays them 3 times.
+
+use std::collections::HashMap;
+
+use iced::Task;
+
+use crate::error_code::ErrorCode;
+use crate::message::Message as AppMessage;
+
+crate::declare_commands! {
+    #[derive(Copy)]
+    enum Command {
+        /// Start recording a macro into the register named by the next key,
+        /// or stop recording if a macro is being recorded
+        ToggleMacroRecording,
+        /// Replay the macro in the register named by the next key
+        ReplayMacro,
+    }
+}
+
+impl crate::command::Handler for Command {
+    fn handle(self, app: &mut crate::App, count: u32) -> Task<AppMessage> {
+        let macros = &mut app.key_macros;
+
+        match self {
+            Self::ToggleMacroRecording => {
+                if let Some((register, commands)) = macros.recording.take() {
+                    macros.registers.insert(register, commands);
+                } else {
+                    macros.pending = Some(Pending::Record);
+                }
+            }
+            Self::ReplayMacro => {
+                macros.pending = Some(Pending::Replay { count });
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Message for key macros
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// The key naming the register was pressed
+    Register(char),
+    /// Do not record or replay the macro after all
+    Cancel,
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut crate::App) -> Task<AppMessage> {
+        match self {
+            Self::Register(register) => match app.key_macros.pending.take() {
+                Some(Pending::Record) => {
+                    app.key_macros.recording = Some((register, vec![]));
+                }
+                Some(Pending::Replay { count }) => {
+                    let Some(commands) = app.key_macros.registers.get(&register).cloned() else {
+                        app.errors.push_code(
+                            ErrorCode::EmptyRegister,
+                            format!("Register `{register}` is empty"),
+                        );
+                        return Task::none();
+                    };
+
+                    // handled right away, so that each command sees the changes of the previous one
+                    return Task::batch(
+                        (0..count)
+                            .flat_map(|_| commands.iter().cloned())
+                            .map(|(action, count)| {
+                                <crate::Command as crate::command::Handler>::handle(
+                                    action, app, count,
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                None => (),
+            },
+            Self::Cancel => app.key_macros.pending = None,
+        }
+
+        Task::none()
+    }
+}
+
+/// What to do once the register is known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pending {
+    /// Start recording into the register
+    Record,
+    /// Replay the register this many times
+    Replay {
+        /// How many times to replay the macro
+        count: u32,
+    },
+}
+
+/// Macros which were recorded, and the one being recorded
+#[derive(Debug, Default)]
+pub struct KeyMacros {
+    /// Commands recorded into each register, with their counts
+    pub registers: HashMap<char, Vec<(crate::Command, u32)>>,
+    /// Register being recorded into, and the commands recorded so far
+    pub recording: Option<(char, Vec<(crate::Command, u32)>)>,
+    /// Waiting for the key which names the register
+    pub pending: Option<Pending>,
+}
+
+impl KeyMacros {
+    /// Record the `action` invoked `count` times, if a macro is being recorded
+    pub fn record(&mut self, action: &crate::Command, count: u32) {
+        // recording would otherwise be stopped as soon as the macro is replayed
+        if matches!(action, crate::Command::KeyMacros(_)) {
+            return;
+        }
+
+        if let Some((_, commands)) = &mut self.recording {
+            commands.push((action.clone(), count));
+        }
+    }
+
+    /// Shown in the status bar while a macro is being recorded
+    pub fn status(&self) -> Option<String> {
+        self.recording
+            .as_ref()
+            .map(|(register, _)| format!("RECORDING @{register}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn records_commands() {
+        let mut macros = KeyMacros::default();
+        let noop = crate::Command::App(crate::ui::app::Command::NoOp);
+
+        macros.record(&noop, 1);
+        assert!(macros.recording.is_none(), "not recording yet");
+
+        macros.recording = Some(('a', vec![]));
+        macros.record(&noop, 3);
+        macros.record(&crate::Command::KeyMacros(Command::ReplayMacro), 1);
+
+        assert_eq!(macros.status().as_deref(), Some("RECORDING @a"));
+        assert_eq!(
+            macros.recording.map(|(_, commands)| commands),
+            Some(vec![(noop, 3)]),
+            "macro commands are not recorded"
+        );
+    }
+}
@@ -2,12 +2,19 @@
 
 use iced::Element;
 
+pub mod animation;
 pub mod app;
 mod background_image;
 pub mod debug_overlay;
 mod errors;
 mod grid;
+pub mod history;
+pub mod key_macros;
 mod selection_icons;
+mod status_bar;
+pub mod toolbar;
+pub mod tutorial;
+pub mod viewport;
 mod welcome_message;
 
 pub mod selection;
@@ -22,9 +29,10 @@ pub mod size_indicator;
 use size_indicator::size_indicator;
 
 use selection_icons::SelectionIcons;
+use status_bar::status_bar;
 use welcome_message::welcome_message;
 
-pub use app::App;
+pub use app::{App, Outcome};
 
 /// An extension trait to show a red border around an element and all children
 #[easy_ext::ext(Explainer)]
@@ -0,0 +1,520 @@
+//! Pick where to save the screenshot, without leaving the app
+//!
+//! Popup contains:
+//!
+//! - Directories the screenshots were recently saved to
+//! - Contents of the current directory
+//! - Name of the file to save the screenshot to
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use iced::{
+    Background, Element,
+    Length::{Fill, Shrink},
+    Size, Task,
+    widget::{button, column, container, horizontal_rule, row, scrollable, text, text_input},
+};
+use image::DynamicImage;
+
+use crate::error_code::ErrorCode;
+use crate::image::{conflict::OnConflict, encode::Format};
+
+/// Name of the file which stores the directories screenshots were recently saved to
+pub const RECENT_DIRS_FILENAME: &str = "ferrishot-recent-dirs.txt";
+
+/// How many of the recent directories to remember
+const MAX_RECENT_DIRS: usize = 5;
+
+/// Could not read or write the recent directories
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Can't find the directory of the file
+    #[error(transparent)]
+    Paths(#[from] crate::paths::Error),
+    /// Failed to read or write the recent directories file
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// An entry of a directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Name of the file or directory
+    pub name: String,
+    /// Whether this is a directory, which can be opened
+    pub is_dir: bool,
+}
+
+/// Contents of `dir`. Directories come first, then files, each sorted by name.
+/// Hidden entries are skipped
+pub fn read_dir(dir: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+
+            (!name.starts_with('.')).then(|| Entry {
+                is_dir: entry.path().is_dir(),
+                name,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(entries)
+}
+
+/// Path to the file which stores the recent directories
+fn recent_dirs_file() -> Result<PathBuf, Error> {
+    Ok(crate::paths::cache_file(RECENT_DIRS_FILENAME)?)
+}
+
+/// Directories the screenshots were recently saved to, most recent first
+pub fn recent_dirs() -> Result<Vec<PathBuf>, Error> {
+    match fs::read_to_string(recent_dirs_file()?) {
+        Ok(contents) => Ok(contents
+            .lines()
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_dir())
+            .collect()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Move `dir` to the front of the `recent` directories, forgetting the oldest ones
+fn push_recent(recent: &[PathBuf], dir: &Path) -> Vec<PathBuf> {
+    std::iter::once(dir.to_path_buf())
+        .chain(recent.iter().filter(|recent| *recent != dir).cloned())
+        .take(MAX_RECENT_DIRS)
+        .collect()
+}
+
+/// Remember that a screenshot was saved to `dir`
+fn remember(recent: &[PathBuf], dir: &Path) -> Result<(), Error> {
+    let contents = push_recent(recent, dir)
+        .iter()
+        .map(|dir| dir.display().to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    fs::write(recent_dirs_file()?, contents)?;
+
+    Ok(())
+}
+
+/// State of the file picker
+#[derive(Debug)]
+pub struct State {
+    /// The screenshot to save
+    image: DynamicImage,
+    /// Directory whose contents are shown, in which the screenshot will be saved
+    pub dir: PathBuf,
+    /// Contents of `dir`
+    pub entries: Vec<Entry>,
+    /// Name of the file to save the screenshot to
+    pub file_name: String,
+    /// Directories the screenshots were recently saved to
+    pub recent_dirs: Vec<PathBuf>,
+    /// File which already exists at the picked path, while asking whether to overwrite it
+    pub conflict: Option<PathBuf>,
+    /// Whether the screenshot is being saved
+    pub is_saving: bool,
+}
+
+impl State {
+    /// Pick where to save the `image`, starting in the directory
+    /// a screenshot was last saved to. The name of the file has the extension of the `format`
+    pub fn new(image: DynamicImage, format: Format) -> Self {
+        let recent_dirs = recent_dirs().unwrap_or_else(|err| {
+            log::error!("Failed to read the recent directories: {err}");
+            vec![]
+        });
+
+        let dir = recent_dirs
+            .first()
+            .cloned()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+
+        let mut state = Self {
+            image,
+            entries: vec![],
+            dir: dir.clone(),
+            file_name: format!(
+                "ferrishot-{}.{}",
+                chrono::Local::now().format("%Y-%m-%d_%H-%M-%S"),
+                format.extension()
+            ),
+            recent_dirs,
+            conflict: None,
+            is_saving: false,
+        };
+
+        if let Err(err) = state.open(dir) {
+            log::error!("Failed to read {}: {err}", state.dir.display());
+        }
+
+        state
+    }
+
+    /// Ask whether to overwrite the file at `path`, which already exists, with the `image`.
+    /// Picking another name instead starts in the directory of the file
+    pub fn conflicting(image: DynamicImage, path: PathBuf) -> Self {
+        // the name of the file is replaced with the one of `path`
+        let mut state = Self::new(image, Format::default());
+
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map_or_else(
+                || std::env::current_dir().unwrap_or_default(),
+                Path::to_path_buf,
+            );
+        if let Err(err) = state.open(dir) {
+            log::error!("Failed to read {}: {err}", state.dir.display());
+        }
+        state.file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        state.conflict = Some(path);
+
+        state
+    }
+
+    /// Show the contents of `dir`
+    fn open(&mut self, dir: PathBuf) -> io::Result<()> {
+        self.entries = read_dir(&dir)?;
+        self.dir = dir;
+
+        Ok(())
+    }
+}
+
+/// Message for the file picker
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// Show the contents of this directory
+    OpenDir(PathBuf),
+    /// Show the contents of the parent of the current directory
+    OpenParent,
+    /// Change the name of the file to save to
+    FileNameChanged(String),
+    /// Save the screenshot in the current directory, exiting
+    Save,
+    /// Replace the file which already exists with the screenshot, exiting
+    Overwrite,
+    /// Save the screenshot next to the file which already exists, with a number
+    /// added to its name, exiting
+    Rename,
+    /// Do not save the screenshot over the file which already exists, to pick another name
+    CancelOverwrite,
+    /// The screenshot was saved to this path, or failed to save
+    Saved(Result<PathBuf, String>),
+}
+
+/// Save the screenshot of the file picker to `path` in the background, so encoding
+/// a large screenshot does not freeze the app
+fn save(
+    state: &mut State,
+    path: PathBuf,
+    config: &crate::Config,
+    on_conflict: OnConflict,
+) -> Task<crate::Message> {
+    state.is_saving = true;
+
+    let image = state.image.clone();
+    let on_duplicate = config.on_duplicate;
+    let parallel_encoding = config.parallel_encoding;
+    let notifications = config.notifications();
+
+    Task::perform(
+        async move {
+            let (image, saved) = tokio::task::spawn_blocking(move || {
+                let saved =
+                    crate::image::save(&image, &path, on_duplicate, on_conflict, parallel_encoding);
+                (image, saved)
+            })
+            .await
+            .map_err(|err| err.to_string())?;
+            let saved_path = saved.map_err(|err| err.to_string())?;
+
+            notifications.saved(&image, &saved_path).await;
+            Ok::<_, String>(saved_path)
+        },
+        |saved| crate::Message::FilePicker(Message::Saved(saved)),
+    )
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut crate::App) -> Task<crate::Message> {
+        let Some(state) = app
+            .popup
+            .as_mut()
+            .and_then(|popup| popup.try_as_file_picker_mut())
+        else {
+            return Task::none();
+        };
+
+        match self {
+            Self::OpenDir(dir) => {
+                if let Err(err) = state.open(dir) {
+                    app.errors
+                        .push(format!("Failed to open the directory: {err}"));
+                }
+            }
+            Self::OpenParent => {
+                if let Some(parent) = state.dir.parent().map(Path::to_path_buf)
+                    && let Err(err) = state.open(parent)
+                {
+                    app.errors
+                        .push(format!("Failed to open the directory: {err}"));
+                }
+            }
+            Self::FileNameChanged(file_name) => {
+                state.file_name = file_name;
+                state.conflict = None;
+            }
+            // the screenshot is already being saved
+            Self::Save | Self::Overwrite | Self::Rename if state.is_saving => {}
+            Self::Save => {
+                let file_name = state.file_name.trim();
+                if file_name.is_empty() {
+                    app.errors.push("Enter a name for the file to save to");
+                    return Task::none();
+                }
+
+                let path = state.dir.join(file_name);
+                if app.config.on_conflict == OnConflict::Ask && path.exists() {
+                    state.conflict = Some(path);
+                    return Task::none();
+                }
+
+                return save(state, path, &app.config, app.config.on_conflict);
+            }
+            Self::Overwrite | Self::Rename => {
+                let Some(path) = state.conflict.take() else {
+                    return Task::none();
+                };
+                let on_conflict = if matches!(self, Self::Overwrite) {
+                    OnConflict::Overwrite
+                } else {
+                    OnConflict::Rename
+                };
+
+                return save(state, path, &app.config, on_conflict);
+            }
+            Self::CancelOverwrite => {
+                state.conflict = None;
+            }
+            Self::Saved(Ok(path)) => {
+                log::info!("Saved the screenshot to {}", path.display());
+
+                if let Err(err) = remember(&state.recent_dirs, &state.dir) {
+                    log::error!("Failed to remember the directory: {err}");
+                }
+
+                return Task::done(crate::Message::Exit);
+            }
+            Self::Saved(Err(err)) => {
+                state.is_saving = false;
+                app.errors.push_code(
+                    ErrorCode::SaveFailed,
+                    format!("Failed to save the screenshot: {err}"),
+                );
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Data for the file picker
+pub struct FilePicker<'app> {
+    /// The App
+    pub app: &'app crate::App,
+    /// State of the file picker
+    pub state: &'app State,
+}
+
+impl<'app> FilePicker<'app> {
+    /// Render the file picker
+    pub fn view(&self) -> Element<'app, crate::Message> {
+        let size = Size::new(700.0, 700.0);
+        let theme = &self.app.config.theme;
+
+        // a file, directory or recent directory
+        let entry = |label: String, message: Message| {
+            button(text(label))
+                .on_press(crate::Message::FilePicker(message))
+                .style(move |_, status| button::Style {
+                    background: matches!(status, button::Status::Hovered)
+                        .then_some(Background::Color(theme.text_selection)),
+                    text_color: theme.file_picker_fg,
+                    ..Default::default()
+                })
+        };
+
+        super::popup(
+            size,
+            container(
+                column![
+                    //
+                    // Heading
+                    //
+                    container(text("Save Screenshot").size(30.0)).center_x(Fill),
+                    //
+                    // Divider
+                    //
+                    container(horizontal_rule(2)).height(10.0),
+                    //
+                    // Current directory
+                    //
+                    row![
+                        entry("..".to_owned(), Message::OpenParent),
+                        text(self.state.dir.display().to_string()),
+                    ]
+                    .spacing(10.0),
+                ]
+                //
+                // Recent directories
+                //
+                .push_maybe((!self.state.recent_dirs.is_empty()).then(|| {
+                    row(self.state.recent_dirs.iter().map(|dir| {
+                        entry(
+                            dir.file_name().map_or_else(
+                                || dir.display().to_string(),
+                                |name| name.to_string_lossy().into_owned(),
+                            ),
+                            Message::OpenDir(dir.clone()),
+                        )
+                        .into()
+                    }))
+                    .spacing(10.0)
+                }))
+                //
+                // Contents of the current directory
+                //
+                .push(
+                    scrollable(
+                        column(self.state.entries.iter().map(|item| {
+                            if item.is_dir {
+                                entry(
+                                    format!("{}/", item.name),
+                                    Message::OpenDir(self.state.dir.join(&item.name)),
+                                )
+                            } else {
+                                entry(
+                                    item.name.clone(),
+                                    Message::FileNameChanged(item.name.clone()),
+                                )
+                            }
+                            .width(Fill)
+                            .into()
+                        }))
+                        .width(Fill),
+                    )
+                    .height(Fill),
+                )
+                //
+                // File name + Save button, or what to do with the file which already exists
+                //
+                .push(if self.state.is_saving {
+                    row![text("Saving...")]
+                } else {
+                    self.state.conflict.as_ref().map_or_else(
+                        || {
+                            row![
+                                text_input("File name", &self.state.file_name)
+                                    .on_input(|file_name| crate::Message::FilePicker(
+                                        Message::FileNameChanged(file_name)
+                                    ))
+                                    .on_submit(crate::Message::FilePicker(Message::Save)),
+                                entry("Save".to_owned(), Message::Save).width(Shrink),
+                            ]
+                            .spacing(10.0)
+                        },
+                        |conflict| {
+                            row![
+                                text(format!(
+                                    "{} already exists",
+                                    conflict
+                                        .file_name()
+                                        .unwrap_or(conflict.as_os_str())
+                                        .to_string_lossy()
+                                ))
+                                .width(Fill),
+                                entry("Overwrite".to_owned(), Message::Overwrite).width(Shrink),
+                                entry("Rename".to_owned(), Message::Rename).width(Shrink),
+                                entry("Cancel".to_owned(), Message::CancelOverwrite).width(Shrink),
+                            ]
+                            .spacing(10.0)
+                        },
+                    )
+                })
+                .spacing(20.0),
+            )
+            .width(size.width)
+            .height(size.height)
+            .style(|_| container::Style {
+                text_color: Some(theme.file_picker_fg),
+                background: Some(Background::Color(theme.file_picker_bg)),
+                ..Default::default()
+            })
+            .padding(30.0),
+            &self.app.config,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn directories_come_first() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["b.png", "a.png", ".hidden"] {
+            fs::write(dir.path().join(file), "").unwrap();
+        }
+        fs::create_dir(dir.path().join("z")).unwrap();
+
+        assert_eq!(
+            read_dir(dir.path()).unwrap(),
+            vec![
+                Entry {
+                    name: "z".to_owned(),
+                    is_dir: true
+                },
+                Entry {
+                    name: "a.png".to_owned(),
+                    is_dir: false
+                },
+                Entry {
+                    name: "b.png".to_owned(),
+                    is_dir: false
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn most_recent_dir_first() {
+        let recent = ["a", "b", "c", "d", "e"].map(PathBuf::from);
+
+        assert_eq!(
+            push_recent(&recent, Path::new("c")),
+            ["c", "a", "b", "d", "e"].map(PathBuf::from)
+        );
+        assert_eq!(
+            push_recent(&recent, Path::new("f")),
+            ["f", "a", "b", "c", "d"].map(PathBuf::from)
+        );
+    }
+}
@@ -4,19 +4,22 @@
 //!
 //! - QR Code
 //! - Copy URL to clipboard
-//! - Image metadata
-//! - Image preview
+//! - Image metadata: dimensions, file size, where it was uploaded and when it expires
+//! - Image preview, which shows the image at full size when clicked
 
 use std::{thread, time::Duration};
 
 use iced::{
     Background, Element,
     Length::{self, Fill},
-    Size, Task,
-    widget::{button, column, container, horizontal_rule, qr_code, row, svg, text, tooltip},
+    Size, Task, mouse,
+    widget::{
+        button, column, container, horizontal_rule, mouse_area, qr_code, row, scrollable, svg,
+        text, tooltip,
+    },
 };
 
-use crate::icon;
+use crate::{error_code::ErrorCode, icon};
 
 use super::Popup;
 use crate::ui::selection_icons::icon_tooltip;
@@ -29,6 +32,10 @@ pub struct State {
     /// When clicking on "Copy" button, change it to be a green tick for a few seconds before
     /// reverting back
     pub has_copied_link: bool,
+    /// Same as `has_copied_link`, for the button which copies the QR code
+    pub has_copied_qr_code: bool,
+    /// The preview is shown at full size, instead of fitting into the popup
+    pub is_preview_zoomed: bool,
 }
 
 /// Message for the image uploaded
@@ -38,13 +45,26 @@ pub enum Message {
     ImageUploaded(ImageUploadedData),
     /// Copy link of image to clipboard
     CopyLink(String),
-    /// Some time has passed after the link was copied
+    /// Copy the QR code of the link to the clipboard, as an image
+    CopyQrCode(String),
+    /// Some time has passed after the link or the QR code was copied
     CopyLinkTimeout,
+    /// Show the preview at full size, or fit it into the popup again
+    TogglePreviewZoom,
 }
 
 impl crate::message::Handler for Message {
     fn handle(self, app: &mut crate::App) -> Task<crate::Message> {
         match self {
+            Self::TogglePreviewZoom => {
+                if let Some(image_uploaded) = app
+                    .popup
+                    .as_mut()
+                    .and_then(|p| p.try_as_image_uploaded_mut())
+                {
+                    image_uploaded.is_preview_zoomed = !image_uploaded.is_preview_zoomed;
+                }
+            }
             Self::CopyLinkTimeout => {
                 if let Some(image_uploaded) = app
                     .popup
@@ -52,11 +72,42 @@ impl crate::message::Handler for Message {
                     .and_then(|p| p.try_as_image_uploaded_mut())
                 {
                     image_uploaded.has_copied_link = false;
+                    image_uploaded.has_copied_qr_code = false;
+                }
+            }
+            Self::CopyQrCode(url) => {
+                let qr_code = match crate::image::qr_code::render(&url) {
+                    Ok(qr_code) => qr_code,
+                    Err(err) => {
+                        app.errors
+                            .push(format!("Failed to render the QR Code: {err}"));
+                        return Task::none();
+                    }
+                };
+
+                if let Err(err) = crate::clipboard::set_image(arboard::ImageData {
+                    width: qr_code.width() as usize,
+                    height: qr_code.height() as usize,
+                    bytes: std::borrow::Cow::Owned(qr_code.into_raw()),
+                }) {
+                    app.errors.push_code(ErrorCode::Clipboard, err.to_string());
+                } else {
+                    if let Some(image_uploaded) = app
+                        .popup
+                        .as_mut()
+                        .and_then(|p| p.try_as_image_uploaded_mut())
+                    {
+                        image_uploaded.has_copied_qr_code = true;
+                    }
+                    return Task::future(async move {
+                        thread::sleep(Duration::from_secs(3));
+                        crate::Message::ImageUploaded(Self::CopyLinkTimeout)
+                    });
                 }
             }
             Self::CopyLink(url) => {
                 if let Err(err) = crate::clipboard::set_text(&url) {
-                    app.errors.push(err.to_string());
+                    app.errors.push_code(ErrorCode::Clipboard, err.to_string());
                 } else {
                     if let Some(image_uploaded) = app
                         .popup
@@ -78,6 +129,8 @@ impl crate::message::Handler for Message {
                         app.popup = Some(Popup::ImageUploaded(State {
                             url: (qr_code, data),
                             has_copied_link: false,
+                            has_copied_qr_code: false,
+                            is_preview_zoomed: false,
                         }));
                         app.selection = None;
                     }
@@ -115,14 +168,90 @@ pub struct ImageUploaded<'app> {
     pub qr_code_data: &'app qr_code::Data,
     /// When the URL Was copied
     pub url_copied: bool,
+    /// When the QR code was copied
+    pub qr_code_copied: bool,
     /// Data of the uploaded image
     pub data: &'app ImageUploadedData,
+    /// Show the preview at full size
+    pub preview_zoomed: bool,
 }
 
 impl<'app> ImageUploaded<'app> {
+    /// The uploaded image, which toggles its zoom when clicked
+    fn preview(&self, full_size: bool) -> Element<'app, crate::Message> {
+        let image = iced::widget::image(self.data.uploaded_image.clone());
+        let image = if full_size {
+            image
+                .width(self.data.width as f32)
+                .height(self.data.height as f32)
+        } else {
+            image.width(Fill)
+        };
+
+        mouse_area(image)
+            .on_press(crate::Message::ImageUploaded(Message::TogglePreviewZoom))
+            .interaction(mouse::Interaction::Pointer)
+            .into()
+    }
+
+    /// Button which copies the QR code to the clipboard, as an image
+    fn copy_qr_code_button(&self) -> Element<'app, crate::Message> {
+        let theme = &self.app.config.theme;
+        let (icon, color, label) = if self.qr_code_copied {
+            (icon!(Check), theme.success, "Copied!")
+        } else {
+            (icon!(Clipboard), theme.image_uploaded_fg, "Copy QR Code")
+        };
+
+        button(
+            row![
+                icon.style(move |_, _| svg::Style { color: Some(color) })
+                    .width(Length::Fixed(20.0))
+                    .height(Length::Fixed(20.0)),
+                text(label).color(color),
+            ]
+            .spacing(10.0),
+        )
+        .on_press(crate::Message::ImageUploaded(Message::CopyQrCode(
+            self.data.image_uploaded.link.clone(),
+        )))
+        .style(|_, _| button::Style {
+            background: Some(Background::Color(iced::Color::TRANSPARENT)),
+            ..Default::default()
+        })
+        .into()
+    }
+
     /// Render the QR Code
     pub fn view(&self) -> Element<'app, crate::Message> {
         let size = Size::new(700.0, 1200.0);
+
+        if self.preview_zoomed {
+            return super::popup(
+                size,
+                container(
+                    column![
+                        container(text("Preview").size(30.0)).center_x(Fill),
+                        container(text("Click the image to go back")).center_x(Fill),
+                        scrollable(self.preview(true)).direction(scrollable::Direction::Both {
+                            vertical: scrollable::Scrollbar::default(),
+                            horizontal: scrollable::Scrollbar::default(),
+                        }),
+                    ]
+                    .spacing(20.0),
+                )
+                .width(size.width)
+                .height(size.height)
+                .style(|_| container::Style {
+                    text_color: Some(self.app.config.theme.image_uploaded_fg),
+                    background: Some(Background::Color(self.app.config.theme.image_uploaded_bg)),
+                    ..Default::default()
+                })
+                .padding(30.0),
+                &self.app.config,
+            );
+        }
+
         super::popup(
             size,
             container(
@@ -177,7 +306,7 @@ impl<'app> ImageUploaded<'app> {
                                                 .height(Length::Fixed(25.0)),
                                         )
                                         .on_press(crate::Message::ImageUploaded(Message::CopyLink(
-                                            self.data.image_uploaded.link.to_string(),
+                                            self.data.image_uploaded.link.clone(),
                                         )))
                                         .style(|_, _| {
                                             button::Style {
@@ -189,7 +318,7 @@ impl<'app> ImageUploaded<'app> {
                                         }),
                                         text(label),
                                         tooltip::Position::Top,
-                                        &self.app.config.theme,
+                                        &self.app.config,
                                     ))
                                     .center_y(Fill)
                                 }
@@ -204,11 +333,15 @@ impl<'app> ImageUploaded<'app> {
                             // QR Code
                             //
                             container(qr_code(self.qr_code_data).total_size(250.0)).center_x(Fill),
+                            //
+                            // Copy the QR Code as an image
+                            //
+                            container(self.copy_qr_code_button()).center_x(Fill),
                         ]
                         .spacing(30.0)
                     )
                     .center(Fill)
-                    .height(Length::Fixed(300.0)),
+                    .height(Length::Fixed(360.0)),
                     //
                     // --- Preview ---
                     //
@@ -235,13 +368,15 @@ impl<'app> ImageUploaded<'app> {
                             "Filesize: {}",
                             human_bytes::human_bytes(self.data.file_size as f64)
                         ),
-                        text!("Link expires in: {}", self.data.image_uploaded.expires_in)
+                        text!("Uploaded to: {}", self.data.image_uploaded.provider),
+                        text!("Link expires in: {}", self.data.image_uploaded.expires_in),
+                        text("Click the preview to see it at full size"),
                     ])
                     .center_x(Fill),
                     //
                     // Image
                     //
-                    iced::widget::image(self.data.uploaded_image.clone()).width(Fill)
+                    self.preview(false)
                 ]
                 .spacing(30.0),
             )
@@ -253,7 +388,7 @@ impl<'app> ImageUploaded<'app> {
                 ..Default::default()
             })
             .padding(30.0),
-            &self.app.config.theme,
+            &self.app.config,
         )
     }
 }
@@ -1,5 +1,7 @@
 //! Keybindings cheatsheet
 
+use std::path::Path;
+
 use iced::{
     Background, Element, Font,
     Length::Fill,
@@ -9,7 +11,7 @@ use iced::{
     widget::{
         canvas,
         canvas::{LineCap, LineJoin, Stroke},
-        column, container,
+        column, container, text,
         text::Shaping,
     },
 };
@@ -23,6 +25,7 @@ use crate::{
 use super::Popup;
 
 crate::declare_commands! {
+    #[derive(Copy)]
     enum Command {
         /// Open the keybindings cheatsheet
         OpenKeybindingsCheatsheet,
@@ -41,6 +44,45 @@ impl crate::command::Handler for Command {
     }
 }
 
+/// Size of the part of the cheatsheet with the default keybindings
+const CANVAS_SIZE: Size = Size::new(1550.0, 1000.0);
+
+/// Could not export the cheatsheet
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum ExportError {
+    /// Neither the GPU nor the software renderer is available
+    #[error("Could not create a renderer to draw the cheatsheet")]
+    NoRenderer,
+    /// Could not encode or write the image
+    #[error(transparent)]
+    Image(#[from] image::ImageError),
+}
+
+/// Draw the cheatsheet offscreen and save it as an image at `path`, to print or share it.
+///
+/// The format of the image is chosen from the extension of the `path`, like `.png`
+pub async fn export(config: &crate::Config, path: &Path) -> Result<(), ExportError> {
+    use iced::advanced::graphics::geometry::Renderer as _;
+    use iced::advanced::renderer::Headless;
+
+    let mut renderer = <Renderer as Headless>::new(Font::DEFAULT, Pixels(16.0), None)
+        .await
+        .ok_or(ExportError::NoRenderer)?;
+
+    let mut frame = canvas::Frame::new(&renderer, CANVAS_SIZE);
+    KeybindingsCheatsheet { config }.draw_cheatsheet(&mut frame);
+    renderer.draw_geometry(frame.into_geometry());
+
+    let size = Size::new(CANVAS_SIZE.width as u32, CANVAS_SIZE.height as u32);
+    let pixels = renderer.screenshot(size, 1.0, config.theme.cheatsheet_bg);
+
+    image::RgbaImage::from_raw(size.width, size.height, pixels)
+        .expect("screenshot has 4 bytes for each pixel")
+        .save(path)?;
+
+    Ok(())
+}
+
 /// Keybindings cheatsheet message
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -64,24 +106,59 @@ impl crate::message::Handler for Message {
 /// Show a cheatsheet for the default keybindings available in ferrishot
 #[derive(Debug, Copy, Clone)]
 pub struct KeybindingsCheatsheet<'app> {
-    /// Theme of the app
-    pub theme: &'app crate::Theme,
+    /// Config of the app
+    pub config: &'app crate::Config,
 }
 
 impl<'app> KeybindingsCheatsheet<'app> {
     /// Show the keybinding cheatsheet
     pub fn view(self) -> Element<'app, crate::Message> {
-        let size = Size::new(1550.0, 1000.0);
+        /// Height of each keybinding with a description
+        const DESCRIBED_HEIGHT: f32 = 26.0;
+        /// Space taken by the title of the keybindings with a description
+        const DESCRIBED_TITLE_HEIGHT: f32 = 60.0;
+
+        let described = self.config.keys.described();
+
+        let size = Size::new(
+            CANVAS_SIZE.width,
+            if described.is_empty() {
+                CANVAS_SIZE.height
+            } else {
+                (described.len() as f32).mul_add(
+                    DESCRIBED_HEIGHT,
+                    CANVAS_SIZE.height + DESCRIBED_TITLE_HEIGHT,
+                )
+            },
+        );
+
+        // keybindings which the user described in the config
+        let described = (!described.is_empty()).then(|| {
+            column![text("Your keybindings:").size(30.0).font(Font::MONOSPACE)]
+                .extend(described.into_iter().map(|(keys, _, description)| {
+                    text(format!("{keys:<20} {description}"))
+                        .size(18.0)
+                        .font(Font::MONOSPACE)
+                        .shaping(Shaping::Advanced)
+                        .into()
+                }))
+                .spacing(8.0)
+                .padding([0.0, 60.0])
+        });
+
         super::popup(
             size,
-            container(column![canvas(self).width(Fill).height(Fill)])
-                .style(|_| container::Style {
-                    background: Some(Background::Color(self.theme.cheatsheet_bg)),
-                    ..Default::default()
-                })
-                .width(size.width)
-                .height(size.height),
-            self.theme,
+            container(
+                column![canvas(self).width(Fill).height(CANVAS_SIZE.height)].push_maybe(described),
+            )
+            .style(|_| container::Style {
+                text_color: Some(self.config.theme.cheatsheet_fg),
+                background: Some(Background::Color(self.config.theme.cheatsheet_bg)),
+                ..Default::default()
+            })
+            .width(size.width)
+            .height(size.height),
+            self.config,
         )
     }
 }
@@ -107,26 +184,34 @@ type CellDefinition<'a> = (
 );
 
 impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
-    type State = ();
+    /// The cheatsheet never changes while it is open, so it is only drawn once
+    type State = canvas::Cache;
 
     fn draw(
         &self,
-        _state: &Self::State,
+        state: &Self::State,
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
         _cursor: iced::advanced::mouse::Cursor,
     ) -> Vec<canvas::Geometry> {
+        vec![state.draw(renderer, bounds.size(), |frame| {
+            self.draw_cheatsheet(frame);
+        })]
+    }
+}
+
+impl KeybindingsCheatsheet<'_> {
+    /// Draw every cell of the cheatsheet
+    fn draw_cheatsheet(&self, frame: &mut canvas::Frame) {
         /// How far away the `new` selection from the `old` selection should be
         const SEL_NEW_OLD_OFFSET: f32 = 20.0;
         /// Size of each arrow
         const ARROW_ICON_SIZE: f32 = 18.0;
 
-        let mut frame = canvas::Frame::new(renderer, bounds.size());
-
         let theme_with_dimmed_sel = crate::Theme {
-            selection_frame: self.theme.selection_frame.scale_alpha(0.3),
-            ..*self.theme
+            selection_frame: self.config.theme.selection_frame.scale_alpha(0.3),
+            ..self.config.theme
         };
 
         let cell_definitions: [CellDefinition; 12] = [
@@ -268,7 +353,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                         )
                         .with_size(|_| Size::square(sel_size));
 
-                        let new_sel = compute_new_sel(old_sel).with_theme(self.theme);
+                        let new_sel = compute_new_sel(old_sel).with_theme(&self.config.theme);
 
                         let icon_pos_relative = icon_pos_fn(new_sel);
 
@@ -283,7 +368,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                                 width: ARROW_ICON_SIZE,
                                 height: ARROW_ICON_SIZE,
                             },
-                            Svg::new(icon.svg()).color(self.theme.cheatsheet_fg),
+                            Svg::new(icon.svg()).color(self.config.theme.cheatsheet_fg),
                         );
 
                         // draw selection AFTER transformation
@@ -292,14 +377,14 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                     })
                     .label(canvas::Text {
                         content: key.to_string(),
-                        color: self.theme.cheatsheet_fg,
+                        color: self.config.theme.cheatsheet_fg,
                         font: Font::MONOSPACE,
                         shaping: Shaping::Advanced,
                         ..Default::default()
                     })
                     .description(canvas::Text {
                         content: label.to_string(),
-                        color: self.theme.selection_frame,
+                        color: self.config.theme.selection_frame,
                         font: Font {
                             family: Family::Monospace,
                             weight: Weight::Normal,
@@ -321,7 +406,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
             .title((
                 geometry::Text {
                     content: "Transform region by 1px:".to_string(),
-                    color: self.theme.cheatsheet_fg,
+                    color: self.config.theme.cheatsheet_fg,
                     font: Font::MONOSPACE,
                     size: Pixels(30.0),
                     shaping: Shaping::Advanced,
@@ -333,7 +418,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                 geometry::Text {
                     content: "Hold ALT while doing any of the above to transform by 125px!"
                         .to_string(),
-                    color: self.theme.cheatsheet_fg,
+                    color: self.config.theme.cheatsheet_fg,
                     size: Pixels(20.0),
                     font: Font::MONOSPACE,
                     shaping: Shaping::Advanced,
@@ -346,7 +431,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
 
         let basic_bindings_size = basic_bindings.size();
 
-        basic_bindings.draw(&mut frame);
+        basic_bindings.draw(frame);
 
         let region_movement_bindings_data: &[(&str, &str, SelectionTransformer)] = &[
             (
@@ -424,7 +509,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                 canvas::Text {
                     content: "Move region:".to_string(),
                     size: 30.0.into(),
-                    color: self.theme.cheatsheet_fg,
+                    color: self.config.theme.cheatsheet_fg,
                     font: Font::MONOSPACE,
                     shaping: Shaping::Advanced,
                     ..Default::default()
@@ -455,13 +540,13 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
 
                                 let new_sel =
                                     transform_old_sel(origin, sel_size, cell_size, old_sel)
-                                        .with_theme(self.theme);
+                                        .with_theme(&self.config.theme);
 
                                 new_sel.draw_border(frame);
                                 new_sel.draw_corners(frame);
                             })
                             .stroke(Stroke {
-                                style: geometry::Style::Solid(self.theme.cheatsheet_fg),
+                                style: geometry::Style::Solid(self.config.theme.cheatsheet_fg),
                                 width: 1.0,
                                 line_cap: LineCap::Round,
                                 line_join: LineJoin::Round,
@@ -472,14 +557,14 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                             })
                             .label(canvas::Text {
                                 content: (*key).to_string(),
-                                color: self.theme.cheatsheet_fg,
+                                color: self.config.theme.cheatsheet_fg,
                                 font: Font::MONOSPACE,
                                 shaping: Shaping::Advanced,
                                 ..Default::default()
                             })
                             .description(canvas::Text {
                                 content: (*desc).to_string(),
-                                color: self.theme.selection_frame,
+                                color: self.config.theme.selection_frame,
                                 font: Font {
                                     family: Family::Monospace,
                                     weight: Weight::Normal,
@@ -496,14 +581,14 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
             .build();
 
         let region_movement_bindings_rect = region_movement_bindings.rect();
-        region_movement_bindings.draw(&mut frame);
+        region_movement_bindings.draw(frame);
 
         Grid::builder()
             .top_left(region_movement_bindings_rect.bottom_left() + Vector::y(60.0))
             .title((
                 canvas::Text {
                     content: "Pick top and then bottom corners".into(),
-                    color: self.theme.cheatsheet_fg,
+                    color: self.config.theme.cheatsheet_fg,
                     size: Pixels(30.0),
                     font: Font::MONOSPACE,
                     ..Default::default()
@@ -513,7 +598,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
             .description((
                 canvas::Text {
                     content: "select any area of the screen in 8 keystrokes!".into(),
-                    color: self.theme.cheatsheet_fg,
+                    color: self.config.theme.cheatsheet_fg,
                     size: Pixels(20.0),
                     font: Font::MONOSPACE,
                     ..Default::default()
@@ -527,15 +612,19 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                     .draw(|frame, cell_rect| {
                         let sel_size = Size::square(100.0);
 
-                        let sel =
-                            Selection::new(cell_rect.center_for(sel_size), self.theme, false, None)
-                                .with_size(|_| sel_size);
+                        let sel = Selection::new(
+                            cell_rect.center_for(sel_size),
+                            &self.config.theme,
+                            false,
+                            None,
+                        )
+                        .with_size(|_| sel_size);
 
                         sel.draw_border(frame);
                         sel.draw_corners(frame);
 
                         let dotted_stroke = Stroke {
-                            style: canvas::Style::Solid(self.theme.selection_frame),
+                            style: canvas::Style::Solid(self.config.theme.selection_frame),
                             width: 3.0,
                             line_cap: LineCap::Round,
                             line_join: LineJoin::Round,
@@ -560,7 +649,7 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                         frame.fill_text(canvas::Text {
                             content: "Pick top left corner: t".into(),
                             position: sel.top_left() - Vector::new(200.0, 20.0),
-                            color: self.theme.cheatsheet_fg,
+                            color: self.config.theme.cheatsheet_fg,
                             ..Default::default()
                         });
 
@@ -568,15 +657,13 @@ impl canvas::Program<crate::Message> for KeybindingsCheatsheet<'_> {
                         frame.fill_text(canvas::Text {
                             content: "Pick bottom right corner: b".into(),
                             position: sel.bottom_right() + Vector::x(50.0),
-                            color: self.theme.cheatsheet_fg,
+                            color: self.config.theme.cheatsheet_fg,
                             ..Default::default()
                         });
                     })
                     .build(),
             ])
             .build()
-            .draw(&mut frame);
-
-        vec![frame.into_geometry()]
+            .draw(frame);
     }
 }
@@ -19,6 +19,7 @@ use crate::ui::selection::Selection;
 use super::Popup;
 
 crate::declare_commands! {
+    #[derive(Copy)]
     enum Command {
         /// Open a grid of letters to pick the top left corner in 3 keystrokes
         PickTopLeftCorner,
@@ -72,6 +73,11 @@ impl crate::message::Handler for Message {
     fn handle(self, app: &mut crate::App) -> Task<crate::Message> {
         match self {
             Self::Pick { point, corner } => {
+                if app.selection.is_some_and(|sel| sel.is_locked) {
+                    app.errors.warn("The selection is locked");
+                    return Task::none();
+                }
+
                 let sel = app.selection.map_or_else(
                     || {
                         // Intentionally do not increment `app.selections`, because
@@ -82,11 +88,14 @@ impl crate::message::Handler for Message {
                             false,
                             app.cli.accept_on_select,
                         )
+                        .with_grab_area(app.config.grab_area())
                     },
                     Selection::norm,
                 );
-                let x = point.x;
-                let y = point.y;
+                // letters cover the whole window, which can be larger than the screenshot
+                let point = app.viewport.to_image(point);
+                let x = point.x.clamp(0.0, app.viewport.image.width);
+                let y = point.y.clamp(0.0, app.viewport.image.height);
                 let new_sel = match corner {
                     PickCorner::TopLeft => {
                         sel.with_x(|_| x)
@@ -101,16 +110,16 @@ impl crate::message::Handler for Message {
                 };
                 app.selection = Some(new_sel);
 
-                if let Some(on_select) = app.cli.accept_on_select {
-                    if new_sel.size() != Size::ZERO {
-                        if app.selections_created == 0 {
-                            return Task::done(crate::Message::Command {
-                                action: on_select.into_key_action(),
-                                count: 1,
-                            });
-                        }
-                        app.selections_created += 1;
+                if let Some(on_select) = app.cli.accept_on_select
+                    && new_sel.size() != Size::ZERO
+                {
+                    if app.selections_created == 0 {
+                        return Task::done(crate::Message::Command {
+                            action: on_select.into_key_action(),
+                            count: 1,
+                        });
                     }
+                    app.selections_created += 1;
                 }
                 app.popup = None;
             }
@@ -329,7 +338,11 @@ impl canvas::Program<crate::Message> for Letters<'_> {
         frame.fill_rectangle(
             bounds.position(),
             bounds.size(),
-            self.app.config.theme.letters_bg,
+            self.app
+                .config
+                .theme
+                .letters_bg
+                .scale_alpha(self.app.popup_progress()),
         );
 
         let x_start = 0.0;
@@ -2,6 +2,10 @@
 //! They block any inputs
 //!
 //! Only one of the popups can be active at any time (see `Popup` enum)
+#![expect(
+    clippy::double_parens,
+    reason = "false positive in the expansion of `strum::EnumTryAs`"
+)]
 
 pub mod keybindings_cheatsheet;
 use iced::Background;
@@ -18,9 +22,25 @@ use iced::widget::{
 pub mod letters;
 pub use letters::Letters;
 
+pub mod pixel_inspector;
+pub use pixel_inspector::PixelInspector;
+
+pub mod file_picker;
+pub use file_picker::FilePicker;
+
+pub mod screen_recording_permission;
+pub use screen_recording_permission::ScreenRecordingPermission;
+
+pub mod settings;
+pub use settings::Settings;
+
 /// Popup are overlaid on top and they block any events. allowing only Escape to close
 /// the popup.
 #[derive(Debug, strum::EnumTryAs)]
+#[expect(
+    clippy::large_enum_variant,
+    reason = "only a single popup exists at any time"
+)]
 pub enum Popup {
     /// Letters allow picking a one of 10,000+ regions on the screen in 4 keystrokes
     Letters(letters::State),
@@ -28,6 +48,31 @@ pub enum Popup {
     ImageUploaded(image_uploaded::State),
     /// Shows available commands
     KeyCheatsheet,
+    /// Magnified view of the pixels around the cursor
+    PixelInspector,
+    /// Pick where to save the screenshot
+    FilePicker(file_picker::State),
+    /// Screenshots are not allowed on macOS, show how to allow them
+    ScreenRecordingPermission,
+    /// Change the most common options
+    Settings,
+}
+
+/// How far below its place a popup starts when it opens, before sliding up into place
+const OPEN_SLIDE: f32 = 40.0;
+
+/// Slide the centered `popup` up into place as it opens.
+///
+/// `progress` is how far along the opening animation is, from `0.0` to `1.0`
+pub fn opening(popup: Element<'_, crate::Message>, progress: f32) -> Element<'_, crate::Message> {
+    if progress >= 1.0 {
+        return popup;
+    }
+
+    // the popup is centered, so it only moves down by half of the padding
+    container(popup)
+        .padding(iced::padding::top(2.0 * OPEN_SLIDE * (1.0 - progress)))
+        .into()
 }
 
 /// Elements inside of a `popup` render in the center of the screen
@@ -35,8 +80,9 @@ pub enum Popup {
 fn popup<'app>(
     size: iced::Size,
     contents: impl Into<Element<'app, crate::Message>>,
-    theme: &'app crate::Theme,
+    config: &'app crate::Config,
 ) -> Element<'app, crate::Message> {
+    let theme = &config.theme;
     container(stack![
         contents.into(),
         //
@@ -62,7 +108,7 @@ fn popup<'app>(
                     }),
                     "Close",
                     tooltip::Position::Right,
-                    theme
+                    config
                 ),
                 horizontal_space().width(10.0)
             ]
@@ -0,0 +1,282 @@
+//! Inspect individual pixels of the screenshot under a magnifying glass
+
+use iced::{
+    Color, Element, Event, Font,
+    Length::Fill,
+    Point, Rectangle, Renderer, Size, Task, Theme, Vector,
+    keyboard::{self, Key, key::Named},
+    mouse::{self, Interaction},
+    widget::{
+        Action, Canvas,
+        canvas::{self, Path, Stroke},
+    },
+};
+
+use super::Popup;
+
+crate::declare_commands! {
+    #[derive(Copy)]
+    enum Command {
+        /// Open a magnified view of the pixels under the cursor
+        OpenPixelInspector,
+    }
+}
+
+impl crate::command::Handler for Command {
+    fn handle(self, app: &mut crate::App, _count: u32) -> Task<crate::Message> {
+        match self {
+            Self::OpenPixelInspector => {
+                app.popup = Some(Popup::PixelInspector);
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Pixel inspector message
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Copy hex color of the inspected pixel to the clipboard, closing the inspector
+    CopyColor(String),
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut crate::App) -> Task<crate::Message> {
+        match self {
+            Self::CopyColor(hex) => {
+                if let Err(err) = crate::clipboard::set_text(&hex) {
+                    app.errors.push(err.to_string());
+                } else {
+                    app.popup = None;
+                }
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// How many times each pixel is magnified
+const ZOOM: f32 = 16.0;
+/// How many pixels to show in each direction around the inspected pixel
+const RADIUS: i64 = 7;
+/// Distance between the inspected pixel and the magnified view
+const CURSOR_OFFSET: f32 = 24.0;
+/// Height of the area below the magnified pixels, containing information about the inspected pixel
+const LABEL_HEIGHT: f32 = 48.0;
+/// Size of the text with information about the inspected pixel
+const FONT_SIZE: f32 = 16.0;
+
+/// State of the pixel inspector
+#[derive(Debug, Default)]
+pub struct State {
+    /// Pixel moved to with the arrow keys. When `None`, the pixel under the cursor
+    /// is inspected instead
+    pinned: Option<Point>,
+}
+
+/// Shows a zoomed in view around the cursor, with a grid of pixels and
+/// the coordinates and color of the pixel in the center
+#[derive(Debug, Clone, Copy)]
+pub struct PixelInspector<'app> {
+    /// The App
+    pub app: &'app crate::App,
+}
+
+impl<'app> PixelInspector<'app> {
+    /// Render the pixel inspector
+    pub fn view(self) -> Element<'app, crate::Message> {
+        Canvas::new(self).width(Fill).height(Fill).into()
+    }
+
+    /// Pixel that is currently being inspected
+    fn inspected(state: &State, bounds: Rectangle, cursor: mouse::Cursor) -> Option<Point> {
+        state
+            .pinned
+            .or_else(|| cursor.position_in(bounds))
+            .map(|point| Point::new(point.x.floor(), point.y.floor()))
+    }
+
+    /// Color of the pixel at the given coordinates, if it is inside of the image
+    fn pixel(self, x: i64, y: i64) -> Option<[u8; 4]> {
+        self.app
+            .image
+            .pixel(u32::try_from(x).ok()?, u32::try_from(y).ok()?)
+    }
+}
+
+impl canvas::Program<crate::Message> for PixelInspector<'_> {
+    type State = State;
+
+    fn draw(
+        &self,
+        state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let theme = &self.app.config.theme;
+
+        let viewport = self.app.viewport;
+        let Some(inspected) =
+            Self::inspected(state, viewport.image_bounds(), viewport.cursor(cursor))
+        else {
+            return vec![frame.into_geometry()];
+        };
+        // where the inspected pixel is shown in the window
+        let anchor = viewport.to_window(inspected);
+
+        let cells = (RADIUS * 2 + 1) as f32;
+        let grid_size = cells * ZOOM;
+        let size = Size::new(grid_size, grid_size + LABEL_HEIGHT);
+
+        // Show the magnified view at the bottom-right of the inspected pixel,
+        // unless that would make it go off-screen
+        let mut origin = anchor + Vector::new(CURSOR_OFFSET, CURSOR_OFFSET);
+        if origin.x + size.width > bounds.width {
+            origin.x = anchor.x - CURSOR_OFFSET - size.width;
+        }
+        if origin.y + size.height > bounds.height {
+            origin.y = anchor.y - CURSOR_OFFSET - size.height;
+        }
+        origin.x = origin.x.max(0.0);
+        origin.y = origin.y.max(0.0);
+
+        frame.fill_rectangle(origin, size, theme.pixel_inspector_bg);
+
+        for dy in -RADIUS..=RADIUS {
+            for dx in -RADIUS..=RADIUS {
+                let Some([r, g, b, a]) =
+                    self.pixel(inspected.x as i64 + dx, inspected.y as i64 + dy)
+                else {
+                    continue;
+                };
+
+                frame.fill_rectangle(
+                    origin + Vector::new((dx + RADIUS) as f32, (dy + RADIUS) as f32) * ZOOM,
+                    Size::new(ZOOM, ZOOM),
+                    Color::from_rgba8(r, g, b, f32::from(a) / f32::from(u8::MAX)),
+                );
+            }
+        }
+
+        // grid which separates each pixel
+        let grid = Path::new(|path| {
+            for i in 0..=RADIUS * 2 + 1 {
+                let offset = i as f32 * ZOOM;
+                path.move_to(origin + Vector::new(offset, 0.0));
+                path.line_to(origin + Vector::new(offset, grid_size));
+                path.move_to(origin + Vector::new(0.0, offset));
+                path.line_to(origin + Vector::new(grid_size, offset));
+            }
+        });
+        frame.stroke(
+            &grid,
+            Stroke::default()
+                .with_color(theme.pixel_inspector_grid)
+                .with_width(1.0),
+        );
+
+        // highlight the pixel in the center
+        frame.stroke_rectangle(
+            origin + Vector::new(RADIUS as f32, RADIUS as f32) * ZOOM,
+            Size::new(ZOOM, ZOOM),
+            Stroke::default()
+                .with_color(theme.selection_frame)
+                .with_width(2.0),
+        );
+
+        let color = self
+            .pixel(inspected.x as i64, inspected.y as i64)
+            .map_or_else(
+                || "-".to_owned(),
+                |[r, g, b, _]| format!("#{r:02x}{g:02x}{b:02x}"),
+            );
+
+        frame.fill_text(canvas::Text {
+            content: format!("{}, {}\n{color}", inspected.x, inspected.y),
+            position: origin + Vector::new(8.0, grid_size + 6.0),
+            color: theme.pixel_inspector_fg,
+            size: FONT_SIZE.into(),
+            font: Font::MONOSPACE,
+            ..Default::default()
+        });
+
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: &Event,
+        _bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Option<Action<crate::Message>> {
+        // pixels are inspected in the coordinates of the screenshot
+        let bounds = self.app.viewport.image_bounds();
+        let cursor = self.app.viewport.cursor(cursor);
+
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                state.pinned = None;
+                return Some(Action::request_redraw());
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+                let step = if modifiers.shift() { 10.0 } else { 1.0 };
+
+                let offset = match key.as_ref() {
+                    Key::Named(Named::ArrowLeft) | Key::Character("h" | "H") => {
+                        Vector::new(-step, 0.0)
+                    }
+                    Key::Named(Named::ArrowDown) | Key::Character("j" | "J") => {
+                        Vector::new(0.0, step)
+                    }
+                    Key::Named(Named::ArrowUp) | Key::Character("k" | "K") => {
+                        Vector::new(0.0, -step)
+                    }
+                    Key::Named(Named::ArrowRight) | Key::Character("l" | "L") => {
+                        Vector::new(step, 0.0)
+                    }
+                    Key::Named(Named::Escape) => {
+                        return Some(Action::publish(crate::Message::ClosePopup));
+                    }
+                    Key::Named(Named::Enter) | Key::Character("y") => {
+                        let inspected = Self::inspected(state, bounds, cursor)?;
+                        let [r, g, b, _] = self.pixel(inspected.x as i64, inspected.y as i64)?;
+
+                        return Some(Action::publish(crate::Message::PixelInspector(
+                            Message::CopyColor(format!("#{r:02x}{g:02x}{b:02x}")),
+                        )));
+                    }
+                    _ => return Some(Action::capture()),
+                };
+
+                if let Some(inspected) = Self::inspected(state, bounds, cursor) {
+                    let moved = inspected + offset;
+                    state.pinned = Some(Point::new(
+                        moved.x.clamp(0.0, bounds.width - 1.0),
+                        moved.y.clamp(0.0, bounds.height - 1.0),
+                    ));
+                }
+
+                return Some(Action::request_redraw());
+            }
+            _ => (),
+        }
+
+        // Any unrecognized event should not propagate to the `App`
+        Some(Action::capture())
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        _bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Interaction {
+        Interaction::Crosshair
+    }
+}
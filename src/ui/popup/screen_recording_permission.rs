@@ -0,0 +1,102 @@
+//! Ask for the Screen Recording permission on macOS
+//!
+//! Without it, screenshots only contain the wallpaper. The permission
+//! can only be granted by the user, in the System Settings
+//!
+//! Popup contains:
+//!
+//! - Steps to grant the permission
+//! - Button which opens the right page of the System Settings
+
+use iced::{
+    Background, Element,
+    Length::Fill,
+    Size, Task,
+    widget::{button, column, container, horizontal_rule, text},
+};
+
+/// Message for the screen recording permission popup
+#[derive(Clone, Debug)]
+pub enum Message {
+    /// Open the page of the System Settings where the permission is granted
+    OpenSettings,
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut crate::App) -> Task<crate::Message> {
+        match self {
+            Self::OpenSettings => {
+                if let Err(err) = crate::image::screenshot::open_permission_settings() {
+                    app.errors
+                        .push(format!("Failed to open the System Settings: {err}"));
+                }
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Data for the screen recording permission popup
+pub struct ScreenRecordingPermission<'app> {
+    /// The App
+    pub app: &'app crate::App,
+}
+
+impl<'app> ScreenRecordingPermission<'app> {
+    /// Render the screen recording permission popup
+    pub fn view(&self) -> Element<'app, crate::Message> {
+        let size = Size::new(600.0, 400.0);
+        let theme = &self.app.config.theme;
+
+        super::popup(
+            size,
+            container(
+                column![
+                    //
+                    // Heading
+                    //
+                    container(text("Screen Recording Permission").size(30.0)).center_x(Fill),
+                    //
+                    // Divider
+                    //
+                    container(horizontal_rule(2)).height(10.0),
+                    //
+                    // Steps
+                    //
+                    text("Without it, screenshots only contain your wallpaper."),
+                    text("1. Open the System Settings > Privacy & Security > Screen Recording"),
+                    text("2. Turn on ferrishot"),
+                    text("3. Restart ferrishot"),
+                    //
+                    // Open System Settings
+                    //
+                    container(
+                        button(text("Open System Settings"))
+                            .on_press(crate::Message::ScreenRecordingPermission(
+                                Message::OpenSettings
+                            ))
+                            .style(move |_, status| button::Style {
+                                background: matches!(status, button::Status::Hovered)
+                                    .then_some(Background::Color(theme.text_selection)),
+                                text_color: theme.permission_fg,
+                                ..Default::default()
+                            })
+                            .padding(10.0)
+                    )
+                    .center_x(Fill),
+                ]
+                .spacing(20.0),
+            )
+            .width(size.width)
+            .height(size.height)
+            .style(|_| container::Style {
+                text_color: Some(theme.permission_fg),
+                background: Some(Background::Color(theme.permission_bg)),
+                ..Default::default()
+            })
+            .padding(30.0),
+            &self.app.config,
+        )
+    }
+}
@@ -0,0 +1,311 @@
+//! Change the most common options without editing the config file by hand
+//!
+//! Each change is written to the user's `ferrishot.kdl` right away, keeping the
+//! comments and formatting of the rest of the file. The config is then read again,
+//! so the change also takes effect right away.
+
+use std::{path::Path, sync::Arc};
+
+use iced::{
+    Background, Element,
+    Length::Fill,
+    Size, Task,
+    widget::{button, column, container, horizontal_rule, horizontal_space, row, text},
+};
+
+use crate::config::{Preset, write};
+use crate::error_code::ErrorCode;
+use crate::image::{encode::Format, upload::UploadProvider};
+
+use super::Popup;
+
+crate::declare_commands! {
+    #[derive(Copy)]
+    enum Command {
+        /// Open the settings
+        OpenSettings,
+    }
+}
+
+impl crate::command::Handler for Command {
+    fn handle(self, app: &mut crate::App, _count: u32) -> Task<crate::Message> {
+        match self {
+            Self::OpenSettings => {
+                app.popup = Some(Popup::Settings);
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// An option which can be changed in the settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    /// `size-indicator`
+    SizeIndicator,
+    /// `dimensions-label`
+    DimensionsLabel,
+    /// `selection-icons`
+    SelectionIcons,
+    /// `toolbar`
+    Toolbar,
+    /// `tooltips`
+    Tooltips,
+    /// `preserve-transparency`
+    PreserveTransparency,
+    /// `allow-upload`
+    Upload,
+    /// `parallel-encoding`
+    ParallelEncoding,
+    /// `animate-selection`
+    AnimateSelection,
+    /// `reduced-motion`
+    ReducedMotion,
+    /// `detailed-errors`
+    DetailedErrors,
+    /// `theme-preset`
+    ThemePreset,
+    /// `image-format`
+    ImageFormat,
+    /// `upload-provider`
+    UploadProvider,
+}
+
+impl Setting {
+    /// Every setting, in the order they are shown
+    pub const ALL: [Self; 14] = [
+        Self::SizeIndicator,
+        Self::DimensionsLabel,
+        Self::SelectionIcons,
+        Self::Toolbar,
+        Self::Tooltips,
+        Self::PreserveTransparency,
+        Self::Upload,
+        Self::ParallelEncoding,
+        Self::AnimateSelection,
+        Self::ReducedMotion,
+        Self::DetailedErrors,
+        Self::ThemePreset,
+        Self::ImageFormat,
+        Self::UploadProvider,
+    ];
+
+    /// Name of the option in the config
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::SizeIndicator => "size-indicator",
+            Self::DimensionsLabel => "dimensions-label",
+            Self::SelectionIcons => "selection-icons",
+            Self::Toolbar => "toolbar",
+            Self::Tooltips => "tooltips",
+            Self::PreserveTransparency => "preserve-transparency",
+            Self::Upload => "allow-upload",
+            Self::ParallelEncoding => "parallel-encoding",
+            Self::AnimateSelection => "animate-selection",
+            Self::ReducedMotion => "reduced-motion",
+            Self::DetailedErrors => "detailed-errors",
+            Self::ThemePreset => "theme-preset",
+            Self::ImageFormat => "image-format",
+            Self::UploadProvider => "upload-provider",
+        }
+    }
+
+    /// What the setting does, as shown to the user
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::SizeIndicator => "Show the size of the selection in its corner",
+            Self::DimensionsLabel => "Show the size of the selection above it",
+            Self::SelectionIcons => "Show icons around the selection",
+            Self::Toolbar => "Show the icons in a floating toolbar",
+            Self::Tooltips => "Show tooltips when hovering the icons",
+            Self::PreserveTransparency => "Keep transparent pixels of the screenshot",
+            Self::Upload => "Allow uploading screenshots to the internet",
+            Self::ParallelEncoding => "Compress large screenshots on all cores",
+            Self::AnimateSelection => "Glide the selection when moving it",
+            Self::ReducedMotion => "Turn off all animations",
+            Self::DetailedErrors => "Show how to fix errors",
+            Self::ThemePreset => "Colors",
+            Self::ImageFormat => "Format of uploaded screenshots",
+            Self::UploadProvider => "Where to upload screenshots",
+        }
+    }
+
+    /// Value of the setting in the `config`
+    pub fn get(self, config: &crate::Config) -> Value {
+        match self {
+            Self::SizeIndicator => Value::Toggle(config.size_indicator),
+            Self::DimensionsLabel => Value::Toggle(config.dimensions_label),
+            Self::SelectionIcons => Value::Toggle(config.selection_icons),
+            Self::Toolbar => Value::Toggle(config.toolbar),
+            Self::Tooltips => Value::Toggle(config.tooltips),
+            Self::PreserveTransparency => Value::Toggle(config.preserve_transparency),
+            Self::Upload => Value::Toggle(config.allow_upload),
+            Self::ParallelEncoding => Value::Toggle(config.parallel_encoding),
+            Self::AnimateSelection => Value::Toggle(config.animate_selection),
+            Self::ReducedMotion => Value::Toggle(config.reduced_motion),
+            Self::DetailedErrors => Value::Toggle(config.detailed_errors),
+            Self::ThemePreset => Value::choice(&Preset::ALL, config.theme_preset, Preset::name),
+            Self::ImageFormat => Value::choice(&Format::ALL, config.image_format, Format::name),
+            Self::UploadProvider => Value::choice(
+                &UploadProvider::ALL,
+                config.upload_provider,
+                UploadProvider::name,
+            ),
+        }
+    }
+}
+
+/// Value of a setting, which is either turned on or off, or one of a few choices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    /// Whether the setting is on
+    Toggle(bool),
+    /// Names of the chosen value, and of the value after it, as written in the config
+    Choice {
+        /// The chosen value
+        current: &'static str,
+        /// The value which clicking the setting picks
+        next: &'static str,
+    },
+}
+
+impl Value {
+    /// The `current` one of the `choices`. Clicking the setting picks the one after it,
+    /// wrapping around to the first one
+    fn choice<T: Copy + PartialEq>(choices: &[T], current: T, name: fn(T) -> &'static str) -> Self {
+        let index = choices
+            .iter()
+            .position(|choice| *choice == current)
+            .unwrap_or_default();
+
+        Self::Choice {
+            current: name(current),
+            next: name(choices[(index + 1) % choices.len()]),
+        }
+    }
+
+    /// The value, as shown to the user
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Toggle(true) => "on",
+            Self::Toggle(false) => "off",
+            Self::Choice { current, .. } => current,
+        }
+    }
+
+    /// The value which clicking the setting writes to the config
+    const fn next(self) -> &'static str {
+        match self {
+            Self::Toggle(is_on) => write::kdl_bool(!is_on),
+            Self::Choice { next, .. } => next,
+        }
+    }
+}
+
+/// Message for the settings popup
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Change the setting to the value, as written in the config
+    Set(Setting, &'static str),
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut crate::App) -> Task<crate::Message> {
+        match self {
+            Self::Set(setting, value) => {
+                let path = &app.cli.config_file;
+
+                if let Err(err) = write::write_option(Path::new(path), setting.key(), value) {
+                    app.errors.push_code(
+                        ErrorCode::ConfigWrite,
+                        format!("Failed to write {path}: {err}"),
+                    );
+                    return Task::none();
+                }
+
+                // the warnings were already shown when ferrishot started
+                match crate::Config::parse(path) {
+                    Ok((config, _warnings)) => app.config = Arc::new(config),
+                    Err(err) => app.errors.push_code(
+                        ErrorCode::ConfigWrite,
+                        format!("Failed to read {path}: {err}"),
+                    ),
+                }
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// Data for the settings popup
+pub struct Settings<'app> {
+    /// The App
+    pub app: &'app crate::App,
+}
+
+impl<'app> Settings<'app> {
+    /// Render the settings popup
+    pub fn view(&self) -> Element<'app, crate::Message> {
+        let size = Size::new(600.0, 620.0);
+        let config = &self.app.config;
+        let theme = &config.theme;
+
+        let settings = Setting::ALL.map(|setting| {
+            let value = setting.get(config);
+
+            button(row![
+                text(setting.label()),
+                horizontal_space(),
+                text(value.label())
+            ])
+            .on_press(crate::Message::Settings(Message::Set(
+                setting,
+                value.next(),
+            )))
+            .style(move |_, status| button::Style {
+                background: matches!(status, button::Status::Hovered)
+                    .then_some(Background::Color(theme.text_selection)),
+                text_color: theme.settings_fg,
+                ..Default::default()
+            })
+            .width(Fill)
+            .into()
+        });
+
+        super::popup(
+            size,
+            container(
+                column![
+                    //
+                    // Heading
+                    //
+                    container(text("Settings").size(30.0)).center_x(Fill),
+                    //
+                    // Divider
+                    //
+                    container(horizontal_rule(2)).height(10.0),
+                    //
+                    // Settings
+                    //
+                    column(settings).spacing(4.0),
+                    //
+                    // Where the settings are saved
+                    //
+                    text(format!("Saved to {}", self.app.cli.config_file)).size(14.0),
+                ]
+                .spacing(20.0),
+            )
+            .width(size.width)
+            .height(size.height)
+            .style(|_| container::Style {
+                text_color: Some(theme.settings_fg),
+                background: Some(Background::Color(theme.settings_bg)),
+                ..Default::default()
+            })
+            .padding(30.0),
+            config,
+        )
+    }
+}
@@ -1,7 +1,10 @@
 //! A `Selection` is the structure representing a selected area in the background image
+use crate::error_code::ErrorCode;
 use crate::geometry::Corners;
 use crate::geometry::Direction;
+use crate::geometry::GrabArea;
 use crate::geometry::RectangleExt as _;
+use crate::geometry::ResizeAnchor;
 use crate::geometry::Side;
 use crate::geometry::SideOrCorner;
 use crate::lazy_rect::LazyRectangle;
@@ -11,7 +14,7 @@ use iced::mouse::Cursor;
 use iced::mouse::Interaction;
 use iced::widget::Action;
 use iced::widget::canvas;
-use iced::{Point, Rectangle, Size};
+use iced::{Point, Rectangle, Size, Vector};
 
 /// A place on the rectangle
 #[derive(ferrishot_knus::DecodeScalar, Debug, Clone, PartialEq, Copy, Eq, Ord, PartialOrd)]
@@ -38,9 +41,157 @@ pub enum Place {
     Top,
     /// Bottom side
     Bottom,
+    /// Center on the line a third of the way from the left
+    LeftThird,
+    /// Center on the line a third of the way from the right
+    RightThird,
+    /// Center on the line a third of the way from the top
+    TopThird,
+    /// Center on the line a third of the way from the bottom
+    BottomThird,
+    /// Center on the line which divides the width in the golden ratio, closer to the left
+    LeftGolden,
+    /// Center on the line which divides the width in the golden ratio, closer to the right
+    RightGolden,
+    /// Center on the line which divides the height in the golden ratio, closer to the top
+    TopGolden,
+    /// Center on the line which divides the height in the golden ratio, closer to the bottom
+    BottomGolden,
+}
+
+/// The golden ratio, `(1 + √5) / 2`
+const GOLDEN_RATIO: f32 = 1.618_034;
+
+/// Position along an axis of a selection with the `length`, so that its center is
+/// `fraction` of the way across the image, without going past the edges of the image
+fn centered_on(fraction: f32, length: f32, image_length: f32) -> f32 {
+    (image_length * fraction - length / 2.0).clamp(0.0, (image_length - length).max(0.0))
+}
+
+/// Snap the distance that `position` has travelled from `origin` to multiples of `step`
+fn snap(position: Point, origin: Point, step: Option<u32>) -> Point {
+    let Some(step) = step.filter(|step| *step > 1).map(|step| step as f32) else {
+        return position;
+    };
+    let delta = position - origin;
+
+    Point::new(
+        origin.x + (delta.x / step).round() * step,
+        origin.y + (delta.y / step).round() * step,
+    )
+}
+
+/// Region stored in the register chosen with `"`. If there is none, shows an error
+fn stored_region(app: &mut crate::App) -> Option<Rectangle> {
+    let register = app.register.unwrap_or(crate::registers::UNNAMED_REGISTER);
+
+    match crate::registers::read(register, app.image.bounds()) {
+        Ok(Some(rect)) => Some(rect),
+        Ok(None) => {
+            app.errors.push_code(
+                ErrorCode::EmptyRegister,
+                format!("Register `{register}` is empty"),
+            );
+            None
+        }
+        Err(err) => {
+            app.errors
+                .push(format!("Failed to read the register `{register}`: {err}"));
+            None
+        }
+    }
+}
+
+/// Replace the current selection with a new one covering `rect`
+pub fn select(app: &mut crate::App, rect: Rectangle) {
+    app.selection = Some(
+        Selection::new(
+            rect.top_left(),
+            &app.config.theme,
+            app.selections_created == 0,
+            app.cli.accept_on_select,
+        )
+        .with_grab_area(app.config.grab_area())
+        .with_size(|_| rect.size()),
+    );
+    app.selections_created += 1;
+}
+
+/// Region of the screenshot described by text pasted from the clipboard: either a region
+/// like `WxH+X+Y`, or a size like `WxH` which keeps the position of the `selection`
+/// (or is centered if there is none)
+fn pasted_region(text: &str, selection: Option<Rectangle>, bounds: Rectangle) -> Option<Rectangle> {
+    let text = text.trim();
+
+    if let Ok(rect) = text.parse::<LazyRectangle>() {
+        return Some(rect.init(bounds));
+    }
+
+    let (width, height) = text.split_once(['x', 'X', '×'])?;
+    let size = Size::new(
+        (width.trim().parse::<u32>().ok()? as f32).min(bounds.width),
+        (height.trim().parse::<u32>().ok()? as f32).min(bounds.height),
+    );
+
+    Some(Rectangle::new(
+        selection.map_or_else(|| bounds.center_for(size), |sel| sel.top_left()),
+        size,
+    ))
+}
+
+/// Render a label with the `content` at the `position`, which receives the size of the label.
+/// The label is kept inside of the `bounds`
+pub fn draw_label(
+    frame: &mut canvas::Frame,
+    bounds: Rectangle,
+    theme: &crate::Theme,
+    content: String,
+    position: impl FnOnce(Size) -> Point,
+) {
+    /// Size of the text in the label
+    const FONT_SIZE: f32 = 14.0;
+    /// Width of a single character of the monospace font, relative to its size
+    const CHAR_WIDTH: f32 = 0.6;
+    /// Space between the text and the edge of the label
+    const PADDING: f32 = 4.0;
+
+    let size = Size::new(
+        content.chars().count() as f32 * FONT_SIZE * CHAR_WIDTH + PADDING * 2.0,
+        FONT_SIZE + PADDING * 2.0,
+    );
+
+    let mut pos = position(size);
+    pos.x = pos.x.clamp(
+        bounds.x,
+        (bounds.x + bounds.width - size.width).max(bounds.x),
+    );
+    pos.y = pos.y.clamp(
+        bounds.y,
+        (bounds.y + bounds.height - size.height).max(bounds.y),
+    );
+
+    frame.fill_rectangle(pos, size, theme.dimensions_label_bg);
+    frame.fill_text(canvas::Text {
+        content,
+        position: Point::new(pos.x + PADDING, pos.y + PADDING),
+        color: theme.dimensions_label_fg,
+        size: FONT_SIZE.into(),
+        font: iced::Font::MONOSPACE,
+        ..Default::default()
+    });
+}
+
+/// How to make the selection stand out from the rest of the screen
+#[derive(ferrishot_knus::DecodeScalar, Debug, Clone, PartialEq, Copy, Eq)]
+pub enum ShadeStyle {
+    /// Darken everything outside of the selection
+    Dim,
+    /// Tint the selection, leaving the rest of the screen untouched
+    Highlight,
 }
 
 crate::declare_commands! {
+    #[derive(Copy)]
     enum Command {
         /// Set the width to whatever number is currently pressed
         SetWidth,
@@ -51,8 +202,46 @@ crate::declare_commands! {
             #[ferrishot_knus(str)]
             selection: LazyRectangle,
         },
+        /// Create a selection of the given size in the center of the screen,
+        /// so a selection can be made without using the mouse
+        CreateCenteredSelection {
+            width: u32 = 400,
+            height: u32 = 300,
+        },
+        /// Select the region that was used the last time ferrishot was run
+        SelectLastRegion,
+        /// Store the selected region into the register chosen with `"`, like `"1y`.
+        /// Registers are remembered across sessions
+        StoreRegion,
+        /// Select the region stored in the register chosen with `"`, like `"1p`
+        RestoreRegion,
+        /// Extend the selection to also cover the region stored in the register chosen with `"`
+        UniteWithRegion,
+        /// Shrink the selection to the part which overlaps the region stored in the register
+        /// chosen with `"`
+        IntersectWithRegion,
         /// Remove the selection
         ClearSelection,
+        /// Toggle precision mode, where moving and resizing with the mouse
+        /// is 10 times slower and only by whole pixels
+        TogglePrecisionMode,
+        /// Toggle smart select, which highlights the UI element under the cursor
+        /// (such as a button or a panel) and selects it on click
+        ToggleSmartSelect,
+        /// Toggle snapping the sides of the selection to the start and end of lines of text
+        /// while resizing, to capture terminal output or tables exactly
+        ToggleSnapToText,
+        /// Lock the selection, so neither the mouse nor keys can move or resize it until it
+        /// is unlocked. It can still be copied, saved and uploaded
+        ToggleLockSelection,
+        /// Copy the region as `WxH+X+Y` to the clipboard, to reuse it later with `--region`
+        CopyRegionString,
+        /// Select the region copied as `WxH+X+Y`, or resize the selection to the size copied
+        /// as `WxH`. Middle-click pastes the primary selection the same way
+        PasteRegion,
+        /// Shrink the selection to exclude margins of a single color inside of it,
+        /// leaving just the content
+        AutoTrim,
         /// Shift the selection in the given direction by pixels
         Move {
             direction: Direction,
@@ -75,12 +264,34 @@ crate::declare_commands! {
     }
 }
 
+impl Command {
+    /// Whether this command moves, resizes or replaces the selection,
+    /// which is not allowed while the selection is locked
+    pub const fn changes_selection(self) -> bool {
+        !matches!(
+            self,
+            Self::StoreRegion
+                | Self::TogglePrecisionMode
+                | Self::ToggleSmartSelect
+                | Self::ToggleSnapToText
+                | Self::ToggleLockSelection
+                | Self::CopyRegionString
+        )
+    }
+}
+
 impl crate::command::Handler for Command {
     fn handle(self, app: &mut crate::App, count: u32) -> Task<crate::Message> {
+        if self.changes_selection() && app.selection.is_some_and(|sel| sel.is_locked) {
+            app.errors.warn("The selection is locked");
+            return Task::none();
+        }
+
         match self {
             Self::SetWidth => {
                 let Some(selection) = app.selection.as_mut() else {
-                    app.errors.push("Nothing is selected.");
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
                     return Task::none();
                 };
                 let image_width = app.image.width() as f32;
@@ -90,7 +301,8 @@ impl crate::command::Handler for Command {
             }
             Self::SetHeight => {
                 let Some(selection) = app.selection.as_mut() else {
-                    app.errors.push("Nothing is selected.");
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
                     return Task::none();
                 };
                 let image_height = app.image.height() as f32;
@@ -99,24 +311,131 @@ impl crate::command::Handler for Command {
                 *selection = sel.with_height(|_| (count as f32).min(image_height - sel.rect.y));
             }
             Self::SelectRegion { selection } => {
-                let rect = selection.init(app.image.bounds());
-                app.selection = Some(
-                    Selection::new(
-                        rect.top_left(),
-                        &app.config.theme,
-                        app.selections_created == 0,
-                        app.cli.accept_on_select,
-                    )
-                    .with_size(|_| rect.size()),
+                select(app, selection.init(app.image.bounds()));
+            }
+            Self::CreateCenteredSelection { width, height } => {
+                let bounds = app.image.bounds();
+                let size = Size::new(
+                    (width as f32).min(bounds.width),
+                    (height as f32).min(bounds.height),
                 );
-                app.selections_created += 1;
+
+                select(app, Rectangle::new(bounds.center_for(size), size));
+            }
+            Self::SelectLastRegion => match crate::last_region::read(app.image.bounds()) {
+                Ok(Some(rect)) => select(app, rect),
+                Ok(None) => app.errors.push("There is no last region"),
+                Err(err) => app
+                    .errors
+                    .push(format!("Failed to read the last region: {err}")),
+            },
+            Self::StoreRegion => {
+                let Some(selection) = app.selection else {
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
+                    return Task::none();
+                };
+                let register = app.register.unwrap_or(crate::registers::UNNAMED_REGISTER);
+
+                if let Err(err) = crate::registers::write(register, selection.rect.norm()) {
+                    app.errors
+                        .push(format!("Failed to store the region in `{register}`: {err}"));
+                }
+            }
+            Self::RestoreRegion => {
+                if let Some(rect) = stored_region(app) {
+                    select(app, rect);
+                }
+            }
+            Self::UniteWithRegion => {
+                let Some(selection) = app.selection else {
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
+                    return Task::none();
+                };
+                let Some(stored) = stored_region(app) else {
+                    return Task::none();
+                };
+
+                select(app, selection.rect.norm().union(&stored));
+            }
+            Self::IntersectWithRegion => {
+                let Some(selection) = app.selection else {
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
+                    return Task::none();
+                };
+                let Some(stored) = stored_region(app) else {
+                    return Task::none();
+                };
+
+                match selection.rect.norm().intersection(&stored) {
+                    Some(rect) => select(app, rect),
+                    None => app
+                        .errors
+                        .push("The selection does not overlap the stored region"),
+                }
             }
             Self::ClearSelection => {
                 app.selection = None;
             }
+            Self::TogglePrecisionMode => {
+                app.is_precision_mode = !app.is_precision_mode;
+            }
+            Self::ToggleSmartSelect => {
+                app.is_smart_select = !app.is_smart_select;
+            }
+            Self::ToggleSnapToText => {
+                app.is_snap_to_text = !app.is_snap_to_text;
+            }
+            Self::ToggleLockSelection => {
+                let Some(selection) = app.selection.as_mut() else {
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
+                    return Task::none();
+                };
+
+                selection.is_locked = !selection.is_locked;
+                selection.status = SelectionStatus::Idle;
+            }
+            Self::CopyRegionString => {
+                let Some(selection) = app.selection else {
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "There is no selection to copy");
+                    return Task::none();
+                };
+
+                if let Err(err) = crate::clipboard::set_text(&selection.rect.norm().as_str()) {
+                    app.errors.push_code(ErrorCode::Clipboard, err.to_string());
+                }
+            }
+            Self::PasteRegion => {
+                return Task::done(crate::Message::Selection(Box::new(Message::PasteRegion {
+                    primary: false,
+                })));
+            }
+            Self::AutoTrim => {
+                let Some(selection) = app.selection.as_mut() else {
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
+                    return Task::none();
+                };
+                let sel = selection.norm();
+
+                let Some(content) = crate::image::trim::content_bounds(&app.image, sel.rect) else {
+                    app.errors
+                        .push("The selection is a single color, there is nothing to trim to");
+                    return Task::none();
+                };
+
+                *selection = sel
+                    .with_pos(|_| content.top_left())
+                    .with_size(|_| content.size());
+            }
             Self::Move { direction, amount } => {
                 let Some(selection) = app.selection.as_mut() else {
-                    app.errors.push("Nothing is selected.");
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
                     return Task::none();
                 };
                 let image_width = app.image.width() as f32;
@@ -137,7 +456,8 @@ impl crate::command::Handler for Command {
             }
             Self::Extend { direction, amount } => {
                 let Some(selection) = app.selection.as_mut() else {
-                    app.errors.push("Nothing is selected.");
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
                     return Task::none();
                 };
                 let image_height = app.image.height() as f32;
@@ -162,7 +482,8 @@ impl crate::command::Handler for Command {
             }
             Self::Shrink { direction, amount } => {
                 let Some(selection) = app.selection.as_mut() else {
-                    app.errors.push("Nothing is selected.");
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
                     return Task::none();
                 };
                 let sel = selection.norm();
@@ -181,11 +502,13 @@ impl crate::command::Handler for Command {
             }
             Self::Goto { place } => {
                 let Some(selection) = app.selection.as_mut() else {
-                    app.errors.push("Nothing is selected.");
+                    app.errors
+                        .push_code(ErrorCode::NoSelection, "Nothing is selected.");
                     return Task::none();
                 };
                 let image_height = app.image.height() as f32;
                 let image_width = app.image.width() as f32;
+                let from = selection.rect;
 
                 match place {
                     Place::Center => {
@@ -226,6 +549,55 @@ impl crate::command::Handler for Command {
                     Place::Right => {
                         selection.rect.x = image_width - selection.rect.width;
                     }
+                    Place::LeftThird => {
+                        selection.rect.x =
+                            centered_on(1.0 / 3.0, selection.rect.width, image_width);
+                    }
+                    Place::RightThird => {
+                        selection.rect.x =
+                            centered_on(2.0 / 3.0, selection.rect.width, image_width);
+                    }
+                    Place::TopThird => {
+                        selection.rect.y =
+                            centered_on(1.0 / 3.0, selection.rect.height, image_height);
+                    }
+                    Place::BottomThird => {
+                        selection.rect.y =
+                            centered_on(2.0 / 3.0, selection.rect.height, image_height);
+                    }
+                    Place::LeftGolden => {
+                        selection.rect.x = centered_on(
+                            1.0 - 1.0 / GOLDEN_RATIO,
+                            selection.rect.width,
+                            image_width,
+                        );
+                    }
+                    Place::RightGolden => {
+                        selection.rect.x =
+                            centered_on(1.0 / GOLDEN_RATIO, selection.rect.width, image_width);
+                    }
+                    Place::TopGolden => {
+                        selection.rect.y = centered_on(
+                            1.0 - 1.0 / GOLDEN_RATIO,
+                            selection.rect.height,
+                            image_height,
+                        );
+                    }
+                    Place::BottomGolden => {
+                        selection.rect.y =
+                            centered_on(1.0 / GOLDEN_RATIO, selection.rect.height, image_height);
+                    }
+                }
+
+                if app.config.animates_selection() {
+                    let to = selection.rect;
+                    selection.rect = from;
+                    app.selection_animation.start(
+                        from,
+                        to,
+                        app.time_elapsed,
+                        app.config.animation_duration(),
+                    );
                 }
             }
         }
@@ -252,11 +624,20 @@ pub enum Message {
         sel_is_some: SelectionIsSome,
         /// Multiplier for how fast we are resizing.
         speed: Speed,
+        /// What stays in place while resizing
+        anchor: ResizeAnchor,
     },
     /// Update status of existing selection
     UpdateStatus(SelectionStatus, SelectionIsSome),
     /// Create a zero size selection
     CreateSelection(Point),
+    /// Left click outside of the selection, without dragging far enough to create a new one
+    ClickOutside,
+    /// Set the selection to the region or size in the clipboard
+    PasteRegion {
+        /// Paste from the primary selection, like middle-click does, instead of the clipboard
+        primary: bool,
+    },
     /// Left mouse is held down and dragged
     ///
     /// Contains the new point of the mouse
@@ -274,9 +655,14 @@ pub enum Message {
     },
     /// Enter idle mode
     EnterIdle,
+    /// Select the UI element under the cursor, in smart select mode
+    SelectElement(Rectangle),
     /// When we have not yet released the left mouse button
     /// and are dragging the selection to extend it
     ExtendNewSelection(Point),
+    /// When we have not yet released the left mouse button and are holding space,
+    /// moving the selection being created by the cursor's movement
+    PanNewSelection(Vector),
     /// Holding right-click, the selection will move the
     /// nearest corner to the cursor
     ResizeToCursor {
@@ -301,19 +687,78 @@ impl crate::message::Handler for Message {
                         app.selections_created == 0,
                         app.cli.accept_on_select,
                     )
+                    .with_grab_area(app.config.grab_area())
                     .with_status(SelectionStatus::Create),
                 );
                 app.selections_created += 1;
             }
+            Self::ClickOutside => {
+                if let Some(action) = app.config.mouse.click_outside.command() {
+                    return Task::done(crate::Message::Command { action, count: 1 });
+                }
+            }
+            Self::PasteRegion { primary } => {
+                if app.selection.is_some_and(|sel| sel.is_locked) {
+                    app.errors.warn("The selection is locked");
+                    return Task::none();
+                }
+
+                let text = match crate::clipboard::get_text(primary) {
+                    Ok(text) => text,
+                    Err(err) => {
+                        app.errors.push_code(ErrorCode::Clipboard, err.to_string());
+                        return Task::none();
+                    }
+                };
+
+                match pasted_region(
+                    &text,
+                    app.selection.map(|sel| sel.rect.norm()),
+                    app.image.bounds(),
+                ) {
+                    Some(rect) => select(app, rect),
+                    None => app.errors.push(format!(
+                        "Expected a region like `WxH+X+Y` or a size like `WxH` to paste, found `{}`",
+                        text.trim()
+                    )),
+                }
+            }
             Self::UpdateStatus(status, sel_is_some) => {
                 let sel = app.selection.unlock(sel_is_some);
                 sel.status = status;
             }
+            Self::SelectElement(rect) => {
+                select(app, rect);
+            }
             Self::EnterIdle => {
                 if let Some(selection) = app.selection.as_mut() {
+                    let velocity = app.selection_animation.release_velocity(app.time_elapsed);
+
+                    if app.config.animates_selection()
+                        && selection.status.is_move()
+                        && let Some(to) = super::animation::SelectionAnimation::inertia_target(
+                            selection.rect.norm(),
+                            velocity,
+                            app.image.bounds(),
+                        )
+                    {
+                        selection.rect = selection.rect.norm();
+                        app.selection_animation.start(
+                            selection.rect,
+                            to,
+                            app.time_elapsed,
+                            app.config.animation_duration(),
+                        );
+                    }
+
                     selection.status = SelectionStatus::Idle;
                 }
             }
+            Self::PanNewSelection(delta) => {
+                app.selection = app
+                    .selection
+                    .map(|selection| selection.with_pos(|pos| pos + delta));
+            }
             Self::ExtendNewSelection(new_mouse_position) => {
                 app.selection = app.selection.take().map(|selected_region| {
                     let width = new_mouse_position.x - selected_region.rect.x;
@@ -349,14 +794,13 @@ impl crate::message::Handler for Message {
                 initial_rect,
                 sel_is_some,
                 speed,
+                anchor,
             } => {
                 let selected_region = app.selection.unlock(sel_is_some);
-                let resize_speed = speed.speed();
 
-                let dy = (current_cursor_pos.y - initial_cursor_pos.y) * resize_speed;
-                let dx = (current_cursor_pos.x - initial_cursor_pos.x) * resize_speed;
+                let Vector { x: dx, y: dy } = speed.delta(current_cursor_pos - initial_cursor_pos);
 
-                selected_region.rect = match resize_side {
+                let resized = match resize_side {
                     SideOrCorner::Side(side) => match side {
                         Side::Top => initial_rect.with_height(|h| h - dy).with_y(|y| y + dy),
                         Side::Right => initial_rect.with_width(|w| w + dx),
@@ -365,12 +809,27 @@ impl crate::message::Handler for Message {
                     },
                     SideOrCorner::Corner(corner) => corner.resize_rect(initial_rect, dy, dx),
                 };
+                selected_region.rect = anchor.apply(initial_rect, resized, resize_side);
+
+                if app.is_snap_to_text {
+                    let rect = selected_region.rect;
+
+                    // resizing past the opposite side flips the selection
+                    let sides = resize_side
+                        .sides()
+                        .into_iter()
+                        .map(|side| match side {
+                            Side::Left | Side::Right if rect.width < 0.0 => side.opposite(),
+                            Side::Top | Side::Bottom if rect.height < 0.0 => side.opposite(),
+                            _ => side,
+                        })
+                        .collect::<Vec<_>>();
+
+                    selected_region.rect =
+                        crate::image::text_lines::snap(&app.image, rect.norm(), &sides);
+                }
 
-                if speed
-                    == (Speed::Slow {
-                        has_speed_changed: true,
-                    })
-                {
+                if speed.has_speed_changed() {
                     selected_region.status = SelectionStatus::Resize {
                         initial_rect: selected_region.rect,
                         initial_cursor_pos: current_cursor_pos,
@@ -386,7 +845,7 @@ impl crate::message::Handler for Message {
                 speed,
             } => {
                 let mut new_selection = current_selection.with_pos(|_| {
-                    initial_rect_pos + ((current_cursor_pos - initial_cursor_pos) * speed.speed())
+                    initial_rect_pos + speed.delta(current_cursor_pos - initial_cursor_pos)
                 });
 
                 let old_x = new_selection.rect.x as u32;
@@ -413,17 +872,15 @@ impl crate::message::Handler for Message {
                     }
                 }
 
-                if speed
-                    == (Speed::Slow {
-                        has_speed_changed: true,
-                    })
-                {
+                if speed.has_speed_changed() {
                     new_selection.status = SelectionStatus::Move {
                         initial_rect_pos: current_selection.pos(),
                         initial_cursor_pos: current_cursor_pos,
                     }
                 }
 
+                app.selection_animation
+                    .record_drag(new_selection.pos(), app.time_elapsed);
                 app.selection = Some(new_selection);
             }
         }
@@ -435,9 +892,8 @@ impl crate::message::Handler for Message {
 /// The size of the lines of the frame of the selection
 pub const FRAME_WIDTH: f32 = 2.0;
 
-/// Size of the button for the icon, which includes the
-/// icon itself and space around it (bigger than `ICON_SIZE`)
-pub const ICON_BUTTON_SIZE: f32 = 37.0;
+/// How many px the cursor has to travel to move or resize the selection by 1px, in precision mode
+const PRECISION_RATIO: f32 = 10.0;
 
 /// How fast the selection resizes
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
@@ -449,14 +905,45 @@ pub enum Speed {
         /// The speed was previously different, so the selection status must be updated to sync
         has_speed_changed: bool,
     },
+    /// Resize by whole pixels only. Cursor moves `PRECISION_RATIO` px -> the selection resizes by 1px
+    Precise,
 }
 
 impl Speed {
-    /// For a given px of cursor movement, how many px does the selection resize by?
-    pub const fn speed(self) -> f32 {
+    /// How many px the selection moves or resizes by, when the cursor has travelled by `cursor_delta`
+    pub fn delta(self, cursor_delta: Vector) -> Vector {
         match self {
-            Self::Regular => 1.0,
-            Self::Slow { .. } => 0.1,
+            Self::Regular => cursor_delta,
+            Self::Slow { .. } => cursor_delta * 0.1,
+            // `cursor_delta` is the total distance travelled since the start, so truncating it
+            // accumulates the movement without losing any of it
+            Self::Precise => Vector::new(
+                (cursor_delta.x / PRECISION_RATIO).trunc(),
+                (cursor_delta.y / PRECISION_RATIO).trunc(),
+            ),
+        }
+    }
+
+    /// The speed was previously different, so the selection status must be updated to sync
+    pub const fn has_speed_changed(self) -> bool {
+        matches!(
+            self,
+            Self::Slow {
+                has_speed_changed: true
+            }
+        )
+    }
+
+    /// Speed of the selection while the cursor is moving
+    const fn of_cursor(is_shift_down: bool, is_precision_mode: bool) -> Self {
+        if is_precision_mode {
+            Self::Precise
+        } else if is_shift_down {
+            Self::Slow {
+                has_speed_changed: false,
+            }
+        } else {
+            Self::Regular
         }
     }
 }
@@ -470,10 +957,14 @@ pub struct Selection {
     pub accept_on_select: Option<crate::image::action::Command>,
     /// Theme of the app
     pub theme: crate::Theme,
+    /// How close the cursor has to be to the frame to grab it
+    pub grab_area: GrabArea,
     /// Area represented by the selection
     pub rect: Rectangle,
     /// Status of the selection
     pub status: SelectionStatus,
+    /// The mouse and keys can't move or resize the selection
+    pub is_locked: bool,
 }
 
 /// What the selection is doing at the moment
@@ -534,6 +1025,14 @@ pub struct SelectionIsSome {
 /// Methods for extracting value from an optional selection,
 /// with a guarantee that it can never be None.
 #[easy_ext::ext(OptionalSelectionExt)]
+#[expect(
+    clippy::allow_attributes,
+    reason = "the lint only fires on the generated impl, not the trait"
+)]
+#[allow(
+    clippy::single_option_map,
+    reason = "the key can only be constructed in this module"
+)]
 pub impl Option<Selection> {
     /// Attempt to get the inner selection. if successful, return a key that allows opening
     /// this option again with a guarantee for existance.
@@ -558,26 +1057,64 @@ impl Selection {
             is_first: true,
             accept_on_select,
             theme: *theme,
+            grab_area: GrabArea::default(),
             rect,
             status: SelectionStatus::Idle,
         }
     }
 
+    /// Set how close the cursor has to be to the frame to grab it
+    pub const fn with_grab_area(mut self, grab_area: GrabArea) -> Self {
+        self.grab_area = grab_area;
+        self
+    }
+
     /// Set a theme to the selection
     pub fn with_theme(mut self, theme: &crate::Theme) -> Self {
         self.theme = *theme;
         self
     }
 
-    /// Draw the `Selection`
-    pub fn draw(&self, frame: &mut canvas::Frame, bounds: Rectangle) {
-        self.draw_shade(frame, bounds);
+    /// Draw the `Selection`, with the handles to resize it while the `handle` is resized or hovered
+    pub fn draw(
+        &self,
+        frame: &mut canvas::Frame,
+        bounds: Rectangle,
+        shade_style: ShadeStyle,
+        handle: Option<SideOrCorner>,
+    ) {
+        match shade_style {
+            ShadeStyle::Dim => self.draw_shade(frame, bounds),
+            ShadeStyle::Highlight => self.draw_highlight(frame),
+        }
         self.draw_border(frame);
-        self.draw_corners(frame);
+        // the corners can't be grabbed while locked
+        if !self.is_locked {
+            self.draw_corners(frame);
+
+            if let Some(handle) = handle {
+                self.draw_handles(frame, handle);
+            }
+        }
+    }
+
+    /// The side or corner being resized.
+    ///
+    /// Resizing a side past the opposite one flips the selection, so the side is flipped too
+    pub fn resizing_side(&self) -> Option<SideOrCorner> {
+        let SelectionStatus::Resize { resize_side, .. } = self.status else {
+            return None;
+        };
+
+        Some(resize_side.flipped(self.rect.width < 0.0, self.rect.height < 0.0))
     }
 
     /// Type of the mouse cursor
     pub fn mouse_interaction(&self, cursor: Cursor) -> Interaction {
+        if self.is_locked {
+            return Interaction::NotAllowed;
+        }
+
         // if we are already resizing, then this cursor takes priority
         // e.g. we are resizing horizontally but we are on the top left
         // corner = we should have horizontal resize cursor.
@@ -592,9 +1129,11 @@ impl Selection {
         .or_else(|| {
             // when we started dragging a side, even if we go outside of the bounds of that side (which
             // happens often when we are dragging the mouse fast), we don't want the cursor to change
-            cursor
-                .position()
-                .and_then(|cursor| self.corners().side_at(cursor).map(SideOrCorner::mouse_icon))
+            cursor.position().and_then(|cursor| {
+                self.corners()
+                    .side_at(cursor, self.grab_area)
+                    .map(SideOrCorner::mouse_icon)
+            })
         })
         .unwrap_or_else(|| {
             if self.cursor_in_selection(cursor).is_some() {
@@ -627,6 +1166,12 @@ impl Selection {
         frame.fill(&outside, self.theme.non_selected_region);
     }
 
+    /// Tint the area inside of the selection
+    pub fn draw_highlight(&self, frame: &mut canvas::Frame) {
+        let sel = self.norm();
+        frame.fill_rectangle(sel.pos(), sel.size(), self.theme.selection_highlight);
+    }
+
     /// Renders border of the selection
     pub fn draw_border(&self, frame: &mut canvas::Frame) {
         // Draw the shadow of the border of the selection
@@ -637,13 +1182,19 @@ impl Selection {
                 .with_color(self.theme.drop_shadow)
                 .with_width(FRAME_WIDTH * 2.0),
         );
-        // Draw the border around the selection (the sides)
+        // Draw the border around the selection (the sides), dashed while it is locked
         frame.stroke_rectangle(
             self.pos(),
             self.size(),
-            canvas::Stroke::default()
-                .with_color(self.theme.selection_frame)
-                .with_width(FRAME_WIDTH),
+            canvas::Stroke {
+                line_dash: canvas::LineDash {
+                    segments: if self.is_locked { &[8.0, 6.0] } else { &[] },
+                    offset: 0,
+                },
+                ..canvas::Stroke::default()
+                    .with_color(self.theme.selection_frame)
+                    .with_width(FRAME_WIDTH)
+            },
         );
     }
 
@@ -653,18 +1204,107 @@ impl Selection {
         const FRAME_CIRCLE_RADIUS: f32 = 6.0;
 
         let corners = self.corners();
-        for circle in [
-            corners.top_left,
-            corners.top_right,
-            corners.bottom_left,
-            corners.bottom_right,
-        ]
-        .map(|corner| canvas::Path::circle(corner, FRAME_CIRCLE_RADIUS))
-        {
-            frame.fill(&circle, self.theme.selection_frame);
+        // a single path, so the circles are tessellated together instead of in 4 separate fills
+        let circles = canvas::Path::new(|builder| {
+            for corner in [
+                corners.top_left,
+                corners.top_right,
+                corners.bottom_left,
+                corners.bottom_right,
+            ] {
+                builder.circle(corner, FRAME_CIRCLE_RADIUS);
+            }
+        });
+
+        frame.fill(&circles, self.theme.selection_frame);
+    }
+
+    /// Render a handle in the middle of each side, so it is clear that the sides can be
+    /// grabbed too. The `active` side or corner is emphasized
+    pub fn draw_handles(&self, frame: &mut canvas::Frame, active: SideOrCorner) {
+        /// Size of the handle on the top and bottom sides. The left and right ones are rotated
+        const SIDE_HANDLE: Size = Size::new(16.0, 6.0);
+        /// Radius of the circle of the corner being resized or hovered
+        const ACTIVE_CORNER_RADIUS: f32 = 9.0;
+
+        let corners = self.corners();
+        let handles = canvas::Path::new(|builder| {
+            for side in [Side::Top, Side::Right, Side::Bottom, Side::Left] {
+                let (start, end) = corners.side(side);
+                let size = match side {
+                    Side::Top | Side::Bottom => SIDE_HANDLE,
+                    Side::Left | Side::Right => Size::new(SIDE_HANDLE.height, SIDE_HANDLE.width),
+                };
+                let center = Point::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0);
+
+                builder.rectangle(
+                    center - Vector::new(size.width / 2.0, size.height / 2.0),
+                    size,
+                );
+            }
+        });
+        frame.fill(&handles, self.theme.selection_frame);
+
+        match active {
+            SideOrCorner::Side(side) => {
+                let (start, end) = corners.side(side);
+                frame.stroke(
+                    &canvas::Path::line(start, end),
+                    canvas::Stroke::default()
+                        .with_color(self.theme.selection_frame)
+                        .with_width(FRAME_WIDTH * 2.0),
+                );
+            }
+            SideOrCorner::Corner(corner) => {
+                frame.fill(
+                    &canvas::Path::circle(corners.corner(corner), ACTIVE_CORNER_RADIUS),
+                    self.theme.selection_frame,
+                );
+            }
         }
     }
 
+    /// Render a `W × H` label just outside of the bottom-right corner of the selection.
+    ///
+    /// If there is no space for it there, it is moved inside of the `bounds`
+    pub fn draw_dimensions_label(&self, frame: &mut canvas::Frame, bounds: Rectangle) {
+        /// Distance between the label and the selection
+        const GAP: f32 = 6.0;
+
+        let sel = self.norm();
+        let content = format!("{} × {}", sel.rect.width as u32, sel.rect.height as u32);
+
+        draw_label(frame, bounds, &self.theme, content, |size| {
+            // Below the bottom-right corner. If that goes off-screen, above the top-right corner instead
+            let mut pos = Point::new(
+                sel.rect.x + sel.rect.width - size.width,
+                sel.rect.y + sel.rect.height + GAP,
+            );
+            if pos.y + size.height > bounds.y + bounds.height {
+                pos.y = sel.rect.y - GAP - size.height;
+            }
+            pos
+        });
+    }
+
+    /// Render how far the `cursor` is from the nearest corner of the selection, like `+12, -3`,
+    /// next to the cursor
+    pub fn draw_cursor_offset(&self, frame: &mut canvas::Frame, bounds: Rectangle, cursor: Point) {
+        /// Distance between the label and the cursor, so the cursor does not cover it
+        const CURSOR_GAP: f32 = 16.0;
+
+        let (corner, _) = self.norm().corners().nearest_corner(cursor);
+        let content = format!(
+            "{:+}, {:+}",
+            (cursor.x - corner.x).round() as i32,
+            (cursor.y - corner.y).round() as i32
+        );
+
+        draw_label(frame, bounds, &self.theme, content, |_| {
+            cursor + Vector::new(CURSOR_GAP, CURSOR_GAP)
+        });
+    }
+
     /// Set status of the selection
     pub const fn with_status(mut self, status: SelectionStatus) -> Self {
         self.status = status;
@@ -682,37 +1322,56 @@ impl Selection {
             rect: Rectangle::new(point, Size::default()),
             status: SelectionStatus::default(),
             theme: *theme,
+            grab_area: GrabArea::default(),
             is_first,
             accept_on_select,
+            is_locked: false,
         }
     }
 
     /// Update the selection
+    ///
+    /// While a `motion_count` is pending, moving and resizing with the mouse
+    /// snaps to multiples of it. Pressing outside of the selection only creates a new one
+    /// once the cursor is dragged further than the `drag_threshold`
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "each is separate state of the app"
+    )]
     pub fn update(
         &self,
         state: &mut SelectionKeysState,
         event: &iced::Event,
         _bounds: Rectangle,
         cursor: Cursor,
+        motion_count: Option<u32>,
+        is_precision_mode: bool,
+        drag_threshold: f32,
     ) -> Option<Action<crate::Message>> {
         use iced::Event::{Keyboard, Mouse, Touch};
         use iced::keyboard::Event::KeyPressed;
         use iced::keyboard::Event::KeyReleased;
         use iced::keyboard::Key::Named;
-        use iced::keyboard::key::Named::{Control, Shift};
+        use iced::keyboard::key::Named::{Alt, Control, Shift, Space};
         use iced::mouse::Button::{Left, Right};
         use iced::mouse::Event::ButtonPressed;
         use iced::mouse::Event::ButtonReleased;
         use iced::mouse::Event::CursorMoved;
         use iced::touch::Event::{FingerLifted, FingerMoved, FingerPressed};
 
+        // a locked selection ignores the mouse, and pressing it does not create a new selection
+        if self.is_locked && matches!(event, Mouse(_) | Touch(_)) {
+            return matches!(event, Touch(FingerPressed { .. }) | Mouse(ButtonPressed(_)))
+                .then(Action::capture);
+        }
+
         let message = match event {
             Touch(FingerPressed { .. }) | Mouse(ButtonPressed(Left)) => {
                 state.is_left_down = true;
 
                 if let Some((cursor, side)) = cursor.position().and_then(|cursor_pos| {
                     self.corners()
-                        .side_at(cursor_pos)
+                        .side_at(cursor_pos, self.grab_area)
                         .map(|side| (cursor_pos, side))
                 }) {
                     // Left click on corners = Start resizing selection
@@ -734,14 +1393,23 @@ impl Selection {
                         SelectionIsSome { _private: () },
                     )))
                 } else if let Some(cursor_position) = cursor.position() {
-                    // Left click outside of selection = Create new selection
-                    crate::Message::Selection(Box::new(Message::CreateSelection(cursor_position)))
+                    // Left click outside of selection = Create new selection, once dragged
+                    state.pending_create = Some(cursor_position);
+                    return Some(Action::capture());
                 } else {
                     return None;
                 }
             }
             Touch(FingerLifted { .. }) | Mouse(ButtonReleased(Left)) => {
                 state.is_left_down = false;
+                state.pan_from = None;
+
+                // released before dragging far enough, so it was just a click
+                if state.pending_create.take().is_some() {
+                    return Some(Action::publish(crate::Message::Selection(Box::new(
+                        Message::ClickOutside,
+                    ))));
+                }
 
                 self.accept_on_select.map_or_else(
                     // stop the creating of the initial selection
@@ -780,6 +1448,33 @@ impl Selection {
                 state.is_shift_down = false;
                 return None;
             }
+            Keyboard(KeyPressed {
+                key: Named(Alt), ..
+            }) => {
+                state.is_alt_down = true;
+                return None;
+            }
+            Keyboard(KeyReleased {
+                key: Named(Alt), ..
+            }) => {
+                state.is_alt_down = false;
+                return None;
+            }
+            // holding space while creating the selection pans it, instead of resizing it
+            Keyboard(KeyPressed {
+                key: Named(Space), ..
+            }) if self.is_create() => {
+                if state.pan_from.is_none() {
+                    state.pan_from = cursor.position();
+                }
+                return Some(Action::capture());
+            }
+            Keyboard(KeyReleased {
+                key: Named(Space), ..
+            }) if state.pan_from.is_some() => {
+                state.pan_from = None;
+                return Some(Action::capture());
+            }
             Mouse(ButtonPressed(Right)) => {
                 state.is_right_down = true;
 
@@ -799,6 +1494,12 @@ impl Selection {
             }) => {
                 state.is_shift_down = true;
 
+                // precision mode replaces the slow speed of shift,
+                // and with Alt, shift keeps the aspect ratio instead
+                if is_precision_mode || state.is_alt_down {
+                    return None;
+                }
+
                 let current_cursor_pos = cursor.position()?;
 
                 // If we are already resizing a side, and we press shift, we
@@ -817,6 +1518,7 @@ impl Selection {
                             speed: Speed::Slow {
                                 has_speed_changed: true,
                             },
+                            anchor: ResizeAnchor::Opposite,
                         }))
                     }
                     SelectionStatus::Move { .. } => {
@@ -833,6 +1535,15 @@ impl Selection {
                     _ => return None,
                 }
             }
+            Touch(FingerMoved { position, .. }) | Mouse(CursorMoved { position })
+                if state
+                    .pending_create
+                    .is_some_and(|start| start.distance(*position) > drag_threshold) =>
+            {
+                let start = state.pending_create.take()?;
+
+                crate::Message::Selection(Box::new(Message::CreateSelection(start)))
+            }
             Touch(FingerMoved { position, .. }) | Mouse(CursorMoved { position })
                 if self.is_resize() =>
             {
@@ -847,18 +1558,17 @@ impl Selection {
                 };
 
                 crate::Message::Selection(Box::new(Message::Resize {
-                    current_cursor_pos: *position,
+                    current_cursor_pos: snap(*position, initial_cursor_pos, motion_count),
                     resize_side,
                     initial_cursor_pos,
                     initial_rect,
                     sel_is_some: SelectionIsSome { _private: () },
-                    speed: if state.is_shift_down {
-                        Speed::Slow {
-                            has_speed_changed: false,
-                        }
-                    } else {
-                        Speed::Regular
-                    },
+                    // with Alt, Shift keeps the aspect ratio instead of slowing down
+                    speed: Speed::of_cursor(
+                        state.is_shift_down && !state.is_alt_down,
+                        is_precision_mode,
+                    ),
+                    anchor: ResizeAnchor::of_modifiers(state.is_alt_down, state.is_shift_down),
                 }))
             }
             Touch(FingerMoved { position, .. }) | Mouse(CursorMoved { position })
@@ -876,19 +1586,20 @@ impl Selection {
                 };
 
                 crate::Message::Selection(Box::new(Message::MoveSelection {
-                    current_cursor_pos: *position,
+                    current_cursor_pos: snap(*position, initial_cursor_pos, motion_count),
                     initial_cursor_pos,
                     current_selection,
                     initial_rect_pos,
-                    speed: if state.is_shift_down {
-                        Speed::Slow {
-                            has_speed_changed: false,
-                        }
-                    } else {
-                        Speed::Regular
-                    },
+                    speed: Speed::of_cursor(state.is_shift_down, is_precision_mode),
                 }))
             }
+            Touch(FingerMoved { position, .. }) | Mouse(CursorMoved { position })
+                if self.is_create() && state.pan_from.is_some() =>
+            {
+                let pan_from = state.pan_from.replace(*position)?;
+
+                crate::Message::Selection(Box::new(Message::PanNewSelection(*position - pan_from)))
+            }
             Touch(FingerMoved { position, .. }) | Mouse(CursorMoved { position })
                 if self.is_create() =>
             {
@@ -993,4 +1704,64 @@ pub struct SelectionKeysState {
     pub is_shift_down: bool,
     /// Control key is currently being held down
     pub is_ctrl_down: bool,
+    /// Alt key is currently being held down
+    pub is_alt_down: bool,
+    /// Where the left mouse button was pressed outside of the selection. It is only
+    /// replaced by a new selection once the cursor is dragged further than `drag-threshold`
+    pub pending_create: Option<Point>,
+    /// Space is held down while creating the selection, so moving the cursor pans the selection.
+    /// Where the cursor was when the selection was last panned
+    pub pan_from: Option<Point>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn pastes_region_or_size() {
+        let bounds = Rectangle::new(Point::ORIGIN, Size::new(1000.0, 800.0));
+        let selection = Rectangle::new(Point::new(10.0, 20.0), Size::new(5.0, 5.0));
+
+        assert_eq!(
+            pasted_region(" 100x50+10+20\n", None, bounds),
+            Some(Rectangle::new(
+                Point::new(10.0, 20.0),
+                Size::new(100.0, 50.0)
+            ))
+        );
+        assert_eq!(
+            pasted_region("300x200", Some(selection), bounds),
+            Some(Rectangle::new(
+                Point::new(10.0, 20.0),
+                Size::new(300.0, 200.0)
+            ))
+        );
+        assert_eq!(
+            pasted_region("400 × 200", None, bounds),
+            Some(Rectangle::new(
+                Point::new(300.0, 300.0),
+                Size::new(400.0, 200.0)
+            ))
+        );
+        assert_eq!(pasted_region("hello", None, bounds), None);
+    }
+
+    #[test]
+    #[expect(clippy::float_cmp, reason = "exact positions")]
+    fn centers_on_lines_across_the_image() {
+        assert_eq!(centered_on(1.0 / 3.0, 100.0, 900.0), 250.0);
+        assert_eq!(centered_on(2.0 / 3.0, 100.0, 900.0), 550.0);
+        assert_eq!(
+            centered_on(1.0 / 3.0, 800.0, 900.0),
+            0.0,
+            "does not go past the left edge"
+        );
+        assert_eq!(
+            centered_on(2.0 / 3.0, 800.0, 900.0),
+            100.0,
+            "does not go past the right edge"
+        );
+    }
 }
@@ -3,14 +3,12 @@
 use core::f32;
 
 use iced::{
-    Element,
-    Length::{self, Fill},
-    Padding, Radians, Rectangle, Rotation,
-    widget::{Column, Row, Space, row, tooltip},
+    Element, Length, Point, Radians, Rectangle, Rotation, Size,
+    widget::{Space, Stack, column, row, tooltip},
 };
 
+use crate::lazy_rect::LazyRectangle;
 use crate::{icon, message::Message, ui::selection::FRAME_WIDTH};
-use crate::{lazy_rect::LazyRectangle, ui::selection::ICON_BUTTON_SIZE};
 use iced::{Background, Border, Shadow, widget};
 
 use super::app::{self};
@@ -21,14 +19,14 @@ use super::app::{self};
 // We want to render every single one of them.
 // Each icon should not be shrunk, nor should it render in weird positions
 //
-// for each side in [bottom, right, top, left] we render
-// all of the icons that fit on that side.
+// The icons are placed in lines. We try each line in order, and place as many
+// icons as fit on that line before moving on to the next one:
 //
-// But then we may have a small selection which doesn't manage to render all of the icons,
-// so we deal with that by rendering a couple extra rows on top and bottom
+// 1. Just outside of each side of the selection, in order [bottom, right, top, left]
+// 2. More rows further away from the bottom and the top of the selection
+// 3. Rows inside of the selection, starting from the bottom. This happens when the selection
+//    takes up most of the screen, so there is no room outside of it
 
-/// Height and width of each icon
-const PX_PER_ICON: f32 = SPACE_BETWEEN_ICONS + ICON_BUTTON_SIZE;
 /// The minimum amount of icons to render at the top
 const MIN_TOP_BOTTOM_ICONS: usize = 3;
 /// The minimum amount of icons to render on the sides
@@ -36,40 +34,75 @@ const MIN_SIDE_ICONS: usize = 1;
 /// Space in-between each icon
 const SPACE_BETWEEN_ICONS: f32 = 2.0;
 
-/// Create a tooltip for an icon
-pub fn icon_tooltip<'a, Message>(
+/// Space between the tooltip and its icon
+const TOOLTIP_GAP: f32 = 10.0;
+/// Roughly how much space a tooltip takes up above or below its icon
+const TOOLTIP_HEIGHT: f32 = TOOLTIP_GAP + 30.0;
+/// Roughly how much space a tooltip takes up to the left or right of its icon
+const TOOLTIP_WIDTH: f32 = TOOLTIP_GAP + 250.0;
+
+/// Create a tooltip for an icon, unless tooltips are disabled
+pub fn icon_tooltip<'a, Message: 'a>(
     content: impl Into<Element<'a, Message>>,
     tooltip: impl Into<Element<'a, Message>>,
     position: tooltip::Position,
-    theme: &'a crate::Theme,
-) -> widget::Tooltip<'a, Message> {
+    config: &'a crate::Config,
+) -> Element<'a, Message> {
+    if !config.tooltips {
+        return content.into();
+    }
+
     widget::Tooltip::new(content, tooltip, position)
         .style(move |_| widget::container::Style {
-            text_color: Some(theme.tooltip_fg),
-            background: Some(Background::Color(theme.tooltip_bg)),
+            text_color: Some(config.theme.tooltip_fg),
+            background: Some(Background::Color(config.theme.tooltip_bg)),
             border: Border::default(),
             shadow: Shadow::default(),
         })
-        .gap(10.0)
+        .gap(TOOLTIP_GAP)
+        .delay(config.tooltip_delay())
+        .into()
 }
 
-/// Styled icon as a button
+/// Flip the position of the tooltip of the icon at `icon_pos` to the other side of it,
+/// if the tooltip would not fit on the screen
+fn flip_tooltip(
+    position: tooltip::Position,
+    icon_pos: Point,
+    button_size: f32,
+    image: Size,
+) -> tooltip::Position {
+    use tooltip::Position::{Bottom, Left, Right, Top};
+
+    match position {
+        Bottom if icon_pos.y + button_size + TOOLTIP_HEIGHT > image.height => Top,
+        Top if icon_pos.y < TOOLTIP_HEIGHT => Bottom,
+        Right if icon_pos.x + button_size + TOOLTIP_WIDTH > image.width => Left,
+        Left if icon_pos.x < TOOLTIP_WIDTH => Right,
+        position => position,
+    }
+}
+
+/// Styled icon as a button, `button_size` px wide and tall
 pub fn selection_icon<'a, Message>(
     icon: widget::Svg<'a>,
+    button_size: f32,
     theme: &'a crate::Theme,
 ) -> widget::Button<'a, Message> {
-    /// Width and height for icons *inside* of buttons
-    const ICON_SIZE: f32 = 32.0;
+    /// Space between the icon and the edge of the button
+    const ICON_PADDING: f32 = 2.5;
+
+    let icon_size = ICON_PADDING.mul_add(-2.0, button_size).max(0.0);
 
     widget::button(
         icon.style(move |_, _| widget::svg::Style {
             color: Some(theme.icon_fg),
         })
-        .width(Length::Fixed(ICON_SIZE))
-        .height(Length::Fixed(ICON_SIZE)),
+        .width(Length::Fixed(icon_size))
+        .height(Length::Fixed(icon_size)),
     )
-    .width(Length::Fixed(ICON_BUTTON_SIZE))
-    .height(Length::Fixed(ICON_BUTTON_SIZE))
+    .width(Length::Fixed(button_size))
+    .height(Length::Fixed(button_size))
     .style(move |_, _| {
         let mut style = widget::button::Style::default().with_background(theme.icon_bg);
         style.shadow = Shadow {
@@ -97,410 +130,376 @@ pub struct SelectionIcons<'app> {
     pub selection_rect: Rectangle,
 }
 
-/// Add icons to the side until the amount of them reaches the minimum required
-fn add_icons_until_there_is_at_least_n_of_them<'a, const MIN_ELEMENTS: usize>(
-    mut icons: Vec<Element<'a, Message>>,
-    mut iter: impl Iterator<Item = (Element<'a, Message>, &'static str)>,
-    mut padding: f32,
-    total_icons_positioned: &mut usize,
-    tooltip_position: tooltip::Position,
-    theme: &'a crate::Theme,
-) -> (Vec<Element<'a, Message>>, f32) {
-    while icons.len() < MIN_ELEMENTS {
-        if let Some((next, tooltip_str)) = iter.by_ref().next() {
-            icons.push(icon_tooltip(next, tooltip_str, tooltip_position, theme).into());
-            *total_icons_positioned += 1;
-            padding -= PX_PER_ICON / 2.0;
-        } else {
-            break;
+/// Direction in which a line of icons goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    /// Left to right
+    Horizontal,
+    /// Top to bottom
+    Vertical,
+}
+
+/// A straight line on which icons can be placed
+#[derive(Debug, Clone, Copy)]
+struct Line {
+    /// Direction of the line
+    axis: Axis,
+    /// Coordinate of the top-left corner of every icon on the line, on the axis
+    /// perpendicular to the line. (`y` for horizontal lines, `x` for vertical lines)
+    cross: f32,
+    /// The icons will be centered around this coordinate, on the axis of the line
+    center: f32,
+    /// How many icons fit on this line
+    capacity: usize,
+    /// Where the tooltip of each icon on this line goes
+    tooltip: tooltip::Position,
+}
+
+/// Lines on which icons may be placed, from the most preferred to the least
+fn lines(image: Size, sel: Rectangle, button_size: f32, px_per_icon: f32) -> Vec<Line> {
+    /// How many icons fit in `len` px
+    fn fits(len: f32, px_per_icon: f32) -> usize {
+        ((len + SPACE_BETWEEN_ICONS) / px_per_icon).max(0.0) as usize
+    }
+
+    let mut lines = vec![];
+
+    // icons on the top and bottom may stick out of the selection, if it is too small
+    let row_capacity = fits(sel.width, px_per_icon)
+        .max(MIN_TOP_BOTTOM_ICONS)
+        .min(fits(image.width, px_per_icon));
+    let column_capacity = fits(FRAME_WIDTH.mul_add(2.0, sel.height), px_per_icon)
+        .max(MIN_SIDE_ICONS)
+        .min(fits(image.height, px_per_icon));
+
+    let row = |cross, tooltip| Line {
+        axis: Axis::Horizontal,
+        cross,
+        center: sel.center_x(),
+        capacity: row_capacity,
+        tooltip,
+    };
+    let column = |cross, tooltip| Line {
+        axis: Axis::Vertical,
+        cross,
+        center: sel.center_y(),
+        capacity: column_capacity,
+        tooltip,
+    };
+
+    let bottom = |ring: f32| ring.mul_add(px_per_icon, sel.y + sel.height + FRAME_WIDTH);
+    let top = |ring: f32| ring.mul_add(-px_per_icon, sel.y - FRAME_WIDTH - button_size);
+    let is_bottom_free = |ring| bottom(ring) + button_size <= image.height;
+    let is_top_free = |ring| top(ring) >= 0.0;
+
+    // first, the line just outside of each side
+    if is_bottom_free(0.0) {
+        lines.push(row(bottom(0.0), tooltip::Position::Bottom));
+    }
+    let right = sel.x + sel.width + FRAME_WIDTH;
+    if right + button_size <= image.width {
+        lines.push(column(right, tooltip::Position::Right));
+    }
+    if is_top_free(0.0) {
+        lines.push(row(top(0.0), tooltip::Position::Top));
+    }
+    let left = sel.x - FRAME_WIDTH - button_size;
+    if left >= 0.0 {
+        lines.push(column(left, tooltip::Position::Left));
+    }
+
+    // then, rows further away from the top and bottom of the selection
+    let mut ring = 1.0;
+    while is_bottom_free(ring) || is_top_free(ring) {
+        if is_bottom_free(ring) {
+            lines.push(row(bottom(ring), tooltip::Position::Bottom));
+        }
+        if is_top_free(ring) {
+            lines.push(row(top(ring), tooltip::Position::Top));
         }
+        ring += 1.0;
     }
-    (icons, padding)
-}
 
-/// Position icons until we reach an adequate amount of them
-fn position_icons_in_line<'a>(
-    space_available: f32,
-    tooltip_position: tooltip::Position,
-    total_icons_positioned: &mut usize,
-    mut icons_iter: impl Iterator<Item = (Element<'a, Message>, &'static str)>,
-    icons_len: usize,
-    theme: &'a crate::Theme,
-) -> (Vec<Element<'a, Message>>, f32) {
-    let icons_left_to_position = icons_len - *total_icons_positioned;
-    let icons_rendered_here =
-        ((space_available / PX_PER_ICON) as usize).min(icons_left_to_position);
-    *total_icons_positioned += icons_rendered_here;
-
-    // we do this thing because we need to know exactly
-    // how many elems we got. size_hint may be unreliable
-    let mut icons = Vec::with_capacity(icons_rendered_here);
-    for _ in 0..icons_rendered_here {
-        if let Some((icon, tooltip_str)) = icons_iter.by_ref().next() {
-            icons.push(icon_tooltip(icon, tooltip_str, tooltip_position, theme).into());
+    // finally, rows inside of the selection
+    let inside_capacity = fits(FRAME_WIDTH.mul_add(-2.0, sel.width), px_per_icon);
+    if inside_capacity > 0 {
+        let mut cross = sel.y + sel.height - FRAME_WIDTH - button_size;
+        while cross >= sel.y + FRAME_WIDTH {
+            lines.push(Line {
+                capacity: inside_capacity,
+                ..row(cross, tooltip::Position::Top)
+            });
+            cross -= px_per_icon;
         }
     }
 
-    // if there is just 0 element it will take away the icon padding so it can be negative
-    // ensure it is positive
-    let space_used = (icons.len() as f32) * PX_PER_ICON + -SPACE_BETWEEN_ICONS.max(0.0);
+    lines
+}
 
-    let padding = (space_available - space_used) / 2.0;
+/// Find the top-left corner of each of the `icons_len` icons, and where their tooltips go.
+///
+/// Icons which could not be placed anywhere are left out.
+fn layout(
+    image: Size,
+    selection: Rectangle,
+    button_size: f32,
+    icons_len: usize,
+) -> Vec<(Point, tooltip::Position)> {
+    // with a size of 0 we would be placing an infinite amount of icons on each line
+    let button_size = button_size.max(1.0);
+    let px_per_icon = button_size + SPACE_BETWEEN_ICONS;
 
-    (icons, padding)
-}
+    let mut placements = Vec::with_capacity(icons_len);
 
-impl<'app> SelectionIcons<'app> {
-    /// Render icons around the selection border
-    // TODO: Currently, this function does not handle the case where the selection has the
-    // same size as the entire screen - so no icons can be rendered at all.
-    //
-    // We should add even more fallbacks so that it can render a little bit inside of the selection.
-    pub fn view(self) -> Element<'app, Message> {
-        let icons = vec![
-            (
-                icon!(Fullscreen),
-                crate::Command::Selection(super::selection::Command::SelectRegion {
-                    selection: LazyRectangle::FULL,
-                }),
-                "Select entire monitor (F11)",
-            ),
-            (
-                icon!(Clipboard),
-                crate::Command::ImageUpload(crate::image::action::Command::CopyToClipboard),
-                "Copy to Clipboard (Enter)",
-            ),
-            (
-                icon!(Save),
-                crate::Command::ImageUpload(crate::image::action::Command::SaveScreenshot),
-                "Save Screenshot (Ctrl + s)",
-            ),
-            (
-                icon!(Close),
-                crate::Command::App(app::Command::Exit),
-                "Exit (esc)",
-            ),
-            if self.app.is_uploading_image {
-                // how many seconds we are into the current spin
-                let current_spin_secs = self.app.time_elapsed.as_secs_f32() % 2.0;
-                // how much % we are through the current spin
-                let current_spin_percent = current_spin_secs / 2.0;
-
-                (
-                    icon!(Spinner).rotation(Rotation::Floating(Radians(
-                        current_spin_percent * f32::consts::TAU,
-                    ))),
-                    // TODO: Clicking this should cancel the image upload
-                    crate::Command::App(app::Command::NoOp),
-                    "Screenshot is being uploaded...",
-                )
-            } else {
-                (
-                    icon!(Upload),
-                    crate::Command::ImageUpload(crate::image::action::Command::UploadScreenshot),
-                    "Upload Screenshot (Ctrl + u)",
-                )
-            },
-        ]
-        .into_iter()
-        .map(|(icon, action, label)| {
+    for line in lines(image, selection, button_size, px_per_icon) {
+        let icons_left = icons_len - placements.len();
+        if icons_left == 0 {
+            break;
+        }
+        let icons_here = line.capacity.min(icons_left);
+
+        let (screen_len, to_point): (f32, fn(f32, f32) -> Point) = match line.axis {
+            Axis::Horizontal => (image.width, |along, cross| Point::new(along, cross)),
+            Axis::Vertical => (image.height, |along, cross| Point::new(cross, along)),
+        };
+
+        // the icons are centered on the line, but must not go off-screen
+        let len = (icons_here as f32).mul_add(px_per_icon, -SPACE_BETWEEN_ICONS);
+        let start = (line.center - len / 2.0).min(screen_len - len).max(0.0);
+
+        placements.extend((0..icons_here).map(|i| {
+            let position = to_point((i as f32).mul_add(px_per_icon, start), line.cross);
             (
-                selection_icon(icon, &self.app.config.theme)
-                    .on_press(Message::Command {
-                        action,
-                        // Count does not actually matter at all, since it does not make sense to
-                        // do any of the buttons multiple times.
-                        count: 1,
-                    })
-                    .into(),
-                label,
+                position,
+                flip_tooltip(line.tooltip, position, button_size, image),
             )
-        });
-
-        let is_enough_space_at_bottom = self.image_height
-            - (self.selection_rect.y + self.selection_rect.height)
-            > ICON_BUTTON_SIZE;
-        let is_enough_space_at_right = self.image_width
-            - (self.selection_rect.x + self.selection_rect.width)
-            > ICON_BUTTON_SIZE;
-        let is_enough_space_at_top = self.selection_rect.y > ICON_BUTTON_SIZE;
-        let is_enough_space_at_left = self.selection_rect.x > ICON_BUTTON_SIZE;
-
-        let icons_len = icons.len();
-        let mut icons_iter = icons.into_iter();
-        let mut total_icons_positioned = 0;
-
-        // first position the icons on each side (bottom -> right -> top -> left)
-        // (bottom_icons, mut bottom_padding)
-
-        let bottom_icons = is_enough_space_at_bottom.then(|| {
-            position_icons_in_line(
-                self.selection_rect.width,
-                tooltip::Position::Bottom,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
-            )
-        });
-
-        let right_icons = is_enough_space_at_right.then(|| {
-            position_icons_in_line(
-                self.selection_rect.height,
-                tooltip::Position::Right,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
-            )
-        });
-
-        let top_icons = is_enough_space_at_top.then(|| {
-            position_icons_in_line(
-                self.selection_rect.width,
-                tooltip::Position::Top,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
-            )
-        });
-
-        let left_icons = is_enough_space_at_left.then(|| {
-            position_icons_in_line(
-                self.selection_rect.height,
-                tooltip::Position::Left,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
-            )
-        });
+        }));
+    }
 
-        // if we reach here, our selection is to small to nicely
-        // render all of the icons so we must "stack" them somehow
+    placements
+}
 
-        // for the 4 sides, combined they will fit at LEAST 8 icons (3 top 3 bottom 1 right 1 left)
+/// Buttons for each of the actions that can be done with the selection,
+/// with the label of their tooltip
+pub fn icons(app: &super::App) -> Vec<(widget::Button<'_, Message>, String)> {
+    let icon_button_size = app.config.icon_button_size;
+    let described = app.config.keys.described();
+
+    vec![
+        (
+            icon!(Fullscreen),
+            crate::Command::Selection(super::selection::Command::SelectRegion {
+                selection: LazyRectangle::FULL,
+            }),
+            "Select entire monitor (F11)",
+        ),
+        (
+            icon!(Clipboard),
+            crate::Command::ImageUpload(crate::image::action::Command::CopyToClipboard),
+            "Copy to Clipboard (Enter)",
+        ),
+        (
+            icon!(Save),
+            crate::Command::ImageUpload(crate::image::action::Command::SaveScreenshot),
+            "Save Screenshot (Ctrl + s)",
+        ),
+        (
+            icon!(Redact),
+            crate::Command::Redact(crate::image::redact::Command::ToggleRedaction {
+                style: crate::image::redact::RedactionStyle::Pixelate,
+            }),
+            "Redact (r)",
+        ),
+        (
+            icon!(Pen),
+            crate::Command::Annotate(crate::image::annotate::Command::ToggleDrawing {
+                tool: crate::image::annotate::Tool::Pen,
+            }),
+            "Draw (w)",
+        ),
+        (
+            icon!(Close),
+            crate::Command::App(app::Command::Exit),
+            "Exit (esc)",
+        ),
+        if app.is_uploading_image {
+            // how many seconds we are into the current spin
+            let current_spin_secs = app.time_elapsed.as_secs_f32() % 2.0;
+            // how much % we are through the current spin
+            let current_spin_percent = current_spin_secs / 2.0;
 
-        let bottom_icons = bottom_icons.map(|(bottom_icons, bottom_padding)| {
-            add_icons_until_there_is_at_least_n_of_them::<MIN_TOP_BOTTOM_ICONS>(
-                bottom_icons,
-                &mut icons_iter,
-                bottom_padding,
-                &mut total_icons_positioned,
-                tooltip::Position::Bottom,
-                &self.app.config.theme,
-            )
-        });
-
-        let top_icons = top_icons.map(|(top_icons, top_padding)| {
-            add_icons_until_there_is_at_least_n_of_them::<MIN_TOP_BOTTOM_ICONS>(
-                top_icons,
-                &mut icons_iter,
-                top_padding,
-                &mut total_icons_positioned,
-                tooltip::Position::Top,
-                &self.app.config.theme,
-            )
-        });
-
-        let left_icons = left_icons.map(|(left_icons, left_padding)| {
-            add_icons_until_there_is_at_least_n_of_them::<MIN_SIDE_ICONS>(
-                left_icons,
-                &mut icons_iter,
-                left_padding,
-                &mut total_icons_positioned,
-                tooltip::Position::Left,
-                &self.app.config.theme,
-            )
-        });
-
-        let right_icons = right_icons.map(|(right_icons, right_padding)| {
-            add_icons_until_there_is_at_least_n_of_them::<MIN_SIDE_ICONS>(
-                right_icons,
-                &mut icons_iter,
-                right_padding,
-                &mut total_icons_positioned,
-                tooltip::Position::Right,
-                &self.app.config.theme,
-            )
-        });
-
-        // position two additional rows of icons on top and bottom
-        // if we STILL have extra icons left
-
-        let extra_top_icons = is_enough_space_at_top.then(|| {
-            position_icons_in_line(
-                self.selection_rect.width,
-                tooltip::Position::Top,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
+            (
+                icon!(Spinner).rotation(Rotation::Floating(Radians(
+                    current_spin_percent * f32::consts::TAU,
+                ))),
+                // TODO: Clicking this should cancel the image upload
+                crate::Command::App(app::Command::NoOp),
+                "Screenshot is being uploaded...",
             )
-        });
-
-        let extra_bottom_icons = is_enough_space_at_bottom.then(|| {
-            position_icons_in_line(
-                self.selection_rect.width,
-                tooltip::Position::Bottom,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
+        } else {
+            (
+                icon!(Upload),
+                crate::Command::ImageUpload(crate::image::action::Command::UploadScreenshot),
+                "Upload Screenshot (Ctrl + u)",
             )
-        });
-
-        let extra_bottom_icons =
-            extra_bottom_icons.map(|(extra_bottom_icons, extra_bottom_padding)| {
-                add_icons_until_there_is_at_least_n_of_them::<MIN_TOP_BOTTOM_ICONS>(
-                    extra_bottom_icons,
-                    &mut icons_iter,
-                    extra_bottom_padding,
-                    &mut total_icons_positioned,
-                    tooltip::Position::Bottom,
-                    &self.app.config.theme,
-                )
+        },
+    ]
+    .into_iter()
+    .filter(|(_, action, _)| {
+        app.config.can_upload()
+            || *action
+                != crate::Command::ImageUpload(crate::image::action::Command::UploadScreenshot)
+    })
+    .map(|(icon, action, label)| {
+        // the user's descriptions of keybindings which do the same as the icon
+        let label = described
+            .iter()
+            .filter(|(_, command, _)| **command == action)
+            .fold(label.to_owned(), |label, (keys, _, description)| {
+                format!("{label}\n{keys}: {description}")
             });
 
-        let extra_top_icons = extra_top_icons.map(|(extra_top_icons, extra_top_padding)| {
-            add_icons_until_there_is_at_least_n_of_them::<MIN_TOP_BOTTOM_ICONS>(
-                extra_top_icons,
-                &mut icons_iter,
-                extra_top_padding,
-                &mut total_icons_positioned,
-                tooltip::Position::Top,
-                &self.app.config.theme,
-            )
-        });
-
-        let extra_extra_top_icons = is_enough_space_at_top.then(|| {
-            position_icons_in_line(
-                self.selection_rect.width,
-                tooltip::Position::Top,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
-            )
-        });
-
-        let extra_extra_bottom_icons = is_enough_space_at_bottom.then(|| {
-            position_icons_in_line(
-                self.selection_rect.width,
-                tooltip::Position::Bottom,
-                &mut total_icons_positioned,
-                &mut icons_iter,
-                icons_len,
-                &self.app.config.theme,
-            )
-        });
-
-        let extra_extra_top_icons =
-            extra_extra_top_icons.map(|(extra_extra_top_icons, extra_extra_top_padding)| {
-                add_icons_until_there_is_at_least_n_of_them::<MIN_TOP_BOTTOM_ICONS>(
-                    extra_extra_top_icons,
-                    &mut icons_iter,
-                    extra_extra_top_padding,
-                    &mut total_icons_positioned,
-                    tooltip::Position::Top,
-                    &self.app.config.theme,
-                )
-            });
+        (
+            selection_icon(icon, icon_button_size, &app.config.theme).on_press(Message::Command {
+                action,
+                // Count does not actually matter at all, since it does not make sense to
+                // do any of the buttons multiple times.
+                count: 1,
+            }),
+            label,
+        )
+    })
+    .collect()
+}
 
-        let extra_extra_bottom_icons = extra_extra_bottom_icons.map(
-            |(extra_extra_bottom_icons, extra_extra_bottom_padding)| {
-                add_icons_until_there_is_at_least_n_of_them::<MIN_TOP_BOTTOM_ICONS>(
-                    extra_extra_bottom_icons,
-                    &mut icons_iter,
-                    extra_extra_bottom_padding,
-                    &mut total_icons_positioned,
-                    tooltip::Position::Bottom,
-                    &self.app.config.theme,
-                )
-            },
+impl<'app> SelectionIcons<'app> {
+    /// Render icons around the selection border
+    ///
+    /// If there is not enough space around the selection, e.g. because it
+    /// is the size of the entire screen, the icons are rendered inside of it.
+    pub fn view(self) -> Element<'app, Message> {
+        let icon_button_size = self.app.config.icon_button_size;
+        let icons = icons(self.app);
+
+        let placements = layout(
+            Size::new(self.image_width, self.image_height),
+            self.selection_rect,
+            icon_button_size,
+            icons.len(),
         );
 
-        let right_icons = right_icons.map(|(right_icons, right_padding)| {
-            Column::from_vec(right_icons)
-                .spacing(SPACE_BETWEEN_ICONS)
-                .width(PX_PER_ICON)
-                .padding(Padding::default().top(right_padding))
-        });
-
-        let left_icons = left_icons.map(|(left_icons, left_padding)| {
-            Column::from_vec(left_icons)
-                .spacing(SPACE_BETWEEN_ICONS)
-                .width(PX_PER_ICON)
-                .padding(Padding::default().top(left_padding))
-        });
-
-        // there is no way to get amount of children
-        // from a Row. that would be prety useful
-        let mut top_icon_rows_count = 0;
-        let top_icons: Column<_> = extra_extra_top_icons
-            .into_iter()
-            .chain(extra_top_icons)
-            .chain(top_icons)
-            .filter_map(|(icons, padding)| {
-                (!icons.is_empty()).then(|| {
-                    top_icon_rows_count += 1;
-                    row![
-                        Space::with_width(self.selection_rect.x),
-                        Row::from_vec(icons)
-                            .spacing(SPACE_BETWEEN_ICONS)
-                            .height(PX_PER_ICON)
-                            .padding(Padding::default().left(padding))
-                    ]
-                    .into()
-                })
-            })
-            .collect();
-
-        let bottom_icons: Column<_> = bottom_icons
-            .into_iter()
-            .chain(extra_bottom_icons)
-            .chain(extra_extra_bottom_icons)
-            .filter_map(|(icons, padding)| {
-                (!icons.is_empty()).then(|| {
+        Stack::with_children(placements.into_iter().zip(icons).map(
+            |((position, tooltip_position), (icon, label))| {
+                column![
+                    Space::with_height(position.y),
                     row![
-                        Space::with_width(self.selection_rect.x),
-                        Row::from_vec(icons)
-                            .spacing(SPACE_BETWEEN_ICONS)
-                            .height(PX_PER_ICON)
-                            .padding(Padding::default().left(padding))
+                        Space::with_width(position.x),
+                        icon_tooltip(
+                            icon,
+                            widget::text(label),
+                            tooltip_position,
+                            &self.app.config
+                        )
                     ]
-                    .into()
-                })
-            })
-            .collect();
-
-        // include the frame so the icons do not touch the frame
-        let selection_height = FRAME_WIDTH * 2.0 + self.selection_rect.height;
-
-        // the left and right rows should be large enough to have at least 1 icon
-        // always.
-        let height_added = (PX_PER_ICON - selection_height).max(0.0);
-
-        iced::widget::column![
-            // just whitespace necessary to align the icons to the selection
-            Space::with_height(Length::Fixed(
-                (top_icon_rows_count as f32) * -PX_PER_ICON + self.selection_rect.y
-                    - height_added / 2.0
-            ))
-            .width(Fill),
-            // top icon row
-            top_icons,
-            // right icon row + left icon row
-            row![Space::with_width(self.selection_rect.x - PX_PER_ICON).height(Fill),]
-                .push_maybe(left_icons)
-                .push(Space::with_width(FRAME_WIDTH * 2.0 + self.selection_rect.width).height(Fill))
-                .push_maybe(right_icons)
-                .padding(Padding::default().top(height_added / 2.0))
-                .height(selection_height + height_added),
-            // bottom icon row
-            bottom_icons,
-        ]
+                ]
+                .into()
+            },
+        ))
+        .width(Length::Fill)
+        .height(Length::Fill)
         .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const BUTTON_SIZE: f32 = 37.0;
+    const SCREEN: Size = Size::new(1920.0, 1080.0);
+
+    /// Whether the icon at this position is fully on the screen
+    fn is_on_screen(position: Point) -> bool {
+        position.x >= 0.0
+            && position.y >= 0.0
+            && position.x + BUTTON_SIZE <= SCREEN.width
+            && position.y + BUTTON_SIZE <= SCREEN.height
+    }
+
+    #[test]
+    fn icons_below_selection() {
+        let sel = Rectangle::new(Point::new(500.0, 500.0), Size::new(400.0, 200.0));
+        let placements = layout(SCREEN, sel, BUTTON_SIZE, 5);
+
+        assert_eq!(placements.len(), 5);
+        for (position, tooltip) in placements {
+            assert_eq!(position.y, 700.0 + FRAME_WIDTH);
+            assert_eq!(tooltip, tooltip::Position::Bottom);
+        }
+    }
+
+    #[test]
+    fn icons_inside_full_screen_selection() {
+        let sel = Rectangle::new(Point::ORIGIN, SCREEN);
+        let placements = layout(SCREEN, sel, BUTTON_SIZE, 5);
+
+        assert_eq!(placements.len(), 5);
+        for (position, _) in placements {
+            assert!(is_on_screen(position), "{position:?} is off-screen");
+            assert!(sel.contains(position));
+        }
+    }
+
+    #[test]
+    fn icons_around_tiny_selection() {
+        let sel = Rectangle::new(Point::new(900.0, 500.0), Size::new(5.0, 5.0));
+        let placements = layout(SCREEN, sel, BUTTON_SIZE, 5);
+
+        assert_eq!(placements.len(), 5);
+        for (position, _) in placements {
+            assert!(is_on_screen(position), "{position:?} is off-screen");
+        }
+    }
+
+    #[test]
+    fn tooltips_flip_near_screen_edges() {
+        use tooltip::Position::{Bottom, Left, Right, Top};
+
+        let middle = Point::new(900.0, 500.0);
+        for position in [Bottom, Top, Left, Right] {
+            assert_eq!(
+                flip_tooltip(position, middle, BUTTON_SIZE, SCREEN),
+                position
+            );
+        }
+
+        assert_eq!(
+            flip_tooltip(Bottom, Point::new(900.0, 1040.0), BUTTON_SIZE, SCREEN),
+            Top
+        );
+        assert_eq!(
+            flip_tooltip(Top, Point::new(900.0, 0.0), BUTTON_SIZE, SCREEN),
+            Bottom
+        );
+        assert_eq!(
+            flip_tooltip(Right, Point::new(1880.0, 500.0), BUTTON_SIZE, SCREEN),
+            Left
+        );
+        assert_eq!(
+            flip_tooltip(Left, Point::new(0.0, 500.0), BUTTON_SIZE, SCREEN),
+            Right
+        );
+    }
+
+    #[test]
+    fn icons_stay_on_screen_in_corner() {
+        let sel = Rectangle::new(Point::new(1900.0, 1060.0), Size::new(20.0, 20.0));
+        let placements = layout(SCREEN, sel, BUTTON_SIZE, 5);
+
+        assert_eq!(placements.len(), 5);
+        for (position, _) in placements {
+            assert!(is_on_screen(position), "{position:?} is off-screen");
+        }
+    }
+}
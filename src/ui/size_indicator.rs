@@ -80,7 +80,7 @@ fn dimension_indicator<'a>(
     theme: &'a crate::Theme,
 ) -> widget::TextInput<'a, crate::Message> {
     let content = value.to_string();
-    let input = widget::text_input(Default::default(), content.as_str())
+    widget::text_input(Default::default(), content.as_str())
         // HACK: iced does not provide a way to mimic `width: min-content` from CSS
         // so we have to "guesstimate" the width that each character will be
         // `Length::Shrink` makes `width = 0` for some reason
@@ -108,9 +108,7 @@ fn dimension_indicator<'a>(
             icon: iced::Color::TRANSPARENT,
             placeholder: iced::Color::TRANSPARENT,
         })
-        .padding(0.0);
-
-    input
+        .padding(0.0)
 }
 
 /// Renders a tiny numeric input which shows a dimension of the rect and allow resizing it
@@ -118,18 +116,17 @@ pub fn size_indicator(
     app: &App,
     selection_rect: Rectangle,
     sel_is_some: SelectionIsSome,
-) -> Element<crate::Message> {
+) -> Element<'_, crate::Message> {
     const SPACING: f32 = 12.0;
     const ESTIMATED_INDICATOR_WIDTH: u32 = 120;
     const ESTIMATED_INDICATOR_HEIGHT: u32 = 26;
 
-    let image_height = app.image.height();
-    let image_width = app.image.width();
+    let window = app.viewport.window;
+    let bottom_right = app.viewport.to_window(selection_rect.bottom_right());
 
-    let x_offset = (selection_rect.bottom_right().x + SPACING)
-        .min((image_width - ESTIMATED_INDICATOR_WIDTH) as f32);
-    let y_offset = (selection_rect.bottom_right().y + SPACING)
-        .min((image_height - ESTIMATED_INDICATOR_HEIGHT) as f32);
+    let x_offset = (bottom_right.x + SPACING).min(window.width - ESTIMATED_INDICATOR_WIDTH as f32);
+    let y_offset =
+        (bottom_right.y + SPACING).min(window.height - ESTIMATED_INDICATOR_HEIGHT as f32);
 
     let horizontal_space = Space::with_width(x_offset);
     let vertical_space = Space::with_height(y_offset);
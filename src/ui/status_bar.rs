@@ -0,0 +1,68 @@
+//! Shows which modes are active, such as the layer of keybindings
+
+use iced::{
+    Background, Element, Font,
+    Length::Fill,
+    alignment::{Horizontal, Vertical},
+    widget::{container, text},
+};
+
+use crate::message::Message;
+
+/// Size of the font of the active modes
+const FONT_SIZE: f32 = 14.0;
+/// Space between the text and the edges of its box
+const PADDING: f32 = 6.0;
+/// Space between the box and the bottom of the screen
+const MARGIN: f32 = 16.0;
+
+/// Renders the active modes at the bottom of the screen, if there are any
+pub fn status_bar(app: &super::App) -> Option<Element<'_, Message>> {
+    let modes = app
+        .layer
+        .as_deref()
+        .map(str::to_uppercase)
+        .into_iter()
+        .chain(app.is_precision_mode.then(|| "PRECISION".to_owned()))
+        .chain(app.is_smart_select.then(|| "SMART SELECT".to_owned()))
+        .chain(app.is_snap_to_text.then(|| "SNAP TO TEXT".to_owned()))
+        .chain(app.key_macros.status())
+        .chain(app.redactions.status())
+        .chain(app.annotations.status())
+        .chain((!app.background_filter.is_none()).then(|| app.background_filter.to_string()))
+        .chain((!app.adjustments.is_identity()).then(|| app.adjustments.to_string()))
+        .collect::<Vec<_>>();
+
+    if modes.is_empty() {
+        return None;
+    }
+
+    Some(
+        container(
+            container(
+                text(format!("-- {} --", modes.join(" | ")))
+                    .size(FONT_SIZE)
+                    .font(Font {
+                        weight: iced::font::Weight::Bold,
+                        ..Font::MONOSPACE
+                    }),
+            )
+            .padding(PADDING)
+            .style(|_| container::Style {
+                text_color: Some(app.config.theme.info_box_fg),
+                background: Some(Background::Color(app.config.theme.info_box_bg)),
+                border: iced::Border::default()
+                    .color(app.config.theme.info_box_border)
+                    .rounded(6.0)
+                    .width(1.5),
+                shadow: iced::Shadow::default(),
+            }),
+        )
+        .width(Fill)
+        .height(Fill)
+        .padding(MARGIN)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Bottom)
+        .into(),
+    )
+}
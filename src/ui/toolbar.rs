@@ -0,0 +1,181 @@
+//! A floating toolbar with the icons, which can be dragged anywhere on the screen
+//!
+//! It is an alternative to the icons around the selection, for when the selection
+//! covers most of the screen. Enabled with the `toolbar` option.
+//!
+//! The position of the toolbar is remembered, so it stays where the user dragged it.
+
+use std::fs;
+
+use iced::{
+    Background, Border, Element, Length, Point, Shadow, Size, Task, Vector,
+    mouse::Interaction,
+    widget::{Row, Space, column, container, mouse_area, row, tooltip},
+};
+
+use crate::{icon, message::Message as AppMessage};
+
+use super::selection_icons::{icon_tooltip, icons, selection_icon};
+
+/// Name of the file which stores the position of the toolbar, in the cache directory
+const POSITION_FILENAME: &str = "ferrishot-toolbar-position.txt";
+
+/// Space between the edge of the toolbar and its icons
+const PADDING: f32 = 6.0;
+
+/// Space between each icon of the toolbar
+const SPACING: f32 = 6.0;
+
+/// Distance between the toolbar and the top of the screen, before it is dragged
+const TOP_MARGIN: f32 = 16.0;
+
+/// Message for the toolbar
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Start dragging the toolbar by its handle
+    StartDrag,
+    /// The cursor moved to this position while dragging the toolbar
+    Drag(Point),
+    /// Stop dragging the toolbar, remembering where it is
+    EndDrag,
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut crate::App) -> Task<AppMessage> {
+        match self {
+            Self::StartDrag => app.toolbar.is_dragging = true,
+            Self::Drag(cursor) => {
+                // the handle, which is the first icon, stays under the cursor
+                let handle_center = PADDING + app.config.icon_button_size / 2.0;
+                app.toolbar.position = Some(cursor - Vector::new(handle_center, handle_center));
+            }
+            Self::EndDrag => {
+                app.toolbar.is_dragging = false;
+
+                if let Some(position) = app.toolbar.position {
+                    write_position(position);
+                }
+            }
+        }
+
+        Task::none()
+    }
+}
+
+/// State of the toolbar
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Toolbar {
+    /// Top-left corner of the toolbar. When `None`, it is at the top center of the screen
+    pub position: Option<Point>,
+    /// Whether the toolbar is being dragged
+    pub is_dragging: bool,
+}
+
+impl Toolbar {
+    /// The toolbar where the user last left it
+    pub fn restore() -> Self {
+        Self {
+            position: read_position(),
+            is_dragging: false,
+        }
+    }
+
+    /// Render the toolbar, inside of a screen of `screen` size
+    pub fn view(self, app: &super::App, screen: Size) -> Element<'_, AppMessage> {
+        let icon_button_size = app.config.icon_button_size;
+        let icons = icons(app);
+
+        let handle = icon_tooltip(
+            mouse_area(selection_icon(
+                icon!(Cursor),
+                icon_button_size,
+                &app.config.theme,
+            ))
+            .on_press(AppMessage::Toolbar(Message::StartDrag))
+            .interaction(if self.is_dragging {
+                Interaction::Grabbing
+            } else {
+                Interaction::Grab
+            }),
+            "Drag to move the toolbar",
+            tooltip::Position::Bottom,
+            &app.config,
+        );
+
+        let size = Size::new(
+            (icons.len() + 1) as f32 * (icon_button_size + SPACING) - SPACING + PADDING * 2.0,
+            icon_button_size + PADDING * 2.0,
+        );
+
+        // keep the toolbar on the screen, even if the screen is smaller than it was
+        let position = self
+            .position
+            .unwrap_or_else(|| Point::new((screen.width - size.width) / 2.0, TOP_MARGIN));
+        let position = Point::new(
+            position.x.clamp(0.0, (screen.width - size.width).max(0.0)),
+            position
+                .y
+                .clamp(0.0, (screen.height - size.height).max(0.0)),
+        );
+
+        let toolbar = container(
+            Row::with_children(std::iter::once(handle).chain(icons.into_iter().map(
+                |(icon, label)| {
+                    icon_tooltip(
+                        icon,
+                        iced::widget::text(label),
+                        tooltip::Position::Bottom,
+                        &app.config,
+                    )
+                },
+            )))
+            .spacing(SPACING),
+        )
+        .padding(PADDING)
+        .style(|_| container::Style {
+            background: Some(Background::Color(app.config.theme.toolbar_bg)),
+            border: Border::default().rounded(PADDING + icon_button_size / 2.0),
+            shadow: Shadow {
+                color: app.config.theme.drop_shadow,
+                blur_radius: 3.0,
+                offset: Vector::ZERO,
+            },
+            ..Default::default()
+        });
+
+        column![
+            Space::with_height(position.y),
+            row![Space::with_width(position.x), toolbar]
+        ]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// Read where the toolbar was left, if it was ever dragged
+fn read_position() -> Option<Point> {
+    let path = crate::paths::cache_file(POSITION_FILENAME)
+        .inspect_err(|err| log::warn!("Could not determine the cache directory: {err}"))
+        .ok()?;
+
+    let position = fs::read_to_string(path).ok()?;
+    let (x, y) = position.trim().split_once(',')?;
+
+    Some(Point::new(x.parse().ok()?, y.parse().ok()?))
+}
+
+/// Remember where the toolbar was left
+fn write_position(position: Point) {
+    let path = match crate::paths::cache_file(POSITION_FILENAME) {
+        Ok(path) => path,
+        Err(err) => {
+            log::warn!("Could not determine the cache directory: {err}");
+            return;
+        }
+    };
+
+    if let Err(err) = fs::write(path, format!("{},{}", position.x, position.y)) {
+        log::error!("Failed to save the position of the toolbar: {err}");
+    }
+}
@@ -0,0 +1,211 @@
+//! A short guided tour, shown instead of the welcome message on the first launch
+//!
+//! Once the user completes it, or chooses to never see it again, a state file is written
+//! so the tutorial does not show up anymore.
+
+use std::{fs, path::PathBuf};
+
+use iced::{
+    Background, Element, Font,
+    Length::Fill,
+    Size, Task,
+    alignment::{Horizontal, Vertical},
+    widget::{button, column, container, row, text},
+};
+
+use crate::geometry::RectangleExt as _;
+use crate::ui::selection::Selection;
+
+/// Name of the file which exists if the tutorial does not need to be shown anymore
+const TUTORIAL_DONE_FILENAME: &str = "ferrishot-tutorial-done";
+/// Width of the tutorial box
+const WIDTH: f32 = 380.0;
+/// Size of the font in the tutorial box
+const FONT_SIZE: f32 = 13.0;
+/// Space between the tutorial box and the top of the screen
+const MARGIN: f32 = 40.0;
+
+/// Could not record that the tutorial was completed
+#[derive(thiserror::Error, miette::Diagnostic, Debug)]
+pub enum Error {
+    /// Can't find the directory of the file
+    #[error(transparent)]
+    Paths(#[from] crate::paths::Error),
+    /// Failed to write the state file
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Path to the file which exists if the tutorial does not need to be shown anymore
+fn state_file() -> Result<PathBuf, Error> {
+    Ok(crate::paths::cache_file(TUTORIAL_DONE_FILENAME)?)
+}
+
+/// Whether the tutorial was already completed, or the user does not want to see it
+pub fn is_done() -> bool {
+    state_file().is_ok_and(|path| path.exists())
+}
+
+/// Never show the tutorial again
+pub fn mark_done() -> Result<(), Error> {
+    let path = state_file()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, "")?;
+    Ok(())
+}
+
+/// A step of the tutorial
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    /// Create a selection by dragging the mouse
+    CreateSelection,
+    /// Resize the selection by dragging one of its sides or corners
+    Resize {
+        /// Size of the selection at the start of this step
+        initial_size: Size,
+    },
+    /// Copy the selection to the clipboard
+    Copy,
+}
+
+impl Step {
+    /// Position of the step in the tutorial, starting at 1
+    const fn number(self) -> usize {
+        match self {
+            Self::CreateSelection => 1,
+            Self::Resize { .. } => 2,
+            Self::Copy => 3,
+        }
+    }
+
+    /// What the user has to do to complete this step
+    const fn instruction(self) -> &'static str {
+        match self {
+            Self::CreateSelection => "Hold the left mouse button and drag to select an area",
+            Self::Resize { .. } => "Drag a side or a corner of the selection to resize it",
+            Self::Copy => "Press Enter to copy the selection to the clipboard",
+        }
+    }
+}
+
+/// How many steps there are in the tutorial
+const STEPS: usize = 3;
+
+/// State of the tutorial
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tutorial {
+    /// The current step
+    pub step: Step,
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self {
+            step: Step::CreateSelection,
+        }
+    }
+}
+
+impl Tutorial {
+    /// Move on to the next step, if the current step was completed
+    pub fn advance(&mut self, selection: Option<Selection>) {
+        let Some(sel) = selection else {
+            // the selection was removed, so the user has to start over
+            self.step = Step::CreateSelection;
+            return;
+        };
+
+        // the selection is still being created or resized
+        if !sel.is_idle() {
+            return;
+        }
+
+        let size = sel.rect.norm().size();
+
+        match self.step {
+            Step::CreateSelection if size != Size::ZERO => {
+                self.step = Step::Resize { initial_size: size };
+            }
+            Step::Resize { initial_size } if size != initial_size => {
+                self.step = Step::Copy;
+            }
+            _ => (),
+        }
+    }
+
+    /// Renders the current step at the top of the screen
+    pub fn view<'app>(self, app: &'app super::App) -> Element<'app, crate::Message> {
+        let theme = &app.config.theme;
+
+        let button_style = move |_: &iced::Theme, _| button::Style {
+            background: Some(Background::Color(theme.info_box_fg.scale_alpha(0.15))),
+            text_color: theme.info_box_fg,
+            border: iced::Border::default().rounded(4.0),
+            ..Default::default()
+        };
+
+        let contents = column![
+            text(format!("Tutorial ({}/{STEPS})", self.step.number()))
+                .size(FONT_SIZE)
+                .font(Font {
+                    weight: iced::font::Weight::Bold,
+                    ..Font::default()
+                }),
+            text(self.step.instruction()).size(FONT_SIZE),
+            row![
+                button(text("Dismiss").size(FONT_SIZE))
+                    .on_press(crate::Message::Tutorial(Message::Dismiss))
+                    .style(button_style),
+                button(text("Don't show again").size(FONT_SIZE))
+                    .on_press(crate::Message::Tutorial(Message::NeverShowAgain))
+                    .style(button_style),
+            ]
+            .spacing(8.0),
+        ]
+        .spacing(8.0)
+        .width(WIDTH)
+        .padding(10.0);
+
+        container(container(contents).style(|_| {
+            container::Style {
+                text_color: Some(theme.info_box_fg),
+                background: Some(Background::Color(theme.info_box_bg)),
+                border: iced::Border::default()
+                    .color(theme.info_box_border)
+                    .rounded(6.0)
+                    .width(1.5),
+                shadow: iced::Shadow::default(),
+            }
+        }))
+        .width(Fill)
+        .height(Fill)
+        .padding(MARGIN)
+        .align_x(Horizontal::Center)
+        .align_y(Vertical::Top)
+        .into()
+    }
+}
+
+/// Tutorial message
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Hide the tutorial until the next launch
+    Dismiss,
+    /// Hide the tutorial, and never show it again
+    NeverShowAgain,
+}
+
+impl crate::message::Handler for Message {
+    fn handle(self, app: &mut crate::App) -> Task<crate::Message> {
+        app.tutorial = None;
+        if matches!(self, Self::NeverShowAgain)
+            && let Err(err) = mark_done()
+        {
+            app.errors
+                .push(format!("Failed to hide the tutorial permanently: {err}"));
+        }
+        Task::none()
+    }
+}
@@ -0,0 +1,191 @@
+//! Where the screenshot is shown inside of the window
+//!
+//! The screenshot is scaled to fit into the window, keeping its aspect ratio, and centered.
+//! Usually the window is fullscreen on the monitor that was captured, so the screenshot
+//! fits exactly and nothing changes. But the window can be resized, for example when the
+//! resolution of the monitor changes while ferrishot is open.
+//!
+//! The selection is always in the coordinates of the screenshot, so it keeps pointing at
+//! the same pixels no matter the size of the window. It is only mapped to the window
+//! to be shown, and the cursor is mapped back to the screenshot.
+
+use iced::{Event, Point, Rectangle, Size, Vector, mouse, touch};
+
+/// How the screenshot is fitted into the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Size of the screenshot
+    pub image: Size,
+    /// Size of the window
+    pub window: Size,
+}
+
+impl Viewport {
+    /// Viewport of a window which is exactly as large as the screenshot of `size`
+    pub const fn new(size: Size) -> Self {
+        Self {
+            image: size,
+            window: size,
+        }
+    }
+
+    /// How many pixels of the window a single pixel of the screenshot takes up
+    pub fn scale(self) -> f32 {
+        let scale =
+            (self.window.width / self.image.width).min(self.window.height / self.image.height);
+
+        // the window can have no size at all while it is minimized
+        if scale.is_finite() && scale > 0.0 {
+            scale
+        } else {
+            1.0
+        }
+    }
+
+    /// Where the top-left corner of the screenshot is in the window
+    pub fn offset(self) -> Vector {
+        let shown = self.image * self.scale();
+
+        Vector::new(
+            (self.window.width - shown.width) / 2.0,
+            (self.window.height - shown.height) / 2.0,
+        )
+    }
+
+    /// The whole screenshot, in its own coordinates
+    pub fn image_bounds(self) -> Rectangle {
+        Rectangle::with_size(self.image)
+    }
+
+    /// Convert a `point` in the window to the pixel of the screenshot shown there
+    pub fn to_image(self, point: Point) -> Point {
+        Point::ORIGIN + (point - Point::ORIGIN - self.offset()) * (1.0 / self.scale())
+    }
+
+    /// Convert a `point` of the screenshot to where it is shown in the window
+    pub fn to_window(self, point: Point) -> Point {
+        Point::ORIGIN + (point - Point::ORIGIN) * self.scale() + self.offset()
+    }
+
+    /// Convert a `rect` of the screenshot to where it is shown in the window
+    pub fn rect_to_window(self, rect: Rectangle) -> Rectangle {
+        Rectangle::new(self.to_window(rect.position()), rect.size() * self.scale())
+    }
+
+    /// The `cursor` in the window, as a cursor over the screenshot
+    pub fn cursor(self, cursor: mouse::Cursor) -> mouse::Cursor {
+        cursor
+            .position()
+            .map_or(mouse::Cursor::Unavailable, |position| {
+                mouse::Cursor::Available(self.to_image(position))
+            })
+    }
+
+    /// The `event` in the window, with its positions mapped to the screenshot
+    pub fn event(self, event: Event) -> Event {
+        match event {
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                Event::Mouse(mouse::Event::CursorMoved {
+                    position: self.to_image(position),
+                })
+            }
+            Event::Touch(touch::Event::FingerPressed { id, position }) => {
+                Event::Touch(touch::Event::FingerPressed {
+                    id,
+                    position: self.to_image(position),
+                })
+            }
+            Event::Touch(touch::Event::FingerMoved { id, position }) => {
+                Event::Touch(touch::Event::FingerMoved {
+                    id,
+                    position: self.to_image(position),
+                })
+            }
+            Event::Touch(touch::Event::FingerLifted { id, position }) => {
+                Event::Touch(touch::Event::FingerLifted {
+                    id,
+                    position: self.to_image(position),
+                })
+            }
+            Event::Touch(touch::Event::FingerLost { id, position }) => {
+                Event::Touch(touch::Event::FingerLost {
+                    id,
+                    position: self.to_image(position),
+                })
+            }
+            event => event,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn fullscreen_changes_nothing() {
+        let viewport = Viewport::new(Size::new(1920.0, 1080.0));
+        let point = Point::new(123.0, 456.0);
+
+        assert_eq!(viewport.to_image(point), point);
+        assert_eq!(viewport.to_window(point), point);
+    }
+
+    #[test]
+    fn fits_into_smaller_window() {
+        let viewport = Viewport {
+            image: Size::new(1920.0, 1080.0),
+            window: Size::new(960.0, 1000.0),
+        };
+
+        assert!(
+            (viewport.scale() - 0.5).abs() < f32::EPSILON,
+            "the width is what limits the size"
+        );
+        assert_eq!(
+            viewport.offset(),
+            Vector::new(0.0, 230.0),
+            "centered vertically"
+        );
+        assert_eq!(
+            viewport.to_image(Point::new(480.0, 500.0)),
+            Point::new(960.0, 540.0),
+            "center of the window is the center of the screenshot"
+        );
+        assert_eq!(
+            viewport.rect_to_window(Rectangle::new(Point::ORIGIN, Size::new(100.0, 50.0))),
+            Rectangle::new(Point::new(0.0, 230.0), Size::new(50.0, 25.0))
+        );
+    }
+
+    #[test]
+    fn maps_events() {
+        let viewport = Viewport {
+            image: Size::new(1920.0, 1080.0),
+            window: Size::new(960.0, 540.0),
+        };
+
+        assert_eq!(
+            viewport.event(Event::Mouse(mouse::Event::CursorMoved {
+                position: Point::new(100.0, 50.0)
+            })),
+            Event::Mouse(mouse::Event::CursorMoved {
+                position: Point::new(200.0, 100.0)
+            })
+        );
+    }
+
+    #[test]
+    fn minimized_window() {
+        let viewport = Viewport {
+            image: Size::new(1920.0, 1080.0),
+            window: Size::ZERO,
+        };
+
+        assert!(
+            (viewport.scale() - 1.0).abs() < f32::EPSILON,
+            "no division by zero"
+        );
+    }
+}
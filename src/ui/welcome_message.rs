@@ -32,11 +32,10 @@ const HEIGHT: f32 =
     30.0 + TIPS.len() as f32 * FONT_SIZE + (TIPS.len() - 1) as f32 * SPACING + (PADDING * 2.0);
 
 /// Renders the welcome message that the user sees when they first launch the program
-pub fn welcome_message(app: &super::App) -> Element<Message> {
-    let image_width = app.image.width();
-    let image_height = app.image.height();
-    let vertical_space = Space::with_height(image_height / 2 - HEIGHT as u32 / 2);
-    let horizontal_space = Space::with_width(image_width / 2 - WIDTH / 2);
+pub fn welcome_message(app: &super::App) -> Element<'_, Message> {
+    let window = app.viewport.window;
+    let vertical_space = Space::with_height(((window.height - HEIGHT) / 2.0).max(0.0));
+    let horizontal_space = Space::with_width(((window.width - WIDTH as f32) / 2.0).max(0.0));
 
     let stuff = iced::widget::container(
         TIPS.into_iter()